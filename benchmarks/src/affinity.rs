@@ -0,0 +1,59 @@
+use log::warn;
+
+/// Keep the requested cores that exist on this machine, warning about the rest.
+/// Returns an empty list (no pinning) where core affinity is unsupported.
+pub fn available_cores(requested: &[usize]) -> Vec<usize> {
+    if requested.is_empty() {
+        return Vec::new();
+    }
+
+    let Some(cores) = core_affinity::get_core_ids() else {
+        warn!("CPU core pinning is not supported on this platform, running unpinned");
+        return Vec::new();
+    };
+
+    let mut available = Vec::new();
+    for &id in requested {
+        if !cores.iter().any(|core| core.id == id) {
+            warn!("CPU core {} is not available, skipping it", id);
+        } else if !available.contains(&id) {
+            available.push(id);
+        }
+    }
+    available
+}
+
+/// Core assigned to the `index`-th client, round-robin over `cores`
+pub fn core_for_client(cores: &[usize], index: usize) -> Option<usize> {
+    if cores.is_empty() {
+        None
+    } else {
+        Some(cores[index % cores.len()])
+    }
+}
+
+/// Distinct cores used by the first `num_clients` clients, in ascending order
+pub fn cores_used(cores: &[usize], num_clients: usize) -> Vec<usize> {
+    let mut used: Vec<usize> = (0..num_clients)
+        .filter_map(|index| core_for_client(cores, index))
+        .collect();
+    used.sort_unstable();
+    used.dedup();
+    used
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_assignment_round_robin() {
+        assert_eq!(core_for_client(&[], 3), None);
+        assert_eq!(core_for_client(&[2, 5], 0), Some(2));
+        assert_eq!(core_for_client(&[2, 5], 3), Some(5));
+
+        assert_eq!(cores_used(&[5, 2, 7], 2), vec![2, 5]);
+        assert_eq!(cores_used(&[5, 2], 10), vec![2, 5]);
+        assert!(cores_used(&[], 4).is_empty());
+    }
+}
@@ -0,0 +1,486 @@
+use common::{
+    verify_body_signature, Result, VerificationRequest, VerificationResponse, VerificationResult,
+    ZkpFlError, SIGNATURE_HEADER,
+};
+use flate2::read::GzDecoder;
+use log::{debug, info};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::convert::Infallible;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+use warp::hyper::Body;
+use warp::reply::Response;
+use warp::{Filter, Reply};
+
+/// Configuration for the faults injected by the mock server
+#[derive(Debug, Clone)]
+pub struct FaultConfig {
+    /// Probability (0.0 - 1.0) that a request is hit by a fault
+    pub fault_rate: f64,
+    /// Extra latency added to delayed requests
+    pub delay_ms: u64,
+    /// Seed so that fault sequences are reproducible between runs
+    pub seed: u64,
+    /// Faults a hit request picks from
+    pub kinds: Vec<Fault>,
+    /// Secret submissions must be signed with, as on the real server
+    pub signing_secret: Option<String>,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            fault_rate: 0.3,
+            delay_ms: 2000,
+            seed: 42,
+            kinds: vec![Fault::Delay, Fault::DropConnection, Fault::ServerError],
+            signing_secret: None,
+        }
+    }
+}
+
+/// Kind of fault applied to a single request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Respond normally, but only after `delay_ms`
+    Delay,
+    /// Abort the connection in the middle of the response
+    DropConnection,
+    /// Respond with 503 Service Unavailable
+    ServerError,
+}
+
+/// Counters describing what the mock server did
+#[derive(Debug, Default)]
+pub struct FaultStats {
+    pub requests: AtomicUsize,
+    pub delays: AtomicUsize,
+    pub dropped_connections: AtomicUsize,
+    pub server_errors: AtomicUsize,
+}
+
+impl FaultStats {
+    pub fn total_faults(&self) -> usize {
+        self.delays.load(Ordering::Relaxed)
+            + self.dropped_connections.load(Ordering::Relaxed)
+            + self.server_errors.load(Ordering::Relaxed)
+    }
+}
+
+/// Decides, per request, whether and how to misbehave
+pub struct FaultInjector {
+    config: FaultConfig,
+    rng: Mutex<StdRng>,
+    stats: FaultStats,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultConfig) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed);
+        Self {
+            config,
+            rng: Mutex::new(rng),
+            stats: FaultStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &FaultStats {
+        &self.stats
+    }
+
+    pub fn next_fault(&self) -> Option<Fault> {
+        self.stats.requests.fetch_add(1, Ordering::Relaxed);
+
+        let mut rng = self.rng.lock().unwrap();
+        if !rng.gen_bool(self.config.fault_rate.clamp(0.0, 1.0)) {
+            return None;
+        }
+
+        let fault = *self.config.kinds.choose(&mut *rng)?;
+
+        match fault {
+            Fault::Delay => self.stats.delays.fetch_add(1, Ordering::Relaxed),
+            Fault::DropConnection => self.stats.dropped_connections.fetch_add(1, Ordering::Relaxed),
+            Fault::ServerError => self.stats.server_errors.fetch_add(1, Ordering::Relaxed),
+        };
+
+        Some(fault)
+    }
+
+    /// Apply the next fault (if any) to an otherwise healthy response
+    async fn intercept(&self, response: Response) -> Response {
+        match self.next_fault() {
+            None => response,
+            Some(Fault::Delay) => {
+                debug!("Injecting {}ms delay", self.config.delay_ms);
+                tokio::time::sleep(Duration::from_millis(self.config.delay_ms)).await;
+                response
+            }
+            Some(Fault::DropConnection) => {
+                debug!("Injecting dropped connection");
+                // A body that errors makes hyper abort the connection mid-response
+                let body = Body::wrap_stream(futures::stream::once(async {
+                    Err::<warp::hyper::body::Bytes, _>(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "injected connection drop",
+                    ))
+                }));
+                let mut dropped = Response::new(body);
+                *dropped.status_mut() = response.status();
+                dropped
+            }
+            Some(Fault::ServerError) => {
+                debug!("Injecting 503 response");
+                warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({
+                        "error": "injected fault",
+                        "code": 503
+                    })),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )
+                .into_response()
+            }
+        }
+    }
+}
+
+/// Lightweight stand-in for the verification server that injects faults.
+/// Shuts down when dropped.
+pub struct MockFaultServer {
+    addr: SocketAddr,
+    injector: Arc<FaultInjector>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockFaultServer {
+    pub fn start(config: FaultConfig) -> Result<Self> {
+        let signing_secret = config.signing_secret.clone();
+        let injector = Arc::new(FaultInjector::new(config));
+
+        let health = warp::path!("api" / "health").and(warp::get()).map(|| {
+            warp::reply::json(&serde_json::json!({
+                "status": "healthy",
+                "service": "zkp-fl-mock-server"
+            }))
+            .into_response()
+        });
+
+        let verify = warp::path!("api" / "verify")
+            .and(warp::post())
+            .and(warp::header::optional::<String>("content-encoding"))
+            .and(warp::header::optional::<String>(SIGNATURE_HEADER))
+            .and(warp::body::bytes())
+            .map(
+                move |encoding: Option<String>, signature: Option<String>, body: Bytes| {
+                    let request = match decode_request(
+                        &body,
+                        encoding.as_deref(),
+                        signature.as_deref(),
+                        signing_secret.as_deref(),
+                    ) {
+                        Ok(request) => request,
+                        Err((status, message)) => {
+                            return warp::reply::with_status(
+                                warp::reply::json(&serde_json::json!({
+                                    "error": message,
+                                    "code": status.as_u16()
+                                })),
+                                status,
+                            )
+                            .into_response()
+                        }
+                    };
+                    // Accept every proof: the scenario is about transport, not proofs
+                    warp::reply::json(&VerificationResponse {
+                        proof_id: request.proof.proof_id,
+                        result: VerificationResult {
+                            verified: true,
+                            verification_time_ms: 0,
+                            verifier_id: "mock_server".to_string(),
+                            verification_timestamp: chrono::Utc::now(),
+                            error_message: None,
+                        },
+                    })
+                    .into_response()
+                },
+            );
+
+        let fault_injector = injector.clone();
+        let routes = health
+            .or(verify)
+            .unify()
+            .and(warp::any().map(move || fault_injector.clone()))
+            .and_then(|response: Response, injector: Arc<FaultInjector>| async move {
+                Ok::<_, Infallible>(injector.intercept(response).await)
+            });
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (addr, server) = warp::serve(routes)
+            .try_bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async {
+                shutdown_rx.await.ok();
+            })
+            .map_err(|e| ZkpFlError::Network(format!("Failed to bind mock server: {}", e)))?;
+
+        tokio::spawn(server);
+        info!("Mock fault server listening on {}", addr);
+
+        Ok(Self {
+            addr,
+            injector,
+            shutdown: Some(shutdown_tx),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn stats(&self) -> &FaultStats {
+        self.injector.stats()
+    }
+}
+
+/// Read a submission the way the server does: check the signature over the
+/// bytes as sent, then decompress and parse them
+fn decode_request(
+    body: &[u8],
+    encoding: Option<&str>,
+    signature: Option<&str>,
+    signing_secret: Option<&str>,
+) -> std::result::Result<VerificationRequest, (StatusCode, String)> {
+    if let Some(secret) = signing_secret {
+        let signed =
+            signature.is_some_and(|signature| verify_body_signature(secret, body, signature));
+        if !signed {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid request signature".to_string(),
+            ));
+        }
+    }
+
+    let json = match encoding {
+        None => body.to_vec(),
+        Some(encoding) if encoding.eq_ignore_ascii_case("gzip") => {
+            let mut json = Vec::new();
+            GzDecoder::new(body)
+                .read_to_end(&mut json)
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid gzip body: {}", e)))?;
+            json
+        }
+        Some(encoding) => {
+            return Err((
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("Unsupported content encoding: {}", encoding),
+            ))
+        }
+    };
+
+    serde_json::from_slice(&json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid request body: {}", e)))
+}
+
+impl Drop for MockFaultServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::network::NetworkClient;
+    use common::{
+        CircuitParams, CommitmentScheme, ModelType, Optimizer, ProofMetadata, TrainingCommitment,
+        ZkpProof,
+    };
+    use uuid::Uuid;
+
+    fn test_proof() -> ZkpProof {
+        ZkpProof::new(
+            Uuid::new_v4(),
+            "fault_client".to_string(),
+            vec![0u8; 256],
+            vec!["0.1".to_string()],
+            CircuitParams {
+                k: 10,
+                num_constraints: 1000,
+                num_advice_columns: 10,
+                num_fixed_columns: 2,
+                max_degree: 3,
+                model_type: ModelType::Linear,
+            },
+            ProofMetadata {
+                generation_time_ms: 1000,
+                proof_size_bytes: 256,
+                witness_generation_time_ms: 300,
+                setup_time_ms: 200,
+                folding_time_ms: 500,
+                folding_iterations: 1,
+                quantization_error: None,
+                loss_comparison: None,
+            },
+            TrainingCommitment {
+                dataset_hash: "a".repeat(128),
+                num_samples: 100,
+                num_features: 5,
+                learning_rate: 0.01,
+                epochs: 10,
+                weights_commitment: "b".repeat(128),
+                final_loss: 0.1,
+                quantization: None,
+                scaler: None,
+                optimizer: Optimizer::Sgd,
+                weights_commitment_scheme: CommitmentScheme::Blake2b,
+                differential_privacy: None,
+                bias_commitment: None,
+            },
+        )
+    }
+
+    async fn probe(client: &reqwest::Client, url: &str) -> std::result::Result<(), String> {
+        let response = client
+            .get(format!("{}/api/health", url))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("status {}", response.status()));
+        }
+        response
+            .json::<serde_json::Value>()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_no_faults_when_rate_is_zero() {
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 0.0,
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        let client = reqwest::Client::new();
+
+        for _ in 0..10 {
+            assert!(probe(&client, &server.url()).await.is_ok());
+        }
+        assert_eq!(server.stats().requests.load(Ordering::Relaxed), 10);
+        assert_eq!(server.stats().total_faults(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_faults_fail_cleanly() {
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 1.0,
+            delay_ms: 500,
+            seed: 7,
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+
+        // Every request is faulted: delays hit the timeout, drops break the
+        // body and 503s are reported, but none of them hang or succeed
+        for _ in 0..30 {
+            assert!(probe(&client, &server.url()).await.is_err());
+        }
+
+        let stats = server.stats();
+        assert_eq!(stats.total_faults(), 30);
+        assert!(stats.delays.load(Ordering::Relaxed) > 0);
+        assert!(stats.dropped_connections.load(Ordering::Relaxed) > 0);
+        assert!(stats.server_errors.load(Ordering::Relaxed) > 0);
+    }
+
+    #[tokio::test]
+    async fn test_network_client_recovers_from_faults() {
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 0.5,
+            delay_ms: 20,
+            seed: 11,
+            signing_secret: Some("fault-secret".to_string()),
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        // Gzipped and signed, as the real client submits
+        let client = NetworkClient::new(&server.url())
+            .unwrap()
+            .with_signing_secret(Some("fault-secret".to_string()))
+            .with_retries(10, Duration::from_millis(1));
+
+        for _ in 0..10 {
+            let result = client.submit_proof(test_proof()).await.unwrap();
+            assert!(result.verified);
+        }
+
+        // Drops and 503s were resent rather than surfaced
+        let stats = server.stats();
+        let resent = stats.dropped_connections.load(Ordering::Relaxed)
+            + stats.server_errors.load(Ordering::Relaxed);
+        assert!(resent > 0);
+        assert_eq!(stats.requests.load(Ordering::Relaxed), 10 + resent);
+    }
+
+    #[tokio::test]
+    async fn test_network_client_gives_up_after_retries() {
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 1.0,
+            kinds: vec![Fault::ServerError],
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        let client = NetworkClient::new(&server.url())
+            .unwrap()
+            .with_retries(2, Duration::from_millis(1));
+
+        let error = client.submit_proof(test_proof()).await.unwrap_err();
+        assert!(error.to_string().contains("503"), "{}", error);
+        assert_eq!(server.stats().requests.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_submission_is_not_retried() {
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 0.0,
+            signing_secret: Some("fault-secret".to_string()),
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        let client = NetworkClient::new(&server.url())
+            .unwrap()
+            .with_retries(2, Duration::from_millis(1));
+
+        let error = client.submit_proof(test_proof()).await.unwrap_err();
+        assert!(error.to_string().contains("401"), "{}", error);
+        assert_eq!(server.stats().requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_reads_plain_bodies() {
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 0.0,
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        let client = NetworkClient::new(&server.url())
+            .unwrap()
+            .with_compression(false);
+
+        assert!(client.submit_proof(test_proof()).await.unwrap().verified);
+    }
+}
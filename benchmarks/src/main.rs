@@ -1,12 +1,20 @@
+mod affinity;
+mod fault_injection;
 mod multi_client;
+mod process;
+mod regression;
 mod scenarios;
 mod single_client;
 
 use chrono::Utc;
 use clap::Parser;
-use common::{BenchmarkResult, Config, MultiClientBenchmark, Result, ZkpFlError};
+use common::{
+    short_id, write_new_file, BenchmarkResult, Config, LatencyPercentiles, LogFormat,
+    MultiClientBenchmark, ReportTimeUnit, Result, ZkpFlError,
+};
 use log::{error, info, warn};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -42,6 +50,10 @@ pub struct Args {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Log line format: text or json
+    #[arg(long, default_value = "text")]
+    log_format: LogFormat,
+
     /// Delay between client starts (ms)
     #[arg(long, default_value = "1000")]
     client_delay_ms: u64,
@@ -49,8 +61,44 @@ pub struct Args {
     /// Maximum concurrent clients
     #[arg(long, default_value = "10")]
     max_concurrent: usize,
+
+    /// Fraction of requests hit by a fault in the network-faults scenario
+    #[arg(long, default_value = "0.3")]
+    fault_rate: f64,
+
+    /// Comma-separated CPU cores to pin clients to, assigned round-robin (opt-in)
+    #[arg(long, value_delimiter = ',')]
+    pin_cores: Vec<usize>,
+
+    /// Kill clients still running after this many seconds (0 disables);
+    /// overrides `benchmarks.client_timeout_secs`
+    #[arg(long)]
+    client_timeout_secs: Option<u64>,
+
+    /// Validate the config and print the planned client commands without
+    /// running them; the report is written with no results
+    #[arg(long)]
+    dry_run: bool,
+
+    /// How multi-client benchmarks start each client
+    #[arg(long, value_enum, default_value = "cargo")]
+    client_mode: ClientMode,
+
+    /// Prebuilt client used with `--client-mode binary`
+    #[arg(long, default_value = "target/release/client")]
+    client_binary: PathBuf,
+
+    /// Earlier `benchmark_report_*.json` to compare this run against; the
+    /// run fails if proofs or verification got slower than allowed
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Slowdown against `--baseline` tolerated before failing, in percent
+    #[arg(long, default_value = "10")]
+    max_regression_percent: f64,
 }
 
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum BenchmarkScenario {
     /// Single client performance test
@@ -63,23 +111,65 @@ pub enum BenchmarkScenario {
     StressTest,
     /// Custom scenario
     Custom,
+    /// Clients against a mock server injecting network faults
+    NetworkFaults,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClientMode {
+    /// `cargo run --bin client`, compiling the client if needed
+    Cargo,
+    /// Run the prebuilt `--client-binary`
+    Binary,
+    /// Call the client library inside the benchmark process
+    InProcess,
+}
+
+/// Client counts the stress test steps through, up to `--num-clients`
+const STRESS_LEVELS: [usize; 4] = [5, 10, 20, 50];
+
+/// What a scenario would execute, as printed by `--dry-run`
+#[derive(Debug)]
+struct DryRunPlan {
+    max_concurrent: usize,
+    commands: Vec<String>,
 }
 
 pub struct BenchmarkRunner {
     config: Config,
     args: Args,
     results: Vec<BenchmarkResult>,
+    baseline: Option<MultiClientBenchmark>,
 }
 
 impl BenchmarkRunner {
-    pub async fn new(args: Args) -> Result<Self> {
+    pub async fn new(mut args: Args) -> Result<Self> {
         // Load configuration
-        let config = Self::load_config(&args.config)?;
+        let mut config = Self::load_config(&args.config)?;
+
+        args.pin_cores = affinity::available_cores(&args.pin_cores);
+        if let Some(secs) = args.client_timeout_secs {
+            config.benchmarks.client_timeout_secs = secs;
+        }
+        if let Some(timeout) = config.benchmarks.client_timeout() {
+            info!("Killing clients that run longer than {}s", timeout.as_secs());
+        }
+        if !args.pin_cores.is_empty() {
+            info!("Pinning clients to CPU cores {:?}", args.pin_cores);
+        }
+
+        // Load the baseline up front so a bad path fails before any clients run
+        let baseline = args
+            .baseline
+            .as_deref()
+            .map(regression::load_baseline)
+            .transpose()?;
 
         Ok(Self {
             config,
             args,
             results: Vec::new(),
+            baseline,
         })
     }
 
@@ -89,6 +179,7 @@ impl BenchmarkRunner {
 
         let config: Config = toml::from_str(&config_str)
             .map_err(|e| ZkpFlError::Config(format!("Failed to parse config: {}", e)))?;
+        config.validate()?;
 
         Ok(config)
     }
@@ -113,6 +204,13 @@ impl BenchmarkRunner {
             self.check_server_availability(server_url).await?;
         }
 
+        if self.args.dry_run {
+            self.print_dry_run_plan(&self.dry_run_plan());
+            self.generate_final_report(&output_dir).await?;
+            info!("Dry run completed, no clients were started");
+            return Ok(());
+        }
+
         // Run the specific benchmark scenario
         match self.args.scenario {
             BenchmarkScenario::SingleClient => {
@@ -130,10 +228,14 @@ impl BenchmarkRunner {
             BenchmarkScenario::Custom => {
                 self.run_custom_scenario().await?;
             }
+            BenchmarkScenario::NetworkFaults => {
+                self.run_network_faults().await?;
+            }
         }
 
         // Generate and save final report
-        self.generate_final_report(&output_dir).await?;
+        let report = self.generate_final_report(&output_dir).await?;
+        self.check_regressions(&report.aggregate_metrics)?;
 
         info!("Benchmark completed successfully");
         Ok(())
@@ -235,9 +337,7 @@ impl BenchmarkRunner {
         info!("Running stress test");
 
         // Gradually increase the number of clients
-        let stress_levels = vec![5, 10, 20, 50];
-
-        for level in stress_levels {
+        for level in STRESS_LEVELS {
             if level > self.args.num_clients {
                 continue;
             }
@@ -270,7 +370,143 @@ impl BenchmarkRunner {
         Ok(())
     }
 
-    async fn generate_final_report(&self, output_dir: &PathBuf) -> Result<()> {
+    async fn run_network_faults(&mut self) -> Result<()> {
+        info!("Running network fault injection scenario");
+
+        let results = scenarios::run_network_fault_scenario(&self.config, &self.args).await?;
+
+        self.results.extend(results);
+
+        Ok(())
+    }
+
+    /// Client commands the scenario would spawn, in the order it spawns them
+    fn dry_run_plan(&self) -> DryRunPlan {
+        let args = &self.args;
+        let multi_client = |server_url: String, num_clients: usize| {
+            let benchmark = multi_client::MultiClientBenchmark::new(
+                self.config.clone(),
+                num_clients,
+                1,
+                args.max_concurrent,
+                Duration::from_millis(args.client_delay_ms),
+                server_url,
+            )
+            .with_pinned_cores(args.pin_cores.clone())
+            .with_launcher(multi_client::ClientLauncher::from_args(args))
+            .with_config_path(args.config.clone());
+            (0..num_clients)
+                .map(|client_id| benchmark.describe_client(client_id))
+                .collect::<Vec<_>>()
+        };
+        let repeat = |commands: Vec<String>| {
+            std::iter::repeat_n(commands, args.rounds).flatten().collect::<Vec<_>>()
+        };
+
+        let (max_concurrent, commands) = match args.scenario {
+            BenchmarkScenario::SingleClient => {
+                let commands = (0..args.rounds).map(|round| {
+                    let client_id = single_client::round_client_id(round);
+                    format!("{:?}", single_client::client_command(args, &client_id).as_std())
+                });
+                (1, commands.collect())
+            }
+            BenchmarkScenario::MultiClientSequential => (
+                1,
+                repeat(multi_client(
+                    multi_client::target_server_url(&self.config, args),
+                    args.num_clients,
+                )),
+            ),
+            BenchmarkScenario::MultiClientConcurrent => (
+                args.max_concurrent,
+                repeat(multi_client(
+                    multi_client::target_server_url(&self.config, args),
+                    args.num_clients,
+                )),
+            ),
+            BenchmarkScenario::StressTest => (
+                args.max_concurrent,
+                STRESS_LEVELS
+                    .into_iter()
+                    .filter(|&level| level <= args.num_clients)
+                    .flat_map(|level| {
+                        multi_client(multi_client::target_server_url(&self.config, args), level)
+                    })
+                    .collect(),
+            ),
+            BenchmarkScenario::Custom => {
+                let benchmark = single_client::SingleClientBenchmark::new(
+                    self.config.clone(),
+                    scenarios::custom_server_url(&self.config, args),
+                );
+                let round_args = benchmark.round_args(args.rounds);
+                let commands = (0..args.rounds).map(|round| {
+                    let client_id = single_client::round_client_id(round);
+                    format!("{:?}", single_client::client_command(&round_args, &client_id).as_std())
+                });
+                (1, commands.collect())
+            }
+            // The mock server only gets its address once it is started
+            BenchmarkScenario::NetworkFaults => (
+                args.max_concurrent,
+                multi_client("<mock fault server>".to_string(), args.num_clients),
+            ),
+        };
+
+        DryRunPlan {
+            max_concurrent: max_concurrent.clamp(1, args.num_clients.max(1)),
+            commands,
+        }
+    }
+
+    fn print_dry_run_plan(&self, plan: &DryRunPlan) {
+        println!("\n=== Dry Run: {:?} ===", self.args.scenario);
+        println!(
+            "Clients: {}, Rounds: {}, Max concurrent: {}",
+            self.args.num_clients, self.args.rounds, plan.max_concurrent
+        );
+        match self.config.benchmarks.client_timeout() {
+            Some(timeout) => println!("Client timeout: {}s", timeout.as_secs()),
+            None => println!("Client timeout: none"),
+        }
+        println!("Client commands ({}):", plan.commands.len());
+        for command in &plan.commands {
+            println!("  {}", command);
+        }
+        println!("==================================\n");
+    }
+
+    /// Fail if this run is slower than `--baseline` allows
+    fn check_regressions(&self, current: &common::AggregateMetrics) -> Result<()> {
+        let Some(baseline) = &self.baseline else {
+            return Ok(());
+        };
+        let threshold = self.args.max_regression_percent;
+        let regressions =
+            regression::find_regressions(&baseline.aggregate_metrics, current, threshold);
+        if regressions.is_empty() {
+            info!("No regressions over {}% against the baseline", threshold);
+            return Ok(());
+        }
+
+        println!("Performance regressions against baseline (allowed {}%):", threshold);
+        for regression in &regressions {
+            println!("  {}", regression);
+        }
+        Err(ZkpFlError::Benchmark(format!(
+            "{} metric(s) regressed beyond {}%: {}",
+            regressions.len(),
+            threshold,
+            regressions
+                .iter()
+                .map(|r| r.metric)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+    }
+
+    async fn generate_final_report(&self, output_dir: &PathBuf) -> Result<MultiClientBenchmark> {
         info!("Generating final benchmark report");
 
         // Create aggregate benchmark data
@@ -288,78 +524,50 @@ impl BenchmarkRunner {
                 .unwrap_or_else(Utc::now),
             num_clients: self.args.num_clients,
             client_results: self.results.clone(),
-            aggregate_metrics: self.calculate_aggregate_metrics(),
+            aggregate_metrics: self
+                .calculate_aggregate_metrics()
+                .rounded(self.config.benchmarks.report_precision),
         };
 
-        // Save detailed JSON report
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+        // Save detailed JSON report, suffixed so runs finishing in the same
+        // second don't share files
+        let timestamp = format!(
+            "{}_{}",
+            Utc::now().format("%Y%m%d_%H%M%S"),
+            short_id(aggregate_benchmark.benchmark_id)
+        );
         let report_file = output_dir.join(format!("benchmark_report_{}.json", timestamp));
 
         let json_report = serde_json::to_string_pretty(&aggregate_benchmark)?;
-        std::fs::write(&report_file, json_report)?;
+        write_new_file(&report_file, json_report.as_bytes())?;
 
         // Save summary report
         let summary_file = output_dir.join(format!("benchmark_summary_{}.txt", timestamp));
         let summary = self.generate_summary_report(&aggregate_benchmark);
-        std::fs::write(&summary_file, summary)?;
+        write_new_file(&summary_file, summary.as_bytes())?;
+
+        // Save one row per client for spreadsheets and dataframes
+        let csv_file = output_dir.join(format!("benchmark_{}.csv", timestamp));
+        write_client_csv(&csv_file, &client_rows(&self.results))?;
 
         info!("Reports saved:");
         info!("  Detailed: {:?}", report_file);
         info!("  Summary: {:?}", summary_file);
+        info!("  Per-client CSV: {:?}", csv_file);
 
         // Print summary to console
         self.print_summary(&aggregate_benchmark);
 
-        Ok(())
+        Ok(aggregate_benchmark)
     }
     fn calculate_aggregate_metrics(&self) -> common::AggregateMetrics {
-        if self.results.is_empty() {
-            return common::AggregateMetrics {
-                avg_proof_generation_time_ms: 0.0,
-                min_proof_generation_time_ms: 0,
-                max_proof_generation_time_ms: 0,
-                avg_proof_verification_time_ms: 0.0,
-                avg_training_time_ms: 0.0,
-                total_proofs_generated: 0,
-                total_proofs_verified: 0,
-                success_rate: 0.0,
-                throughput_proofs_per_second: 0.0,
-            };
-        } // For multi-client benchmarks, use the data from the aggregated result
-        let first_result = &self.results[0];
-
-        let successful_results = if first_result.success {
-            first_result.successful_clients.unwrap_or(1)
-        } else {
-            0
-        };
-
-        let total_clients =
-            first_result.successful_clients.unwrap_or(1) + first_result.failed_clients.unwrap_or(0);
-
-        // Calculate throughput using the total duration from the multi-client benchmark
-        let total_duration_ms = first_result.total_duration_ms;
-        let throughput = if total_duration_ms > 0 {
-            (total_clients as f64) / (total_duration_ms as f64 / 1000.0) // Convert ms to seconds
-        } else {
-            0.0
-        };
-
-        common::AggregateMetrics {
-            avg_proof_generation_time_ms: first_result.zkp_metrics.proof_generation_time_ms as f64,
-            min_proof_generation_time_ms: first_result.zkp_metrics.proof_generation_time_ms,
-            max_proof_generation_time_ms: first_result.zkp_metrics.proof_generation_time_ms,
-            avg_proof_verification_time_ms: first_result.zkp_metrics.proof_verification_time_ms
-                as f64,
-            avg_training_time_ms: first_result.training_metrics.training_time_ms as f64,
-            total_proofs_generated: total_clients,
-            total_proofs_verified: successful_results,
-            success_rate: successful_results as f64 / total_clients as f64,
-            throughput_proofs_per_second: throughput,
-        }
+        aggregate_metrics(&self.results)
     }
 
     fn generate_summary_report(&self, benchmark: &MultiClientBenchmark) -> String {
+        let unit = self.config.benchmarks.report_time_unit;
+        let p = self.config.benchmarks.report_precision;
+        let metrics = &benchmark.aggregate_metrics;
         format!(
             r#"
 ZKP-FL Benchmark Summary Report
@@ -368,7 +576,7 @@ ZKP-FL Benchmark Summary Report
 Benchmark ID: {}
 Scenario: {:?}
 Date: {}
-Duration: {:.2} seconds
+Duration: {:.p$} seconds
 
 Test Configuration:
 - Number of clients: {}
@@ -376,13 +584,20 @@ Test Configuration:
 - Total executions: {}
 
 Performance Metrics:
-- Average proof generation time: {:.2} ms
-- Min proof generation time: {} ms
-- Max proof generation time: {} ms
-- Average verification time: {:.2} ms
-- Average training time: {:.2} ms
-- Success rate: {:.1}%
-- Throughput: {:.2} proofs/second
+- Average proof generation time: {:.p$} {u}
+- Min proof generation time: {:.p$} {u}
+- Max proof generation time: {:.p$} {u}
+- Average verification time: {:.p$} {u}
+- Average training time: {:.p$} {u}
+- Success rate: {:.p$}%
+- Throughput: {:.p$} proofs/second
+
+Tail Latency:
+{}
+{}
+
+Model Quality:
+{}
 
 Results:
 - Total proofs generated: {}
@@ -390,8 +605,8 @@ Results:
 - Failed executions: {}
 
 Time Breakdown:
-- Total benchmark time: {:.2} seconds
-- Average per client: {:.2} seconds
+- Total benchmark time: {:.p$} seconds
+- Average per client: {:.p$} seconds
 
 System Performance:
 - Memory usage: Varied
@@ -399,9 +614,10 @@ System Performance:
 - Network latency: Varied
 
 Notes:
-- All times are in milliseconds unless specified
+- Durations are in {u} unless specified
 - Success rate is based on successful proof verification
 - Throughput is calculated as total proofs / total time
+- Normalized RMSE is RMSE divided by the target range, comparable across datasets
 "#,
             benchmark.benchmark_id,
             self.args.scenario,
@@ -410,24 +626,29 @@ Notes:
             benchmark.num_clients,
             self.args.rounds,
             benchmark.client_results.len(),
-            benchmark.aggregate_metrics.avg_proof_generation_time_ms,
-            benchmark.aggregate_metrics.min_proof_generation_time_ms,
-            benchmark.aggregate_metrics.max_proof_generation_time_ms,
-            benchmark.aggregate_metrics.avg_proof_verification_time_ms,
-            benchmark.aggregate_metrics.avg_training_time_ms,
-            benchmark.aggregate_metrics.success_rate * 100.0,
-            benchmark.aggregate_metrics.throughput_proofs_per_second,
-            benchmark.aggregate_metrics.total_proofs_generated,
-            benchmark.aggregate_metrics.total_proofs_verified,
-            benchmark.aggregate_metrics.total_proofs_generated
-                - benchmark.aggregate_metrics.total_proofs_verified,
+            unit.convert_ms(metrics.avg_proof_generation_time_ms),
+            unit.convert_ms(metrics.min_proof_generation_time_ms as f64),
+            unit.convert_ms(metrics.max_proof_generation_time_ms as f64),
+            unit.convert_ms(metrics.avg_proof_verification_time_ms),
+            unit.convert_ms(metrics.avg_training_time_ms),
+            metrics.success_rate * 100.0,
+            metrics.throughput_proofs_per_second,
+            format_percentiles("Proof generation", &metrics.proof_generation_percentiles, unit, p),
+            format_percentiles("Verification", &metrics.verification_percentiles, unit, p),
+            format_loss_summary(metrics.loss.as_ref()),
+            metrics.total_proofs_generated,
+            metrics.total_proofs_verified,
+            metrics.total_proofs_generated - metrics.total_proofs_verified,
             (benchmark.end_time - benchmark.start_time).num_seconds() as f64,
             (benchmark.end_time - benchmark.start_time).num_seconds() as f64
                 / benchmark.num_clients as f64,
+            p = p,
+            u = unit.label(),
         )
     }
 
     fn print_summary(&self, benchmark: &MultiClientBenchmark) {
+        let p = self.config.benchmarks.report_precision;
         println!("\n=== Benchmark Results Summary ===");
         println!("Scenario: {:?}", self.args.scenario);
         println!(
@@ -435,33 +656,242 @@ Notes:
             self.args.num_clients, self.args.rounds
         );
         println!("Total executions: {}", benchmark.client_results.len());
+        let timed_out: usize = benchmark
+            .client_results
+            .iter()
+            .map(|r| r.timed_out_clients.unwrap_or(usize::from(r.timed_out)))
+            .sum();
+        if timed_out > 0 {
+            println!("Timed out clients: {}", timed_out);
+        }
         println!(
-            "Success rate: {:.1}%",
+            "Success rate: {:.p$}%",
             benchmark.aggregate_metrics.success_rate * 100.0
         );
+        let unit = self.config.benchmarks.report_time_unit;
         println!(
-            "Average proof generation: {:.2} ms",
-            benchmark.aggregate_metrics.avg_proof_generation_time_ms
+            "Average proof generation: {:.p$} {}",
+            unit.convert_ms(benchmark.aggregate_metrics.avg_proof_generation_time_ms),
+            unit.label()
         );
         println!(
-            "Average verification: {:.2} ms",
-            benchmark.aggregate_metrics.avg_proof_verification_time_ms
+            "Average verification: {:.p$} {}",
+            unit.convert_ms(benchmark.aggregate_metrics.avg_proof_verification_time_ms),
+            unit.label()
         );
+        if let Some(loss) = &benchmark.aggregate_metrics.loss {
+            println!(
+                "Final loss: MSE {:.6}, RMSE {:.6}, normalized RMSE {:.6}",
+                loss.mse, loss.rmse, loss.normalized_rmse
+            );
+        }
         println!(
-            "Throughput: {:.2} proofs/second",
+            "Throughput: {:.p$} proofs/second",
             benchmark.aggregate_metrics.throughput_proofs_per_second
         );
         println!("==================================\n");
     }
 }
 
+/// Proof, verification and training times of one client run, in ms
+struct RunTimings {
+    proof: u64,
+    verification: u64,
+    training: u64,
+}
+
+/// Client runs behind a result: every client's run when it aggregates
+/// several clients, otherwise the result itself if it succeeded
+fn run_timings(result: &BenchmarkResult) -> Vec<RunTimings> {
+    match result.client_metrics.as_deref() {
+        Some(clients) if !clients.is_empty() => clients
+            .iter()
+            .flat_map(|client| {
+                client
+                    .proof_times
+                    .iter()
+                    .zip(&client.verification_times)
+                    .zip(&client.training_times)
+                    .map(|((proof, verification), training)| RunTimings {
+                        proof: proof.as_millis() as u64,
+                        verification: verification.as_millis() as u64,
+                        training: training.as_millis() as u64,
+                    })
+            })
+            .collect(),
+        _ if result.success => vec![RunTimings {
+            proof: result.zkp_metrics.proof_generation_time_ms,
+            verification: result.zkp_metrics.proof_verification_time_ms,
+            training: result.training_metrics.training_time_ms,
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Metrics over every round and client in `results`. Rounds run one after
+/// another, so throughput is over their summed durations.
+fn aggregate_metrics(results: &[BenchmarkResult]) -> common::AggregateMetrics {
+    let runs: Vec<RunTimings> = results.iter().flat_map(run_timings).collect();
+    let mean = |value: fn(&RunTimings) -> u64| {
+        if runs.is_empty() {
+            0.0
+        } else {
+            runs.iter().map(value).sum::<u64>() as f64 / runs.len() as f64
+        }
+    };
+
+    // Multi-client results count their clients, single runs count once
+    let total_proofs_generated: usize = results
+        .iter()
+        .map(|r| match r.successful_clients {
+            Some(successful) => successful + r.failed_clients.unwrap_or(0),
+            None => 1,
+        })
+        .sum();
+    let total_proofs_verified: usize = results
+        .iter()
+        .map(|r| r.successful_clients.unwrap_or(usize::from(r.success)))
+        .sum();
+    let total_duration_ms: u64 = results.iter().map(|r| r.total_duration_ms).sum();
+
+    common::AggregateMetrics {
+        avg_proof_generation_time_ms: mean(|run| run.proof),
+        min_proof_generation_time_ms: runs.iter().map(|run| run.proof).min().unwrap_or(0),
+        max_proof_generation_time_ms: runs.iter().map(|run| run.proof).max().unwrap_or(0),
+        avg_proof_verification_time_ms: mean(|run| run.verification),
+        avg_training_time_ms: mean(|run| run.training),
+        total_proofs_generated,
+        total_proofs_verified,
+        success_rate: if total_proofs_generated > 0 {
+            total_proofs_verified as f64 / total_proofs_generated as f64
+        } else {
+            0.0
+        },
+        throughput_proofs_per_second: if total_duration_ms > 0 {
+            total_proofs_generated as f64 / (total_duration_ms as f64 / 1000.0)
+        } else {
+            0.0
+        },
+        loss: common::LossSummary::from_metrics(results.iter().map(|r| &r.training_metrics)),
+        proof_generation_percentiles: LatencyPercentiles::from_values(
+            runs.iter().map(|run| run.proof),
+        ),
+        verification_percentiles: LatencyPercentiles::from_values(
+            runs.iter().map(|run| run.verification),
+        ),
+    }
+}
+
+/// One client's run in the per-client CSV; field order is the column order
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ClientCsvRow {
+    client_id: String,
+    training_time_ms: u64,
+    witness_time_ms: u64,
+    proof_time_ms: u64,
+    verification_time_ms: u64,
+    proof_size_bytes: usize,
+    success: bool,
+}
+
+/// Every client behind `results`, like `run_timings` but keeping failures
+fn client_rows(results: &[BenchmarkResult]) -> Vec<ClientCsvRow> {
+    results
+        .iter()
+        .flat_map(|result| match result.client_metrics.as_deref() {
+            Some(clients) if !clients.is_empty() => clients
+                .iter()
+                .map(|client| ClientCsvRow {
+                    client_id: client.client_id.clone(),
+                    training_time_ms: client.avg_training_time.as_millis() as u64,
+                    witness_time_ms: client.avg_witness_time.as_millis() as u64,
+                    proof_time_ms: client.avg_proof_time.as_millis() as u64,
+                    verification_time_ms: client.avg_verification_time.as_millis() as u64,
+                    proof_size_bytes: client.avg_proof_size,
+                    success: client.success_count > 0 && client.failure_count == 0,
+                })
+                .collect(),
+            _ => vec![ClientCsvRow {
+                client_id: result.client_id.clone(),
+                training_time_ms: result.training_metrics.training_time_ms,
+                witness_time_ms: result.zkp_metrics.witness_generation_time_ms,
+                proof_time_ms: result.zkp_metrics.proof_generation_time_ms,
+                verification_time_ms: result.zkp_metrics.proof_verification_time_ms,
+                proof_size_bytes: result.zkp_metrics.proof_size_bytes,
+                success: result.success,
+            }],
+        })
+        .collect()
+}
+
+fn write_client_csv(path: &Path, rows: &[ClientCsvRow]) -> Result<()> {
+    let csv_error = |e: csv::Error| ZkpFlError::Benchmark(format!("Failed to write CSV: {}", e));
+    // Write the header explicitly so a run without clients still has one
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_path(path)
+        .map_err(csv_error)?;
+    writer
+        .write_record([
+            "client_id",
+            "training_time_ms",
+            "witness_time_ms",
+            "proof_time_ms",
+            "verification_time_ms",
+            "proof_size_bytes",
+            "success",
+        ])
+        .map_err(csv_error)?;
+    for row in rows {
+        writer.serialize(row).map_err(csv_error)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Summary line with the tail latencies of one timing
+fn format_percentiles(
+    label: &str,
+    percentiles: &LatencyPercentiles,
+    unit: ReportTimeUnit,
+    precision: usize,
+) -> String {
+    format!(
+        "- {}: p50 {:.p$}, p90 {:.p$}, p95 {:.p$}, p99 {:.p$} {}",
+        label,
+        unit.convert_ms(percentiles.p50_ms),
+        unit.convert_ms(percentiles.p90_ms),
+        unit.convert_ms(percentiles.p95_ms),
+        unit.convert_ms(percentiles.p99_ms),
+        unit.label(),
+        p = precision,
+    )
+}
+
+/// Summary lines for model quality; loss is kept at full precision since
+/// typical values vanish at report precision
+fn format_loss_summary(loss: Option<&common::LossSummary>) -> String {
+    match loss {
+        Some(loss) => format!(
+            "- Final loss (MSE): {:.6}\n- RMSE: {:.6}\n- Normalized RMSE: {:.6}",
+            loss.mse, loss.rmse, loss.normalized_rmse
+        ),
+        None => "- Not available (no target statistics reported)".to_string(),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Initialize logging
     let log_level = if args.verbose { "debug" } else { "info" };
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level));
+    if args.log_format == LogFormat::Json {
+        logger.format(|buf, record| writeln!(buf, "{}", common::json_log_line(record)));
+    }
+    logger.init();
 
     info!("ZKP-FL Benchmarking Tool starting...");
 
@@ -478,3 +908,153 @@ async fn main() -> Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ClientReport;
+
+    #[tokio::test]
+    async fn test_dry_run_starts_no_clients() {
+        let output_dir = std::env::temp_dir().join(format!("zkp_fl_dry_run_{}", Uuid::new_v4()));
+        let config = concat!(env!("CARGO_MANIFEST_DIR"), "/../config.toml");
+        let args = Args::try_parse_from([
+            "zkp-fl-benchmarks",
+            "--config",
+            config,
+            "--scenario",
+            "multi-client-concurrent",
+            "--num-clients",
+            "3",
+            "--rounds",
+            "2",
+            "--max-concurrent",
+            "2",
+            "--output-dir",
+            output_dir.to_str().unwrap(),
+            "--dry-run",
+        ])
+        .unwrap();
+
+        let mut runner = BenchmarkRunner::new(args).await.unwrap();
+        let plan = runner.dry_run_plan();
+        assert_eq!(plan.max_concurrent, 2);
+        assert_eq!(plan.commands.len(), 6);
+        assert!(plan.commands[2].contains("\"benchmark_client_2\""));
+
+        runner.run().await.unwrap();
+        assert!(runner.results.is_empty());
+
+        let report = std::fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .unwrap();
+        let report: MultiClientBenchmark =
+            serde_json::from_str(&std::fs::read_to_string(report).unwrap()).unwrap();
+        assert!(report.client_results.is_empty());
+        assert_eq!(report.aggregate_metrics.total_proofs_generated, 0);
+
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    fn round_result(proof_ms: u64, verification_ms: u64, success: bool) -> BenchmarkResult {
+        let mut result = BenchmarkResult::new(Uuid::new_v4(), "client".to_string());
+        result.zkp_metrics.proof_generation_time_ms = proof_ms;
+        result.zkp_metrics.proof_verification_time_ms = verification_ms;
+        result.training_metrics.training_time_ms = 10;
+        result.finish(success, None);
+        result.total_duration_ms = 1000;
+        result
+    }
+
+    #[test]
+    fn test_aggregate_metrics_cover_every_result() {
+        let mut multi_client = round_result(0, 0, true);
+        multi_client.successful_clients = Some(2);
+        multi_client.failed_clients = Some(1);
+        multi_client.client_metrics = Some(
+            [(400, 40), (700, 70)]
+                .into_iter()
+                .map(|(proof, verification)| {
+                    let mut report = ClientReport::from(&round_result(proof, verification, true));
+                    report.total_duration_ms = 1000;
+                    report.client_metrics()
+                })
+                .collect(),
+        );
+        let results = vec![
+            round_result(100, 10, true),
+            round_result(300, 30, true),
+            // Failed runs count against the success rate but have no timings
+            round_result(0, 0, false),
+            multi_client,
+        ];
+
+        let metrics = aggregate_metrics(&results);
+        assert_eq!(metrics.min_proof_generation_time_ms, 100);
+        assert_eq!(metrics.max_proof_generation_time_ms, 700);
+        assert_eq!(metrics.avg_proof_generation_time_ms, 375.0);
+        assert!(
+            (metrics.min_proof_generation_time_ms as f64) < metrics.avg_proof_generation_time_ms
+        );
+        assert!(metrics.avg_proof_generation_time_ms < metrics.max_proof_generation_time_ms as f64);
+        assert_eq!(metrics.avg_proof_verification_time_ms, 37.5);
+        assert_eq!(metrics.avg_training_time_ms, 10.0);
+        assert_eq!(metrics.total_proofs_generated, 6);
+        assert_eq!(metrics.total_proofs_verified, 4);
+        assert!((metrics.success_rate - 4.0 / 6.0).abs() < 1e-12);
+        assert!((metrics.throughput_proofs_per_second - 1.5).abs() < 1e-12);
+        // Runs of 100, 300, 400 and 700ms
+        assert_eq!(metrics.proof_generation_percentiles.p50_ms, 300.0);
+        assert_eq!(metrics.proof_generation_percentiles.p99_ms, 700.0);
+        assert_eq!(metrics.verification_percentiles.p90_ms, 70.0);
+
+        let empty = aggregate_metrics(&[]);
+        assert_eq!(empty.total_proofs_generated, 0);
+        assert_eq!(empty.success_rate, 0.0);
+    }
+
+    #[test]
+    fn test_client_csv_round_trip() {
+        let mut multi_client = round_result(0, 0, true);
+        multi_client.client_metrics = Some(
+            [("a", 400, true), ("b", 700, false)]
+                .into_iter()
+                .map(|(client_id, proof, success)| {
+                    let mut report = ClientReport::from(&round_result(proof, 70, success));
+                    report.client_id = client_id.to_string();
+                    report.zkp_metrics.proof_size_bytes = 1082;
+                    report.client_metrics()
+                })
+                .collect(),
+        );
+        let results = vec![round_result(100, 10, true), multi_client];
+        let rows = client_rows(&results);
+        assert_eq!(rows.len(), 3);
+
+        let path = std::env::temp_dir().join(format!("zkp_fl_clients_{}.csv", Uuid::new_v4()));
+        write_client_csv(&path, &rows).unwrap();
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec![
+                "client_id",
+                "training_time_ms",
+                "witness_time_ms",
+                "proof_time_ms",
+                "verification_time_ms",
+                "proof_size_bytes",
+                "success",
+            ]
+        );
+        let read: Vec<ClientCsvRow> = reader.deserialize().map(|row| row.unwrap()).collect();
+        assert_eq!(read, rows);
+        assert_eq!(read[0].client_id, "client");
+        assert_eq!(read[1].proof_time_ms, 400);
+        assert_eq!(read[2].proof_size_bytes, 1082);
+        assert!(!read[2].success);
+        std::fs::remove_file(path).unwrap();
+    }
+}
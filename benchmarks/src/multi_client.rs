@@ -1,14 +1,39 @@
 use chrono::Utc;
 use common::types::ProofResult;
-use common::{BenchmarkResult, ClientMetrics, Config, Result, ZkpFlError};
+use common::{BenchmarkResult, ClientMetrics, ClientReport, Config, Result, ZkpFlError};
 use futures::future::try_join_all;
 use log::{debug, error, info};
 use rand;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+/// How each benchmark client is started
+#[derive(Debug, Clone, Default)]
+pub enum ClientLauncher {
+    /// `cargo run --bin client`, which may rebuild the client first
+    #[default]
+    Cargo,
+    /// A prebuilt client binary
+    Binary(PathBuf),
+    /// The client library, run inside the benchmark process
+    InProcess,
+}
+
+impl ClientLauncher {
+    pub fn from_args(args: &crate::Args) -> Self {
+        match args.client_mode {
+            crate::ClientMode::Cargo => Self::Cargo,
+            crate::ClientMode::Binary => Self::Binary(args.client_binary.clone()),
+            crate::ClientMode::InProcess => Self::InProcess,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct MultiClientBenchmark {
     config: Config,
     num_clients: usize,
@@ -16,6 +41,9 @@ pub struct MultiClientBenchmark {
     max_concurrent: usize,
     client_delay: Duration,
     server_url: String,
+    pinned_cores: Vec<usize>,
+    launcher: ClientLauncher,
+    config_path: String,
 }
 
 impl MultiClientBenchmark {
@@ -34,6 +62,35 @@ impl MultiClientBenchmark {
             max_concurrent,
             client_delay,
             server_url,
+            pinned_cores: Vec::new(),
+            launcher: ClientLauncher::default(),
+            config_path: "config.toml".to_string(),
+        }
+    }
+
+    /// Pin clients to these CPU cores, assigned round-robin by client id
+    pub fn with_pinned_cores(mut self, cores: Vec<usize>) -> Self {
+        self.pinned_cores = cores;
+        self
+    }
+
+    pub fn with_launcher(mut self, launcher: ClientLauncher) -> Self {
+        self.launcher = launcher;
+        self
+    }
+
+    /// Config file the clients load, `config.toml` by default
+    pub fn with_config_path(mut self, path: impl Into<String>) -> Self {
+        self.config_path = path.into();
+        self
+    }
+
+    /// Copy of this benchmark that runs a single client, for per-client tasks
+    fn single_client_task(&self) -> Self {
+        Self {
+            num_clients: 1,
+            max_concurrent: 1,
+            ..self.clone()
         }
     }
 
@@ -44,6 +101,7 @@ impl MultiClientBenchmark {
         let mut all_metrics = Vec::new();
         let mut successful_clients = 0;
         let mut failed_clients = 0;
+        let mut timed_out_clients = 0;
 
         for client_id in 0..self.num_clients {
             info!("Starting client {}/{}", client_id + 1, self.num_clients);
@@ -57,6 +115,9 @@ impl MultiClientBenchmark {
                 Err(e) => {
                     error!("Client {} failed: {}", client_id, e);
                     failed_clients += 1;
+                    if matches!(e, ZkpFlError::Timeout(_)) {
+                        timed_out_clients += 1;
+                    }
                 }
             }
 
@@ -74,6 +135,7 @@ impl MultiClientBenchmark {
             all_metrics,
             successful_clients,
             failed_clients,
+            timed_out_clients,
         ))
     }
 
@@ -89,14 +151,7 @@ impl MultiClientBenchmark {
                     client_id as u64 * self.client_delay.as_millis() as u64
                         / self.num_clients as u64,
                 );
-                let benchmark = MultiClientBenchmark::new(
-                    self.config.clone(),
-                    1, // Single client for this task
-                    self.rounds,
-                    1, // Single concurrent for individual client
-                    self.client_delay,
-                    self.server_url.clone(),
-                );
+                let benchmark = self.single_client_task();
 
                 tokio::spawn(async move {
                     // Stagger client starts
@@ -115,6 +170,7 @@ impl MultiClientBenchmark {
         let mut all_metrics = Vec::new();
         let mut successful_clients = 0;
         let mut failed_clients = 0;
+        let mut timed_out_clients = 0;
 
         for result in results {
             match result {
@@ -125,6 +181,9 @@ impl MultiClientBenchmark {
                 Err(e) => {
                     error!("Client failed: {}", e);
                     failed_clients += 1;
+                    if matches!(e, ZkpFlError::Timeout(_)) {
+                        timed_out_clients += 1;
+                    }
                 }
             }
         }
@@ -137,6 +196,7 @@ impl MultiClientBenchmark {
             all_metrics,
             successful_clients,
             failed_clients,
+            timed_out_clients,
         ))
     }
 
@@ -151,6 +211,7 @@ impl MultiClientBenchmark {
         let mut all_metrics = Vec::new();
         let mut successful_clients = 0;
         let mut failed_clients = 0;
+        let mut timed_out_clients = 0;
 
         for batch_start in (0..self.num_clients).step_by(batch_size) {
             let batch_end = (batch_start + batch_size).min(self.num_clients);
@@ -159,14 +220,7 @@ impl MultiClientBenchmark {
             let batch_tasks: Vec<_> = (batch_start..batch_end)
                 .map(|client_id| {
                     let semaphore = semaphore.clone();
-                    let benchmark = MultiClientBenchmark::new(
-                        self.config.clone(),
-                        1, // Single client for this task
-                        self.rounds,
-                        1, // Single concurrent for individual client
-                        self.client_delay,
-                        self.server_url.clone(),
-                    );
+                    let benchmark = self.single_client_task();
 
                     tokio::spawn(async move {
                         let _permit = semaphore.acquire().await.unwrap();
@@ -188,6 +242,9 @@ impl MultiClientBenchmark {
                     Err(e) => {
                         error!("Client in batch failed: {}", e);
                         failed_clients += 1;
+                        if matches!(e, ZkpFlError::Timeout(_)) {
+                            timed_out_clients += 1;
+                        }
                     }
                 }
             }
@@ -206,14 +263,11 @@ impl MultiClientBenchmark {
             all_metrics,
             successful_clients,
             failed_clients,
+            timed_out_clients,
         ))
     }
-    async fn run_single_client(&self, client_id: usize) -> Result<ClientMetrics> {
-        debug!("Running actual client {}", client_id);
-
-        let start_time = Instant::now();
-        let client_name = format!("benchmark_client_{}", client_id);
-
+    /// Command-line arguments `client_id` is started with
+    fn client_args(&self, client_id: usize) -> Vec<String> {
         // Ensure server URL has proper http:// prefix
         let server_url =
             if self.server_url.starts_with("http://") || self.server_url.starts_with("https://") {
@@ -222,234 +276,117 @@ impl MultiClientBenchmark {
                 format!("http://{}", self.server_url)
             };
 
-        // Prepare client command
-        let mut cmd = tokio::process::Command::new("cargo");
-        cmd.args(&["run", "--bin", "client"])
-            .arg("--")
-            .arg("--config")
-            .arg("config.toml")
-            .arg("--client-id")
-            .arg(&client_name)
-            .arg("--epochs")
-            .arg("10")
-            .arg("--server-url")
-            .arg(&server_url)
-            .arg("--benchmark")
-            .arg("--verbose");
-
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
-
-        debug!(
-            "Executing client command for client {}: {:?}",
-            client_id, cmd
-        );
-
-        // Execute the client
-        let output = cmd.output().await.map_err(|e| {
-            ZkpFlError::Config(format!("Failed to execute client {}: {}", client_id, e))
-        })?;
-
-        let total_time = start_time.elapsed();
+        let mut args = vec![
+            "--config".to_string(),
+            self.config_path.clone(),
+            "--client-id".to_string(),
+            format!("benchmark_client_{}", client_id),
+            "--epochs".to_string(),
+            "10".to_string(),
+            "--server-url".to_string(),
+            server_url,
+            "--benchmark".to_string(),
+            "--verbose".to_string(),
+        ];
 
-        // Try to parse JSON file first, fallback to stdout parsing
-        let json_metrics = self.parse_client_json_file(&client_name).await;
+        if let Some(core) = crate::affinity::core_for_client(&self.pinned_cores, client_id) {
+            args.push("--cpu-core".to_string());
+            args.push(core.to_string());
+        }
+        args
+    }
 
-        match json_metrics {
-            Ok(metrics) => {
-                info!("Successfully parsed JSON metrics for client {}", client_id);
-                Ok(metrics)
+    /// Process spawned for `client_id` by the cargo and binary launchers
+    fn client_command(&self, client_id: usize) -> tokio::process::Command {
+        let mut cmd = match &self.launcher {
+            ClientLauncher::Binary(path) => tokio::process::Command::new(path),
+            ClientLauncher::Cargo | ClientLauncher::InProcess => {
+                let mut cmd = tokio::process::Command::new("cargo");
+                cmd.args(["run", "--bin", "client", "--"]);
+                cmd
             }
-            Err(_) => {
-                // Fallback to stdout parsing if JSON file not found
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    error!("Client {} failed. Stderr: {}", client_id, stderr);
-                    return Ok(ClientMetrics {
-                        client_id: client_name,
-                        training_times: vec![],
-                        proof_times: vec![],
-                        witness_times: vec![],
-                        verification_times: vec![],
-                        proof_sizes: vec![],
-                        success_count: 0,
-                        failure_count: 1,
-                        avg_training_time: Duration::ZERO,
-                        avg_proof_time: Duration::ZERO,
-                        avg_witness_time: Duration::ZERO,
-                        avg_verification_time: Duration::ZERO,
-                        avg_proof_size: 0,
-                        total_time,
-                    });
-                }
+        };
+        cmd.args(self.client_args(client_id));
+        cmd
+    }
 
-                // Parse client output for performance metrics
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let performance = self.parse_client_output(&stdout);
-                Ok(ClientMetrics {
-                    client_id: client_name,
-                    training_times: vec![performance.training_time],
-                    proof_times: vec![performance.proof_time],
-                    witness_times: vec![performance.witness_time],
-                    verification_times: vec![performance.verification_time],
-                    proof_sizes: vec![performance.proof_size],
-                    success_count: 1,
-                    failure_count: 0,
-                    avg_training_time: performance.training_time,
-                    avg_proof_time: performance.proof_time,
-                    avg_witness_time: performance.witness_time,
-                    avg_verification_time: performance.verification_time,
-                    avg_proof_size: performance.proof_size,
-                    total_time,
-                })
+    /// How `client_id` is started, as logged and printed by `--dry-run`
+    pub fn describe_client(&self, client_id: usize) -> String {
+        match self.launcher {
+            ClientLauncher::InProcess => {
+                format!("in-process client {:?}", self.client_args(client_id))
             }
+            _ => format!("{:?}", self.client_command(client_id).as_std()),
         }
     }
 
-    async fn parse_client_json_file(&self, client_name: &str) -> Result<ClientMetrics> {
-        use serde_json::Value;
-        use std::fs;
-        use std::path::Path;
-
-        // Look for the most recent JSON file for this client
-        let benchmark_dir = Path::new("./benchmarks");
-        let mut latest_file = None;
-        let mut latest_time = std::time::SystemTime::UNIX_EPOCH;
-
-        if benchmark_dir.exists() {
-            for entry in fs::read_dir(benchmark_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                    if filename.contains(client_name)
-                        && filename.ends_with(".json")
-                        && !filename.contains("_report_")
-                    {
-                        if let Ok(metadata) = entry.metadata() {
-                            if let Ok(modified) = metadata.modified() {
-                                if modified > latest_time {
-                                    latest_time = modified;
-                                    latest_file = Some(path);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    /// Run the client library inside this process, so timings leave out
+    /// process startup and compilation. `--cpu-core` has no effect here.
+    async fn run_in_process(&self, client_id: usize) -> Result<ClientMetrics> {
+        let argv = std::iter::once("client".to_string()).chain(self.client_args(client_id));
+        let args = <client::Args as clap::Parser>::try_parse_from(argv)
+            .map_err(|e| ZkpFlError::Config(format!("Invalid client arguments: {}", e)))?;
+        let mut client = client::Client::new(args).await?;
+
+        let run = client.run_to_completion(CancellationToken::new());
+        let outcome = match self.config.benchmarks.client_timeout() {
+            None => run.await,
+            Some(limit) => tokio::time::timeout(limit, run).await.map_err(|_| {
+                ZkpFlError::Timeout(format!(
+                    "client {}: did not finish within {}s",
+                    client_id,
+                    limit.as_secs_f64()
+                ))
+            })?,
+        };
+        if let Err(e) = outcome {
+            error!("In-process client {} failed: {}", client_id, e);
         }
 
-        if let Some(json_path) = latest_file {
-            let json_content = fs::read_to_string(&json_path).map_err(|e| {
-                ZkpFlError::Benchmark(format!("Failed to read JSON file {:?}: {}", json_path, e))
-            })?;
+        Ok(ClientReport::from(client.benchmark_result()).client_metrics())
+    }
 
-            let json_data: Value = serde_json::from_str(&json_content)
-                .map_err(|e| ZkpFlError::Benchmark(format!("Failed to parse JSON: {}", e)))?;
-            // Extract metrics from JSON
-            let zkp_metrics = &json_data["zkp_metrics"];
-            let training_metrics = &json_data["training_metrics"];
-
-            let training_time =
-                Duration::from_millis(training_metrics["training_time_ms"].as_u64().unwrap_or(0));
-            let proof_time = Duration::from_millis(
-                zkp_metrics["proof_generation_time_ms"]
-                    .as_u64()
-                    .unwrap_or(0),
-            );
-            let witness_time = Duration::from_millis(
-                zkp_metrics["witness_generation_time_ms"]
-                    .as_u64()
-                    .unwrap_or(0),
-            );
-            let verification_time = Duration::from_millis(
-                zkp_metrics["proof_verification_time_ms"]
-                    .as_u64()
-                    .unwrap_or(0),
-            );
-            let proof_size = zkp_metrics["proof_size_bytes"].as_u64().unwrap_or(0) as usize;
-            let success = json_data["success"].as_bool().unwrap_or(false);
-
-            let total_duration =
-                Duration::from_millis(json_data["total_duration_ms"].as_u64().unwrap_or(0));
-            Ok(ClientMetrics {
-                client_id: client_name.to_string(),
-                training_times: vec![training_time],
-                proof_times: vec![proof_time],
-                witness_times: vec![witness_time],
-                verification_times: vec![verification_time],
-                proof_sizes: vec![proof_size],
-                success_count: if success { 1 } else { 0 },
-                failure_count: if success { 0 } else { 1 },
-                avg_training_time: training_time,
-                avg_proof_time: proof_time,
-                avg_witness_time: witness_time,
-                avg_verification_time: verification_time,
-                avg_proof_size: proof_size,
-                total_time: total_duration,
-            })
-        } else {
-            Err(ZkpFlError::Benchmark("JSON file not found".to_string()))
+    async fn run_single_client(&self, client_id: usize) -> Result<ClientMetrics> {
+        if let ClientLauncher::InProcess = self.launcher {
+            return self.run_in_process(client_id).await;
         }
-    }
-    fn parse_client_output(&self, output: &str) -> ClientPerformance {
-        // Extract performance metrics from client logs
-        let mut training_time = Duration::from_millis(10);
-        let mut proof_time = Duration::from_millis(100);
-        let mut witness_time = Duration::from_millis(30); // Default estimated witness time
-        let mut verification_time = Duration::from_millis(50);
-        let mut proof_size = 1082;
-
-        for line in output.lines() {
-            if line.contains("Model training completed in") {
-                if let Some(time_str) = line.split("in ").nth(1) {
-                    if let Some(ms_str) = time_str.split("ms").next() {
-                        if let Ok(ms) = ms_str.parse::<u64>() {
-                            training_time = Duration::from_millis(ms);
-                        }
-                    }
-                }
-            } else if line.contains("Proof generated in") {
-                if let Some(time_str) = line.split("in ").nth(1) {
-                    if let Some(ms_str) = time_str.split("ms").next() {
-                        if let Ok(ms) = ms_str.parse::<u64>() {
-                            proof_time = Duration::from_millis(ms);
-                        }
-                    }
-                }
-            } else if line.contains("Witness time:") {
-                if let Some(time_str) = line.split("Witness time: ").nth(1) {
-                    if let Some(ms_str) = time_str.split("ms").next() {
-                        if let Ok(ms) = ms_str.parse::<u64>() {
-                            witness_time = Duration::from_millis(ms);
-                        }
-                    }
-                }
-            } else if line.contains("Proof verified successfully in") {
-                if let Some(time_str) = line.split("in ").nth(1) {
-                    if let Some(ms_str) = time_str.split("ms").next() {
-                        if let Ok(ms) = ms_str.parse::<u64>() {
-                            verification_time = Duration::from_millis(ms);
-                        }
-                    }
+        debug!("Running actual client {}", client_id);
+
+        let client_name = format!("benchmark_client_{}", client_id);
+        let launched_at = SystemTime::now();
+        let mut cmd = self.client_command(client_id);
+
+        debug!(
+            "Executing client command for client {}: {:?}",
+            client_id, cmd
+        );
+
+        // Execute the client; a timeout is passed through so it is counted as such
+        let timeout = self.config.benchmarks.client_timeout();
+        let output = crate::process::output_with_timeout(&mut cmd, timeout)
+            .await
+            .map_err(|e| match e {
+                ZkpFlError::Timeout(reason) => {
+                    ZkpFlError::Timeout(format!("client {}: {}", client_id, reason))
                 }
-            } else if line.contains("size: ") && line.contains("bytes") {
-                if let Some(size_str) = line.split("size: ").nth(1) {
-                    if let Some(bytes_str) = size_str.split(" bytes").next() {
-                        if let Ok(size) = bytes_str.parse::<usize>() {
-                            proof_size = size;
-                        }
-                    }
+                e => ZkpFlError::Config(format!("Failed to execute client {}: {}", client_id, e)),
+            })?;
+
+        // Metrics come only from the report the client saves, never its logs
+        let output_dir = Path::new(&self.config.benchmarks.output_path);
+        match ClientReport::find_latest(output_dir, &client_name, launched_at) {
+            Ok(report) => {
+                debug!("Read benchmark report for client {}", client_id);
+                Ok(report.client_metrics())
+            }
+            Err(e) => {
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    error!("Client {} failed. Stderr: {}", client_id, stderr);
                 }
+                Err(e)
             }
         }
-
-        ClientPerformance {
-            training_time,
-            proof_time,
-            witness_time,
-            verification_time,
-            proof_size,
-        }
     }
 
     async fn simulate_training(
@@ -528,6 +465,7 @@ impl MultiClientBenchmark {
         client_metrics: Vec<ClientMetrics>,
         successful_clients: usize,
         failed_clients: usize,
+        timed_out_clients: usize,
     ) -> BenchmarkResult {
         let avg_training_time = if !client_metrics.is_empty() {
             let total_training_count = client_metrics
@@ -626,6 +564,7 @@ impl MultiClientBenchmark {
             circuit_advice_columns: 0, // Not tracked in benchmarks
             circuit_fixed_columns: 0,  // Not tracked in benchmarks
             folding_iterations: 0,     // Not tracked in benchmarks
+            quantization_error: None,
         };
 
         let training_metrics = common::TrainingMetrics {
@@ -637,6 +576,13 @@ impl MultiClientBenchmark {
             initial_loss: 0.0,    // Not aggregated
             convergence_epoch: None,
             loss_history: vec![],
+            degenerate_target: false,
+            target_min: None,
+            target_max: None,
+            batches_per_epoch: 0,
+            stopped_early: false,
+            best_epoch: None,
+            learning_rate_history: vec![],
         };
 
         let mut result =
@@ -653,10 +599,12 @@ impl MultiClientBenchmark {
         result.timestamp = Some(Utc::now());
         result.scenario = Some(scenario_type.to_string());
         result.num_clients = Some(self.num_clients);
+        result.pinned_cores = crate::affinity::cores_used(&self.pinned_cores, self.num_clients);
         result.num_rounds = Some(self.rounds);
         result.total_duration = Some(total_duration);
         result.successful_clients = Some(successful_clients);
         result.failed_clients = Some(failed_clients);
+        result.timed_out_clients = Some(timed_out_clients);
         result.avg_training_time = Some(avg_training_time);
         result.avg_proof_time = Some(avg_proof_time);
         result.avg_verification_time = Some(avg_verification_time);
@@ -669,6 +617,14 @@ impl MultiClientBenchmark {
     }
 }
 
+/// Server the multi-client scenarios point clients at: `--server-url`, or
+/// the configured server address
+pub fn target_server_url(config: &Config, args: &crate::Args) -> String {
+    args.server_url
+        .clone()
+        .unwrap_or_else(|| config.server.host.clone() + ":" + &config.server.port.to_string())
+}
+
 /// Run sequential benchmark - simplified implementation
 pub async fn run_sequential_benchmark(
     config: &Config,
@@ -686,10 +642,11 @@ pub async fn run_sequential_benchmark(
         1, // Single round
         1, // Sequential: max 1 concurrent
         Duration::from_millis(args.client_delay_ms),
-        args.server_url
-            .clone()
-            .unwrap_or_else(|| config.server.host.clone() + ":" + &config.server.port.to_string()),
-    );
+        target_server_url(config, args),
+    )
+    .with_pinned_cores(args.pin_cores.clone())
+    .with_launcher(ClientLauncher::from_args(args))
+    .with_config_path(args.config.clone());
 
     let result = benchmark.run_sequential().await?;
     Ok(vec![result])
@@ -712,10 +669,11 @@ pub async fn run_concurrent_benchmark(
         1, // Single round
         args.max_concurrent,
         Duration::from_millis(args.client_delay_ms),
-        args.server_url
-            .clone()
-            .unwrap_or_else(|| config.server.host.clone() + ":" + &config.server.port.to_string()),
-    );
+        target_server_url(config, args),
+    )
+    .with_pinned_cores(args.pin_cores.clone())
+    .with_launcher(ClientLauncher::from_args(args))
+    .with_config_path(args.config.clone());
 
     let result = benchmark.run_concurrent().await?;
     Ok(vec![result])
@@ -730,11 +688,41 @@ struct TrainingResult {
     dataset_size: usize,
 }
 
-#[derive(Debug)]
-struct ClientPerformance {
-    training_time: Duration,
-    proof_time: Duration,
-    witness_time: Duration,
-    verification_time: Duration,
-    proof_size: usize,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fault_injection::{FaultConfig, MockFaultServer};
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_in_process_client_reports_metrics() {
+        let dir = std::env::temp_dir().join(format!("zkp_fl_in_process_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config: Config = toml::from_str(include_str!("../../config.toml")).unwrap();
+        config.benchmarks.output_path = dir.join("results").to_string_lossy().into_owned();
+        config.client.proof_cache_dir = dir.join("proof_cache").to_string_lossy().into_owned();
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let server = MockFaultServer::start(FaultConfig {
+            fault_rate: 0.0,
+            ..FaultConfig::default()
+        })
+        .unwrap();
+        let benchmark = MultiClientBenchmark::new(config, 1, 1, 1, Duration::ZERO, server.url())
+            .with_launcher(ClientLauncher::InProcess)
+            .with_config_path(config_path.to_string_lossy());
+        assert!(benchmark.describe_client(0).starts_with("in-process client"));
+
+        let metrics = benchmark.run_single_client(0).await.unwrap();
+        assert_eq!(metrics.client_id, "benchmark_client_0");
+        assert_eq!((metrics.success_count, metrics.failure_count), (1, 0));
+        assert_eq!(metrics.proof_sizes.len(), 1);
+        assert!(metrics.avg_proof_size > 0);
+        assert!(metrics.total_time > Duration::ZERO);
+        // The proof went to the mock server rather than a spawned client
+        assert!(server.stats().requests.load(Ordering::Relaxed) > 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,143 @@
+use common::{Result, ZkpFlError};
+use std::process::{Output, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+
+/// Run `cmd` to completion and collect its output, killing it and every
+/// process it started if it is still running after `timeout`
+pub async fn output_with_timeout(cmd: &mut Command, timeout: Option<Duration>) -> Result<Output> {
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Lead a process group of its own, so a timeout also reaches the
+    // processes it starts, e.g. the client `cargo run` builds and launches
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd.spawn()?;
+
+    // Drain both pipes while waiting so a chatty child never blocks on a full pipe
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = match timeout {
+        None => child.wait().await?,
+        Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+            Ok(status) => status?,
+            Err(_) => {
+                kill_process_group(&child);
+                // kill() waits for the process too, so it is reaped, not left a zombie
+                child.kill().await?;
+                return Err(ZkpFlError::Timeout(format!(
+                    "Client did not finish within {}s and was killed",
+                    limit.as_secs_f64()
+                )));
+            }
+        },
+    };
+
+    Ok(Output {
+        status,
+        stdout: join_pipe(stdout).await?,
+        stderr: join_pipe(stderr).await?,
+    })
+}
+
+#[cfg(unix)]
+fn kill_process_group(child: &Child) {
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+
+    if let Some(pid) = child.id() {
+        if let Err(e) = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL) {
+            log::warn!("Failed to kill process group {}: {}", pid, e);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_child: &Child) {}
+
+fn read_pipe<R>(pipe: Option<R>) -> JoinHandle<std::io::Result<Vec<u8>>>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buffer).await?;
+        }
+        Ok(buffer)
+    })
+}
+
+async fn join_pipe(handle: JoinHandle<std::io::Result<Vec<u8>>>) -> Result<Vec<u8>> {
+    handle
+        .await
+        .map_err(|e| ZkpFlError::Benchmark(format!("Failed to read client output: {}", e)))?
+        .map_err(ZkpFlError::from)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Processes in group `pgid` that have not exited, read from /proc
+    #[cfg(target_os = "linux")]
+    fn live_group_members(pgid: u32) -> usize {
+        let pgid = pgid.to_string();
+        std::fs::read_dir("/proc")
+            .unwrap()
+            .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path().join("stat")).ok())
+            .filter(|stat| {
+                // After the command name in parentheses: state, ppid, pgrp, ...
+                let fields: Vec<&str> = stat
+                    .rsplit_once(')')
+                    .map_or_else(Vec::new, |(_, rest)| rest.split_whitespace().collect());
+                fields.get(2) == Some(&pgid.as_str()) && fields.first() != Some(&"Z")
+            })
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_hung_child_is_killed() {
+        // The shell forks sleep rather than exec'ing it, leaving a grandchild
+        // the way `cargo run` does
+        let pid_file = std::env::temp_dir().join(format!("zkp_fl_hung_{}", uuid::Uuid::new_v4()));
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(format!("echo $$ > {}; sleep 30; true", pid_file.display()));
+
+        let start = Instant::now();
+        let result = output_with_timeout(&mut cmd, Some(Duration::from_millis(500))).await;
+        assert!(matches!(result, Err(ZkpFlError::Timeout(_))));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        // The shell led the group, so nothing may be left in it
+        #[cfg(target_os = "linux")]
+        {
+            let pid: u32 = std::fs::read_to_string(&pid_file).unwrap().trim().parse().unwrap();
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while live_group_members(pid) > 0 && Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            assert_eq!(live_group_members(pid), 0, "sleep outlived its timed-out parent");
+        }
+        std::fs::remove_file(&pid_file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_output_collected_within_timeout() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo out; echo err >&2");
+
+        let output = output_with_timeout(&mut cmd, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "err\n");
+    }
+}
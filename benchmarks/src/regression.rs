@@ -0,0 +1,158 @@
+use common::{AggregateMetrics, MultiClientBenchmark, Result, ZkpFlError};
+use std::fmt;
+use std::path::Path;
+
+/// An aggregate timing that got slower than the baseline allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub metric: &'static str,
+    pub baseline_ms: f64,
+    pub current_ms: f64,
+}
+
+impl Regression {
+    /// How much slower the current run is, as a percentage of the baseline
+    pub fn slowdown_percent(&self) -> f64 {
+        (self.current_ms / self.baseline_ms - 1.0) * 100.0
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.2}ms -> {:.2}ms ({:+.1}%)",
+            self.metric,
+            self.baseline_ms,
+            self.current_ms,
+            self.slowdown_percent()
+        )
+    }
+}
+
+/// Load a `benchmark_report_*.json` written by an earlier run
+pub fn load_baseline(path: &Path) -> Result<MultiClientBenchmark> {
+    let json = std::fs::read_to_string(path).map_err(|e| {
+        ZkpFlError::Benchmark(format!("Failed to read baseline report {:?}: {}", path, e))
+    })?;
+    serde_json::from_str(&json).map_err(|e| {
+        ZkpFlError::Benchmark(format!("Failed to parse baseline report {:?}: {}", path, e))
+    })
+}
+
+/// Timings of `current` more than `max_slowdown_percent` slower than in
+/// `baseline`. Timings either run did not record (zero) are not compared,
+/// so baselines from before percentiles were reported still work.
+pub fn find_regressions(
+    baseline: &AggregateMetrics,
+    current: &AggregateMetrics,
+    max_slowdown_percent: f64,
+) -> Vec<Regression> {
+    let timings = [
+        (
+            "avg_proof_generation_time_ms",
+            baseline.avg_proof_generation_time_ms,
+            current.avg_proof_generation_time_ms,
+        ),
+        (
+            "p95_proof_generation_time_ms",
+            baseline.proof_generation_percentiles.p95_ms,
+            current.proof_generation_percentiles.p95_ms,
+        ),
+        (
+            "avg_proof_verification_time_ms",
+            baseline.avg_proof_verification_time_ms,
+            current.avg_proof_verification_time_ms,
+        ),
+        (
+            "p95_verification_time_ms",
+            baseline.verification_percentiles.p95_ms,
+            current.verification_percentiles.p95_ms,
+        ),
+    ];
+
+    let allowed = 1.0 + max_slowdown_percent / 100.0;
+    timings
+        .into_iter()
+        .filter(|&(_, baseline_ms, current_ms)| {
+            baseline_ms > 0.0 && current_ms > 0.0 && current_ms > baseline_ms * allowed
+        })
+        .map(|(metric, baseline_ms, current_ms)| Regression {
+            metric,
+            baseline_ms,
+            current_ms,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::LatencyPercentiles;
+
+    fn metrics(proof_ms: f64, verification_ms: f64) -> AggregateMetrics {
+        AggregateMetrics {
+            avg_proof_generation_time_ms: proof_ms,
+            min_proof_generation_time_ms: proof_ms as u64,
+            max_proof_generation_time_ms: proof_ms as u64,
+            avg_proof_verification_time_ms: verification_ms,
+            avg_training_time_ms: 10.0,
+            total_proofs_generated: 4,
+            total_proofs_verified: 4,
+            success_rate: 1.0,
+            throughput_proofs_per_second: 1.0,
+            loss: None,
+            proof_generation_percentiles: LatencyPercentiles {
+                p50_ms: proof_ms,
+                p90_ms: proof_ms,
+                p95_ms: proof_ms,
+                p99_ms: proof_ms,
+            },
+            verification_percentiles: LatencyPercentiles::default(),
+        }
+    }
+
+    #[test]
+    fn test_slow_run_is_flagged() {
+        let baseline = metrics(1000.0, 100.0);
+
+        // Proof generation 50% slower, verification within the 10% allowance
+        let regressions = find_regressions(&baseline, &metrics(1500.0, 105.0), 10.0);
+        let regressed: Vec<_> = regressions.iter().map(|r| r.metric).collect();
+        assert_eq!(
+            regressed,
+            ["avg_proof_generation_time_ms", "p95_proof_generation_time_ms"]
+        );
+        assert!((regressions[0].slowdown_percent() - 50.0).abs() < 1e-9);
+        assert_eq!(
+            regressions[0].to_string(),
+            "avg_proof_generation_time_ms: 1000.00ms -> 1500.00ms (+50.0%)"
+        );
+
+        // Faster or merely noisy runs pass
+        assert!(find_regressions(&baseline, &metrics(800.0, 109.0), 10.0).is_empty());
+        assert!(find_regressions(&baseline, &baseline, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_baseline_report_round_trips() {
+        let report = MultiClientBenchmark {
+            benchmark_id: uuid::Uuid::new_v4(),
+            start_time: chrono::Utc::now(),
+            end_time: chrono::Utc::now(),
+            num_clients: 2,
+            client_results: Vec::new(),
+            aggregate_metrics: metrics(1000.0, 100.0),
+        };
+        let path = std::env::temp_dir()
+            .join(format!("zkp_fl_baseline_{}.json", report.benchmark_id));
+        std::fs::write(&path, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+
+        let baseline = load_baseline(&path).unwrap();
+        assert_eq!(baseline.aggregate_metrics.avg_proof_generation_time_ms, 1000.0);
+        std::fs::remove_file(&path).unwrap();
+
+        let err = load_baseline(&path).unwrap_err();
+        assert!(err.to_string().contains("Failed to read baseline report"), "{}", err);
+    }
+}
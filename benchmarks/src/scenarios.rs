@@ -1,7 +1,9 @@
 use common::{Config, BenchmarkResult, Result, ZkpFlError};
+use crate::fault_injection::{FaultConfig, MockFaultServer};
 use crate::multi_client::MultiClientBenchmark;
 use crate::single_client::SingleClientBenchmark;
 use log::{info, warn};
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 pub struct ScenarioRunner {
@@ -93,6 +95,51 @@ impl ScenarioRunner {
         benchmark.run_stress_test().await
     }
 
+    /// Run clients against a mock server that injects delays, dropped
+    /// connections and 5xx responses. Clients must either recover or exit
+    /// with an error; a hung client shows up as a benchmark timeout.
+    pub async fn run_network_faults(
+        &self,
+        num_clients: usize,
+        max_concurrent: usize,
+        fault_config: FaultConfig,
+    ) -> Result<BenchmarkResult> {
+        info!("Running network fault scenario: {} clients, fault rate {:.0}%",
+               num_clients, fault_config.fault_rate * 100.0);
+
+        let mock_server = MockFaultServer::start(fault_config)?;
+
+        let benchmark = MultiClientBenchmark::new(
+            self.config.clone(),
+            num_clients,
+            1,
+            max_concurrent,
+            Duration::from_millis(100),
+            mock_server.url(),
+        );
+
+        let mut result = benchmark.run_concurrent().await?;
+
+        let stats = mock_server.stats();
+        info!("Injected {} faults ({} delays, {} dropped connections, {} server errors) over {} requests",
+              stats.total_faults(),
+              stats.delays.load(Ordering::Relaxed),
+              stats.dropped_connections.load(Ordering::Relaxed),
+              stats.server_errors.load(Ordering::Relaxed),
+              stats.requests.load(Ordering::Relaxed));
+
+        // Clients may recover or give up after their retries, but never hang on a fault
+        let timed_out = result.timed_out_clients.unwrap_or(0);
+        if timed_out > 0 {
+            result.success = false;
+            result.error_message = Some(format!(
+                "{} of {} clients hung on injected faults", timed_out, num_clients));
+        }
+
+        result.scenario = Some("network_faults".to_string());
+        Ok(result)
+    }
+
     pub async fn run_custom_scenario(
         &self,
         scenario_config: CustomScenarioConfig,
@@ -217,6 +264,7 @@ impl ScenarioRunner {
         let total_clients: usize = results.iter().filter_map(|r| r.num_clients).sum();
         let total_successful: usize = results.iter().filter_map(|r| r.successful_clients).sum();
         let total_failed: usize = results.iter().filter_map(|r| r.failed_clients).sum();
+        let total_timed_out: usize = results.iter().filter_map(|r| r.timed_out_clients).sum();
           let avg_training_time = Duration::from_nanos(
             (results.iter().filter_map(|r| r.avg_training_time).map(|d| d.as_nanos()).sum::<u128>() / results.len() as u128) as u64
         );
@@ -254,6 +302,7 @@ impl ScenarioRunner {
         result.total_duration = Some(total_duration);
         result.successful_clients = Some(total_successful);
         result.failed_clients = Some(total_failed);
+        result.timed_out_clients = Some(total_timed_out);
         result.avg_training_time = Some(avg_training_time);
         result.avg_proof_time = Some(avg_proof_time);
         result.avg_verification_time = Some(avg_verification_time);
@@ -265,6 +314,25 @@ impl ScenarioRunner {
     }
 }
 
+/// Run the network fault injection scenario
+pub async fn run_network_fault_scenario(
+    config: &Config,
+    args: &crate::Args,
+) -> Result<Vec<BenchmarkResult>> {
+    let runner = ScenarioRunner::new(config.clone(), String::new());
+
+    let fault_config = FaultConfig {
+        fault_rate: args.fault_rate,
+        signing_secret: config.client.request_signing_secret.clone(),
+        ..FaultConfig::default()
+    };
+
+    let result = runner
+        .run_network_faults(args.num_clients, args.max_concurrent, fault_config)
+        .await?;
+    Ok(vec![result])
+}
+
 /// Run custom scenarios - simplified implementation
 pub async fn run_custom_scenarios(
     config: &Config,
@@ -272,16 +340,20 @@ pub async fn run_custom_scenarios(
 ) -> Result<Vec<BenchmarkResult>> {
     info!("Running custom scenarios");
     
-    let runner = ScenarioRunner::new(
-        config.clone(),
-        args.server_url.clone().unwrap_or_else(|| format!("http://{}:{}", config.server.host, config.server.port)),
-    );
+    let runner = ScenarioRunner::new(config.clone(), custom_server_url(config, args));
     
     // Run a simple single client scenario as a demonstration
     let result = runner.run_single_client(args.rounds).await?;
     Ok(vec![result])
 }
 
+/// Server the custom scenarios point clients at
+pub fn custom_server_url(config: &Config, args: &crate::Args) -> String {
+    args.server_url
+        .clone()
+        .unwrap_or_else(|| format!("http://{}:{}", config.server.host, config.server.port))
+}
+
 #[derive(Debug, Clone)]
 pub struct CustomScenarioConfig {
     pub num_clients: usize,
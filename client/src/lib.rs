@@ -0,0 +1,895 @@
+pub mod network;
+pub mod optimizer;
+pub mod prover;
+pub mod trainer;
+
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use common::{
+    BenchmarkResult, Config, DatasetSchema, DatasetSource, GlobalModel, LogFormat, MetricsSampler,
+    OperationMetrics, Result, Session, SessionStatus, VerificationResult, ZkpFlError, ZkpProof,
+    ZkpVerifier, PROOF_FORMAT_VERSION,
+};
+use log::{debug, error, info, warn};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+#[derive(Parser, Debug)]
+#[command(name = "zkp-fl-client")]
+#[command(about = "ZKP Federated Learning Client")]
+pub struct Args {
+    /// Configuration file path
+    #[arg(short, long, default_value = "config.toml")]
+    pub config: String,
+
+    /// Client ID
+    #[arg(short = 'i', long)]
+    pub client_id: Option<String>,
+
+    /// Server URL
+    #[arg(short, long)]
+    pub server_url: Option<String>,
+
+    /// Dataset file path or `synthetic[:samples,features[,seed]]` spec
+    #[arg(short, long)]
+    pub dataset_path: Option<DatasetSource>,
+
+    /// Number of training epochs
+    #[arg(short, long)]
+    pub epochs: Option<usize>,
+
+    /// Enable detailed logging
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Run in benchmark mode
+    #[arg(short, long)]
+    pub benchmark: bool,
+
+    /// Pin all client threads to this CPU core (ignored if unsupported)
+    #[arg(long)]
+    pub cpu_core: Option<usize>,
+
+    /// Always generate a fresh proof, even if identical training was proven before
+    #[arg(long)]
+    pub no_proof_cache: bool,
+
+    /// Log line format: text or json
+    #[arg(long, default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Take part in this many federated rounds, training each one from the
+    /// server's latest global model
+    #[arg(long)]
+    pub rounds: Option<u64>,
+
+    /// Also write each generated proof to this file, for checking with `verify`
+    #[arg(long)]
+    pub save_proof: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check a saved proof locally instead of running a client
+    Verify {
+        /// Proof JSON as saved by a client
+        proof: PathBuf,
+    },
+}
+
+/// Check the proof saved at `proof_path` against the circuit in the config
+/// at `config_path`, without a server
+pub fn verify_saved_proof(config_path: &str, proof_path: &Path) -> Result<VerificationResult> {
+    let config = Client::load_config(config_path)?;
+    let json = std::fs::read_to_string(proof_path).map_err(|e| {
+        ZkpFlError::ProofVerification(format!(
+            "Failed to read proof {}: {}",
+            proof_path.display(),
+            e
+        ))
+    })?;
+    let proof: ZkpProof = serde_json::from_str(&json)?;
+
+    let start = Instant::now();
+    let result = ZkpVerifier::new(&config.circuit)
+        .with_proof_size_tolerance(config.server.proof_size_tolerance)
+        .verify(&proof)?;
+    Ok(VerificationResult {
+        verified: result.is_valid,
+        verification_time_ms: start.elapsed().as_millis() as u64,
+        verifier_id: "client".to_string(),
+        verification_timestamp: Utc::now(),
+        error_message: (!result.is_valid).then_some(result.error_message),
+    })
+}
+
+pub struct Client {
+    config: Config,
+    client_id: String,
+    session: Session,
+    benchmark_result: BenchmarkResult,
+    trainer: trainer::Trainer,
+    prover: prover::ZkpProver,
+    network: network::NetworkClient,
+    metrics_sampler: MetricsSampler,
+    /// Federated rounds to run; `None` trains and submits once
+    rounds: Option<u64>,
+    /// Where each generated proof is written, if anywhere
+    save_proof: Option<PathBuf>,
+}
+
+impl Client {
+    pub async fn new(args: Args) -> Result<Self> {
+        // Load configuration
+        let mut config = Self::load_config(&args.config)?;
+
+        // Override config with command line arguments
+        let mut client_config = config.client.clone();
+        if let Some(client_id) = args.client_id {
+            client_config.client_id = client_id;
+        }
+        if let Some(server_url) = args.server_url {
+            client_config.server_url = server_url;
+        }
+        if let Some(epochs) = args.epochs {
+            client_config.training_epochs = epochs;
+        }
+        if let Some(dataset_path) = args.dataset_path {
+            config.dataset.path = dataset_path;
+        }
+
+        let session_id = Uuid::new_v4();
+        let session = Session {
+            id: session_id,
+            client_id: client_config.client_id.clone(),
+            start_time: Utc::now(),
+            end_time: None,
+            status: SessionStatus::Starting,
+            metrics: common::SessionMetrics {
+                training_time_ms: 0,
+                proof_generation_time_ms: 0,
+                proof_verification_time_ms: 0,
+                proof_size_bytes: 0,
+                final_loss: 0.0,
+                num_epochs: 0,
+            },
+            round: 0,
+        };
+
+        let benchmark_result = BenchmarkResult::new(session_id, client_config.client_id.clone());
+
+        // Initialize components
+        let trainer = trainer::Trainer::new(&config.circuit, &config.dataset)?
+            .with_batching(client_config.batch_size, client_config.shuffle_seed)
+            .with_early_stopping(client_config.patience, client_config.min_delta)
+            .with_optimizer(client_config.optimizer)
+            .with_learning_rate(client_config.learning_rate, client_config.learning_rate_schedule)
+            .with_differential_privacy(client_config.differential_privacy(), client_config.dp_seed);
+        let prover = prover::ZkpProver::new(&config.circuit)?
+            .with_proof_cache(
+                (!args.no_proof_cache).then(|| PathBuf::from(&client_config.proof_cache_dir)),
+            )
+            .with_progress(|progress| {
+                info!(
+                    "Proof phase {}/{} ({}) done in {}ms (elapsed {}ms)",
+                    progress.phase.number(),
+                    prover::ProofPhase::ALL.len(),
+                    progress.phase,
+                    progress.phase_time.as_millis(),
+                    progress.elapsed.as_millis()
+                );
+            });
+        let network = network::NetworkClient::new(&client_config.server_url)?
+            .with_compression(client_config.compress_requests)
+            .with_signing_secret(client_config.request_signing_secret.clone())
+            .with_retries(
+                client_config.submit_retries,
+                std::time::Duration::from_millis(client_config.submit_retry_backoff_ms),
+            );
+        let metrics_sampler = MetricsSampler::new(client_config.metrics_sample_every);
+
+        Ok(Self {
+            config,
+            client_id: client_config.client_id,
+            session,
+            benchmark_result,
+            trainer,
+            prover,
+            network,
+            metrics_sampler,
+            rounds: args.rounds,
+            save_proof: args.save_proof,
+        })
+    }
+
+    /// Record the CPU core the client threads were pinned to, if any
+    pub fn with_pinned_core(mut self, core: Option<usize>) -> Self {
+        self.benchmark_result.pinned_cores = core.into_iter().collect();
+        self
+    }
+
+    /// Results of the run so far, complete once `run` returns
+    pub fn benchmark_result(&self) -> &BenchmarkResult {
+        &self.benchmark_result
+    }
+
+    fn load_config(path: &str) -> Result<Config> {
+        let config_str = std::fs::read_to_string(path)
+            .map_err(|e| ZkpFlError::Config(format!("Failed to read config file: {}", e)))?;
+
+        let config: Config = toml::from_str(&config_str)
+            .map_err(|e| ZkpFlError::Config(format!("Failed to parse config: {}", e)))?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub async fn run(&mut self, cancel: CancellationToken) -> Result<()> {
+        info!("Starting ZKP-FL client: {}", self.client_id);
+
+        // Refuse to train if the server could never verify our proof
+        self.check_server_capabilities().await?;
+
+        self.session.status = SessionStatus::Training;
+
+        // Phase 1: Load and prepare dataset
+        Self::check_cancelled(&cancel)?;
+        let dataset_metrics = self.load_dataset().await?;
+        self.record_operation(dataset_metrics);
+
+        // Phases 2 to 4, once per round when running several
+        match self.rounds {
+            Some(rounds) => self.run_rounds(rounds, &cancel).await?,
+            None => self.train_prove_submit(&cancel).await?,
+        }
+
+        // Phase 5: Finalize session
+        self.session.status = SessionStatus::Completed;
+        self.session.end_time = Some(Utc::now());
+
+        self.benchmark_result.finish(true, None);
+        self.save_benchmark_results()?;
+
+        info!("Client run completed successfully");
+        Ok(())
+    }
+
+    /// `run`, also saving the benchmark results when the run fails or is
+    /// cancelled
+    pub async fn run_to_completion(&mut self, cancel: CancellationToken) -> Result<()> {
+        match self.run(cancel).await {
+            Ok(()) => {
+                info!("Client completed successfully");
+                Ok(())
+            }
+            Err(ZkpFlError::Cancelled) => {
+                warn!("Client run cancelled, saving partial results");
+                self.session.status = SessionStatus::Cancelled;
+                self.session.end_time = Some(Utc::now());
+                self.benchmark_result.cancelled = true;
+                self.benchmark_result
+                    .finish(false, Some(ZkpFlError::Cancelled.to_string()));
+                self.save_benchmark_results()?;
+                Err(ZkpFlError::Cancelled)
+            }
+            Err(e) => {
+                error!("Client failed: {}", e);
+                self.benchmark_result.finish(false, Some(e.to_string()));
+                self.save_benchmark_results()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Train, prove and submit once
+    async fn train_prove_submit(&mut self, cancel: &CancellationToken) -> Result<()> {
+        // Phase 2: Train model
+        Self::check_cancelled(cancel)?;
+        self.session.status = SessionStatus::Training;
+        let training_metrics = self.train_model(cancel).await?;
+        self.record_operation(training_metrics);
+
+        // Phase 3: Generate proof
+        Self::check_cancelled(cancel)?;
+        self.session.status = SessionStatus::GeneratingProof;
+        let proof_metrics = self.generate_proof(cancel).await?;
+        self.record_operation(proof_metrics);
+
+        // Phase 4: Send proof to server
+        Self::check_cancelled(cancel)?;
+        self.session.status = SessionStatus::Verifying;
+        let verification_metrics = self.submit_proof().await?;
+        self.record_operation(verification_metrics);
+        Ok(())
+    }
+
+    /// Train from the global model, prove and submit `rounds` times, waiting
+    /// for the server to aggregate a newer global model between rounds
+    async fn run_rounds(&mut self, rounds: u64, cancel: &CancellationToken) -> Result<()> {
+        let mut global_model = self.network.get_global_model().await?;
+        for round in 1..=rounds {
+            if round > 1 {
+                self.session.status = SessionStatus::WaitingForGlobalModel;
+                global_model = self.wait_for_global_model(global_model.round, cancel).await?;
+            }
+
+            self.session.round = round;
+            info!(
+                "Round {}/{}: training from global model round {}",
+                round, rounds, global_model.round
+            );
+            self.trainer
+                .set_initial_model(global_model.weights.clone(), global_model.bias)?;
+            self.train_prove_submit(cancel).await?;
+        }
+        Ok(())
+    }
+
+    /// Poll the server until it has aggregated a round after `round`
+    async fn wait_for_global_model(
+        &self,
+        round: u64,
+        cancel: &CancellationToken,
+    ) -> Result<GlobalModel> {
+        let interval = Duration::from_millis(self.config.client.global_model_poll_ms);
+        info!("Waiting for the server to aggregate round {}", round + 1);
+        loop {
+            Self::check_cancelled(cancel)?;
+            let global_model = self.network.get_global_model().await?;
+            if global_model.round > round {
+                return Ok(global_model);
+            }
+
+            debug!("Global model still at round {}", global_model.round);
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(ZkpFlError::Cancelled),
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+
+    fn check_cancelled(cancel: &CancellationToken) -> Result<()> {
+        if cancel.is_cancelled() {
+            return Err(ZkpFlError::Cancelled);
+        }
+        Ok(())
+    }
+
+    fn record_operation(&mut self, metrics: OperationMetrics) {
+        // Always counted; only sampled operations keep their detailed record
+        self.benchmark_result.total_operations += 1;
+        if self.metrics_sampler.should_sample() {
+            self.benchmark_result.operations.push(metrics);
+        }
+    }
+
+    async fn check_server_capabilities(&self) -> Result<()> {
+        let capabilities = match self.network.get_capabilities().await {
+            Ok(capabilities) => capabilities,
+            Err(e) => {
+                warn!("Could not query server capabilities, continuing: {}", e);
+                return Ok(());
+            }
+        };
+
+        if capabilities.circuit_k != self.config.circuit.k {
+            return Err(ZkpFlError::Config(format!(
+                "Circuit k mismatch: client uses k={}, server expects k={}",
+                self.config.circuit.k, capabilities.circuit_k
+            )));
+        }
+
+        if capabilities.num_features != self.config.circuit.num_features {
+            return Err(ZkpFlError::Config(format!(
+                "Feature count mismatch: client uses {}, server expects {}",
+                self.config.circuit.num_features, capabilities.num_features
+            )));
+        }
+
+        if let Some(versions) = capabilities.proof_format_versions {
+            versions.check(PROOF_FORMAT_VERSION).map_err(|_| {
+                ZkpFlError::Config(format!(
+                    "Proof format mismatch: client produces version {}, server supports {}..{}",
+                    PROOF_FORMAT_VERSION, versions.min, versions.max
+                ))
+            })?;
+        }
+
+        debug!(
+            "Server {} accepts k={}, {} features",
+            capabilities.server_version, capabilities.circuit_k, capabilities.num_features
+        );
+        Ok(())
+    }
+
+    async fn load_dataset(&mut self) -> Result<OperationMetrics> {
+        let mut metrics = OperationMetrics::new("dataset_loading".to_string());
+        info!("Loading dataset...");
+
+        let _start = Instant::now();
+
+        let dataset_config = &self.config.dataset;
+        info!("Loading dataset from {}", dataset_config.path);
+        let (dataset, sampled) = if let Some(schema_path) = &dataset_config.schema_path {
+            let schema = DatasetSchema::from_file(schema_path)?;
+            let dataset = dataset_config.path.load_with_schema(&schema)?;
+            let num_features = self.config.circuit.num_features;
+            if dataset.num_features != num_features {
+                return Err(ZkpFlError::Dataset(format!(
+                    "Schema '{}' encodes {} features ({}) but circuit.num_features is {}",
+                    schema_path.display(),
+                    dataset.num_features,
+                    dataset.feature_names.join(", "),
+                    num_features
+                )));
+            }
+            if dataset_config.max_samples > 0 {
+                let (dataset, sampled) =
+                    dataset.sample_rows(dataset_config.max_samples, dataset_config.sample_seed);
+                (dataset, Some(sampled))
+            } else {
+                (dataset, None)
+            }
+        } else if dataset_config.max_samples > 0 {
+            let (dataset, sampled) = dataset_config.path.load_sampled(
+                &dataset_config.target_column,
+                &dataset_config.feature_columns,
+                dataset_config.max_samples,
+                dataset_config.sample_seed,
+            )?;
+            info!(
+                "Kept {} of {} dataset rows",
+                sampled.rows_retained, sampled.rows_seen
+            );
+            (dataset, Some(sampled))
+        } else {
+            let dataset = dataset_config
+                .path
+                .load(&dataset_config.target_column, &dataset_config.feature_columns)?;
+            (dataset, None)
+        };
+
+        let dataset_hash = dataset.content_hash();
+        info!("Dataset content hash: {}", dataset_hash);
+        self.benchmark_result.dataset_hash = Some(dataset_hash.clone());
+
+        self.trainer.set_dataset(dataset)?;
+
+        metrics.finish();
+        metrics.add_metadata("dataset_hash", dataset_hash);
+        metrics.add_metadata("num_samples", self.trainer.get_dataset_size());
+        metrics.add_metadata("num_features", self.trainer.get_num_features());
+        if let Some(sampled) = sampled {
+            metrics.add_metadata("rows_seen", sampled.rows_seen);
+            metrics.add_metadata("rows_retained", sampled.rows_retained);
+        }
+
+        info!("Dataset loaded in {}ms", metrics.duration_ms);
+        Ok(metrics)
+    }
+    async fn train_model(&mut self, cancel: &CancellationToken) -> Result<OperationMetrics> {
+        let mut metrics = OperationMetrics::new("model_training".to_string());
+        info!("Starting model training...");
+
+        let _start = Instant::now();
+        let training_result = self
+            .trainer
+            .train(self.config.client.training_epochs, cancel)
+            .await?;
+
+        metrics.finish();
+        metrics.add_metadata("epochs", training_result.epochs_completed);
+        metrics.add_metadata("final_loss", training_result.final_loss);
+        metrics.add_metadata("convergence_epoch", training_result.convergence_epoch);
+        if self.session.round > 0 {
+            metrics.add_metadata("round", self.session.round);
+        }
+
+        // Update session metrics
+        self.session.metrics.training_time_ms = metrics.duration_ms;
+        self.session.metrics.final_loss = training_result.final_loss;
+        self.session.metrics.num_epochs = training_result.epochs_completed;
+        // Update benchmark result
+        let final_loss = training_result.final_loss;
+        let target_range = match (training_result.target_min, training_result.target_max) {
+            (Some(min), Some(max)) => format!(", target range: [{:.6}, {:.6}]", min, max),
+            _ => String::new(),
+        };
+        self.benchmark_result.training_metrics = training_result;
+
+        info!(
+            "Model training completed in {}ms, final loss: {:.6}{}",
+            metrics.duration_ms, final_loss, target_range
+        );
+
+        Ok(metrics)
+    }
+
+    async fn generate_proof(&mut self, cancel: &CancellationToken) -> Result<OperationMetrics> {
+        let mut metrics = OperationMetrics::new("proof_generation".to_string());
+        info!("Generating ZKP proof...");
+        let training_params = self.trainer.get_training_params()?;
+        let samples = self.trainer.get_training_samples()?;
+
+        let _start = Instant::now();
+        self.prover.set_scaler(self.trainer.scaler_params().cloned());
+        let mut proof = self
+            .prover
+            .generate_proof(samples, &training_params, cancel)
+            .await?;
+        // The prover leaves identity to us, cached proofs included; the
+        // server aggregates one contribution per client id
+        proof.client_id = self.client_id.clone();
+        proof.session_id = self.session.id;
+
+        metrics.finish();
+        metrics.add_metadata("proof_size_bytes", proof.proof_size());
+        metrics.add_metadata(
+            "circuit_constraints",
+            proof.proof_data.circuit_params.num_constraints,
+        );
+
+        // Update session metrics
+        self.session.metrics.proof_generation_time_ms = metrics.duration_ms;
+        self.session.metrics.proof_size_bytes = proof.proof_size();
+        // Update benchmark result with proof metadata
+        self.benchmark_result.zkp_metrics.proof_generation_time_ms =
+            proof.proof_data.metadata.generation_time_ms;
+        self.benchmark_result.zkp_metrics.witness_generation_time_ms =
+            proof.proof_data.metadata.witness_generation_time_ms;
+        self.benchmark_result.zkp_metrics.setup_time_ms = proof.proof_data.metadata.setup_time_ms;
+        self.benchmark_result.zkp_metrics.proof_size_bytes = proof.proof_size();
+        self.benchmark_result.zkp_metrics.circuit_constraints =
+            proof.proof_data.circuit_params.num_constraints;
+        self.benchmark_result.zkp_metrics.quantization_error =
+            proof.proof_data.metadata.quantization_error;
+
+        // Store proof size before moving
+        let proof_size = proof.proof_size();
+
+        if let Some(path) = &self.save_proof {
+            std::fs::write(path, serde_json::to_string_pretty(&proof)?).map_err(|e| {
+                ZkpFlError::ProofGeneration(format!(
+                    "Failed to save proof to {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            info!("Proof saved to {}", path.display());
+        }
+
+        // Store proof for submission
+        self.prover.set_current_proof(proof);
+
+        info!(
+            "Proof generated in {}ms, size: {} bytes",
+            metrics.duration_ms, proof_size
+        );
+
+        Ok(metrics)
+    }
+
+    async fn submit_proof(&mut self) -> Result<OperationMetrics> {
+        let mut metrics = OperationMetrics::new("proof_submission".to_string());
+        info!("Submitting proof to server...");
+        let proof = self.prover.get_current_proof()?;
+
+        // Catch obviously invalid proofs before paying for a round trip
+        proof.validate_self(&self.config.circuit)?;
+        if self.config.client.verify_dataset_hash {
+            let samples = self.trainer.get_training_samples()?;
+            proof.training_commitment.check_dataset_hash(&samples)?;
+        }
+
+        let _start = Instant::now();
+        let verification_result = self.network.submit_proof(proof).await?;
+
+        metrics.finish();
+        metrics.add_metadata("verified", verification_result.verified);
+        metrics.add_metadata(
+            "verification_time_ms",
+            verification_result.verification_time_ms,
+        );
+
+        // Update session metrics
+        self.session.metrics.proof_verification_time_ms = verification_result.verification_time_ms;
+
+        // Update benchmark result
+        self.benchmark_result.zkp_metrics.proof_verification_time_ms =
+            verification_result.verification_time_ms;
+
+        if verification_result.verified {
+            info!(
+                "Proof verified successfully in {}ms",
+                verification_result.verification_time_ms
+            );
+            self.session.status = SessionStatus::Verified;
+        } else {
+            error!(
+                "Proof verification failed: {:?}",
+                verification_result.error_message
+            );
+            self.session.status = SessionStatus::Failed;
+            return Err(ZkpFlError::ProofVerification(
+                verification_result
+                    .error_message
+                    .unwrap_or_else(|| "Unknown verification error".to_string()),
+            ));
+        }
+
+        Ok(metrics)
+    }
+
+    fn save_benchmark_results(&self) -> Result<()> {
+        let output_dir = std::path::Path::new(&self.config.benchmarks.output_path);
+        let filepath = self.benchmark_result.save(output_dir)?;
+
+        info!("Benchmark results saved to: {:?}", filepath);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Optimizer, Sample, TrainingParams, VerificationRequest, VerificationResponse};
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use warp::Filter;
+
+    #[tokio::test]
+    async fn test_each_round_pulls_the_global_model_and_submits_a_proof() {
+        // Mock server that aggregates a new round as soon as a proof arrives
+        let pulls = Arc::new(AtomicU64::new(0));
+        let submissions = Arc::new(AtomicU64::new(0));
+        let global_model = {
+            let (pulls, submissions) = (pulls.clone(), submissions.clone());
+            warp::path!("api" / "model" / "global").and(warp::get()).map(move || {
+                pulls.fetch_add(1, Ordering::SeqCst);
+                warp::reply::json(&GlobalModel {
+                    round: submissions.load(Ordering::SeqCst),
+                    ..GlobalModel::initial(5)
+                })
+            })
+        };
+        let verify = {
+            let submissions = submissions.clone();
+            warp::path!("api" / "verify")
+                .and(warp::post())
+                .and(warp::body::bytes())
+                .map(move |_body| {
+                    submissions.fetch_add(1, Ordering::SeqCst);
+                    warp::reply::json(&VerificationResponse {
+                        proof_id: Uuid::new_v4(),
+                        result: VerificationResult {
+                            verified: true,
+                            verification_time_ms: 1,
+                            verifier_id: "mock".to_string(),
+                            verification_timestamp: Utc::now(),
+                            error_message: None,
+                        },
+                    })
+                })
+        };
+        let (address, server) =
+            warp::serve(global_model.or(verify)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dir = std::env::temp_dir().join(format!("zkp_fl_rounds_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Client::load_config("../config.toml").unwrap();
+        config.client.training_epochs = 2;
+        config.client.proof_cache_dir = dir.join("proof_cache").display().to_string();
+        config.benchmarks.output_path = dir.join("benchmarks").display().to_string();
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let args = Args::parse_from([
+            "zkp-fl-client",
+            "--config",
+            config_path.to_str().unwrap(),
+            "--server-url",
+            &format!("http://{}", address),
+            "--rounds",
+            "2",
+            "--no-proof-cache",
+        ]);
+        let mut client = Client::new(args).await.unwrap();
+        client.run(CancellationToken::new()).await.unwrap();
+
+        assert_eq!(pulls.load(Ordering::SeqCst), 2);
+        assert_eq!(submissions.load(Ordering::SeqCst), 2);
+        assert_eq!(client.session.round, 2);
+        assert!(matches!(client.session.status, SessionStatus::Completed));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_clients_submit_under_their_own_identity() {
+        // Mock server keeping the latest contribution per client, as the
+        // aggregator does
+        let contributions = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let verify = {
+            let contributions = contributions.clone();
+            warp::path!("api" / "verify")
+                .and(warp::post())
+                .and(warp::body::bytes())
+                .map(move |body: warp::hyper::body::Bytes| {
+                    let mut json = Vec::new();
+                    flate2::read::GzDecoder::new(body.as_ref()).read_to_end(&mut json).unwrap();
+                    let request: VerificationRequest = serde_json::from_slice(&json).unwrap();
+                    let proof = request.proof;
+                    let mut contributions = contributions.lock().unwrap();
+                    contributions.insert(proof.client_id.clone(), proof.session_id);
+                    warp::reply::json(&VerificationResponse {
+                        proof_id: proof.proof_id,
+                        result: VerificationResult {
+                            verified: true,
+                            verification_time_ms: 1,
+                            verifier_id: "mock".to_string(),
+                            verification_timestamp: Utc::now(),
+                            error_message: None,
+                        },
+                    })
+                })
+        };
+        let (address, server) = warp::serve(verify).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dir = std::env::temp_dir().join(format!("zkp_fl_identity_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Client::load_config("../config.toml").unwrap();
+        config.client.training_epochs = 2;
+        config.client.proof_cache_dir = dir.join("proof_cache").display().to_string();
+        config.benchmarks.output_path = dir.join("benchmarks").display().to_string();
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let mut sessions = HashMap::new();
+        for (client_id, dataset, epochs) in
+            [("alice", "synthetic:40,5,1", "2"), ("bob", "synthetic:60,5,2", "3")]
+        {
+            let args = Args::parse_from([
+                "zkp-fl-client",
+                "--config",
+                config_path.to_str().unwrap(),
+                "--server-url",
+                &format!("http://{}", address),
+                "--client-id",
+                client_id,
+                "--dataset-path",
+                dataset,
+                "--epochs",
+                epochs,
+            ]);
+            let mut client = Client::new(args).await.unwrap();
+            client.run(CancellationToken::new()).await.unwrap();
+            sessions.insert(client_id.to_string(), client.session.id);
+        }
+
+        assert_eq!(*contributions.lock().unwrap(), sessions);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_saved_proof_verifies_offline() {
+        let dir = std::env::temp_dir().join(format!("zkp_fl_offline_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Client::load_config("../config.toml").unwrap();
+        config.circuit.k = 10;
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let samples = vec![
+            Sample {
+                features: vec![1.0, 2.0, 0.0, 0.0, 0.0],
+                target: 3.0,
+            },
+            Sample {
+                features: vec![2.0, 3.0, 0.0, 0.0, 0.0],
+                target: 5.0,
+            },
+        ];
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0, 0.0, 0.0, 0.0],
+            bias: 0.0,
+            loss: 0.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let mut proof = prover::ZkpProver::new(&config.circuit)
+            .unwrap()
+            .generate_proof(samples, &training_params, &CancellationToken::new())
+            .await
+            .unwrap();
+        let proof_path = dir.join("proof.json");
+        std::fs::write(&proof_path, serde_json::to_string(&proof).unwrap()).unwrap();
+
+        let args = Args::parse_from([
+            "zkp-fl-client",
+            "--config",
+            config_path.to_str().unwrap(),
+            "verify",
+            proof_path.to_str().unwrap(),
+        ]);
+        let Some(Command::Verify { proof: saved }) = args.command else {
+            panic!("expected the verify subcommand");
+        };
+        let result = verify_saved_proof(&args.config, &saved).unwrap();
+        assert!(result.verified, "{:?}", result.error_message);
+        assert_eq!(result.verifier_id, "client");
+
+        // A proof claiming a loss it was not built over fails
+        proof.proof_data.public_inputs[0] = "0.5".to_string();
+        std::fs::write(&proof_path, serde_json::to_string(&proof).unwrap()).unwrap();
+        let result = verify_saved_proof(&args.config, &saved).unwrap();
+        assert!(!result.verified);
+        assert!(result.error_message.is_some());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_proof_saved_by_a_run_verifies_offline() {
+        let verify = warp::path!("api" / "verify").and(warp::post()).map(|| {
+            warp::reply::json(&VerificationResponse {
+                proof_id: Uuid::new_v4(),
+                result: VerificationResult {
+                    verified: true,
+                    verification_time_ms: 1,
+                    verifier_id: "mock".to_string(),
+                    verification_timestamp: Utc::now(),
+                    error_message: None,
+                },
+            })
+        });
+        let (address, server) = warp::serve(verify).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dir = std::env::temp_dir().join(format!("zkp_fl_save_proof_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Client::load_config("../config.toml").unwrap();
+        config.client.training_epochs = 2;
+        config.client.proof_cache_dir = dir.join("proof_cache").display().to_string();
+        config.benchmarks.output_path = dir.join("benchmarks").display().to_string();
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+        let proof_path = dir.join("proof.json");
+
+        let args = Args::parse_from([
+            "zkp-fl-client",
+            "--config",
+            config_path.to_str().unwrap(),
+            "--server-url",
+            &format!("http://{}", address),
+            "--dataset-path",
+            "synthetic:40,5,1",
+            "--save-proof",
+            proof_path.to_str().unwrap(),
+        ]);
+        let mut client = Client::new(args).await.unwrap();
+        client.run(CancellationToken::new()).await.unwrap();
+
+        // The file holds the proof that was submitted, and checks out without a server
+        let saved: ZkpProof =
+            serde_json::from_str(&std::fs::read_to_string(&proof_path).unwrap()).unwrap();
+        assert_eq!(saved.proof_id, client.prover.get_current_proof().unwrap().proof_id);
+        assert_eq!(saved.client_id, client.client_id);
+        let result = verify_saved_proof(config_path.to_str().unwrap(), &proof_path).unwrap();
+        assert!(result.verified, "{:?}", result.error_message);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,14 +1,52 @@
 use common::{
-    Result, VerificationRequest, VerificationResponse, VerificationResult, ZkpFlError, ZkpProof,
+    sign_body, GlobalModel, Result, ServerCapabilities, VerificationRequest, VerificationResponse,
+    VerificationResult, ZkpFlError, ZkpProof, SIGNATURE_HEADER,
 };
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use log::{debug, error, info, warn};
+use reqwest::header::{CONTENT_ENCODING, CONTENT_TYPE};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Write;
 use std::time::Duration;
 use uuid::Uuid;
 
+/// Times a rate-limited submission is retried before giving up
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+/// Wait used when a 429 response carries no usable `Retry-After`
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// Longest `Retry-After` honored, so a bad header cannot stall the client
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// Resends of a submission that failed in transit or with a 5xx, unless configured
+const DEFAULT_SUBMIT_RETRIES: usize = 3;
+/// Wait before the first resend, doubled for each one after it
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
 pub struct NetworkClient {
     client: Client,
     server_url: String,
+    compress_requests: bool,
+    signing_secret: Option<String>,
+    max_retries: usize,
+    retry_backoff: Duration,
+}
+
+/// Why one submission attempt produced no result
+enum AttemptError {
+    /// 429: resend once the server's requested wait is over
+    RateLimited { wait: Duration, error: ZkpFlError },
+    /// Connection failures, cut-off responses and 5xx: resending may succeed
+    Transient(ZkpFlError),
+    /// The server would answer a resend the same way
+    Fatal(ZkpFlError),
+}
+
+/// Serialized request body, gzipped unless compression is off
+struct RequestBody {
+    bytes: Vec<u8>,
+    gzipped: bool,
 }
 
 impl NetworkClient {
@@ -21,9 +59,76 @@ impl NetworkClient {
         Ok(Self {
             client,
             server_url: server_url.to_string(),
+            compress_requests: true,
+            signing_secret: None,
+            max_retries: DEFAULT_SUBMIT_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+        })
+    }
+
+    /// Sign submissions with `secret` so a server requiring signatures
+    /// accepts them
+    pub fn with_signing_secret(mut self, secret: Option<String>) -> Self {
+        self.signing_secret = secret;
+        self
+    }
+
+    /// Send submissions as plain JSON instead of gzip when `enabled` is false
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compress_requests = enabled;
+        self
+    }
+
+    /// Resend failed submissions up to `max_retries` times, waiting `backoff`
+    /// before the first resend and twice as long before each one after it
+    pub fn with_retries(mut self, max_retries: usize, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Wait before resend number `attempt`, counting from 1
+    fn backoff(&self, attempt: usize) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(16) as u32;
+        self.retry_backoff.saturating_mul(1 << doublings).min(MAX_RETRY_AFTER)
+    }
+
+    fn encode_body<T: Serialize>(&self, body: &T) -> Result<RequestBody> {
+        let json = serde_json::to_vec(body)?;
+        if !self.compress_requests {
+            return Ok(RequestBody {
+                bytes: json,
+                gzipped: false,
+            });
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&json)?;
+        let bytes = encoder.finish()?;
+        debug!("Compressed request body from {} to {} bytes", json.len(), bytes.len());
+        Ok(RequestBody {
+            bytes,
+            gzipped: true,
         })
     }
 
+    fn post_body(&self, url: &str, body: &RequestBody) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.bytes.clone());
+        let request = match &self.signing_secret {
+            Some(secret) => request.header(SIGNATURE_HEADER, sign_body(secret, &body.bytes)),
+            None => request,
+        };
+        if body.gzipped {
+            request.header(CONTENT_ENCODING, "gzip")
+        } else {
+            request
+        }
+    }
+
     pub async fn submit_proof(&self, proof: ZkpProof) -> Result<VerificationResult> {
         info!("Submitting proof {} to server", proof.proof_id);
 
@@ -36,43 +141,104 @@ impl NetworkClient {
 
         debug!("POST {}", url);
         debug!("Proof size: {} bytes", proof.proof_size());
+        let body = self.encode_body(&request)?;
+        let what = format!("proof {}", proof.proof_id);
+        let response: VerificationResponse = self.post_with_retries(&url, &body, &what).await?;
+        let result = response.result;
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                error!("Failed to send proof to server: {}", e);
-                ZkpFlError::Network(format!("Failed to send request: {}", e))
-            })?;
+        info!(
+            "Received verification result: verified={}, time={}ms",
+            result.verified, result.verification_time_ms
+        );
 
-        if !response.status().is_success() {
-            let status = response.status();
+        Ok(result)
+    }
+
+    /// POST `body` until the server answers it, resending after transport
+    /// failures, 5xx and 429 responses up to the configured limits
+    async fn post_with_retries<R: DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &RequestBody,
+        what: &str,
+    ) -> Result<R> {
+        let mut rate_limit_retries = 0;
+        let mut retries = 0;
+        loop {
+            let error = match self.post_once(url, body).await {
+                Ok(response) => return Ok(response),
+                Err(AttemptError::RateLimited { wait, error }) => {
+                    if rate_limit_retries >= MAX_RATE_LIMIT_RETRIES {
+                        return Err(error);
+                    }
+                    // Back off for as long as the server asks instead of hammering it
+                    rate_limit_retries += 1;
+                    warn!(
+                        "Rate limited by server, retrying in {}s (attempt {}/{})",
+                        wait.as_secs(),
+                        rate_limit_retries,
+                        MAX_RATE_LIMIT_RETRIES
+                    );
+                    tokio::time::sleep(wait).await;
+                    continue;
+                }
+                Err(AttemptError::Transient(error)) => error,
+                Err(AttemptError::Fatal(error)) => return Err(error),
+            };
+
+            if retries >= self.max_retries {
+                error!("Giving up on {} after {} attempts", what, retries + 1);
+                return Err(error);
+            }
+            retries += 1;
+            let wait = self.backoff(retries);
+            warn!(
+                "Submission failed ({}), retrying in {}ms (attempt {}/{})",
+                error,
+                wait.as_millis(),
+                retries,
+                self.max_retries
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Send one submission, sorting failures by whether a resend can help
+    async fn post_once<R: DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &RequestBody,
+    ) -> std::result::Result<R, AttemptError> {
+        let response = self.post_body(url, body).send().await.map_err(|e| {
+            AttemptError::Transient(ZkpFlError::Network(format!("Failed to send request: {}", e)))
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let wait = retry_after(response.headers()).unwrap_or(DEFAULT_RETRY_AFTER);
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             error!("Server returned error {}: {}", status, error_text);
-            return Err(ZkpFlError::Network(format!(
-                "Server error {}: {}",
-                status, error_text
-            )));
+            let error = server_error(status, &error_text);
+            return Err(if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                AttemptError::RateLimited { wait, error }
+            } else if status.is_server_error() {
+                AttemptError::Transient(error)
+            } else {
+                AttemptError::Fatal(error)
+            });
         }
 
-        let verification_response: VerificationResponse = response.json().await.map_err(|e| {
-            error!("Failed to parse verification response: {}", e);
-            ZkpFlError::Network(format!("Failed to parse response: {}", e))
+        // A response cut off mid-body is a transport failure, not a bad reply
+        let bytes = response.bytes().await.map_err(|e| {
+            AttemptError::Transient(ZkpFlError::Network(format!("Failed to read response: {}", e)))
         })?;
-
-        info!(
-            "Received verification result: verified={}, time={}ms",
-            verification_response.result.verified,
-            verification_response.result.verification_time_ms
-        );
-
-        Ok(verification_response.result)
+        serde_json::from_slice(&bytes).map_err(|e| {
+            error!("Failed to parse response: {}", e);
+            AttemptError::Fatal(ZkpFlError::Network(format!("Failed to parse response: {}", e)))
+        })
     }
 
     pub async fn health_check(&self) -> Result<bool> {
@@ -95,6 +261,54 @@ impl NetworkClient {
         }
     }
 
+    pub async fn get_capabilities(&self) -> Result<ServerCapabilities> {
+        let url = format!("{}/api/capabilities", self.server_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ZkpFlError::Network(format!("Failed to get capabilities: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ZkpFlError::Network(format!(
+                "Server capabilities error: {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ZkpFlError::Network(format!("Failed to parse capabilities: {}", e)))
+    }
+
+    /// Model of the server's last aggregation round, the zero model before
+    /// the first
+    pub async fn get_global_model(&self) -> Result<GlobalModel> {
+        let url = format!("{}/api/model/global", self.server_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ZkpFlError::Network(format!("Failed to get global model: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ZkpFlError::Network(format!(
+                "Server global model error: {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ZkpFlError::Network(format!("Failed to parse global model: {}", e)))
+    }
+
     pub async fn get_server_status(&self) -> Result<ServerStatus> {
         let url = format!("{}/api/status", self.server_url);
 
@@ -132,26 +346,10 @@ impl NetworkClient {
         };
 
         let url = format!("{}/api/verify_batch", self.server_url);
-
-        let response = self
-            .client
-            .post(&url)
-            .json(&batch_request)
-            .send()
-            .await
-            .map_err(|e| ZkpFlError::Network(format!("Failed to send batch: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(ZkpFlError::Network(format!(
-                "Batch verification error: {}",
-                response.status()
-            )));
-        }
-
-        let batch_response: BatchVerificationResponse = response
-            .json()
-            .await
-            .map_err(|e| ZkpFlError::Network(format!("Failed to parse batch response: {}", e)))?;
+        let body = self.encode_body(&batch_request)?;
+        let what = format!("batch of {} proofs", batch_request.proofs.len());
+        let batch_response: BatchVerificationResponse =
+            self.post_with_retries(&url, &body, &what).await?;
 
         info!(
             "Batch verification completed: {}/{} proofs verified",
@@ -163,6 +361,44 @@ impl NetworkClient {
     }
 }
 
+/// Map an error response to a `ZkpFlError`, keeping the server's message.
+/// Bad signatures, oversized bodies and rate limiting each get their own error;
+/// other client errors (4xx) mean the proof was rejected, not that the network failed.
+fn server_error(status: reqwest::StatusCode, body: &str) -> ZkpFlError {
+    let json = serde_json::from_str::<serde_json::Value>(body).ok();
+    let field = |name: &str| {
+        json.as_ref()
+            .and_then(|json| json.get(name).and_then(|e| e.as_str()).map(str::to_string))
+    };
+    let message = field("error").unwrap_or_else(|| body.to_string());
+
+    if field("error_code").as_deref() == Some("proof_serialization_error") {
+        ZkpFlError::ProofSerialization(format!("Server could not decode proof: {}", message))
+    } else if status == reqwest::StatusCode::UNAUTHORIZED {
+        ZkpFlError::Unauthorized(format!("Server refused request ({}): {}", status, message))
+    } else if status == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
+        ZkpFlError::PayloadTooLarge(format!("Server refused request ({}): {}", status, message))
+    } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        ZkpFlError::RateLimited(format!("Server refused request ({}): {}", status, message))
+    } else if status.is_client_error() {
+        ZkpFlError::ProofVerification(format!("Server rejected proof ({}): {}", status, message))
+    } else {
+        ZkpFlError::Network(format!("Server error {}: {}", status, message))
+    }
+}
+
+/// Delay requested by a `Retry-After` header given in seconds
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let secs = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct ServerStatus {
     pub uptime_seconds: u64,
@@ -170,6 +406,9 @@ pub struct ServerStatus {
     pub total_proofs_processed: usize,
     pub total_proofs_verified: usize,
     pub average_verification_time_ms: f64,
+    /// Missing from servers that predate it
+    #[serde(default)]
+    pub verification_success_rate: f64,
     pub server_version: String,
 }
 
@@ -196,5 +435,132 @@ mod tests {
         assert!(client.is_ok());
     }
 
+    #[test]
+    fn test_server_error_keeps_message() {
+        let body = r#"{"error": "Circuit parameter k mismatch: expected 12, got 10", "code": 400}"#;
+        match server_error(reqwest::StatusCode::BAD_REQUEST, body) {
+            ZkpFlError::ProofVerification(msg) => assert!(msg.contains("expected 12, got 10")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let body = r#"{"error": "Failed to decode proof", "code": 400, "error_code": "proof_serialization_error"}"#;
+        assert!(matches!(
+            server_error(reqwest::StatusCode::BAD_REQUEST, body),
+            ZkpFlError::ProofSerialization(_)
+        ));
+
+        let body = r#"{"error": "Invalid request signature", "code": 401}"#;
+        match server_error(reqwest::StatusCode::UNAUTHORIZED, body) {
+            ZkpFlError::Unauthorized(msg) => assert!(msg.contains("401")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(matches!(
+            server_error(reqwest::StatusCode::PAYLOAD_TOO_LARGE, ""),
+            ZkpFlError::PayloadTooLarge(_)
+        ));
+        assert!(matches!(
+            server_error(reqwest::StatusCode::TOO_MANY_REQUESTS, ""),
+            ZkpFlError::RateLimited(_)
+        ));
+
+        match server_error(reqwest::StatusCode::INTERNAL_SERVER_ERROR, "oops") {
+            ZkpFlError::Network(msg) => assert!(msg.contains("oops")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_retry_after_parsing() {
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(5)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("86400"));
+        assert_eq!(retry_after(&headers), Some(MAX_RETRY_AFTER));
+
+        // HTTP dates are not used by the server and fall back to the default
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_large_proof_body_is_compressed() {
+        use flate2::read::GzDecoder;
+        use rand::{Rng, SeedableRng};
+        use std::io::Read;
+
+        // Incompressible proof bytes still shrink, since JSON spells them out as numbers
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let proof_bytes: Vec<u8> = (0..256 * 1024).map(|_| rng.gen()).collect();
+        let proof = ZkpProof::new(
+            Uuid::new_v4(),
+            "client".to_string(),
+            proof_bytes,
+            vec!["0.1".to_string()],
+            common::CircuitParams {
+                k: 8,
+                num_constraints: 100,
+                num_advice_columns: 10,
+                num_fixed_columns: 5,
+                max_degree: 3,
+                model_type: common::ModelType::Linear,
+            },
+            common::ProofMetadata {
+                generation_time_ms: 1,
+                proof_size_bytes: 256 * 1024,
+                witness_generation_time_ms: 1,
+                setup_time_ms: 1,
+                folding_time_ms: 1,
+                folding_iterations: 1,
+                quantization_error: None,
+                loss_comparison: None,
+            },
+            common::TrainingCommitment {
+                dataset_hash: "a".repeat(128),
+                num_samples: 1,
+                num_features: 1,
+                learning_rate: 0.01,
+                epochs: 1,
+                weights_commitment: "b".repeat(64),
+                final_loss: 0.1,
+                quantization: None,
+                scaler: None,
+                optimizer: common::Optimizer::Sgd,
+                weights_commitment_scheme: common::CommitmentScheme::Blake2b,
+                differential_privacy: None,
+                bias_commitment: None,
+            },
+        );
+        let request = VerificationRequest {
+            proof,
+            requester_id: "client".to_string(),
+        };
+
+        let client = NetworkClient::new("http://localhost:8080").unwrap();
+        let plain = client.with_compression(false).encode_body(&request).unwrap();
+        assert!(!plain.gzipped);
+        assert_eq!(plain.bytes, serde_json::to_vec(&request).unwrap());
+
+        let client = NetworkClient::new("http://localhost:8080").unwrap();
+        let compressed = client.encode_body(&request).unwrap();
+        assert!(compressed.gzipped);
+        assert!(
+            compressed.bytes.len() * 2 < plain.bytes.len(),
+            "{} compressed vs {} plain",
+            compressed.bytes.len(),
+            plain.bytes.len()
+        );
+
+        let mut decoded = Vec::new();
+        GzDecoder::new(compressed.bytes.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, plain.bytes);
+    }
+
     // Note: Integration tests would require a running server
 }
@@ -0,0 +1,84 @@
+use common::Optimizer;
+
+/// Per-parameter state an optimizer carries from one step to the next
+pub struct OptimizerState {
+    optimizer: Optimizer,
+    /// Momentum velocity, or Adam's first moment
+    first_moment: Vec<f64>,
+    /// Adam's second moment
+    second_moment: Vec<f64>,
+    steps: i32,
+}
+
+impl OptimizerState {
+    pub fn new(optimizer: Optimizer, num_params: usize) -> Self {
+        Self {
+            optimizer,
+            first_moment: vec![0.0; num_params],
+            second_moment: vec![0.0; num_params],
+            steps: 0,
+        }
+    }
+
+    /// Move `params` against `gradients`
+    pub fn step(&mut self, params: &mut [f64], gradients: &[f64], learning_rate: f64) {
+        self.steps += 1;
+        match self.optimizer {
+            Optimizer::Sgd => {
+                for (param, gradient) in params.iter_mut().zip(gradients) {
+                    *param -= learning_rate * gradient;
+                }
+            }
+            Optimizer::Momentum { beta } => {
+                let steps = params.iter_mut().zip(gradients).zip(self.first_moment.iter_mut());
+                for ((param, gradient), velocity) in steps {
+                    *velocity = beta * *velocity + gradient;
+                    *param -= learning_rate * *velocity;
+                }
+            }
+            Optimizer::Adam { beta1, beta2, epsilon } => {
+                // Both moments start at zero, so early estimates are scaled up
+                let first_correction = 1.0 - beta1.powi(self.steps);
+                let second_correction = 1.0 - beta2.powi(self.steps);
+                let moments = self.first_moment.iter_mut().zip(self.second_moment.iter_mut());
+                for ((param, gradient), (first, second)) in
+                    params.iter_mut().zip(gradients).zip(moments)
+                {
+                    *first = beta1 * *first + (1.0 - beta1) * gradient;
+                    *second = beta2 * *second + (1.0 - beta2) * gradient * gradient;
+                    let first_hat = *first / first_correction;
+                    let second_hat = *second / second_correction;
+                    *param -= learning_rate * first_hat / (second_hat.sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_rules() {
+        let mut params = vec![1.0, 1.0];
+
+        let mut sgd = OptimizerState::new(Optimizer::Sgd, 2);
+        sgd.step(&mut params, &[2.0, -4.0], 0.1);
+        assert!((params[0] - 0.8).abs() < 1e-12 && (params[1] - 1.4).abs() < 1e-12);
+
+        // Momentum keeps moving along past gradients
+        let mut momentum = OptimizerState::new(Optimizer::Momentum { beta: 0.5 }, 1);
+        let mut param = [0.0];
+        momentum.step(&mut param, &[1.0], 0.1);
+        momentum.step(&mut param, &[0.0], 0.1);
+        assert!((param[0] + 0.15).abs() < 1e-12);
+
+        // Adam's first step is the learning rate, whatever the gradient's scale
+        let mut adam = OptimizerState::new(Optimizer::adam(), 2);
+        let mut params = vec![0.0, 0.0];
+        adam.step(&mut params, &[1000.0, -0.001], 0.01);
+        assert!((params[0] + 0.01).abs() < 1e-6);
+        assert!((params[1] - 0.01).abs() < 1e-4);
+    }
+}
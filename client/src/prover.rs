@@ -1,25 +1,155 @@
 use blake2b_simd::blake2b;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use common::{
-    f64_to_field, CircuitBuilder, CircuitConfig, CircuitParams, ProofMetadata, Result, Sample,
-    TrainingCommitment, TrainingParams, ZkpFlError, ZkpProof,
+    create_accumulator, field_to_f64, CircuitBuilder, CircuitConfig, CircuitParams, LinearModel,
+    LogisticModel, LossComparison, ModelType, ProofMetadata, ProofPayload, RegressionModel, Result,
+    Sample, ScalerParams, TrainingCommitment, TrainingParams, ZkpFlError, ZkpProof,
 };
 use halo2_proofs::protostar;
 use halo2_proofs::{
+    circuit::Layouter,
+    plonk::{Circuit, ConstraintSystem, Error},
     poly::commitment::ParamsProver,
     poly::ipa::commitment::ParamsIPA,
-    transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
 };
 use halo2curves::pasta::pallas;
-use log::{debug, info};
-use std::time::Instant;
+use ff::Field;
+use log::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+/// Layout a proving key was generated for. Keys depend on how many samples
+/// the circuit lays out, never on the values witnessed in them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CircuitShape {
+    k: u32,
+    num_features: usize,
+    num_samples: usize,
+    model_type: ModelType,
+}
+
+/// Wraps a circuit to time its `synthesize` calls, which is where the prover
+/// assigns the witness during accumulation
+struct TimedCircuit<C> {
+    inner: C,
+    synthesis_nanos: Arc<AtomicU64>,
+}
+
+impl<C> TimedCircuit<C> {
+    fn new(inner: C) -> Self {
+        Self {
+            inner,
+            synthesis_nanos: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn synthesis_time(&self) -> Duration {
+        Duration::from_nanos(self.synthesis_nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl<F: Field, C: Circuit<F>> Circuit<F> for TimedCircuit<C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            inner: self.inner.without_witnesses(),
+            synthesis_nanos: self.synthesis_nanos.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        layouter: impl Layouter<F>,
+    ) -> std::result::Result<(), Error> {
+        let start = Instant::now();
+        let result = self.inner.synthesize(config, layouter);
+        let nanos = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.synthesis_nanos.fetch_add(nanos, Ordering::Relaxed);
+        result
+    }
+}
+
+/// Stage of proof generation. Progress is reported once each stage
+/// finishes, in declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofPhase {
+    /// Building the circuit and computing its public instance
+    CircuitBuild,
+    /// Generating the proving key, or reusing the cached one
+    KeyGeneration,
+    /// Assigning the witness while the circuit is synthesized
+    Witness,
+    /// Folding the witness commitments into the accumulator
+    Accumulation,
+    /// Encoding the accumulator into proof bytes
+    Serialization,
+}
+
+impl ProofPhase {
+    pub const ALL: [ProofPhase; 5] = [
+        ProofPhase::CircuitBuild,
+        ProofPhase::KeyGeneration,
+        ProofPhase::Witness,
+        ProofPhase::Accumulation,
+        ProofPhase::Serialization,
+    ];
+
+    /// Position of the phase, counting from 1
+    pub fn number(self) -> usize {
+        self as usize + 1
+    }
+}
+
+impl std::fmt::Display for ProofPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProofPhase::CircuitBuild => "circuit build",
+            ProofPhase::KeyGeneration => "keygen",
+            ProofPhase::Witness => "witness",
+            ProofPhase::Accumulation => "accumulation",
+            ProofPhase::Serialization => "serialization",
+        })
+    }
+}
+
+/// A finished phase of proof generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofProgress {
+    pub phase: ProofPhase,
+    /// Time spent in this phase
+    pub phase_time: Duration,
+    /// Time since proof generation started
+    pub elapsed: Duration,
+}
+
+/// Called with each phase of proof generation as it finishes
+pub type ProgressCallback = Arc<dyn Fn(ProofProgress) + Send + Sync>;
+
 pub struct ZkpProver {
     circuit_builder: CircuitBuilder,
     params: ParamsIPA<pallas::Affine>,
     circuit_config: CircuitConfig,
     current_proof: Option<ZkpProof>,
+    // Reused while proofs keep the same shape, replaced when it changes
+    proving_key: Option<(CircuitShape, Arc<protostar::ProvingKey<pallas::Affine>>)>,
+    // Directory proofs are reused from when the same training is proven again
+    proof_cache: Option<PathBuf>,
+    progress: Option<ProgressCallback>,
+    // How raw features were scaled before training, committed to in each proof
+    scaler: Option<ScalerParams>,
 }
 
 impl ZkpProver {
@@ -33,8 +163,12 @@ impl ZkpProver {
 
         let circuit_builder = CircuitBuilder::new(
             circuit_config.num_features,
-            100, // max_samples for circuit constraints
-        );
+            circuit_config.max_samples,
+        )
+        .with_strict_floats(circuit_config.strict_floats)
+        .with_precision_bits(circuit_config.precision_bits as u32)
+        .with_quantization(circuit_config.quantization()?)
+        .with_k(circuit_config.k);
 
         info!(
             "ZKP prover initialized in {}ms",
@@ -46,101 +180,258 @@ impl ZkpProver {
             params,
             circuit_config: circuit_config.clone(),
             current_proof: None,
+            proving_key: None,
+            proof_cache: None,
+            progress: None,
+            scaler: None,
         })
     }
 
+    /// Report each phase of proof generation to `callback` as it finishes
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(ProofProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    fn report_progress(&self, phase: ProofPhase, phase_time: Duration, total_start: Instant) {
+        let progress = ProofProgress {
+            phase,
+            phase_time,
+            elapsed: total_start.elapsed(),
+        };
+        debug!(
+            "Proof phase {}/{} ({}) done in {}ms (elapsed {}ms)",
+            phase.number(),
+            ProofPhase::ALL.len(),
+            phase,
+            phase_time.as_millis(),
+            progress.elapsed.as_millis()
+        );
+        if let Some(callback) = &self.progress {
+            callback(progress);
+        }
+    }
+
+    /// Keep generated proofs in `dir` and return a stored one, under a new
+    /// proof id, whenever the same training would be proven again
+    pub fn with_proof_cache(mut self, dir: Option<PathBuf>) -> Self {
+        self.proof_cache = dir;
+        self
+    }
+
+    /// Prove training over `samples`, checking `cancel` between phases
     pub async fn generate_proof(
         &mut self,
         samples: Vec<Sample>,
         training_params: &TrainingParams,
+        cancel: &CancellationToken,
+    ) -> Result<ZkpProof> {
+        self.generate_seeded_proof(samples, training_params, None, cancel).await
+    }
+
+    /// `generate_proof` drawing the prover's randomness from `seed`, so the
+    /// same samples, training and seed give byte-identical `proof_bytes` for
+    /// golden-file tests. The encoded payload is stamped with the Unix epoch
+    /// instead of the current time, and the proof cache is bypassed.
+    pub async fn generate_proof_with_seed(
+        &mut self,
+        samples: Vec<Sample>,
+        training_params: &TrainingParams,
+        seed: u64,
+        cancel: &CancellationToken,
+    ) -> Result<ZkpProof> {
+        self.generate_seeded_proof(samples, training_params, Some(seed), cancel).await
+    }
+
+    async fn generate_seeded_proof(
+        &mut self,
+        samples: Vec<Sample>,
+        training_params: &TrainingParams,
+        seed: Option<u64>,
+        cancel: &CancellationToken,
+    ) -> Result<ZkpProof> {
+        match self.circuit_config.model_type {
+            ModelType::Linear => {
+                self.generate_model_proof::<LinearModel>(samples, training_params, seed, cancel)
+                    .await
+            }
+            ModelType::Logistic => {
+                self.generate_model_proof::<LogisticModel>(samples, training_params, seed, cancel)
+                    .await
+            }
+        }
+    }
+
+    /// Prove the loss of model `M` over `samples`, with randomness from
+    /// `seed` when one is given. Cancelling stops before the next phase
+    /// starts; a phase already running is finished first.
+    async fn generate_model_proof<M: RegressionModel>(
+        &mut self,
+        samples: Vec<Sample>,
+        training_params: &TrainingParams,
+        seed: Option<u64>,
+        cancel: &CancellationToken,
     ) -> Result<ZkpProof> {
-        info!("Starting proof generation for {} samples", samples.len());
+        info!(
+            "Starting {:?} proof generation for {} samples",
+            M::MODEL_TYPE,
+            samples.len()
+        );
         let total_start = Instant::now();
+        let training_loss = training_params.loss;
 
-        // Phase 1: Build circuit
-        let circuit_start = Instant::now();
+        // Quantized circuits prove the snapped values, so everything below
+        // (public loss, commitments, shared weights) uses them too
+        let (training_params, quantization_error) = match self.circuit_builder.quantization {
+            Some(scheme) => {
+                let (quantized, error) = scheme.quantize_params(training_params)?;
+                info!(
+                    "Quantized model to {} fractional bits, max error {:.3e}",
+                    scheme.precision_bits, error
+                );
+                (quantized, Some(error))
+            }
+            None => (training_params.clone(), None),
+        };
+        let training_params = &training_params;
+
+        // Phase 1: Circuit build - build the circuit from the samples and compute
+        // the field assignments and public instance the prover will use
+        let witness_start = Instant::now();
         let circuit = self
             .circuit_builder
-            .build_circuit(samples.clone(), training_params)?;
-        let circuit_time = circuit_start.elapsed();
-        debug!("Circuit built in {}ms", circuit_time.as_millis());
-
-        // Phase 2: Generate proving key
-        let keygen_start = Instant::now();
-        let proving_key = protostar::ProvingKey::new(&self.params, &circuit)
-            .map_err(|e| ZkpFlError::ProofGeneration(format!("Key generation failed: {:?}", e)))?;
-        let keygen_time = keygen_start.elapsed();
-        debug!("Proving key generated in {}ms", keygen_time.as_millis()); // Phase 3: Prepare witness generation
-        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-        let mut rng = rand::thread_rng();
+            .build_model_circuit::<M>(samples.clone(), training_params)?;
 
-        // Create public instances - for our simple circuit, we'll use the loss as public input
+        // The public instance is the mean squared error the circuit computes
+        // from the witnessed samples, not the loss training reported
         use halo2curves::pasta::Fq;
-        let loss_field: Fq = f64_to_field(training_params.loss);
-        let public_instance = vec![loss_field];
-        let public_instances: &[&[Fq]] = &[&public_instance];
+        let loss_field: Fq = circuit.expected_loss;
+        let proven_loss = match self.circuit_builder.quantization {
+            Some(scheme) => scheme.decode_field(loss_field)?,
+            None => field_to_f64(loss_field, self.circuit_builder.precision_bits)?,
+        };
+        let loss_comparison = LossComparison::new(training_loss, proven_loss);
+        let witness_time = witness_start.elapsed();
+        let training_commitment =
+            self.create_training_commitment(&samples, training_params, proven_loss)?;
+        self.report_progress(ProofPhase::CircuitBuild, witness_time, total_start);
 
-        // Phase 4: Generate proof using Protostar (includes witness and proof generation)
-        let proof_start = Instant::now();
+        let cache_path = match self.proof_cache.as_ref().filter(|_| seed.is_none()) {
+            Some(dir) => Some(self.cached_proof_path(dir, &training_commitment, training_params)?),
+            None => None,
+        };
+        if let Some(mut proof) = cache_path.as_deref().and_then(load_cached_proof) {
+            info!(
+                "Reusing cached proof for identical training ({}ms)",
+                total_start.elapsed().as_millis()
+            );
+            proof.proof_id = Uuid::new_v4();
+            proof.timestamp = Utc::now();
+            proof.verification_result = None;
+            return Ok(proof);
+        }
 
-        // Create accumulator (this includes both witness generation and proof creation)
-        let accumulator = protostar::prover::create_accumulator(
-            &self.params,
-            &proving_key,
-            &circuit,
-            &public_instances,
-            &mut rng,
-            &mut transcript,
-        )
-        .map_err(|e| ZkpFlError::ProofGeneration(format!("Proof generation failed: {:?}", e)))?;
+        // Phase 2: Generate the proving key, unless one for this shape is cached
+        check_cancelled(cancel, ProofPhase::KeyGeneration)?;
+        let keygen_start = Instant::now();
+        let shape = CircuitShape {
+            k: self.circuit_config.k,
+            num_features: self.circuit_builder.num_features,
+            num_samples: samples.len(),
+            model_type: M::MODEL_TYPE,
+        };
+        let (proving_key, cached) = match &self.proving_key {
+            Some((cached_shape, key)) if *cached_shape == shape => (key.clone(), true),
+            _ => {
+                let key = protostar::ProvingKey::new(&self.params, &circuit).map_err(|e| {
+                    ZkpFlError::ProofGeneration(format!("Key generation failed: {:?}", e))
+                })?;
+                let key = Arc::new(key);
+                self.proving_key = Some((shape, key.clone()));
+                (key, false)
+            }
+        };
+        let keygen_time = keygen_start.elapsed();
+        if cached {
+            debug!("Reusing the proving key for this circuit shape");
+        }
+        self.report_progress(ProofPhase::KeyGeneration, keygen_time, total_start);
 
-        let total_proof_time = proof_start.elapsed();
+        // Phases 3 and 4: Witness and accumulation - synthesize the circuit and
+        // commit to the witness. Synthesis assigns the witness, so its share
+        // counts as witness time and only the rest as folding.
+        check_cancelled(cancel, ProofPhase::Witness)?;
+        let circuit = TimedCircuit::new(circuit);
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let accumulate_start = Instant::now();
+        let (accumulator, transcript) =
+            create_accumulator(&self.params, &proving_key, &circuit, loss_field, rng)?;
+        let accumulate_time = accumulate_start.elapsed();
+        let synthesis_time = circuit.synthesis_time().min(accumulate_time);
+        let witness_time = witness_time + synthesis_time;
+        let folding_time = accumulate_time - synthesis_time;
+        self.report_progress(ProofPhase::Witness, synthesis_time, total_start);
+        self.report_progress(ProofPhase::Accumulation, folding_time, total_start);
 
-        // For Protostar, witness generation is integrated with proof generation
-        // We'll estimate witness time as ~30% of total proof time (empirical estimation)
-        let witness_time = total_proof_time * 30 / 100;
-        let proof_time = total_proof_time - witness_time;
+        // Phase 5: Serialize the proof
+        check_cancelled(cancel, ProofPhase::Serialization)?;
+        let serialize_start = Instant::now();
+        // The default timestamp is the Unix epoch
+        let encoded_at = if seed.is_some() { DateTime::default() } else { Utc::now() };
+        let payload = ProofPayload::for_accumulator(
+            &self.circuit_config,
+            &accumulator,
+            transcript,
+            encoded_at,
+        );
+        let proof_bytes = payload.to_bytes()?;
+        let serialize_time = serialize_start.elapsed();
         let total_time = total_start.elapsed();
-
-        // Serialize the proof
-        let proof_bytes = self.serialize_accumulator(&accumulator)?;
+        self.report_progress(ProofPhase::Serialization, serialize_time, total_start);
 
         info!("Proof generated successfully:");
         info!("  Total time: {}ms", total_time.as_millis());
-        info!(
-            "  Setup time: {}ms",
-            (circuit_time + keygen_time).as_millis()
-        );
+        info!("  Setup time: {}ms", keygen_time.as_millis());
         info!("  Witness time: {}ms", witness_time.as_millis());
-        info!("  Proof time: {}ms", proof_time.as_millis());
+        info!("  Folding time: {}ms", folding_time.as_millis());
         info!("  Proof size: {} bytes", proof_bytes.len());
+        info!(
+            "  Training loss: {:.9}, proven loss: {:.9} (gap {:.3e})",
+            loss_comparison.training_loss, loss_comparison.proven_loss, loss_comparison.gap
+        );
 
         // Create proof metadata
         let metadata = ProofMetadata {
-            generation_time_ms: proof_time.as_millis() as u64,
+            generation_time_ms: total_time.as_millis() as u64,
             proof_size_bytes: proof_bytes.len(),
             witness_generation_time_ms: witness_time.as_millis() as u64,
-            setup_time_ms: (circuit_time + keygen_time).as_millis() as u64,
+            setup_time_ms: keygen_time.as_millis() as u64,
+            folding_time_ms: folding_time.as_millis() as u64,
             folding_iterations: 1, // Single iteration for now
+            quantization_error,
+            loss_comparison: Some(loss_comparison),
         };
 
         // Create circuit parameters info
-        let circuit_params = CircuitParams {
-            k: self.circuit_config.k,
-            num_constraints: 1000, // TODO: Get actual constraint count from circuit
-            num_advice_columns: 10, // TODO: Get actual advice column count
-            num_fixed_columns: 2,  // TODO: Get actual fixed column count
-            max_degree: proving_key.max_folding_constraints_degree(),
-        };
-
-        // Create training commitment
-        let training_commitment = self.create_training_commitment(&samples, training_params)?;
+        let circuit_params = CircuitParams::for_model(
+            self.circuit_config.k,
+            M::MODEL_TYPE,
+            proving_key.max_folding_constraints_degree(),
+        );
 
         // Create public inputs (for verification)
-        let public_inputs = vec![format!("{:.6}", training_params.loss)];
+        // Display for f64 round-trips exactly, so the verifier sees the same
+        // value the circuit decoded instead of a rounded one
+        let public_inputs = vec![proven_loss.to_string()];
 
         // Create the final proof
-        let proof = ZkpProof::new(
+        let mut proof = ZkpProof::new(
             Uuid::new_v4(),       // session_id will be set by caller
             "client".to_string(), // client_id will be set by caller
             proof_bytes,
@@ -150,45 +441,53 @@ impl ZkpProver {
             training_commitment,
         );
 
+        // Share the weights for aggregation; the server checks them against the commitment
+        proof.weights = Some(training_params.weights.clone());
+        proof.bias = Some(training_params.bias);
+
+        if let Some(path) = &cache_path {
+            if let Err(e) = store_cached_proof(path, &proof) {
+                warn!("Failed to cache proof at {}: {}", path.display(), e);
+            }
+        }
+
         Ok(proof)
     }
 
-    fn serialize_accumulator(
+    /// Where the proof of this training is cached: named by a hash of the
+    /// training commitment, the bias and the circuit it is proven in
+    fn cached_proof_path(
         &self,
-        _accumulator: &protostar::accumulator::Accumulator<pallas::Affine>,
-    ) -> Result<Vec<u8>> {
-        // In a real implementation, you would properly serialize the accumulator
-        // For now, we create a placeholder serialization
-        use bincode;
-
-        // Create a simplified representation for serialization
-        let simplified_proof = SimplifiedProof {
-            timestamp: Utc::now(),
-            circuit_k: self.circuit_config.k,
-            num_features: self.circuit_config.num_features,
-            // In practice, you would serialize the actual accumulator components
-            placeholder_data: vec![0u8; 1024], // Placeholder proof data
-        };
-
-        bincode::serialize(&simplified_proof)
-            .map_err(|e| ZkpFlError::ProofGeneration(format!("Serialization failed: {}", e)))
+        dir: &Path,
+        commitment: &TrainingCommitment,
+        params: &TrainingParams,
+    ) -> Result<PathBuf> {
+        let key_material = serde_json::to_vec(&(
+            commitment,
+            params.bias,
+            self.circuit_config.k,
+            self.circuit_config.precision_bits,
+            self.circuit_config.model_type,
+        ))?;
+        let key = hex::encode(&blake2b(&key_material).as_bytes()[..32]);
+        Ok(dir.join(format!("proof_{}.json", key)))
     }
+
     fn create_training_commitment(
         &self,
         samples: &[Sample],
         params: &TrainingParams,
+        proven_loss: f64,
     ) -> Result<TrainingCommitment> {
-        // Create dataset hash
-        let dataset_bytes = bincode::serialize(samples).map_err(|e| {
-            ZkpFlError::ProofGeneration(format!("Dataset serialization failed: {}", e))
-        })?;
-        let dataset_hash = hex::encode(blake2b(&dataset_bytes).as_bytes());
+        let dataset_hash = TrainingCommitment::hash_samples(samples)?;
 
         // Create weights commitment
-        let weights_bytes = bincode::serialize(&params.weights).map_err(|e| {
-            ZkpFlError::ProofGeneration(format!("Weights serialization failed: {}", e))
-        })?;
-        let weights_commitment = hex::encode(blake2b(&weights_bytes).as_bytes());
+        let scheme = self.circuit_config.weights_commitment;
+        let precision_bits = self.circuit_config.precision_bits as u32;
+        let weights_commitment =
+            TrainingCommitment::commit_weights_with(scheme, &params.weights, precision_bits)?;
+        let bias_commitment =
+            TrainingCommitment::commit_bias_with(scheme, params.bias, precision_bits)?;
 
         Ok(TrainingCommitment {
             dataset_hash,
@@ -197,10 +496,22 @@ impl ZkpProver {
             learning_rate: params.learning_rate,
             epochs: params.epoch,
             weights_commitment,
-            final_loss: params.loss,
+            final_loss: proven_loss,
+            quantization: self.circuit_builder.quantization,
+            scaler: self.scaler.clone(),
+            optimizer: params.optimizer,
+            weights_commitment_scheme: scheme,
+            differential_privacy: params.differential_privacy,
+            bias_commitment: Some(bias_commitment),
         })
     }
 
+    /// Commit to `scaler` in the proofs generated from now on, so the server
+    /// knows how raw features map onto the trained model
+    pub fn set_scaler(&mut self, scaler: Option<ScalerParams>) {
+        self.scaler = scaler;
+    }
+
     pub fn set_current_proof(&mut self, proof: ZkpProof) {
         self.current_proof = Some(proof);
     }
@@ -212,28 +523,43 @@ impl ZkpProver {
     }
 }
 
-// Simplified proof structure for serialization
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SimplifiedProof {
-    timestamp: chrono::DateTime<Utc>,
-    circuit_k: u32,
-    num_features: usize,
-    placeholder_data: Vec<u8>,
+/// `ZkpFlError::Cancelled` if `cancel` fired before `phase` could start
+fn check_cancelled(cancel: &CancellationToken, phase: ProofPhase) -> Result<()> {
+    if cancel.is_cancelled() {
+        info!("Proof generation cancelled before the {} phase", phase);
+        return Err(ZkpFlError::Cancelled);
+    }
+    Ok(())
+}
+
+fn load_cached_proof(path: &Path) -> Option<ZkpProof> {
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| warn!("Ignoring unreadable cached proof {}: {}", path.display(), e))
+        .ok()
+}
+
+fn store_cached_proof(path: &Path, proof: &ZkpProof) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Write then rename, so concurrent clients never read a partial file
+    let partial = path.with_extension(format!("{}.tmp", proof.proof_id));
+    std::fs::write(&partial, serde_json::to_vec(proof)?)?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use common::{CircuitConfig, Sample, TrainingParams};
+    use common::test_utils::test_circuit_config;
+    use common::{deserialize_accumulator, CircuitConfig, Optimizer, Sample, TrainingParams};
 
     #[tokio::test]
     async fn test_proof_generation() {
-        let circuit_config = CircuitConfig {
-            k: 8,
-            num_features: 2,
-            precision_bits: 32,
-            max_iterations: 100,
-        };
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
 
         let mut prover = ZkpProver::new(&circuit_config).unwrap();
 
@@ -251,18 +577,588 @@ mod tests {
         let training_params = TrainingParams {
             weights: vec![1.0, 1.0],
             bias: 0.0,
-            loss: 0.1,
+            loss: 0.123_456_789,
             epoch: 10,
             learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
         };
 
         let proof = prover
-            .generate_proof(samples, &training_params)
+            .generate_proof(samples, &training_params, &cancel)
             .await
             .unwrap();
 
         assert!(proof.proof_size() > 0);
         assert_eq!(proof.training_commitment.num_features, 2);
         assert_eq!(proof.proof_data.circuit_params.k, 8);
+
+        // The model fits both samples exactly, so the proven loss is zero
+        // whatever training reported
+        let public_loss: f64 = proof.proof_data.public_inputs[0].parse().unwrap();
+        assert_eq!(public_loss, 0.0);
+        assert_eq!(proof.training_commitment.final_loss, public_loss);
+        assert_eq!(proof.proof_data.metadata.quantization_error, None);
+
+        let comparison = proof.proof_data.metadata.loss_comparison.unwrap();
+        assert_eq!(comparison.training_loss, training_params.loss);
+        assert_eq!(comparison.proven_loss, public_loss);
+        assert_eq!(comparison.gap, -training_params.loss);
+    }
+
+    #[tokio::test]
+    async fn test_samples_beyond_circuit_capacity_are_rejected() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig {
+            num_features: 2,
+            max_samples: 2,
+            ..test_circuit_config()
+        };
+        let mut prover = ZkpProver::new(&circuit_config).unwrap();
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples: Vec<Sample> = (0..3)
+            .map(|i| Sample {
+                features: vec![i as f64, 1.0],
+                target: i as f64 + 1.0,
+            })
+            .collect();
+
+        match prover.generate_proof(samples.clone(), &training_params, &cancel).await {
+            Err(ZkpFlError::Circuit(msg)) => {
+                assert!(msg.contains("3 samples exceed the circuit capacity of 2"), "{}", msg)
+            }
+            other => panic!("expected capacity error, got {:?}", other.map(|_| ())),
+        }
+
+        // A full circuit proves every sample it was given
+        let proof = prover
+            .generate_proof(samples[..2].to_vec(), &training_params, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(proof.training_commitment.num_samples, 2);
+        let public_loss: f64 = proof.proof_data.public_inputs[0].parse().unwrap();
+        assert_eq!(public_loss, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_hash_mismatch_is_detected() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let mut prover = ZkpProver::new(&circuit_config).unwrap();
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![
+            Sample {
+                features: vec![1.0, 2.0],
+                target: 3.0,
+            },
+            Sample {
+                features: vec![2.0, 1.0],
+                target: 3.0,
+            },
+        ];
+
+        let proof = prover
+            .generate_proof(samples.clone(), &training_params, &cancel)
+            .await
+            .unwrap();
+        let commitment = &proof.training_commitment;
+        assert!(commitment.check_dataset_hash(&samples).is_ok());
+
+        // Training on other data than the proof commits to is caught
+        let mut other = samples.clone();
+        other[1].target = 4.0;
+        match commitment.check_dataset_hash(&other) {
+            Err(ZkpFlError::Dataset(msg)) => {
+                assert!(msg.contains("Dataset hash mismatch"), "{}", msg)
+            }
+            other => panic!("expected dataset hash mismatch, got {:?}", other),
+        }
+        assert!(commitment.check_dataset_hash(&samples[..1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undersized_k_is_rejected_before_proving() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 5, ..test_circuit_config() };
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut prover = ZkpProver::new(&circuit_config).unwrap().with_progress({
+            let reported = reported.clone();
+            move |progress| reported.lock().unwrap().push(progress.phase)
+        });
+        let training_params = TrainingParams {
+            weights: vec![0.1; 5],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![
+            Sample {
+                features: vec![1.0; 5],
+                target: 1.0,
+            };
+            100
+        ];
+
+        // 100 samples of 5 features need 2^10 rows, so proving never starts
+        match prover.generate_proof(samples, &training_params, &cancel).await {
+            Err(ZkpFlError::Circuit(msg)) => {
+                assert!(msg.contains("k=8 only provides 256"), "{}", msg);
+                assert!(msg.contains("use k >= 10"), "{}", msg);
+            }
+            other => panic!("expected row budget error, got {:?}", other.map(|_| ())),
+        }
+        assert!(reported.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_proving_key_is_cached_per_shape() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let mut prover = ZkpProver::new(&circuit_config).unwrap();
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = |targets: &[f64]| -> Vec<Sample> {
+            targets
+                .iter()
+                .map(|target| Sample {
+                    features: vec![1.0, 2.0],
+                    target: *target,
+                })
+                .collect()
+        };
+        let cached_key = |prover: &ZkpProver| prover.proving_key.as_ref().unwrap().1.clone();
+
+        prover.generate_proof(samples(&[3.0, 2.0]), &training_params, &cancel).await.unwrap();
+        let first_key = cached_key(&prover);
+
+        // Same shape with different values: the key is reused, not regenerated
+        let proof = prover
+            .generate_proof(samples(&[1.0, 0.5]), &training_params, &cancel)
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&first_key, &cached_key(&prover)));
+        assert_eq!(proof.proof_data.metadata.setup_time_ms, 0);
+        assert!(proof.proof_size() > 0);
+
+        // A different number of samples changes the layout and needs a new key
+        prover.generate_proof(samples(&[3.0]), &training_params, &cancel).await.unwrap();
+        assert!(!Arc::ptr_eq(&first_key, &cached_key(&prover)));
+        assert_eq!(prover.proving_key.as_ref().unwrap().0.num_samples, 1);
+    }
+
+    #[tokio::test]
+    async fn test_identical_training_reuses_cached_proof() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let cache_dir = std::env::temp_dir().join(format!("zkp_fl_proof_cache_{}", Uuid::new_v4()));
+        let mut prover = ZkpProver::new(&circuit_config)
+            .unwrap()
+            .with_proof_cache(Some(cache_dir.clone()));
+        let mut training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+
+        let first = prover
+            .generate_proof(samples.clone(), &training_params, &cancel)
+            .await
+            .unwrap();
+        let second = prover
+            .generate_proof(samples.clone(), &training_params, &cancel)
+            .await
+            .unwrap();
+        // Freshly generated proofs differ, so equal bytes mean a cache hit
+        assert_eq!(second.proof_data.proof_bytes, first.proof_data.proof_bytes);
+        assert_ne!(second.proof_id, first.proof_id);
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+        // Any change to the training is proven again
+        training_params.learning_rate = 0.02;
+        let third = prover
+            .generate_proof(samples.clone(), &training_params, &cancel)
+            .await
+            .unwrap();
+        assert_ne!(third.proof_data.proof_bytes, first.proof_data.proof_bytes);
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 2);
+
+        // A different scaling is a different training, and the cached copy
+        // keeps the scaler it was proven with
+        let scaler = common::ScalerParams {
+            method: common::ScalingMethod::Standard,
+            offsets: vec![0.5, 1.0],
+            scales: vec![2.0, 2.0],
+        };
+        prover.set_scaler(Some(scaler.clone()));
+        let scaled = prover
+            .generate_proof(samples.clone(), &training_params, &cancel)
+            .await
+            .unwrap();
+        assert_ne!(scaled.proof_data.proof_bytes, third.proof_data.proof_bytes);
+        assert_eq!(scaled.training_commitment.scaler.as_ref(), Some(&scaler));
+        let cached = prover.generate_proof(samples, &training_params, &cancel).await.unwrap();
+        assert_eq!(cached.proof_data.proof_bytes, scaled.proof_data.proof_bytes);
+        assert_eq!(cached.training_commitment.scaler, Some(scaler));
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 3);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_seeded_proofs_are_byte_identical() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let cache_dir =
+            std::env::temp_dir().join(format!("zkp_fl_seeded_cache_{}", Uuid::new_v4()));
+        let mut prover = ZkpProver::new(&circuit_config)
+            .unwrap()
+            .with_proof_cache(Some(cache_dir.clone()));
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+
+        let first = prover
+            .generate_proof_with_seed(samples.clone(), &training_params, 7, &cancel)
+            .await
+            .unwrap();
+        // A separate prover, so the match does not come from any shared state
+        let mut other = ZkpProver::new(&circuit_config).unwrap();
+        let second = other
+            .generate_proof_with_seed(samples.clone(), &training_params, 7, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(second.proof_data.proof_bytes, first.proof_data.proof_bytes);
+        assert_ne!(second.proof_id, first.proof_id);
+
+        let reseeded = prover
+            .generate_proof_with_seed(samples.clone(), &training_params, 8, &cancel)
+            .await
+            .unwrap();
+        assert_ne!(reseeded.proof_data.proof_bytes, first.proof_data.proof_bytes);
+        let unseeded = prover.generate_proof(samples, &training_params, &cancel).await.unwrap();
+        assert_ne!(unseeded.proof_data.proof_bytes, first.proof_data.proof_bytes);
+
+        // Seeded proofs never go through the proof cache
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_progress_reports_every_phase_in_order() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut prover = ZkpProver::new(&circuit_config).unwrap().with_progress({
+            let reported = reported.clone();
+            move |progress| reported.lock().unwrap().push(progress)
+        });
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+
+        prover.generate_proof(samples, &training_params, &cancel).await.unwrap();
+        let reported = reported.lock().unwrap();
+        let phases: Vec<ProofPhase> = reported.iter().map(|progress| progress.phase).collect();
+        assert_eq!(phases, ProofPhase::ALL);
+        assert!(reported.windows(2).all(|pair| pair[0].elapsed <= pair[1].elapsed));
+        assert!(reported.iter().all(|progress| progress.phase_time <= progress.elapsed));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_proof_stops_before_proving() {
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let cache_dir =
+            std::env::temp_dir().join(format!("zkp_fl_cancelled_proof_{}", Uuid::new_v4()));
+        let cancel = CancellationToken::new();
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        // Cancel as soon as the circuit is built, as Ctrl-C would
+        let mut prover = ZkpProver::new(&circuit_config)
+            .unwrap()
+            .with_proof_cache(Some(cache_dir.clone()))
+            .with_progress({
+                let (cancel, reported) = (cancel.clone(), reported.clone());
+                move |progress| {
+                    reported.lock().unwrap().push(progress.phase);
+                    cancel.cancel();
+                }
+            });
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.0,
+            loss: 0.1,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+
+        let result = prover.generate_proof(samples, &training_params, &cancel).await;
+        assert!(matches!(result, Err(ZkpFlError::Cancelled)), "{:?}", result.map(|_| ()));
+        assert_eq!(*reported.lock().unwrap(), vec![ProofPhase::CircuitBuild]);
+        assert!(prover.get_current_proof().is_err());
+        let cached = std::fs::read_dir(&cache_dir).map_or(0, |entries| entries.count());
+        assert_eq!(cached, 0);
+    }
+
+    #[test]
+    fn test_timed_circuit_measures_synthesis() {
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+        let training_params = TrainingParams {
+            weights: vec![1.0, 0.5],
+            bias: 0.0,
+            loss: 1.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let circuit = CircuitBuilder::new(2, 1)
+            .with_precision_bits(16)
+            .build_circuit(samples, &training_params)
+            .unwrap();
+        let public_inputs = vec![vec![circuit.expected_loss]];
+
+        let timed = TimedCircuit::new(circuit);
+        assert_eq!(timed.synthesis_time(), Duration::ZERO);
+        let prover = halo2_proofs::dev::MockProver::run(8, &timed, public_inputs).unwrap();
+        assert!(prover.verify().is_ok());
+        assert!(timed.synthesis_time() > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_proof_phases_sum_to_total() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig { k: 10, ..test_circuit_config() };
+        let mut prover = ZkpProver::new(&circuit_config).unwrap();
+        let samples = common::HealthcareDataset::create_synthetic_seeded(20, 3, 1).to_samples();
+        let training_params = TrainingParams {
+            weights: vec![0.1, 0.2, 0.3],
+            bias: 0.0,
+            loss: 0.5,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+
+        let start = Instant::now();
+        let proof = prover.generate_proof(samples, &training_params, &cancel).await.unwrap();
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        // Witness, keygen and folding account for nearly all of the recorded
+        // total; each phase loses under 1ms to rounding, plus a little
+        // bookkeeping
+        let metadata = &proof.proof_data.metadata;
+        assert!(metadata.generation_time_ms <= total_ms);
+        let phases_ms = metadata.witness_generation_time_ms
+            + metadata.setup_time_ms
+            + metadata.folding_time_ms;
+        assert!(
+            phases_ms <= metadata.generation_time_ms,
+            "{} > {}",
+            phases_ms,
+            metadata.generation_time_ms
+        );
+        assert!(
+            total_ms - phases_ms <= 5 + total_ms / 10,
+            "phases {}ms of {}ms total",
+            phases_ms,
+            total_ms
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quantized_proof_generation() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig {
+            num_features: 2,
+            quantize: true,
+            ..test_circuit_config()
+        };
+        let mut prover = ZkpProver::new(&circuit_config).unwrap();
+
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+        let training_params = TrainingParams {
+            weights: vec![0.1, 0.2],
+            bias: 0.0,
+            loss: 0.123_456_789,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+
+        let proof = prover.generate_proof(samples, &training_params, &cancel).await.unwrap();
+        let scheme = proof.training_commitment.quantization.unwrap();
+        assert_eq!(scheme.precision_bits, 16);
+
+        // Snapped weights still predict 0.5 exactly, so the residual is -2.5
+        let public_loss: f64 = proof.proof_data.public_inputs[0].parse().unwrap();
+        assert_eq!(public_loss, 6.25);
+        assert_eq!(proof.training_commitment.final_loss, public_loss);
+
+        let error = proof.proof_data.metadata.quantization_error.unwrap();
+        assert!(error > 0.0 && error <= 0.5 / scheme.scale());
+    }
+
+    #[tokio::test]
+    async fn test_logistic_proof_generation() {
+        let cancel = CancellationToken::new();
+        let circuit_config = CircuitConfig {
+            k: 10,
+            num_features: 2,
+            model_type: ModelType::Logistic,
+            ..test_circuit_config()
+        };
+        let mut prover = ZkpProver::new(&circuit_config).unwrap();
+
+        let dataset = common::HealthcareDataset::create_synthetic_binary_seeded(8, 2, 3);
+        let samples = dataset.to_samples();
+        let training_params = TrainingParams {
+            weights: vec![2.0, 2.0],
+            bias: -2.0,
+            loss: 0.5,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+
+        let proof = prover
+            .generate_proof(samples.clone(), &training_params, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(proof.proof_data.circuit_params.model_type, ModelType::Logistic);
+
+        // The public loss is the Brier score of the circuit's sigmoid
+        let brier = samples
+            .iter()
+            .map(|sample| {
+                let z = training_params.bias
+                    + sample
+                        .features
+                        .iter()
+                        .zip(&training_params.weights)
+                        .map(|(x, w)| x * w)
+                        .sum::<f64>();
+                (common::approx_sigmoid(z) - sample.target).powi(2)
+            })
+            .sum::<f64>()
+            / samples.len() as f64;
+        let public_loss: f64 = proof.proof_data.public_inputs[0].parse().unwrap();
+        assert!((public_loss - brier).abs() < 1e-3, "{} vs {}", public_loss, brier);
+    }
+
+    #[test]
+    fn test_accumulator_round_trip() {
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let prover = ZkpProver::new(&circuit_config).unwrap();
+
+        let samples = vec![Sample {
+            features: vec![1.0, 2.0],
+            target: 3.0,
+        }];
+        let training_params = TrainingParams {
+            weights: vec![1.0, 0.5],
+            bias: 0.0,
+            loss: 1.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let circuit = prover
+            .circuit_builder
+            .build_circuit(samples, &training_params)
+            .unwrap();
+        let proving_key = protostar::ProvingKey::new(&prover.params, &circuit).unwrap();
+        let (accumulator, transcript) = create_accumulator(
+            &prover.params,
+            &proving_key,
+            &circuit,
+            circuit.expected_loss,
+            rand::thread_rng(),
+        )
+        .unwrap();
+
+        let bytes =
+            ProofPayload::for_accumulator(&circuit_config, &accumulator, transcript, Utc::now())
+                .to_bytes()
+                .unwrap();
+        let mut payload = ProofPayload::from_bytes(&bytes).unwrap();
+        let decoded = deserialize_accumulator(&payload).unwrap();
+        assert!(!decoded.advice_commitments.is_empty());
+        assert_eq!(decoded.advice_commitments, accumulator.advice_commitments);
+        assert_eq!(decoded.challenges, accumulator.challenges);
+        assert_eq!(decoded.instances, accumulator.instances);
+
+        // A truncated transcript no longer holds whole commitments
+        payload.data.pop();
+        let err = deserialize_accumulator(&payload).unwrap_err();
+        assert!(matches!(err, ZkpFlError::ProofSerialization(_)));
     }
 }
@@ -1,15 +1,52 @@
+use crate::optimizer::OptimizerState;
 use common::{
-    HealthcareDataset, Sample, TrainingParams, TrainingMetrics, 
-    CircuitConfig, DatasetConfig, Result, ZkpFlError
+    HealthcareDataset, Sample, ScalerParams, TrainingParams, TrainingMetrics,
+    CircuitConfig, DatasetConfig, DifferentialPrivacy, LearningRateSchedule, ModelType, Optimizer,
+    Result, ZkpFlError,
 };
-use log::{info, debug};
+use log::{info, debug, warn};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Target variance below which training is flagged as degenerate
+const DEGENERATE_TARGET_VARIANCE: f64 = 1e-10;
+
+/// Learning rate used unless one is configured
+const DEFAULT_LEARNING_RATE: f64 = 0.01;
+
+/// Probabilities are clamped this far from 0 and 1 so cross-entropy stays finite
+const PROBABILITY_EPSILON: f64 = 1e-12;
 
 pub struct Trainer {
     dataset: Option<HealthcareDataset>,
     training_params: Option<TrainingParams>,
     circuit_config: CircuitConfig,
     dataset_config: DatasetConfig,
+    /// Samples per gradient step; 0 trains on the whole set at once
+    batch_size: usize,
+    /// Seed for the per-epoch shuffle, so runs are reproducible
+    shuffle_seed: u64,
+    /// Epochs without validation improvement before stopping; 0 disables
+    patience: usize,
+    /// Smallest validation loss drop that counts as an improvement
+    min_delta: f64,
+    /// Update rule for every gradient step
+    optimizer: Optimizer,
+    /// Learning rate of the first epoch
+    learning_rate: f64,
+    /// How the learning rate changes after the first epoch
+    learning_rate_schedule: LearningRateSchedule,
+    /// Scaling fitted on the dataset, kept so new samples can be scaled alike
+    scaler: Option<ScalerParams>,
+    /// Gradient clipping and noise; `None` trains without privacy
+    differential_privacy: Option<DifferentialPrivacy>,
+    /// Seed for the gradient noise, so private runs are reproducible
+    dp_seed: u64,
+    /// Weights and bias training starts from, e.g. the last global model
+    initial_model: Option<(Vec<f64>, f64)>,
 }
 
 impl Trainer {
@@ -19,18 +56,90 @@ impl Trainer {
             training_params: None,
             circuit_config: circuit_config.clone(),
             dataset_config: dataset_config.clone(),
+            batch_size: 0,
+            shuffle_seed: 0,
+            patience: 0,
+            min_delta: 0.0,
+            optimizer: Optimizer::Sgd,
+            learning_rate: DEFAULT_LEARNING_RATE,
+            learning_rate_schedule: LearningRateSchedule::Constant,
+            scaler: None,
+            differential_privacy: None,
+            dp_seed: 0,
+            initial_model: None,
         })
     }
 
+    /// Train on shuffled mini-batches of `batch_size` samples (0 for full
+    /// batches), shuffling deterministically from `shuffle_seed`
+    pub fn with_batching(mut self, batch_size: usize, shuffle_seed: u64) -> Self {
+        self.batch_size = batch_size;
+        self.shuffle_seed = shuffle_seed;
+        self
+    }
+
+    /// Stop once the loss on the held-out split has not dropped by more
+    /// than `min_delta` for `patience` epochs, keeping the best weights seen
+    pub fn with_early_stopping(mut self, patience: usize, min_delta: f64) -> Self {
+        self.patience = patience;
+        self.min_delta = min_delta;
+        self
+    }
+
+    /// Take gradient steps with `optimizer` instead of plain SGD
+    pub fn with_optimizer(mut self, optimizer: Optimizer) -> Self {
+        self.optimizer = optimizer;
+        self
+    }
+
+    /// Start from `learning_rate` and change it every epoch per `schedule`
+    pub fn with_learning_rate(
+        mut self,
+        learning_rate: f64,
+        schedule: LearningRateSchedule,
+    ) -> Self {
+        self.learning_rate = learning_rate;
+        self.learning_rate_schedule = schedule;
+        self
+    }
+
+    /// Clip every sample's gradient and add Gaussian noise per `dp`, drawn
+    /// from `seed`. The weights and the bias are clipped separately.
+    pub fn with_differential_privacy(
+        mut self,
+        dp: Option<DifferentialPrivacy>,
+        seed: u64,
+    ) -> Self {
+        self.differential_privacy = dp;
+        self.dp_seed = seed;
+        self
+    }
+
+    /// Start the next training run from `weights` and `bias` instead of the
+    /// default small initialization
+    pub fn set_initial_model(&mut self, weights: Vec<f64>, bias: f64) -> Result<()> {
+        if weights.len() != self.circuit_config.num_features {
+            return Err(ZkpFlError::Dataset(format!(
+                "Initial model has {} weights but the circuit has {} features",
+                weights.len(),
+                self.circuit_config.num_features
+            )));
+        }
+        self.initial_model = Some((weights, bias));
+        Ok(())
+    }
+
     pub fn set_dataset(&mut self, mut dataset: HealthcareDataset) -> Result<()> {
         info!("Setting dataset with {} samples, {} features", 
               dataset.num_samples, dataset.num_features);
         
         // Normalize if configured
-        if self.dataset_config.normalize {
+        self.scaler = if self.dataset_config.normalize {
             debug!("Normalizing dataset features");
-            dataset.normalize();
-        }
+            Some(dataset.normalize())
+        } else {
+            None
+        };
         
         // Validate dataset size
         if dataset.num_features != self.circuit_config.num_features {
@@ -44,7 +153,12 @@ impl Trainer {
         Ok(())
     }
 
-    pub async fn train(&mut self, epochs: usize) -> Result<TrainingMetrics> {
+    /// Train, checking `cancel` before every epoch
+    pub async fn train(
+        &mut self,
+        epochs: usize,
+        cancel: &CancellationToken,
+    ) -> Result<TrainingMetrics> {
         let dataset = self.dataset.as_ref()
             .ok_or_else(|| ZkpFlError::Dataset("No dataset loaded".to_string()))?;
         
@@ -53,48 +167,126 @@ impl Trainer {
         
         // Initialize weights and bias
         let num_features = dataset.num_features;
-        let mut weights = vec![0.01; num_features]; // Small random initialization
-        let mut bias = 0.0;
-        let learning_rate = 0.01;
+        let (mut weights, mut bias) = match &self.initial_model {
+            Some((weights, bias)) => (weights.clone(), *bias),
+            None => (vec![0.01; num_features], 0.0), // Small random initialization
+        };
         
         let mut loss_history = Vec::new();
+        let mut learning_rate_history = Vec::new();
         let mut initial_loss = None;
         let mut convergence_epoch = None;
         let convergence_threshold = 1e-6;
         
         // Split dataset into train/test
-        let (train_dataset, _test_dataset) = dataset.train_test_split(0.8);
+        let train_ratio = self.dataset_config.train_test_split;
+        let (train_dataset, test_dataset) = if self.dataset_config.stratified {
+            dataset.stratified_split(train_ratio, self.dataset_config.split_seed)
+        } else {
+            dataset.train_test_split(train_ratio)
+        };
         let samples = train_dataset.to_samples();
         
         info!("Training on {} samples", samples.len());
+
+        let model_type = self.circuit_config.model_type;
+        if model_type == ModelType::Logistic
+            && samples.iter().any(|sample| sample.target != 0.0 && sample.target != 1.0)
+        {
+            return Err(ZkpFlError::Dataset(
+                "Logistic regression needs binary targets (0 or 1)".to_string(),
+            ));
+        }
+
+        // A constant target is fit trivially by the bias alone
+        let degenerate_target = train_dataset.target_variance() < DEGENERATE_TARGET_VARIANCE;
+        if degenerate_target {
+            warn!("Training targets have (near) zero variance; the resulting loss is not meaningful");
+        }
+        let target_range = train_dataset.target_range();
+
+        let batch_size = match self.batch_size {
+            0 => samples.len(),
+            size => size.min(samples.len()),
+        }
+        .max(1);
+        let batches_per_epoch = samples.len().div_ceil(batch_size);
+        info!("Using {} batches of up to {} samples per epoch", batches_per_epoch, batch_size);
+        let mut rng = StdRng::seed_from_u64(self.shuffle_seed);
+        let mut shuffled = samples.clone();
+        // Optimizer state carries over from one epoch to the next
+        let mut weight_optimizer = OptimizerState::new(self.optimizer, num_features);
+        let mut bias_optimizer = OptimizerState::new(self.optimizer, 1);
+        let mut noise_rng = StdRng::seed_from_u64(self.dp_seed);
+
+        // Early stopping watches the loss on the held-out split
+        let validation = test_dataset.to_samples();
+        let early_stopping = self.patience > 0 && !validation.is_empty();
+        let mut best: Option<(usize, f64, Vec<f64>, f64)> = None;
+        let mut stopped_early = false;
+        let mut epochs_completed = epochs;
         
         // Training loop
         for epoch in 0..epochs {
+            if cancel.is_cancelled() {
+                info!("Training cancelled at epoch {}", epoch);
+                return Err(ZkpFlError::Cancelled);
+            }
+
             let epoch_start = Instant::now();
-            
-            // Forward pass and gradient computation
-            let (loss, gradients) = self.compute_gradients(&samples, &weights, bias)?;
+            shuffled.shuffle(&mut rng);
+            let learning_rate = self.learning_rate_schedule.rate(self.learning_rate, epoch, epochs);
+            learning_rate_history.push(learning_rate);
+
+            let mut epoch_loss = 0.0;
+            for batch in shuffled.chunks(batch_size) {
+                // Forward pass and gradient computation
+                let (loss, gradients) = match self.differential_privacy {
+                    Some(dp) => self.private_gradients(batch, &weights, bias, dp, &mut noise_rng),
+                    None => self.compute_gradients(batch, &weights, bias)?,
+                };
+                if !loss.is_finite() {
+                    return Err(self.divergence_error(epoch, "loss", learning_rate));
+                }
+                if !gradients.iter().all(|gradient| gradient.is_finite()) {
+                    return Err(self.divergence_error(epoch, "gradient", learning_rate));
+                }
+                epoch_loss += loss * batch.len() as f64;
+
+                // Update weights and bias with the configured optimizer
+                weight_optimizer.step(&mut weights, &gradients, learning_rate);
+
+                // Update bias (gradient for bias is mean of residuals)
+                let residuals = batch.iter().map(|sample| {
+                    let prediction = self.predict(&sample.features, &weights, bias);
+                    prediction - sample.target
+                });
+                let bias_gradient = match self.differential_privacy {
+                    Some(dp) => {
+                        let clipped: f64 = residuals
+                            .map(|residual| residual.clamp(-dp.clip_norm, dp.clip_norm))
+                            .sum();
+                        let noise = dp.sigma * dp.clip_norm * standard_normal(&mut noise_rng);
+                        (clipped + noise) / batch.len() as f64
+                    }
+                    None => residuals.sum::<f64>() / batch.len() as f64,
+                };
+
+                let bias_param = std::slice::from_mut(&mut bias);
+                bias_optimizer.step(bias_param, &[bias_gradient], learning_rate);
+                if !bias.is_finite() || !weights.iter().all(|weight| weight.is_finite()) {
+                    return Err(self.divergence_error(epoch, "model weights", learning_rate));
+                }
+            }
+
+            // Mean of the batch losses, each taken before its update
+            let loss = epoch_loss / samples.len() as f64;
             loss_history.push(loss);
             
             if initial_loss.is_none() {
                 initial_loss = Some(loss);
             }
             
-            // Update weights and bias using gradient descent
-            for (weight, gradient) in weights.iter_mut().zip(gradients.iter()) {
-                *weight -= learning_rate * gradient;
-            }
-            
-            // Update bias (gradient for bias is mean of residuals)
-            let bias_gradient: f64 = samples.iter()
-                .map(|sample| {
-                    let prediction = self.predict(&sample.features, &weights, bias);
-                    prediction - sample.target
-                })
-                .sum::<f64>() / samples.len() as f64;
-            
-            bias -= learning_rate * bias_gradient;
-            
             // Check for convergence
             if epoch > 0 {
                 let loss_change = (loss_history[epoch - 1] - loss).abs();
@@ -108,65 +300,169 @@ impl Trainer {
                 debug!("Epoch {}: loss = {:.6}, took {}ms", 
                        epoch, loss, epoch_start.elapsed().as_millis());
             }
+
+            if early_stopping {
+                let (validation_loss, _) = self.compute_gradients(&validation, &weights, bias)?;
+                match &best {
+                    Some((best_epoch, best_loss, _, _))
+                        if validation_loss >= best_loss - self.min_delta =>
+                    {
+                        if epoch - best_epoch >= self.patience {
+                            info!(
+                                "No validation improvement since epoch {}, stopping at epoch {}",
+                                best_epoch, epoch
+                            );
+                            stopped_early = true;
+                            epochs_completed = epoch + 1;
+                            break;
+                        }
+                    }
+                    _ => best = Some((epoch, validation_loss, weights.clone(), bias)),
+                }
+            }
+        }
+
+        // Keep the weights that did best on the held-out split
+        let best_epoch = best.as_ref().map(|(epoch, _, _, _)| *epoch);
+        if let Some((_, _, best_weights, best_bias)) = best {
+            weights = best_weights;
+            bias = best_bias;
         }
         
         let training_time = start_time.elapsed();
-        let final_loss = loss_history.last().copied().unwrap_or(0.0);
+        let final_loss = match best_epoch {
+            Some(epoch) => loss_history[epoch],
+            None => loss_history.last().copied().unwrap_or(0.0),
+        };
         
         // Store training parameters
         self.training_params = Some(TrainingParams {
             weights: weights.clone(),
             bias,
             loss: final_loss,
-            epoch: epochs,
-            learning_rate,
+            epoch: epochs_completed,
+            learning_rate: self.learning_rate,
+            optimizer: self.optimizer,
+            differential_privacy: self.differential_privacy,
         });
         
         let metrics = TrainingMetrics {
             dataset_size: samples.len(),
             num_features,
             training_time_ms: training_time.as_millis() as u64,
-            epochs_completed: epochs,
+            epochs_completed,
             final_loss,
             initial_loss: initial_loss.unwrap_or(0.0),
             convergence_epoch,
             loss_history,
+            degenerate_target,
+            target_min: target_range.map(|(min, _)| min),
+            target_max: target_range.map(|(_, max)| max),
+            batches_per_epoch,
+            stopped_early,
+            best_epoch,
+            learning_rate_history,
         };
         
         info!("Training completed: {} epochs, final loss: {:.6}, time: {}ms",
-              epochs, final_loss, training_time.as_millis());
+              epochs_completed, final_loss, training_time.as_millis());
         
         Ok(metrics)
     }
 
+    /// Error for a run whose `quantity` stopped being finite in `epoch`,
+    /// which would otherwise be proven as a meaningless field element
+    fn divergence_error(&self, epoch: usize, quantity: &str, learning_rate: f64) -> ZkpFlError {
+        let mut hint = format!("lower the learning rate (currently {})", learning_rate);
+        if !self.dataset_config.normalize {
+            hint.push_str(" or set dataset.normalize = true");
+        }
+        ZkpFlError::Dataset(format!(
+            "Training diverged at epoch {}: the {} is not finite; {}",
+            epoch, quantity, hint
+        ))
+    }
+
     fn compute_gradients(&self, samples: &[Sample], weights: &[f64], bias: f64) -> Result<(f64, Vec<f64>)> {
         let n = samples.len() as f64;
         let mut gradients = vec![0.0; weights.len()];
         let mut total_loss = 0.0;
         
         for sample in samples {
-            let prediction = self.predict(&sample.features, weights, bias);
-            let residual = prediction - sample.target;
-            
-            // Squared loss
-            total_loss += residual * residual;
+            let (loss, gradient_scale, residual) = self.sample_loss(sample, weights, bias);
+            total_loss += loss;
             
             // Gradients for weights
             for (i, &feature) in sample.features.iter().enumerate() {
-                gradients[i] += 2.0 * residual * feature / n;
+                gradients[i] += gradient_scale * residual * feature / n;
             }
         }
         
-        let mse_loss = total_loss / n;
-        Ok((mse_loss, gradients))
+        let mean_loss = total_loss / n;
+        Ok((mean_loss, gradients))
     }
 
+    /// `compute_gradients` with each sample's gradient clipped to an L2 norm
+    /// of `dp.clip_norm` and Gaussian noise added to the sum before averaging
+    fn private_gradients(
+        &self,
+        samples: &[Sample],
+        weights: &[f64],
+        bias: f64,
+        dp: DifferentialPrivacy,
+        rng: &mut StdRng,
+    ) -> (f64, Vec<f64>) {
+        let n = samples.len() as f64;
+        let mut gradients = vec![0.0; weights.len()];
+        let mut sample_gradient = vec![0.0; weights.len()];
+        let mut total_loss = 0.0;
+
+        for sample in samples {
+            let (loss, gradient_scale, residual) = self.sample_loss(sample, weights, bias);
+            total_loss += loss;
+
+            for (gradient, &feature) in sample_gradient.iter_mut().zip(&sample.features) {
+                *gradient = gradient_scale * residual * feature;
+            }
+            let norm = sample_gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+            let clip = if norm > dp.clip_norm { dp.clip_norm / norm } else { 1.0 };
+            for (total, gradient) in gradients.iter_mut().zip(&sample_gradient) {
+                *total += gradient * clip / n;
+            }
+        }
+
+        for total in &mut gradients {
+            *total += dp.sigma * dp.clip_norm * standard_normal(rng) / n;
+        }
+        (total_loss / n, gradients)
+    }
+
+    /// Loss of one sample with the factor and residual its weight gradients
+    /// are scaled by: squared error, or cross-entropy whose gradient is the
+    /// plain residual
+    fn sample_loss(&self, sample: &Sample, weights: &[f64], bias: f64) -> (f64, f64, f64) {
+        let prediction = self.predict(&sample.features, weights, bias);
+        let residual = prediction - sample.target;
+        match self.circuit_config.model_type {
+            ModelType::Linear => (residual * residual, 2.0, residual),
+            ModelType::Logistic => {
+                let p = prediction.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                let loss = -(sample.target * p.ln() + (1.0 - sample.target) * (1.0 - p).ln());
+                (loss, 1.0, residual)
+            }
+        }
+    }
+
+    /// Model output: the linear prediction, or its sigmoid for logistic models
     fn predict(&self, features: &[f64], weights: &[f64], bias: f64) -> f64 {
         let mut prediction = bias;
         for (feature, weight) in features.iter().zip(weights.iter()) {
             prediction += feature * weight;
         }
-        prediction
+        match self.circuit_config.model_type {
+            ModelType::Linear => prediction,
+            ModelType::Logistic => 1.0 / (1.0 + (-prediction).exp()),
+        }
     }
 
     pub fn get_training_params(&self) -> Result<TrainingParams> {
@@ -177,10 +473,31 @@ impl Trainer {
     pub fn get_training_samples(&self) -> Result<Vec<Sample>> {
         let dataset = self.dataset.as_ref()
             .ok_or_else(|| ZkpFlError::Dataset("No dataset loaded".to_string()))?;
-        
-        // Return a subset of samples for circuit (to fit in circuit constraints)
-        let max_samples = std::cmp::min(dataset.num_samples, 100); // Limit for circuit
-        Ok(dataset.to_samples().into_iter().take(max_samples).collect())
+
+        // The proof must cover every sample the commitment claims
+        let max_samples = self.circuit_config.max_samples;
+        if dataset.num_samples > max_samples {
+            return Err(ZkpFlError::Dataset(format!(
+                "Dataset has {} samples but the circuit holds at most {}; raise \
+                 circuit.max_samples (and k) or sample it down with dataset.max_samples",
+                dataset.num_samples, max_samples
+            )));
+        }
+        Ok(dataset.to_samples())
+    }
+
+    /// Scaling applied to the training features, if any
+    pub fn scaler_params(&self) -> Option<&ScalerParams> {
+        self.scaler.as_ref()
+    }
+
+    /// Scale a raw sample the way the training data was scaled, so the
+    /// trained weights apply to it
+    pub fn transform_sample(&self, sample: &Sample) -> Result<Sample> {
+        match &self.scaler {
+            Some(scaler) => scaler.transform_sample(sample),
+            None => Ok(sample.clone()),
+        }
     }
 
     pub fn get_dataset_size(&self) -> usize {
@@ -192,26 +509,35 @@ impl Trainer {
     }
 }
 
+/// Standard normal draw by the Box-Muller transform
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    // 1 - [0, 1) is never zero, so the logarithm stays finite
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::test_utils::test_circuit_config;
     use common::{CircuitConfig, DatasetConfig};
 
     #[tokio::test]
     async fn test_trainer_synthetic_data() {
-        let circuit_config = CircuitConfig {
-            k: 8,
-            num_features: 3,
-            precision_bits: 32,
-            max_iterations: 100,
-        };
+        let circuit_config = CircuitConfig { precision_bits: 32, ..test_circuit_config() };
         
         let dataset_config = DatasetConfig {
-            path: "synthetic".to_string(),
+            path: "synthetic".parse().unwrap(),
             target_column: "target".to_string(),
             feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
             train_test_split: 0.8,
             normalize: true,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
         };
         
         let mut trainer = Trainer::new(&circuit_config, &dataset_config).unwrap();
@@ -221,7 +547,7 @@ mod tests {
         trainer.set_dataset(dataset).unwrap();
         
         // Train model
-        let metrics = trainer.train(10).await.unwrap();
+        let metrics = trainer.train(10, &CancellationToken::new()).await.unwrap();
         
         assert!(metrics.epochs_completed == 10);
         assert!(metrics.final_loss >= 0.0);
@@ -230,5 +556,466 @@ mod tests {
         // Get training parameters
         let params = trainer.get_training_params().unwrap();
         assert_eq!(params.weights.len(), 3);
+        assert!(!metrics.degenerate_target);
+    }
+
+    #[test]
+    fn test_dataset_beyond_circuit_capacity_is_rejected() {
+        let circuit_config = CircuitConfig {
+            max_samples: 50,
+            precision_bits: 32,
+            ..test_circuit_config()
+        };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: false,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config).unwrap();
+        trainer.set_dataset(HealthcareDataset::create_synthetic(60, 3)).unwrap();
+        let err = trainer.get_training_samples().unwrap_err();
+        assert!(err.to_string().contains("circuit.max_samples"), "{}", err);
+
+        // Samples are never silently dropped to fit
+        trainer.set_dataset(HealthcareDataset::create_synthetic(50, 3)).unwrap();
+        assert_eq!(trainer.get_training_samples().unwrap().len(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_single_mini_batch_matches_full_batch() {
+        let circuit_config = CircuitConfig { precision_bits: 32, ..test_circuit_config() };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: true,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+        let dataset = HealthcareDataset::create_synthetic_seeded(100, 3, 5);
+
+        let mut full = Trainer::new(&circuit_config, &dataset_config).unwrap();
+        full.set_dataset(dataset.clone()).unwrap();
+        let full_metrics = full.train(20, &CancellationToken::new()).await.unwrap();
+
+        // 80 training samples after the split, so this is one batch per epoch
+        let mut batched = Trainer::new(&circuit_config, &dataset_config)
+            .unwrap()
+            .with_batching(80, 7);
+        batched.set_dataset(dataset.clone()).unwrap();
+        let batched_metrics = batched.train(20, &CancellationToken::new()).await.unwrap();
+
+        assert_eq!(full_metrics.batches_per_epoch, 1);
+        assert_eq!(batched_metrics.batches_per_epoch, 1);
+        assert!((full_metrics.final_loss - batched_metrics.final_loss).abs() < 1e-9);
+        let full_params = full.get_training_params().unwrap();
+        let batched_params = batched.get_training_params().unwrap();
+        for (a, b) in full_params.weights.iter().zip(&batched_params.weights) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        assert!((full_params.bias - batched_params.bias).abs() < 1e-9);
+
+        // Smaller batches take more steps, reproducibly for the same seed
+        let mut small = Trainer::new(&circuit_config, &dataset_config)
+            .unwrap()
+            .with_batching(16, 7);
+        small.set_dataset(dataset.clone()).unwrap();
+        let small_metrics = small.train(20, &CancellationToken::new()).await.unwrap();
+        assert_eq!(small_metrics.batches_per_epoch, 5);
+
+        let mut again = Trainer::new(&circuit_config, &dataset_config)
+            .unwrap()
+            .with_batching(16, 7);
+        again.set_dataset(dataset).unwrap();
+        let again_metrics = again.train(20, &CancellationToken::new()).await.unwrap();
+        assert_eq!(small_metrics.loss_history, again_metrics.loss_history);
+    }
+
+    #[tokio::test]
+    async fn test_trainer_logistic_mode() {
+        let circuit_config = CircuitConfig {
+            k: 10,
+            model_type: ModelType::Logistic,
+            ..test_circuit_config()
+        };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "label".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: false,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config).unwrap();
+        trainer
+            .set_dataset(HealthcareDataset::create_synthetic_binary_seeded(200, 3, 11))
+            .unwrap();
+        let metrics = trainer.train(50, &CancellationToken::new()).await.unwrap();
+
+        // Cross-entropy starts near ln 2 for weights this small and decreases
+        assert!((metrics.initial_loss - std::f64::consts::LN_2).abs() < 0.05);
+        assert!(metrics.final_loss < metrics.initial_loss);
+        assert!(metrics.loss_history.windows(2).all(|pair| pair[1] <= pair[0]));
+
+        // Regression targets are rejected rather than treated as labels
+        trainer.set_dataset(HealthcareDataset::create_synthetic_seeded(50, 3, 11)).unwrap();
+        let result = trainer.train(5, &CancellationToken::new()).await;
+        assert!(matches!(result, Err(ZkpFlError::Dataset(_))));
+    }
+
+    #[tokio::test]
+    async fn test_trainer_flags_constant_target() {
+        let circuit_config = CircuitConfig {
+            num_features: 2,
+            precision_bits: 32,
+            ..test_circuit_config()
+        };
+
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string()],
+            train_test_split: 0.8,
+            normalize: false,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config).unwrap();
+
+        let num_samples = 50;
+        let dataset = HealthcareDataset {
+            name: "Constant".to_string(),
+            description: "Constant target".to_string(),
+            features: ndarray::Array2::from_shape_fn((num_samples, 2), |(i, j)| (i + j) as f64),
+            targets: ndarray::Array1::from_elem(num_samples, 1.5),
+            feature_names: vec!["f1".to_string(), "f2".to_string()],
+            target_name: "target".to_string(),
+            num_samples,
+            num_features: 2,
+            multi_targets: None,
+        };
+        trainer.set_dataset(dataset).unwrap();
+
+        let metrics = trainer.train(5, &CancellationToken::new()).await.unwrap();
+        assert!(metrics.degenerate_target);
+        assert_eq!(metrics.target_min, Some(1.5));
+        assert_eq!(metrics.target_max, Some(1.5));
+        assert_eq!(metrics.normalized_rmse(), None);
+
+        // A cancelled token stops training before the first epoch
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result = trainer.train(5, &cancel).await;
+        assert!(matches!(result, Err(ZkpFlError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_diverging_training_is_reported() {
+        let circuit_config = CircuitConfig {
+            num_features: 2,
+            precision_bits: 32,
+            ..test_circuit_config()
+        };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string()],
+            train_test_split: 0.8,
+            normalize: false,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+
+        // Unscaled features in the thousands with a large step overshoot
+        // further every epoch until the loss overflows
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config)
+            .unwrap()
+            .with_learning_rate(10.0, LearningRateSchedule::Constant);
+        let num_samples = 50;
+        let dataset = HealthcareDataset {
+            name: "Exploding".to_string(),
+            description: "Unnormalized features".to_string(),
+            features: ndarray::Array2::from_shape_fn((num_samples, 2), |(i, j)| {
+                (i + j) as f64 * 1000.0
+            }),
+            targets: ndarray::Array1::from_shape_fn(num_samples, |i| i as f64),
+            feature_names: vec!["f1".to_string(), "f2".to_string()],
+            target_name: "target".to_string(),
+            num_samples,
+            num_features: 2,
+            multi_targets: None,
+        };
+        trainer.set_dataset(dataset).unwrap();
+
+        let err = trainer.train(100, &CancellationToken::new()).await.unwrap_err();
+        assert!(matches!(err, ZkpFlError::Dataset(_)));
+        let message = err.to_string();
+        assert!(message.contains("Training diverged at epoch"), "{}", message);
+        assert!(message.contains("lower the learning rate (currently 10)"), "{}", message);
+        assert!(message.contains("dataset.normalize"), "{}", message);
+        assert!(trainer.get_training_params().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transform_sample_matches_training_scaling() {
+        let circuit_config = CircuitConfig { precision_bits: 32, ..test_circuit_config() };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: true,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+        let raw = HealthcareDataset::create_synthetic_seeded(50, 3, 9);
+
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config).unwrap();
+        trainer.set_dataset(raw.clone()).unwrap();
+        trainer.train(10, &CancellationToken::new()).await.unwrap();
+        let params = trainer.get_training_params().unwrap();
+        let scaler = trainer.scaler_params().unwrap();
+        assert_eq!(scaler.num_features(), 3);
+
+        let trained_on = trainer.get_training_samples().unwrap();
+        for (raw_sample, scaled) in raw.to_samples().iter().zip(&trained_on).take(10) {
+            let transformed = trainer.transform_sample(raw_sample).unwrap();
+            for (a, b) in transformed.features.iter().zip(&scaled.features) {
+                assert!((a - b).abs() < 1e-12);
+            }
+            let expected = trainer.predict(&scaled.features, &params.weights, params.bias);
+            let predicted = trainer.predict(&transformed.features, &params.weights, params.bias);
+            assert!((predicted - expected).abs() < 1e-12);
+        }
+
+        // Samples of the wrong width are rejected instead of silently truncated
+        let short = Sample { features: vec![1.0, 2.0], target: 0.0 };
+        assert!(trainer.transform_sample(&short).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_early_stopping_halts_on_plateau() {
+        let circuit_config = CircuitConfig { precision_bits: 32, ..test_circuit_config() };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: true,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+        let dataset = HealthcareDataset::create_synthetic_seeded(100, 3, 11);
+        let max_epochs = 1000;
+
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config)
+            .unwrap()
+            .with_early_stopping(3, 1e-4);
+        trainer.set_dataset(dataset.clone()).unwrap();
+        let metrics = trainer.train(max_epochs, &CancellationToken::new()).await.unwrap();
+
+        assert!(metrics.stopped_early);
+        assert!(metrics.epochs_completed < max_epochs);
+        assert_eq!(metrics.loss_history.len(), metrics.epochs_completed);
+        // Training ran `patience` epochs past the best one, then rolled back
+        let best_epoch = metrics.best_epoch.unwrap();
+        assert_eq!(metrics.epochs_completed, best_epoch + 3 + 1);
+        assert_eq!(metrics.final_loss, metrics.loss_history[best_epoch]);
+        assert_eq!(trainer.get_training_params().unwrap().epoch, metrics.epochs_completed);
+
+        // Without patience every epoch runs
+        let mut full = Trainer::new(&circuit_config, &dataset_config).unwrap();
+        full.set_dataset(dataset).unwrap();
+        let metrics = full.train(50, &CancellationToken::new()).await.unwrap();
+        assert!(!metrics.stopped_early);
+        assert_eq!(metrics.epochs_completed, 50);
+        assert_eq!(metrics.best_epoch, None);
+    }
+
+    #[tokio::test]
+    async fn test_adam_converges_faster_than_sgd() {
+        let circuit_config = CircuitConfig {
+            num_features: 2,
+            precision_bits: 32,
+            ..test_circuit_config()
+        };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string()],
+            train_test_split: 0.8,
+            normalize: false,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+        // Noise-free y = 2 x1 - x2 + 0.5 over uncorrelated features in [0, 1)
+        let num_samples = 100;
+        let features = ndarray::Array2::from_shape_fn((num_samples, 2), |(i, j)| {
+            if j == 0 { (i % 10) as f64 / 10.0 } else { (i / 10) as f64 / 10.0 }
+        });
+        let targets = features.rows().into_iter().map(|x| 2.0 * x[0] - x[1] + 0.5).collect();
+        let dataset = HealthcareDataset {
+            name: "Plane".to_string(),
+            description: "Noise-free linear target".to_string(),
+            features,
+            targets,
+            feature_names: vec!["f1".to_string(), "f2".to_string()],
+            target_name: "target".to_string(),
+            num_samples,
+            num_features: 2,
+            multi_targets: None,
+        };
+
+        let epochs_to_fit = |optimizer: Optimizer| {
+            let circuit_config = circuit_config.clone();
+            let dataset_config = dataset_config.clone();
+            let dataset = dataset.clone();
+            async move {
+                let mut trainer = Trainer::new(&circuit_config, &dataset_config)
+                    .unwrap()
+                    .with_batching(10, 3)
+                    .with_optimizer(optimizer);
+                trainer.set_dataset(dataset).unwrap();
+                let metrics = trainer.train(300, &CancellationToken::new()).await.unwrap();
+                assert_eq!(trainer.get_training_params().unwrap().optimizer, optimizer);
+                metrics.loss_history.iter().position(|&loss| loss < 1e-3)
+            }
+        };
+
+        let sgd = epochs_to_fit(Optimizer::Sgd).await.unwrap();
+        let momentum = epochs_to_fit(Optimizer::momentum()).await.unwrap();
+        let adam = epochs_to_fit(Optimizer::adam()).await.unwrap();
+        assert!(adam < sgd, "Adam took {} epochs, SGD {}", adam, sgd);
+        assert!(momentum < sgd, "Momentum took {} epochs, SGD {}", momentum, sgd);
+    }
+
+    #[test]
+    fn test_learning_rate_schedules() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+        assert!(close(LearningRateSchedule::Constant.rate(0.1, 50, 100), 0.1));
+
+        let step = LearningRateSchedule::Step { step_size: 10, gamma: 0.5 };
+        assert!(close(step.rate(0.1, 9, 100), 0.1));
+        assert!(close(step.rate(0.1, 10, 100), 0.05));
+        assert!(close(step.rate(0.1, 25, 100), 0.025));
+
+        let exponential = LearningRateSchedule::Exponential { gamma: 0.9 };
+        assert!(close(exponential.rate(0.1, 0, 100), 0.1));
+        assert!(close(exponential.rate(0.1, 2, 100), 0.081));
+
+        let cosine = LearningRateSchedule::Cosine { min_rate: 0.01 };
+        assert!(close(cosine.rate(0.1, 0, 100), 0.1));
+        assert!(close(cosine.rate(0.1, 50, 100), 0.055));
+        assert!(close(cosine.rate(0.1, 100, 100), 0.01));
+    }
+
+    #[tokio::test]
+    async fn test_learning_rate_history_follows_schedule() {
+        let circuit_config = CircuitConfig { precision_bits: 32, ..test_circuit_config() };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: true,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+        let schedule = LearningRateSchedule::Step { step_size: 4, gamma: 0.5 };
+        let mut trainer = Trainer::new(&circuit_config, &dataset_config)
+            .unwrap()
+            .with_learning_rate(0.05, schedule);
+        trainer
+            .set_dataset(HealthcareDataset::create_synthetic_seeded(50, 3, 2))
+            .unwrap();
+        let metrics = trainer.train(10, &CancellationToken::new()).await.unwrap();
+
+        assert_eq!(metrics.learning_rate_history.len(), metrics.loss_history.len());
+        assert_eq!(metrics.learning_rate_history[..4], [0.05; 4]);
+        assert_eq!(*metrics.learning_rate_history.last().unwrap(), schedule.rate(0.05, 9, 10));
+        assert_eq!(*metrics.learning_rate_history.last().unwrap(), 0.0125);
+        assert_eq!(trainer.get_training_params().unwrap().learning_rate, 0.05);
+    }
+
+    #[tokio::test]
+    async fn test_differential_privacy_noise_is_seeded() {
+        let circuit_config = CircuitConfig { precision_bits: 32, ..test_circuit_config() };
+        let dataset_config = DatasetConfig {
+            path: "synthetic".parse().unwrap(),
+            target_column: "target".to_string(),
+            feature_columns: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+            train_test_split: 0.8,
+            normalize: true,
+            stratified: false,
+            split_seed: 0,
+            max_samples: 0,
+            sample_seed: 0,
+            schema_path: None,
+        };
+        let train = |dp: Option<DifferentialPrivacy>, seed: u64| {
+            let mut trainer = Trainer::new(&circuit_config, &dataset_config)
+                .unwrap()
+                .with_differential_privacy(dp, seed);
+            trainer
+                .set_dataset(HealthcareDataset::create_synthetic_seeded(50, 3, 2))
+                .unwrap();
+            async move {
+                trainer.train(5, &CancellationToken::new()).await.unwrap();
+                trainer.get_training_params().unwrap()
+            }
+        };
+
+        let plain = train(None, 0).await;
+        assert!(plain.differential_privacy.is_none());
+
+        // No noise and a clip bound nothing reaches trains exactly like plain SGD
+        let unclipped = DifferentialPrivacy { clip_norm: 1e12, sigma: 0.0 };
+        let params = train(Some(unclipped), 0).await;
+        assert_eq!(params.weights, plain.weights);
+        assert_eq!(params.bias, plain.bias);
+
+        let dp = DifferentialPrivacy { clip_norm: 1.0, sigma: 1.0 };
+        let noisy = train(Some(dp), 7).await;
+        assert_eq!(noisy.differential_privacy, Some(dp));
+        assert_ne!(noisy.weights, plain.weights);
+        assert_eq!(train(Some(dp), 7).await.weights, noisy.weights);
+        assert_ne!(train(Some(dp), 8).await.weights, noisy.weights);
     }
 }
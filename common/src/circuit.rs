@@ -1,134 +1,866 @@
+use std::fmt;
 use std::marker::PhantomData;
 use ff::PrimeField;
 use halo2_proofs::{
-    circuit::{Layouter, Value},    plonk::{
-        Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector,
+    circuit::{AssignedCell, Layouter, Region, Value},    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
     },
     poly::Rotation,
 };
 use halo2curves::pasta::Fq;
-use crate::{TrainingParams, Sample, Result, ZkpFlError};
+use num_bigint::BigUint;
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use crate::{ModelType, TrainingParams, Sample, Result, ZkpFlError};
 
-/// Helper function to convert f64 to field element
-pub fn f64_to_field<F: PrimeField>(value: f64) -> F {
-    let scaled = (value * 1000000.0).abs() as u64;
-    let mut result = F::from(scaled);
-    if value < 0.0 {
-        result = -result;
+/// Fractional bits the float embedding uses when none are configured
+pub const DEFAULT_PRECISION_BITS: u32 = 32;
+
+/// Largest supported precision; the scale `2^precision_bits` must fit in a u64
+pub const MAX_PRECISION_BITS: u32 = 63;
+
+fn check_precision(precision_bits: u32) -> Result<()> {
+    if precision_bits > MAX_PRECISION_BITS {
+        return Err(ZkpFlError::Circuit(format!(
+            "Fixed-point precision of {} bits exceeds the supported {} bits",
+            precision_bits, MAX_PRECISION_BITS
+        )));
+    }
+    Ok(())
+}
+
+/// The `2^precision_bits` factor values are scaled by before embedding them
+pub fn fixed_point_scale(precision_bits: u32) -> Result<f64> {
+    check_precision(precision_bits)?;
+    Ok((1u64 << precision_bits) as f64)
+}
+
+fn field_to_biguint<F: PrimeField>(value: F) -> BigUint {
+    BigUint::from_bytes_le(value.to_repr().as_ref())
+}
+
+fn biguint_to_field<F: PrimeField>(value: &BigUint) -> Result<F> {
+    let bytes = value.to_bytes_le();
+    let mut repr = F::Repr::default();
+    if bytes.len() > repr.as_ref().len() {
+        return Err(ZkpFlError::Circuit("Value does not fit in the field".to_string()));
+    }
+    repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+    Option::from(F::from_repr(repr))
+        .ok_or_else(|| ZkpFlError::Circuit("Value does not fit in the field".to_string()))
+}
+
+/// Absolute value of the signed integer a field element encodes
+fn field_magnitude<F: PrimeField>(value: F) -> BigUint {
+    field_to_biguint(value).min(field_to_biguint(-value))
+}
+
+/// Largest magnitude a signed value can have and still decode unambiguously:
+/// negative values are stored as `p - |v|`, so both halves of the field are used
+fn max_field_magnitude<F: PrimeField>() -> BigUint {
+    field_to_biguint(-F::ONE) >> 1
+}
+
+/// Convert f64 to a field element at a `2^precision_bits` fixed-point scale,
+/// truncating whatever lies below the scale. Scaling by a power of two is
+/// exact, so truncation is the only precision lost.
+pub fn f64_to_field<F: PrimeField>(value: f64, precision_bits: u32) -> Result<F> {
+    if !value.is_finite() {
+        return Err(ZkpFlError::Circuit(format!(
+            "Value {} is not a finite number and has no field encoding",
+            value
+        )));
+    }
+    let scaled = (value * fixed_point_scale(precision_bits)?).trunc();
+    let magnitude = BigUint::from_f64(scaled.abs())
+        .filter(|m| scaled.is_finite() && *m <= max_field_magnitude::<F>());
+    let Some(magnitude) = magnitude else {
+        return Err(ZkpFlError::Circuit(format!(
+            "Value {} exceeds the field modulus at {} bits of precision",
+            value, precision_bits
+        )));
+    };
+
+    let result = biguint_to_field::<F>(&magnitude)?;
+    Ok(if value < 0.0 { -result } else { result })
+}
+
+/// Signed integer a field element encodes, failing if it does not fit in an i64
+pub fn field_to_i64<F: PrimeField>(value: F) -> Result<i64> {
+    let magnitude = field_magnitude(value)
+        .to_u64()
+        .filter(|&m| m <= i64::MAX as u64)
+        .ok_or_else(|| {
+            ZkpFlError::Circuit(
+                "Field element is too large to decode as a fixed-point value".to_string(),
+            )
+        })?;
+
+    // Negative values are stored as p - |v|; the negation then is the small one
+    if field_to_biguint(value) > max_field_magnitude::<F>() {
+        Ok(-(magnitude as i64))
+    } else {
+        Ok(magnitude as i64)
+    }
+}
+
+/// Inverse of `f64_to_field`, up to the precision lost when truncating
+pub fn field_to_f64<F: PrimeField>(value: F, precision_bits: u32) -> Result<f64> {
+    let scale = fixed_point_scale(precision_bits)?;
+    let magnitude = field_magnitude(value).to_f64().unwrap_or(f64::INFINITY) / scale;
+    if field_to_biguint(value) > max_field_magnitude::<F>() {
+        Ok(-magnitude)
+    } else {
+        Ok(magnitude)
+    }
+}
+
+/// Convert f64 to a field element, failing if the value would be truncated
+pub fn f64_to_field_strict<F: PrimeField>(value: f64, precision_bits: u32) -> Result<F> {
+    let scaled = value * fixed_point_scale(precision_bits)?;
+    if !scaled.is_finite() || scaled.fract() != 0.0 {
+        return Err(ZkpFlError::Circuit(format!(
+            "Value {} cannot be represented exactly with {} bits of precision",
+            value, precision_bits
+        )));
+    }
+    f64_to_field(value, precision_bits)
+}
+
+/// Convert f64 to a field element using the configured float mode
+pub fn convert_f64<F: PrimeField>(
+    value: f64,
+    precision_bits: u32,
+    strict_floats: bool,
+) -> Result<F> {
+    if strict_floats {
+        f64_to_field_strict(value, precision_bits)
+    } else {
+        f64_to_field(value, precision_bits)
+    }
+}
+
+/// Integer fixed-point representation of model values: `v` is stored as
+/// `round(v * 2^precision_bits)`. Circuits over quantized values embed the
+/// integers directly instead of scaling each f64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantizationScheme {
+    pub precision_bits: u32,
+}
+
+impl QuantizationScheme {
+    pub fn new(precision_bits: u32) -> Result<Self> {
+        if precision_bits > 52 {
+            return Err(ZkpFlError::Circuit(format!(
+                "Quantization precision of {} bits exceeds the 52 bits an f64 can hold",
+                precision_bits
+            )));
+        }
+        Ok(Self { precision_bits })
+    }
+
+    pub fn scale(&self) -> f64 {
+        (1u64 << self.precision_bits) as f64
+    }
+
+    /// Nearest representable integer for `value`
+    pub fn quantize(&self, value: f64) -> Result<i64> {
+        let scaled = (value * self.scale()).round();
+        if !scaled.is_finite() || scaled.abs() >= i64::MAX as f64 {
+            return Err(ZkpFlError::Circuit(format!(
+                "Value {} cannot be quantized with {} bits of precision",
+                value, self.precision_bits
+            )));
+        }
+        Ok(scaled as i64)
+    }
+
+    pub fn dequantize(&self, value: i64) -> f64 {
+        value as f64 / self.scale()
+    }
+
+    /// Value a field element built by `to_field` stands for
+    pub fn decode_field<F: PrimeField>(&self, value: F) -> Result<f64> {
+        Ok(self.dequantize(field_to_i64(value)?))
+    }
+
+    /// `value` rounded to the nearest representable value
+    pub fn snap(&self, value: f64) -> Result<f64> {
+        Ok(self.dequantize(self.quantize(value)?))
+    }
+
+    pub fn to_field<F: PrimeField>(&self, value: f64) -> Result<F> {
+        let quantized = self.quantize(value)?;
+        let magnitude = F::from(quantized.unsigned_abs());
+        Ok(if quantized < 0 { -magnitude } else { magnitude })
+    }
+
+    /// Training parameters with weights, bias and loss snapped to the grid,
+    /// and the largest absolute change that caused
+    pub fn quantize_params(&self, params: &TrainingParams) -> Result<(TrainingParams, f64)> {
+        let mut max_error = 0.0f64;
+        let mut snap = |value: f64| -> Result<f64> {
+            let snapped = self.snap(value)?;
+            max_error = max_error.max((snapped - value).abs());
+            Ok(snapped)
+        };
+
+        let weights = params.weights.iter().map(|&w| snap(w)).collect::<Result<_>>()?;
+        let bias = snap(params.bias)?;
+        let loss = snap(params.loss)?;
+
+        let quantized = TrainingParams {
+            weights,
+            bias,
+            loss,
+            ..params.clone()
+        };
+        Ok((quantized, max_error))
     }
-    result
 }
 
-/// Configuration for the linear regression circuit
+/// Rows used per sample beyond one per feature (the residual row)
+pub const ROWS_PER_SAMPLE_OVERHEAD: usize = 1;
+
+/// Rows halo2 keeps back for blinding factors at the end of every column
+pub const RESERVED_ROWS: usize = 6;
+
+/// Bits the proven loss is range checked to, so the division behind the
+/// mean cannot wrap around the field
+pub const LOSS_BITS: usize = 64;
+
+/// Denominator `Q` of the integer sigmoid coefficients
+pub const SIGMOID_DENOMINATOR: u64 = 1 << 16;
+
+/// Coefficients of `sigmoid(z) ~ (C0 + C1 * z - C3 * z^3) / Q`: the cubic
+/// least-squares fit on [-8, 8] (Kim et al., 2018) rounded to integers
+const SIGMOID_C0: u64 = 1 << 15;
+const SIGMOID_C1: u64 = 9838;
+const SIGMOID_C3: u64 = 104;
+
+/// Polynomial sigmoid the logistic circuit evaluates. Within about 0.11 of
+/// the real one for `|z| <= 8` and diverging beyond, so features should be
+/// normalized.
+pub fn approx_sigmoid(z: f64) -> f64 {
+    (SIGMOID_C0 as f64 + SIGMOID_C1 as f64 * z - SIGMOID_C3 as f64 * z.powi(3))
+        / SIGMOID_DENOMINATOR as f64
+}
+
+/// Divisor taking the sum of squared residuals to the mean at scale `S`.
+/// Linear residuals are at scale `S^2`, so their squares are at `S^4`;
+/// logistic residuals are at `Q * S^6`, so their squares are at `Q^2 * S^12`.
+fn loss_divisor(model_type: ModelType, num_samples: usize, scale: u64) -> BigUint {
+    let scale = BigUint::from(scale);
+    let num_samples = BigUint::from(num_samples);
+    match model_type {
+        ModelType::Linear => num_samples * scale.pow(3),
+        ModelType::Logistic => {
+            num_samples * BigUint::from(SIGMOID_DENOMINATOR).pow(2) * scale.pow(11)
+        }
+    }
+}
+
+/// Bits needed to range check a remainder of the mean division
+fn remainder_bits(model_type: ModelType, num_samples: usize, scale: u64) -> usize {
+    (loss_divisor(model_type, num_samples, scale) - 1u32).bits().max(1) as usize
+}
+
+/// Samples a circuit holds when `circuit.max_samples` is not configured
+pub const DEFAULT_MAX_PROOF_SAMPLES: usize = 100;
+
+/// Number of rows the regression layout needs for the given dataset shape:
+/// one row per feature product plus the residual for every sample, the
+/// loss row, range checks for the division remainder and the loss, and the
+/// rows reserved for blinding
+pub fn required_rows(
+    model_type: ModelType,
+    num_samples: usize,
+    num_features: usize,
+    scale: u64,
+) -> usize {
+    num_samples * (num_features + ROWS_PER_SAMPLE_OVERHEAD)
+        + 1
+        + 2 * (remainder_bits(model_type, num_samples, scale) + 1)
+        + LOSS_BITS
+        + 1
+        + RESERVED_ROWS
+}
+
+/// Smallest circuit size parameter `k` with `2^k >= rows`
+pub fn min_k_for_rows(rows: usize) -> u32 {
+    rows.max(1).next_power_of_two().trailing_zeros()
+}
+
+/// Configuration for the regression circuits
+///
+/// Each sample takes one row per feature, accumulating the prediction as a
+/// running dot product in `acc`, followed by a residual row:
+///
+/// | row      | input     | weight     | acc                  | aux         | factor |
+/// |----------|-----------|------------|----------------------|-------------|--------|
+/// | first    | x_0       | w_0        | b*S + x_0*w_0        | b           | S      |
+/// | dot      | x_j       | w_j        | acc_prev + x_j*w_j   |             |        |
+/// | residual | target    | sum_prev   | r(acc_prev, target)  | sum + r^2   | S      |
+/// | loss     | sum       | loss       | remainder            | complement  | D      |
+///
+/// The linear residual is `z - t*S` for the prediction `z`. The logistic one
+/// compares the polynomial sigmoid to the label `y` (witnessed at `S^2`),
+/// as `F^2 * (C0*F + C1*z - Q*y) - C3*z^3` with `F = S^2` in `factor`.
+///
+/// The loss row proves `sum = loss * D + remainder` with `remainder < D`,
+/// where `D` takes the squared residuals back to scale `S`, and the loss
+/// cell is bound to the public instance.
 #[derive(Debug, Clone)]
-pub struct LinearRegressionConfig {
-    /// Advice columns
-    pub prediction_col: Column<Advice>,
-    
+pub struct RegressionConfig {
+    /// Features, targets, sums and range-check bits
+    pub input: Column<Advice>,
+    /// Weights, running sums of squares and the loss
+    pub weight: Column<Advice>,
+    /// Running dot products, residuals and range-check accumulators
+    pub acc: Column<Advice>,
+    /// Bias, updated sums of squares and the remainder complement
+    pub aux: Column<Advice>,
+    /// Scale constants, copied from fixed cells
+    pub factor: Column<Advice>,
+    /// Holds the constants `factor` cells are copied from
+    pub constants: Column<Fixed>,
+
     /// Instance column for public inputs
     pub instance: Column<Instance>,
-    
-    /// Selector
-    pub selector: Selector,
+
+    pub q_first: Selector,
+    pub q_dot: Selector,
+    pub q_residual: Selector,
+    pub q_loss: Selector,
+    pub q_bit: Selector,
+}
+
+/// Size of the constraint system `configure` lays out. It depends only on the
+/// model, not on the samples or `k`, so a verifier can check what a proof
+/// reports against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintCounts {
+    /// Polynomial constraints across all gates
+    pub num_constraints: usize,
+    pub num_advice_columns: usize,
+    pub num_fixed_columns: usize,
 }
 
-/// Simple linear regression circuit implementation
+impl ConstraintCounts {
+    /// Counts of the circuit proving `model_type`
+    pub fn for_model(model_type: ModelType) -> Self {
+        match model_type {
+            ModelType::Linear => LinearRegressionCircuit::<Fq>::constraint_counts(),
+            ModelType::Logistic => LogisticRegressionCircuit::<Fq>::constraint_counts(),
+        }
+    }
+}
+
+/// Witness for the division turning the sum of squared residuals into the
+/// mean: `sum = loss * D + remainder` and `remainder + complement = D - 1`
 #[derive(Debug, Clone)]
-pub struct LinearRegressionCircuit<F: PrimeField> {
+struct LossWitness {
+    remainder: BigUint,
+    complement: BigUint,
+    loss: BigUint,
+}
+
+/// Model a regression circuit proves the loss of
+pub trait RegressionModel: fmt::Debug + Clone + Send + Sync + 'static {
+    const MODEL_TYPE: ModelType;
+}
+
+/// Least squares on the raw prediction
+#[derive(Debug, Clone, Copy)]
+pub struct LinearModel;
+
+impl RegressionModel for LinearModel {
+    const MODEL_TYPE: ModelType = ModelType::Linear;
+}
+
+/// Polynomial sigmoid of the prediction against binary labels
+#[derive(Debug, Clone, Copy)]
+pub struct LogisticModel;
+
+impl RegressionModel for LogisticModel {
+    const MODEL_TYPE: ModelType = ModelType::Logistic;
+}
+
+/// Linear regression circuit proving the mean squared error of a model over
+/// its training samples
+pub type LinearRegressionCircuit<F> = RegressionCircuit<F, LinearModel>;
+
+/// Logistic regression circuit proving the mean squared error (Brier score)
+/// of the polynomial sigmoid's probabilities over the training labels.
+/// Cross-entropy has no low-degree form, so it is not what gets proven.
+pub type LogisticRegressionCircuit<F> = RegressionCircuit<F, LogisticModel>;
+
+/// Regression circuit proving the mean squared residual of model `M`
+#[derive(Debug, Clone)]
+pub struct RegressionCircuit<F: PrimeField, M: RegressionModel> {
     /// Training samples (private inputs)
     pub samples: Vec<Sample>,
+    /// Sample features as embedded in the field (private inputs)
+    pub features: Vec<Vec<F>>,
+    /// Sample targets as embedded in the field (private inputs); logistic
+    /// labels are at scale `S^2`
+    pub targets: Vec<F>,
     /// Model weights (private inputs)
     pub weights: Vec<F>,
     /// Model bias (private input)
     pub bias: F,
-    /// Expected loss (public input)
+    /// Mean squared error of the model over the samples (public input)
     pub expected_loss: F,
     /// Number of features
     pub num_features: usize,
     /// Number of samples
     pub num_samples: usize,
-    
-    _marker: PhantomData<F>,
+    /// Integer scale `S = 2^precision_bits` every value was embedded with:
+    /// features, targets, weights and the bias all stand for `v * S`
+    pub scale: u64,
+    /// Integer representation the values were embedded with, if quantized
+    pub quantization: Option<QuantizationScheme>,
+
+    loss_witness: Option<LossWitness>,
+    _marker: PhantomData<(F, M)>,
 }
 
-impl<F: PrimeField> LinearRegressionCircuit<F> {
+impl<F: PrimeField, M: RegressionModel> RegressionCircuit<F, M> {
+    /// Configure a fresh constraint system and count what it holds
+    pub fn constraint_counts() -> ConstraintCounts {
+        let mut meta = ConstraintSystem::<F>::default();
+        let _ = Self::configure(&mut meta);
+        ConstraintCounts {
+            num_constraints: meta.gates().iter().map(|gate| gate.polynomials().len()).sum(),
+            num_advice_columns: meta.num_advice_columns(),
+            num_fixed_columns: meta.num_fixed_columns(),
+        }
+    }
+
     pub fn new(
         samples: Vec<Sample>,
         training_params: &TrainingParams,
         num_features: usize,
     ) -> Result<Self> {
+        Self::with_float_mode(
+            samples,
+            training_params,
+            num_features,
+            DEFAULT_PRECISION_BITS,
+            false,
+        )
+    }
+
+    pub fn with_float_mode(
+        samples: Vec<Sample>,
+        training_params: &TrainingParams,
+        num_features: usize,
+        precision_bits: u32,
+        strict_floats: bool,
+    ) -> Result<Self> {
+        Self::check_shape(&samples, training_params, num_features)?;
+        let convert = |value: f64| convert_f64(value, precision_bits, strict_floats);
+
+        // Convert f64 weights to field elements
+        let weights: Vec<F> = training_params.weights
+            .iter()
+            .map(|&w| convert(w))
+            .collect::<Result<_>>()?;
+
+        let bias = convert(training_params.bias)?;
+
+        Self::from_parts(samples, weights, bias, precision_bits, None, convert)
+    }
+
+    /// Build over integers: weights, bias and samples are quantized with
+    /// `scheme` and embedded without further scaling
+    pub fn quantized(
+        samples: Vec<Sample>,
+        training_params: &TrainingParams,
+        num_features: usize,
+        scheme: QuantizationScheme,
+    ) -> Result<Self> {
+        Self::check_shape(&samples, training_params, num_features)?;
+        let samples = samples
+            .into_iter()
+            .map(|sample| {
+                Ok(Sample {
+                    features: sample
+                        .features
+                        .iter()
+                        .map(|&x| scheme.snap(x))
+                        .collect::<Result<_>>()?,
+                    target: scheme.snap(sample.target)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let weights = training_params
+            .weights
+            .iter()
+            .map(|&w| scheme.to_field(w))
+            .collect::<Result<_>>()?;
+        let bias = scheme.to_field(training_params.bias)?;
+
+        Self::from_parts(
+            samples,
+            weights,
+            bias,
+            scheme.precision_bits,
+            Some(scheme),
+            |value| scheme.to_field(value),
+        )
+    }
+
+    /// Circuit of the given shape without any witness, as a verifier
+    /// rebuilds it from the public parameters of a proof
+    pub fn unassigned(
+        num_samples: usize,
+        num_features: usize,
+        precision_bits: u32,
+        quantization: Option<QuantizationScheme>,
+    ) -> Result<Self> {
+        if num_samples == 0 || num_features == 0 {
+            return Err(ZkpFlError::Circuit(
+                "Circuit needs at least one sample and one feature".to_string(),
+            ));
+        }
+        let precision_bits = quantization.map_or(precision_bits, |scheme| scheme.precision_bits);
+
+        Ok(Self {
+            samples: vec![],
+            features: vec![],
+            targets: vec![],
+            weights: vec![],
+            bias: F::ZERO,
+            expected_loss: F::ZERO,
+            num_features,
+            num_samples,
+            scale: fixed_point_scale(precision_bits)? as u64,
+            quantization,
+            loss_witness: None,
+            _marker: PhantomData,
+        })
+    }
+
+    fn check_shape(
+        samples: &[Sample],
+        training_params: &TrainingParams,
+        num_features: usize,
+    ) -> Result<()> {
         if samples.is_empty() {
             return Err(ZkpFlError::Circuit("No samples provided".to_string()));
         }
-        
+
+        if num_features == 0 {
+            return Err(ZkpFlError::Circuit("Circuit needs at least one feature".to_string()));
+        }
+
         if training_params.weights.len() != num_features {
             return Err(ZkpFlError::Circuit(
                 "Weights length doesn't match number of features".to_string()
             ));
         }
 
-        // Convert f64 weights to field elements
-        let weights: Vec<F> = training_params.weights
-            .iter()
-            .map(|&w| f64_to_field(w))
-            .collect();
-
-        let bias = f64_to_field(training_params.bias);
-        let expected_loss = f64_to_field(training_params.loss);
+        if samples.iter().any(|sample| sample.features.len() != num_features) {
+            return Err(ZkpFlError::Circuit(
+                "Sample features length doesn't match number of features".to_string()
+            ));
+        }
+        Ok(())
+    }
 
-        let num_samples = samples.len();
+    fn from_parts(
+        samples: Vec<Sample>,
+        weights: Vec<F>,
+        bias: F,
+        precision_bits: u32,
+        quantization: Option<QuantizationScheme>,
+        convert: impl Fn(f64) -> Result<F>,
+    ) -> Result<Self> {
+        let features = samples
+            .iter()
+            .map(|sample| sample.features.iter().map(|&x| convert(x)).collect())
+            .collect::<Result<Vec<Vec<F>>>>()?;
+        let scale = fixed_point_scale(precision_bits)? as u64;
+        let target_factor = match M::MODEL_TYPE {
+            ModelType::Linear => F::ONE,
+            ModelType::Logistic => F::from(scale),
+        };
+        let targets = samples
+            .iter()
+            .map(|sample| convert(sample.target).map(|target| target * target_factor))
+            .collect::<Result<Vec<F>>>()?;
 
-        Ok(Self {
+        let mut circuit = Self {
+            num_features: weights.len(),
+            num_samples: samples.len(),
             samples,
+            features,
+            targets,
             weights,
             bias,
-            expected_loss,
-            num_features,
-            num_samples,
+            expected_loss: F::ZERO,
+            scale,
+            quantization,
+            loss_witness: None,
             _marker: PhantomData,
+        };
+
+        let witness = circuit.compute_loss()?;
+        circuit.expected_loss = biguint_to_field(&witness.loss)?;
+        circuit.loss_witness = Some(witness);
+        Ok(circuit)
+    }
+
+    /// Prediction of the model for sample `i`, at scale `S^2`
+    fn prediction(&self, i: usize) -> F {
+        self.features[i]
+            .iter()
+            .zip(&self.weights)
+            .fold(self.bias * F::from(self.scale), |acc, (&x, &w)| acc + x * w)
+    }
+
+    /// Constant the residual row's `factor` cell holds
+    fn residual_factor(&self) -> F {
+        let scale = F::from(self.scale);
+        match M::MODEL_TYPE {
+            ModelType::Linear => scale,
+            ModelType::Logistic => scale.square(),
+        }
+    }
+
+    /// Residual of a prediction against its target, as the residual gate
+    /// constrains it
+    fn residual(&self, prediction: F, target: F) -> F {
+        let factor = self.residual_factor();
+        match M::MODEL_TYPE {
+            ModelType::Linear => prediction - target * factor,
+            ModelType::Logistic => {
+                factor.square()
+                    * (F::from(SIGMOID_C0) * factor + F::from(SIGMOID_C1) * prediction
+                        - F::from(SIGMOID_DENOMINATOR) * target)
+                    - F::from(SIGMOID_C3) * prediction.square() * prediction
+            }
+        }
+    }
+
+    fn compute_loss(&self) -> Result<LossWitness> {
+        let mut sum = F::ZERO;
+        let mut exact_sum = BigUint::from(0u32);
+        for (i, &target) in self.targets.iter().enumerate() {
+            let residual = self.residual(self.prediction(i), target);
+            sum += residual.square();
+            let magnitude = field_magnitude(residual);
+            exact_sum += &magnitude * &magnitude;
+        }
+
+        // The circuit only sees the field sum, which must not have wrapped
+        if field_to_biguint(sum) != exact_sum {
+            return Err(ZkpFlError::Circuit(
+                "Squared residuals overflow the field; normalize the data or lower the precision"
+                    .to_string(),
+            ));
+        }
+
+        let divisor = loss_divisor(M::MODEL_TYPE, self.num_samples, self.scale);
+        let loss = &exact_sum / &divisor;
+        if loss.bits() as usize > LOSS_BITS {
+            return Err(ZkpFlError::Circuit(format!(
+                "Loss does not fit in the {} bits the circuit range checks",
+                LOSS_BITS
+            )));
+        }
+        let remainder = &exact_sum % &divisor;
+        let complement = &divisor - 1u32 - &remainder;
+        Ok(LossWitness {
+            remainder,
+            complement,
+            loss,
         })
     }
+
+    /// Decompose `value` into `bits` bits (most significant first) starting
+    /// at `offset`, and constrain the recomposition to equal `target`.
+    /// Returns the first row after the decomposition.
+    fn assign_range_check(
+        config: &RegressionConfig,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        target: &AssignedCell<F, F>,
+        value: Option<&BigUint>,
+        bits: usize,
+    ) -> std::result::Result<usize, Error> {
+        region.assign_advice_from_constant(|| "range check start", config.acc, offset, F::ZERO)?;
+
+        let mut running = F::ZERO;
+        let mut last = None;
+        for i in 0..bits {
+            let row = offset + 1 + i;
+            let bit = value.map(|v| v.bit((bits - 1 - i) as u64));
+            let bit_field = F::from(u64::from(bit.unwrap_or(false)));
+            running = running.double() + bit_field;
+
+            config.q_bit.enable(region, row)?;
+            region.assign_advice(
+                || "bit",
+                config.input,
+                row,
+                || witness(bit.map(|_| bit_field)),
+            )?;
+            last = Some(region.assign_advice(
+                || "bits so far",
+                config.acc,
+                row,
+                || witness(value.map(|_| running)),
+            )?);
+        }
+
+        if let Some(last) = last {
+            region.constrain_equal(last.cell(), target.cell())?;
+        }
+        Ok(offset + bits + 1)
+    }
 }
 
-impl<F: PrimeField> Circuit<F> for LinearRegressionCircuit<F> {
-    type Config = LinearRegressionConfig;
+/// A witness value, unknown when synthesizing without witnesses
+fn witness<F: PrimeField>(value: Option<F>) -> Value<F> {
+    value.map_or(Value::unknown(), Value::known)
+}
+
+impl<F: PrimeField, M: RegressionModel> Circuit<F> for RegressionCircuit<F, M> {
+    type Config = RegressionConfig;
     type FloorPlanner = halo2_proofs::circuit::floor_planner::V1;
 
     fn without_witnesses(&self) -> Self {
         Self {
             samples: vec![],
-            weights: vec![F::ZERO; self.num_features],
+            features: vec![],
+            targets: vec![],
+            weights: vec![],
             bias: F::ZERO,
             expected_loss: F::ZERO,
             num_features: self.num_features,
-            num_samples: 0,
+            num_samples: self.num_samples,
+            scale: self.scale,
+            quantization: self.quantization,
+            loss_witness: None,
             _marker: PhantomData,
         }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        // Simple configuration with just one advice column
-        let prediction_col = meta.advice_column();
+        let input = meta.advice_column();
+        let weight = meta.advice_column();
+        let acc = meta.advice_column();
+        let aux = meta.advice_column();
+        let factor = meta.advice_column();
+        let constants = meta.fixed_column();
         let instance = meta.instance_column();
-        let selector = meta.selector();
-        
+
         // Enable equality constraints
-        meta.enable_equality(prediction_col);
+        for column in [input, weight, acc, aux, factor] {
+            meta.enable_equality(column);
+        }
         meta.enable_equality(instance);
+        meta.enable_constant(constants);
+
+        let q_first = meta.selector();
+        let q_dot = meta.selector();
+        let q_residual = meta.selector();
+        let q_loss = meta.selector();
+        let q_bit = meta.selector();
+
+        // acc = bias * S + x_0 * w_0
+        meta.create_gate("prediction start", |meta| {
+            let s = meta.query_selector(q_first);
+            let x = meta.query_advice(input, Rotation::cur());
+            let w = meta.query_advice(weight, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let bias = meta.query_advice(aux, Rotation::cur());
+            let scale = meta.query_advice(factor, Rotation::cur());
+            vec![s * (acc - (bias * scale + x * w))]
+        });
 
-        // Simple gate: just a tautology to prove the circuit works
-        meta.create_gate("simple constraint", |meta| {
-            let s = meta.query_selector(selector);
-            let prediction = meta.query_advice(prediction_col, Rotation::cur());
-            
-            // Constraint: prediction equals itself (always true)
-            vec![s * (prediction.clone() - prediction)]
+        // acc = acc_prev + x_j * w_j
+        meta.create_gate("prediction step", |meta| {
+            let s = meta.query_selector(q_dot);
+            let x = meta.query_advice(input, Rotation::cur());
+            let w = meta.query_advice(weight, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (acc - (acc_prev + x * w))]
         });
 
-        LinearRegressionConfig {
-            prediction_col,
+        // residual = r(prediction, target), sum = sum_prev + residual^2
+        meta.create_gate("squared residual", |meta| {
+            let s = meta.query_selector(q_residual);
+            let target = meta.query_advice(input, Rotation::cur());
+            let sum_prev = meta.query_advice(weight, Rotation::cur());
+            let prediction = meta.query_advice(acc, Rotation::prev());
+            let residual = meta.query_advice(acc, Rotation::cur());
+            let sum = meta.query_advice(aux, Rotation::cur());
+            let factor = meta.query_advice(factor, Rotation::cur());
+            let expected = match M::MODEL_TYPE {
+                ModelType::Linear => prediction - target * factor,
+                ModelType::Logistic => {
+                    let constant = |value: u64| Expression::Constant(F::from(value));
+                    factor.clone()
+                        * factor.clone()
+                        * (constant(SIGMOID_C0) * factor
+                            + constant(SIGMOID_C1) * prediction.clone()
+                            - constant(SIGMOID_DENOMINATOR) * target)
+                        - constant(SIGMOID_C3)
+                            * prediction.clone()
+                            * prediction.clone()
+                            * prediction
+                }
+            };
+            vec![
+                s.clone() * (residual.clone() - expected),
+                s * (sum - (sum_prev + residual.clone() * residual)),
+            ]
+        });
+
+        // sum = loss * D + remainder, remainder + complement = D - 1
+        meta.create_gate("mean", |meta| {
+            let s = meta.query_selector(q_loss);
+            let sum = meta.query_advice(input, Rotation::cur());
+            let loss = meta.query_advice(weight, Rotation::cur());
+            let remainder = meta.query_advice(acc, Rotation::cur());
+            let complement = meta.query_advice(aux, Rotation::cur());
+            let divisor = meta.query_advice(factor, Rotation::cur());
+            vec![
+                s.clone() * (sum - (loss * divisor.clone() + remainder.clone())),
+                s * (complement - (divisor - Expression::Constant(F::ONE) - remainder)),
+            ]
+        });
+
+        // bit is boolean, acc = 2 * acc_prev + bit
+        meta.create_gate("range check", |meta| {
+            let s = meta.query_selector(q_bit);
+            let bit = meta.query_advice(input, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![
+                s.clone() * bit.clone() * (Expression::Constant(F::ONE) - bit.clone()),
+                s * (acc - (acc_prev * Expression::Constant(F::from(2)) + bit)),
+            ]
+        });
+
+        RegressionConfig {
+            input,
+            weight,
+            acc,
+            aux,
+            factor,
+            constants,
             instance,
-            selector,
+            q_first,
+            q_dot,
+            q_residual,
+            q_loss,
+            q_bit,
         }
     }
 
@@ -136,34 +868,202 @@ impl<F: PrimeField> Circuit<F> for LinearRegressionCircuit<F> {
         &self,
         config: Self::Config,
         mut layouter: impl Layouter<F>,
-    ) -> std::result::Result<(), Error> {        // Simple synthesis that just assigns a value and enables the selector
-        layouter.assign_region(
-            || "simple linear regression",
+    ) -> std::result::Result<(), Error> {
+        let known = self.loss_witness.is_some();
+        let value = |v: F| witness(Some(v).filter(|_| known));
+        let scale = F::from(self.scale);
+        let residual_factor = self.residual_factor();
+        let divisor = loss_divisor(M::MODEL_TYPE, self.num_samples, self.scale);
+        let divisor_field = biguint_to_field::<F>(&divisor).map_err(|_| Error::Synthesis)?;
+
+        let loss_cell = layouter.assign_region(
+            || "regression",
             |mut region| {
-                // Assign a test value
-                let test_value: F = f64_to_field(1.0);
-                region.assign_advice(
-                    || "test prediction",
-                    config.prediction_col,
-                    0,
-                    || Value::known(test_value),
+                let mut weight_cells: Vec<AssignedCell<F, F>> =
+                    Vec::with_capacity(self.num_features);
+                let mut bias_cell: Option<AssignedCell<F, F>> = None;
+                let mut sum_cell: Option<AssignedCell<F, F>> = None;
+                let mut sum = F::ZERO;
+                let mut offset = 0;
+
+                for i in 0..self.num_samples {
+                    let mut prediction = F::ZERO;
+                    for j in 0..self.num_features {
+                        let row = offset + j;
+                        let x = self.features.get(i).map_or(F::ZERO, |f| f[j]);
+                        let w = self.weights.get(j).copied().unwrap_or(F::ZERO);
+                        region.assign_advice(|| "feature", config.input, row, || value(x))?;
+
+                        // Weights and bias are witnessed once and copied to every sample
+                        if i == 0 {
+                            weight_cells.push(region.assign_advice(
+                                || "weight",
+                                config.weight,
+                                row,
+                                || value(w),
+                            )?);
+                        } else {
+                            weight_cells[j].copy_advice(
+                                || "weight",
+                                &mut region,
+                                config.weight,
+                                row,
+                            )?;
+                        }
+
+                        if j == 0 {
+                            config.q_first.enable(&mut region, row)?;
+                            match &bias_cell {
+                                None => {
+                                    bias_cell = Some(region.assign_advice(
+                                        || "bias",
+                                        config.aux,
+                                        row,
+                                        || value(self.bias),
+                                    )?);
+                                }
+                                Some(cell) => {
+                                    cell.copy_advice(|| "bias", &mut region, config.aux, row)?;
+                                }
+                            }
+                            region.assign_advice_from_constant(
+                                || "scale",
+                                config.factor,
+                                row,
+                                scale,
+                            )?;
+                            prediction = self.bias * scale + x * w;
+                        } else {
+                            config.q_dot.enable(&mut region, row)?;
+                            prediction += x * w;
+                        }
+                        region.assign_advice(
+                            || "prediction",
+                            config.acc,
+                            row,
+                            || value(prediction),
+                        )?;
+                    }
+
+                    let row = offset + self.num_features;
+                    let target = self.targets.get(i).copied().unwrap_or(F::ZERO);
+                    let residual = self.residual(prediction, target);
+                    config.q_residual.enable(&mut region, row)?;
+                    region.assign_advice(|| "target", config.input, row, || value(target))?;
+                    match &sum_cell {
+                        None => {
+                            region.assign_advice_from_constant(
+                                || "empty sum",
+                                config.weight,
+                                row,
+                                F::ZERO,
+                            )?;
+                        }
+                        Some(cell) => {
+                            cell.copy_advice(|| "sum so far", &mut region, config.weight, row)?;
+                        }
+                    }
+                    region.assign_advice_from_constant(
+                        || "residual factor",
+                        config.factor,
+                        row,
+                        residual_factor,
+                    )?;
+                    region.assign_advice(|| "residual", config.acc, row, || value(residual))?;
+                    sum += residual.square();
+                    sum_cell = Some(region.assign_advice(
+                        || "sum of squares",
+                        config.aux,
+                        row,
+                        || value(sum),
+                    )?);
+
+                    offset = row + 1;
+                }
+
+                let loss_witness = self.loss_witness.as_ref();
+                let as_field = |v: &BigUint| biguint_to_field::<F>(v).unwrap_or(F::ZERO);
+                let loss = loss_witness.map(|w| as_field(&w.loss));
+                let remainder = loss_witness.map(|w| as_field(&w.remainder));
+                let complement = loss_witness.map(|w| as_field(&w.complement));
+
+                config.q_loss.enable(&mut region, offset)?;
+                sum_cell.as_ref().ok_or(Error::Synthesis)?.copy_advice(
+                    || "sum of squares",
+                    &mut region,
+                    config.input,
+                    offset,
+                )?;
+                let loss_cell =
+                    region.assign_advice(|| "loss", config.weight, offset, || witness(loss))?;
+                let remainder_cell = region.assign_advice(
+                    || "remainder",
+                    config.acc,
+                    offset,
+                    || witness(remainder),
                 )?;
-                
-                // Enable the selector
-                config.selector.enable(&mut region, 0)?;
-                
-                Ok(())
+                let complement_cell = region.assign_advice(
+                    || "complement",
+                    config.aux,
+                    offset,
+                    || witness(complement),
+                )?;
+                region.assign_advice_from_constant(
+                    || "divisor",
+                    config.factor,
+                    offset,
+                    divisor_field,
+                )?;
+                offset += 1;
+
+                // Both remainder and complement stay below D only if neither wrapped
+                let bits = remainder_bits(M::MODEL_TYPE, self.num_samples, self.scale);
+                offset = Self::assign_range_check(
+                    &config,
+                    &mut region,
+                    offset,
+                    &remainder_cell,
+                    loss_witness.map(|w| &w.remainder),
+                    bits,
+                )?;
+                offset = Self::assign_range_check(
+                    &config,
+                    &mut region,
+                    offset,
+                    &complement_cell,
+                    loss_witness.map(|w| &w.complement),
+                    bits,
+                )?;
+                Self::assign_range_check(
+                    &config,
+                    &mut region,
+                    offset,
+                    &loss_cell,
+                    loss_witness.map(|w| &w.loss),
+                    LOSS_BITS,
+                )?;
+
+                Ok(loss_cell)
             },
         )?;
-        
-        Ok(())
+
+        layouter.constrain_instance(loss_cell.cell(), config.instance, 0)
     }
 }
 
 /// Circuit builder for easier construction
 pub struct CircuitBuilder {
     pub num_features: usize,
+    /// Most samples a built circuit may cover; larger datasets are an error
     pub max_samples: usize,
+    pub strict_floats: bool,
+    /// Fractional bits of the float embedding; quantized circuits use the
+    /// scheme's precision instead
+    pub precision_bits: u32,
+    /// Circuit size the built circuits must fit in, if known
+    pub k: Option<u32>,
+    /// Build circuits over quantized integers instead of scaled floats
+    pub quantization: Option<QuantizationScheme>,
 }
 
 impl CircuitBuilder {
@@ -171,7 +1071,88 @@ impl CircuitBuilder {
         Self {
             num_features,
             max_samples,
+            strict_floats: false,
+            precision_bits: DEFAULT_PRECISION_BITS,
+            k: None,
+            quantization: None,
+        }
+    }
+
+    pub fn with_k(mut self, k: u32) -> Self {
+        self.k = Some(k);
+        self
+    }
+
+    /// Check that `num_samples` is within the circuit's sample capacity
+    pub fn check_capacity(&self, num_samples: usize) -> Result<()> {
+        if num_samples > self.max_samples {
+            return Err(ZkpFlError::Circuit(format!(
+                "{} samples exceed the circuit capacity of {}; raise circuit.max_samples \
+                 (and k) or sample the dataset down with dataset.max_samples",
+                num_samples, self.max_samples
+            )));
         }
+        Ok(())
+    }
+
+    /// Check that a dataset of this shape fits in `2^k` rows
+    pub fn check_row_budget(&self, model_type: ModelType, num_samples: usize) -> Result<()> {
+        let Some(k) = self.k else {
+            return Ok(());
+        };
+
+        let scale = fixed_point_scale(self.embedding_precision_bits())? as u64;
+        let rows = required_rows(model_type, num_samples, self.num_features, scale);
+        let available = 1usize << k;
+        if rows > available {
+            return Err(ZkpFlError::Circuit(format!(
+                "{} samples with {} features need {} rows but k={} only provides {}; use k >= {}",
+                num_samples,
+                self.num_features,
+                rows,
+                k,
+                available,
+                min_k_for_rows(rows)
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn with_quantization(mut self, scheme: Option<QuantizationScheme>) -> Self {
+        self.quantization = scheme;
+        self
+    }
+
+    pub fn with_strict_floats(mut self, strict_floats: bool) -> Self {
+        self.strict_floats = strict_floats;
+        self
+    }
+
+    pub fn with_precision_bits(mut self, precision_bits: u32) -> Self {
+        self.precision_bits = precision_bits;
+        self
+    }
+
+    /// Fractional bits values are embedded with, whichever mode is used
+    pub fn embedding_precision_bits(&self) -> u32 {
+        self.quantization
+            .map_or(self.precision_bits, |scheme| scheme.precision_bits)
+    }
+
+    /// Witness-less circuit for `num_samples` samples, for keygen on the
+    /// verifier side
+    pub fn build_verifier_circuit<M: RegressionModel>(
+        &self,
+        num_samples: usize,
+    ) -> Result<RegressionCircuit<Fq, M>> {
+        self.check_capacity(num_samples)?;
+        self.check_row_budget(M::MODEL_TYPE, num_samples)?;
+        RegressionCircuit::unassigned(
+            num_samples,
+            self.num_features,
+            self.precision_bits,
+            self.quantization,
+        )
     }
 
     pub fn build_circuit(
@@ -179,13 +1160,39 @@ impl CircuitBuilder {
         samples: Vec<Sample>,
         training_params: &TrainingParams,
     ) -> Result<LinearRegressionCircuit<Fq>> {
-        LinearRegressionCircuit::new(samples, training_params, self.num_features)
+        self.build_model_circuit(samples, training_params)
+    }
+
+    /// Like `build_circuit`, for the regression model `M`
+    pub fn build_model_circuit<M: RegressionModel>(
+        &self,
+        samples: Vec<Sample>,
+        training_params: &TrainingParams,
+    ) -> Result<RegressionCircuit<Fq, M>> {
+        self.check_capacity(samples.len())?;
+        self.check_row_budget(M::MODEL_TYPE, samples.len())?;
+        if let Some(scheme) = self.quantization {
+            return RegressionCircuit::quantized(
+                samples,
+                training_params,
+                self.num_features,
+                scheme,
+            );
+        }
+        RegressionCircuit::with_float_mode(
+            samples,
+            training_params,
+            self.num_features,
+            self.precision_bits,
+            self.strict_floats,
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{CommitmentScheme, Optimizer, TrainingCommitment};
     use halo2_proofs::dev::MockProver;
     
     #[test]
@@ -204,13 +1211,307 @@ mod tests {
             loss: 0.0,
             epoch: 1,
             learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
         };
         
         let circuit = LinearRegressionCircuit::new(samples, &training_params, 5).unwrap();
-        let k = 8; // Circuit size parameter
+        let k = 9; // Circuit size parameter
         let public_inputs = vec![vec![Fq::from(0)]]; // Expected loss
         
         let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
         assert!(prover.verify().is_ok());
     }
+
+    #[test]
+    fn test_circuit_constrains_mean_squared_error() {
+        // Residuals 0.5 and -1.0 give a mean squared error of 0.625
+        let samples = vec![
+            Sample {
+                features: vec![1.0],
+                target: 2.0,
+            },
+            Sample {
+                features: vec![0.0],
+                target: 1.5,
+            },
+        ];
+        let training_params = TrainingParams {
+            weights: vec![2.0],
+            bias: 0.5,
+            loss: 0.625,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+
+        let circuit = LinearRegressionCircuit::<Fq>::new(samples, &training_params, 1).unwrap();
+        assert_eq!(circuit.expected_loss, Fq::from(2_684_354_560));
+        assert_eq!(field_to_f64(circuit.expected_loss, DEFAULT_PRECISION_BITS).unwrap(), 0.625);
+
+        let prover = MockProver::run(9, &circuit, vec![vec![circuit.expected_loss]]).unwrap();
+        assert!(prover.verify().is_ok());
+
+        for wrong in [circuit.expected_loss + Fq::from(1), Fq::from(0), -circuit.expected_loss] {
+            let prover = MockProver::run(9, &circuit, vec![vec![wrong]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // Same data embedded as integers
+        let scheme = QuantizationScheme::new(8).unwrap();
+        let circuit =
+            LinearRegressionCircuit::<Fq>::quantized(circuit.samples, &training_params, 1, scheme)
+                .unwrap();
+        assert_eq!(scheme.decode_field(circuit.expected_loss).unwrap(), 0.625);
+        let prover = MockProver::run(8, &circuit, vec![vec![circuit.expected_loss]]).unwrap();
+        assert!(prover.verify().is_ok());
+    }
+
+    #[test]
+    fn test_bias_is_proven_and_committed() {
+        let samples = vec![
+            Sample {
+                features: vec![1.0],
+                target: 2.0,
+            },
+            Sample {
+                features: vec![0.0],
+                target: 1.5,
+            },
+        ];
+        let training_params = TrainingParams {
+            weights: vec![2.0],
+            bias: 0.5,
+            loss: 0.625,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let unbiased = TrainingParams {
+            bias: 0.0,
+            ..training_params.clone()
+        };
+
+        let circuit =
+            LinearRegressionCircuit::<Fq>::new(samples.clone(), &training_params, 1).unwrap();
+        let changed = LinearRegressionCircuit::<Fq>::new(samples, &unbiased, 1).unwrap();
+
+        // Only the bias differs, so only the bias term of each prediction does
+        let bias_term = f64_to_field::<Fq>(0.5, DEFAULT_PRECISION_BITS).unwrap()
+            * Fq::from(circuit.scale);
+        for i in 0..2 {
+            assert_eq!(circuit.prediction(i), changed.prediction(i) + bias_term);
+        }
+        // Residuals 0.0 and -1.5 without the bias
+        assert_eq!(field_to_f64(changed.expected_loss, DEFAULT_PRECISION_BITS).unwrap(), 1.125);
+        let prover = MockProver::run(9, &changed, vec![vec![changed.expected_loss]]).unwrap();
+        assert!(prover.verify().is_ok());
+        let prover = MockProver::run(9, &changed, vec![vec![circuit.expected_loss]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        for scheme in [CommitmentScheme::Blake2b, CommitmentScheme::Poseidon] {
+            let commit = |params: &TrainingParams| {
+                TrainingCommitment::commit_bias_with(scheme, params.bias, DEFAULT_PRECISION_BITS)
+                    .unwrap()
+            };
+            let commitment = commit(&training_params);
+            assert_eq!(commitment.len(), scheme.hex_len());
+            assert_ne!(commitment, commit(&unbiased));
+            // A bias is never mistaken for a single weight of the same value
+            let weight = TrainingCommitment::commit_weights_with(
+                scheme,
+                &[training_params.bias],
+                DEFAULT_PRECISION_BITS,
+            )
+            .unwrap();
+            assert_ne!(commitment, weight);
+        }
+    }
+
+    #[test]
+    fn test_logistic_circuit_constrains_brier_score() {
+        // Logits 1 and -1 against labels 1 and 0
+        let samples = vec![
+            Sample {
+                features: vec![1.0],
+                target: 1.0,
+            },
+            Sample {
+                features: vec![0.0],
+                target: 0.0,
+            },
+        ];
+        let training_params = TrainingParams {
+            weights: vec![2.0],
+            bias: -1.0,
+            loss: 0.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+
+        let circuit = LogisticRegressionCircuit::<Fq>::with_float_mode(
+            samples,
+            &training_params,
+            1,
+            16,
+            false,
+        )
+        .unwrap();
+        let expected = ((approx_sigmoid(1.0) - 1.0).powi(2) + approx_sigmoid(-1.0).powi(2)) / 2.0;
+        let proven = field_to_f64(circuit.expected_loss, 16).unwrap();
+        assert!((proven - expected).abs() < 1.0 / 65536.0, "{} vs {}", proven, expected);
+        for z in [-8.0, -1.0, 0.0, 2.5, 8.0] {
+            assert!((approx_sigmoid(z) - 1.0 / (1.0 + (-z).exp())).abs() < 0.12);
+        }
+
+        let prover = MockProver::run(9, &circuit, vec![vec![circuit.expected_loss]]).unwrap();
+        assert!(prover.verify().is_ok());
+        let prover =
+            MockProver::run(9, &circuit, vec![vec![circuit.expected_loss + Fq::from(1)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The default precision pushes the sigmoid past the field
+        let err = CircuitBuilder::new(1, 2)
+            .build_model_circuit::<LogisticModel>(circuit.samples.clone(), &training_params)
+            .unwrap_err();
+        assert!(err.to_string().contains("overflow the field"), "{}", err);
+    }
+
+    #[test]
+    fn test_strict_float_conversion() {
+        assert!(f64_to_field_strict::<Fq>(0.25, 32).is_ok());
+        assert!(f64_to_field_strict::<Fq>(-1.5, 32).is_ok());
+        assert!(f64_to_field_strict::<Fq>(0.123_456_7, 32).is_err());
+        assert!(f64_to_field_strict::<Fq>(f64::NAN, 32).is_err());
+        // Only two fractional bits, so a quarter is the finest step
+        assert!(f64_to_field_strict::<Fq>(0.125, 2).is_err());
+
+        // Lenient mode truncates below the scale instead
+        assert_eq!(convert_f64::<Fq>(0.123_456_7, 32, false).unwrap(), Fq::from(530_242_488));
+    }
+
+    #[test]
+    fn test_field_decoding() {
+        for precision_bits in [0, 8, 20, 32, 52, MAX_PRECISION_BITS] {
+            let step = 1.0 / fixed_point_scale(precision_bits).unwrap();
+            for value in [1.25, -3.5, 0.123_456_789, -250.75, 312.4] {
+                let field = f64_to_field::<Fq>(value, precision_bits).unwrap();
+                let decoded = field_to_f64(field, precision_bits).unwrap();
+                // Truncation moves values towards zero by less than one step
+                assert!(decoded.abs() <= value.abs(), "{} at {} bits", value, precision_bits);
+                assert!((decoded - value).abs() < step, "{} at {} bits", value, precision_bits);
+            }
+        }
+
+        // Values on the grid round-trip exactly, including ones past a u64
+        assert_eq!(field_to_f64(f64_to_field::<Fq>(-3.5, 32).unwrap(), 32).unwrap(), -3.5);
+        let large = 2f64.powi(100);
+        assert_eq!(field_to_f64(f64_to_field::<Fq>(large, 32).unwrap(), 32).unwrap(), large);
+
+        // Out of the field's range instead of wrapping
+        assert!(matches!(f64_to_field::<Fq>(1e80, 32), Err(ZkpFlError::Circuit(_))));
+        assert!(f64_to_field::<Fq>(-1e80, 32).is_err());
+        assert!(f64_to_field::<Fq>(f64::INFINITY, 32).is_err());
+        let err = f64_to_field::<Fq>(f64::NAN, 32).unwrap_err();
+        assert!(err.to_string().contains("not a finite number"), "{}", err);
+        assert!(f64_to_field::<Fq>(1.0, MAX_PRECISION_BITS + 1).is_err());
+        assert!(field_to_i64(Fq::from(u64::MAX)).is_err());
+        assert_eq!(field_to_i64(-Fq::from(42)).unwrap(), -42);
+    }
+
+    #[test]
+    fn test_row_budget_guard() {
+        let training_params = TrainingParams {
+            weights: vec![0.5; 5],
+            bias: 0.0,
+            loss: 0.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![
+            Sample {
+                features: vec![1.0; 5],
+                target: 1.0,
+            };
+            100
+        ];
+
+        // 100 * 6 + 1 + 2 * 104 + 65 + 6 = 880 rows: too many for k=8, enough for k=10
+        assert_eq!(required_rows(ModelType::Linear, 100, 5, 1 << DEFAULT_PRECISION_BITS), 880);
+        let builder = CircuitBuilder::new(5, 100).with_k(8);
+        match builder.build_circuit(samples.clone(), &training_params) {
+            Err(ZkpFlError::Circuit(msg)) => assert!(msg.contains("k >= 10"), "{}", msg),
+            other => panic!("expected row budget error, got {:?}", other.map(|_| ())),
+        }
+
+        let builder = CircuitBuilder::new(5, 100).with_k(10);
+        assert!(builder.build_circuit(samples, &training_params).is_ok());
+        assert_eq!(min_k_for_rows(1024), 10);
+        assert_eq!(min_k_for_rows(1025), 11);
+    }
+
+    #[test]
+    fn test_quantization_scheme() {
+        let scheme = QuantizationScheme::new(8).unwrap();
+        assert_eq!(scheme.quantize(1.5).unwrap(), 384);
+        assert_eq!(scheme.quantize(-0.25).unwrap(), -64);
+        assert_eq!(scheme.dequantize(384), 1.5);
+        assert_eq!(scheme.to_field::<Fq>(-0.25).unwrap(), -Fq::from(64));
+        assert!(scheme.quantize(f64::NAN).is_err());
+        assert!(QuantizationScheme::new(53).is_err());
+
+        let params = TrainingParams {
+            weights: vec![0.1, -0.7],
+            bias: 0.5,
+            loss: 0.123,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let (quantized, max_error) = scheme.quantize_params(&params).unwrap();
+        assert!(max_error > 0.0 && max_error <= 0.5 / scheme.scale());
+        // Snapped values are exact, so quantizing again changes nothing
+        let (_, error_again) = scheme.quantize_params(&quantized).unwrap();
+        assert_eq!(error_again, 0.0);
+
+        let samples = vec![
+            Sample {
+                features: vec![1.0, 2.0],
+                target: 3.0,
+            },
+        ];
+        let circuit = CircuitBuilder::new(2, 10)
+            .with_quantization(Some(scheme))
+            .build_circuit(samples, &quantized)
+            .unwrap();
+        assert_eq!(circuit.quantization, Some(scheme));
+        assert_eq!(circuit.bias, Fq::from(128));
+    }
+
+    #[test]
+    fn test_constraint_counts_match_configure() {
+        // Two single-constraint prediction gates plus three gates of two
+        // constraints, over five advice columns and the constants column
+        let expected = ConstraintCounts {
+            num_constraints: 8,
+            num_advice_columns: 5,
+            num_fixed_columns: 1,
+        };
+        assert_eq!(LinearRegressionCircuit::<Fq>::constraint_counts(), expected);
+        assert_eq!(ConstraintCounts::for_model(ModelType::Logistic), expected);
+
+        // The circuit MockProver lays out is the one the counts describe
+        let mut meta = ConstraintSystem::<Fq>::default();
+        let _ = LinearRegressionCircuit::<Fq>::configure(&mut meta);
+        assert_eq!(meta.num_advice_columns(), expected.num_advice_columns);
+        assert_eq!(meta.num_fixed_columns(), expected.num_fixed_columns);
+        assert_eq!(meta.num_selectors(), 5);
+    }
 }
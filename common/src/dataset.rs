@@ -1,9 +1,273 @@
-use crate::{Result, ZkpFlError, Sample};
-use csv::Reader;
-use ndarray::{Array1, Array2};
+use crate::{ColumnSchema, ColumnType, DatasetSchema, Result, ZkpFlError, Sample};
+use blake2b_simd::Params;
+use csv::{Reader, StringRecord};
+use flate2::read::GzDecoder;
+use ndarray::{Array1, Array2, Axis};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Number of samples used when a synthetic spec omits it
+pub const DEFAULT_SYNTHETIC_SAMPLES: usize = 1000;
+/// Number of features used when a synthetic spec omits it
+pub const DEFAULT_SYNTHETIC_FEATURES: usize = 5;
+
+/// File formats the dataset loader understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatasetFormat {
+    /// Plain or gzip-compressed (`.csv.gz`) CSV
+    Csv,
+}
+
+impl DatasetFormat {
+    /// Infer the format from the file extension, looking past a `.gz`
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let inner = if is_gzipped(path) { path.file_stem().map(Path::new) } else { Some(path) };
+        match inner.and_then(Path::extension).and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Ok(DatasetFormat::Csv),
+            Some(ext) => Err(ZkpFlError::Dataset(format!(
+                "Unsupported dataset format '.{}' for '{}' (expected .csv or .csv.gz)",
+                ext,
+                path.display()
+            ))),
+            None => Err(ZkpFlError::Dataset(format!(
+                "Cannot infer dataset format for '{}' (expected a .csv or .csv.gz file)",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// Whether `path` has a `.gz` extension and is read through a decompressor
+fn is_gzipped(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Where a client's dataset comes from.
+///
+/// Written in config files and on the command line as either a file path
+/// (`data/heart.csv`) or a synthetic spec (`synthetic`, `synthetic:1000,5`
+/// or `synthetic:1000,5,42` for samples, features and seed).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DatasetSource {
+    /// Generated data; without a seed every run draws fresh values
+    SyntheticSeeded {
+        samples: usize,
+        features: usize,
+        seed: Option<u64>,
+    },
+    /// Data read from disk
+    File { path: PathBuf, format: DatasetFormat },
+}
+
+impl DatasetSource {
+    const SYNTHETIC_PREFIX: &'static str = "synthetic";
+
+    /// Load or generate the dataset described by this source, keeping a
+    /// seeded uniform sample of at most `max_samples` rows. Files are
+    /// streamed, so only the retained rows are ever held in memory.
+    pub fn load_sampled(
+        &self,
+        target_column: &str,
+        feature_columns: &[String],
+        max_samples: usize,
+        seed: u64,
+    ) -> Result<(HealthcareDataset, SampledRows)> {
+        match self {
+            DatasetSource::File { path, format: DatasetFormat::Csv } => {
+                HealthcareDataset::load_from_csv_sampled(
+                    path,
+                    target_column,
+                    feature_columns,
+                    max_samples,
+                    seed,
+                )
+            }
+            DatasetSource::SyntheticSeeded { .. } => Ok(self
+                .load(target_column, feature_columns)?
+                .sample_rows(max_samples, seed)),
+        }
+    }
+
+    /// Load the file this source names with the columns `schema` describes
+    pub fn load_with_schema(&self, schema: &DatasetSchema) -> Result<HealthcareDataset> {
+        match self {
+            DatasetSource::File { path, format: DatasetFormat::Csv } => {
+                HealthcareDataset::load_from_csv_schema(path, schema)
+            }
+            DatasetSource::SyntheticSeeded { .. } => Err(ZkpFlError::Dataset(
+                "A dataset schema describes a file; synthetic datasets have no schema".to_string(),
+            )),
+        }
+    }
+
+    /// Load or generate the dataset described by this source
+    pub fn load(&self, target_column: &str, feature_columns: &[String]) -> Result<HealthcareDataset> {
+        match self {
+            DatasetSource::SyntheticSeeded { samples, features, seed: Some(seed) } => {
+                Ok(HealthcareDataset::create_synthetic_seeded(*samples, *features, *seed))
+            }
+            DatasetSource::SyntheticSeeded { samples, features, seed: None } => {
+                Ok(HealthcareDataset::create_synthetic(*samples, *features))
+            }
+            DatasetSource::File { path, format: DatasetFormat::Csv } => {
+                HealthcareDataset::load_from_csv(path, target_column, feature_columns)
+            }
+        }
+    }
+
+    fn parse_synthetic(spec: &str) -> Result<Self> {
+        let params: Vec<&str> = if spec.is_empty() {
+            Vec::new()
+        } else {
+            spec.split(',').map(str::trim).collect()
+        };
+        if params.len() > 3 {
+            return Err(ZkpFlError::Dataset(format!(
+                "Invalid synthetic dataset spec '{}': expected 'synthetic:<samples>,<features>[,<seed>]'",
+                spec
+            )));
+        }
+
+        let parse_count = |index: usize, name: &str, default: usize| -> Result<usize> {
+            match params.get(index) {
+                None => Ok(default),
+                Some(value) => match value.parse::<usize>() {
+                    Ok(count) if count > 0 => Ok(count),
+                    _ => Err(ZkpFlError::Dataset(format!(
+                        "Invalid synthetic dataset {} '{}': expected a positive integer",
+                        name, value
+                    ))),
+                },
+            }
+        };
+
+        let samples = parse_count(0, "sample count", DEFAULT_SYNTHETIC_SAMPLES)?;
+        let features = parse_count(1, "feature count", DEFAULT_SYNTHETIC_FEATURES)?;
+        let seed = params
+            .get(2)
+            .map(|value| {
+                value.parse::<u64>().map_err(|_| {
+                    ZkpFlError::Dataset(format!(
+                        "Invalid synthetic dataset seed '{}': expected an unsigned integer",
+                        value
+                    ))
+                })
+            })
+            .transpose()?;
+
+        Ok(DatasetSource::SyntheticSeeded { samples, features, seed })
+    }
+}
+
+impl FromStr for DatasetSource {
+    type Err = ZkpFlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ZkpFlError::Dataset("Dataset path is empty".to_string()));
+        }
+
+        if s == Self::SYNTHETIC_PREFIX {
+            return Self::parse_synthetic("");
+        }
+        if let Some(spec) = s
+            .strip_prefix(Self::SYNTHETIC_PREFIX)
+            .and_then(|rest| rest.strip_prefix(':'))
+        {
+            return Self::parse_synthetic(spec);
+        }
+
+        let path = PathBuf::from(s);
+        let format = DatasetFormat::from_path(&path)?;
+        Ok(DatasetSource::File { path, format })
+    }
+}
+
+impl fmt::Display for DatasetSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetSource::SyntheticSeeded { samples, features, seed: Some(seed) } => {
+                write!(f, "synthetic:{},{},{}", samples, features, seed)
+            }
+            DatasetSource::SyntheticSeeded { samples, features, seed: None } => {
+                write!(f, "synthetic:{},{}", samples, features)
+            }
+            DatasetSource::File { path, .. } => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl TryFrom<String> for DatasetSource {
+    type Error = ZkpFlError;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<DatasetSource> for String {
+    fn from(source: DatasetSource) -> Self {
+        source.to_string()
+    }
+}
+
+/// How features were rescaled before training
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScalingMethod {
+    /// Column minimum and range, mapping features to [0, 1]
+    MinMax,
+    /// Column mean and standard deviation
+    Standard,
+}
+
+/// Per-feature scaling learned from a dataset: each feature `x` becomes
+/// `(x - offset) / scale`. Constant columns keep an offset of 0 and a scale
+/// of 1, so they pass through unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScalerParams {
+    pub method: ScalingMethod,
+    pub offsets: Vec<f64>,
+    pub scales: Vec<f64>,
+}
+
+impl ScalerParams {
+    pub fn num_features(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Scale raw feature values the way the training data was scaled
+    pub fn transform(&self, features: &[f64]) -> Result<Vec<f64>> {
+        if features.len() != self.num_features() {
+            return Err(ZkpFlError::Dataset(format!(
+                "Sample has {} features, the scaler was fitted on {}",
+                features.len(),
+                self.num_features()
+            )));
+        }
+        Ok(features
+            .iter()
+            .zip(self.offsets.iter().zip(&self.scales))
+            .map(|(x, (offset, scale))| (x - offset) / scale)
+            .collect())
+    }
+
+    pub fn transform_sample(&self, sample: &Sample) -> Result<Sample> {
+        Ok(Sample {
+            features: self.transform(&sample.features)?,
+            target: sample.target,
+        })
+    }
+}
 
 /// Healthcare dataset structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,26 +280,25 @@ pub struct HealthcareDataset {
     pub target_name: String,
     pub num_samples: usize,
     pub num_features: usize,
+    /// Every target column when more than one was loaded; `targets` holds
+    /// the first of them
+    #[serde(default)]
+    pub multi_targets: Option<MultiTargets>,
 }
 
-/// Dataset record from CSV
-#[derive(Debug, Deserialize)]
-pub struct DatasetRecord {
-    // Common healthcare features
-    pub age: Option<f64>,
-    pub sex: Option<f64>,
-    pub chest_pain_type: Option<f64>,
-    pub resting_bp: Option<f64>,
-    pub cholesterol: Option<f64>,
-    pub fasting_blood_sugar: Option<f64>,
-    pub resting_ecg: Option<f64>,
-    pub max_heart_rate: Option<f64>,
-    pub exercise_angina: Option<f64>,
-    pub st_depression: Option<f64>,
-    pub st_slope: Option<f64>,
-    pub vessels_colored: Option<f64>,
-    pub thalassemia: Option<f64>,
-    pub target: Option<f64>,
+/// Rows a capped load read against the rows it kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampledRows {
+    pub rows_seen: usize,
+    pub rows_retained: usize,
+}
+
+/// Target matrix for multi-output regression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiTargets {
+    pub names: Vec<String>,
+    /// One row per sample, one column per entry in `names`
+    pub values: Array2<f64>,
 }
 
 impl HealthcareDataset {
@@ -45,74 +308,290 @@ impl HealthcareDataset {
         target_column: &str,
         feature_columns: &[String],
     ) -> Result<Self> {
-        let file = File::open(&path)
-            .map_err(|e| ZkpFlError::Dataset(format!("Failed to open file: {}", e)))?;
-        
-        let mut reader = Reader::from_reader(file);
-        let mut records = Vec::new();
-        
-        for result in reader.deserialize() {
-            let record: DatasetRecord = result
-                .map_err(|e| ZkpFlError::Dataset(format!("Failed to parse record: {}", e)))?;
-            records.push(record);
+        Self::load_from_csv_targets(path, &[target_column.to_string()], feature_columns)
+    }
+
+    /// Load a CSV file with one or more target columns. With several, all
+    /// of them end up in `multi_targets`. Columns are looked up by name in
+    /// the header row, so any schema works as long as the named columns exist.
+    pub fn load_from_csv_targets<P: AsRef<Path>>(
+        path: P,
+        target_columns: &[String],
+        feature_columns: &[String],
+    ) -> Result<Self> {
+        if target_columns.is_empty() {
+            return Err(ZkpFlError::Dataset("At least one target column is required".to_string()));
         }
 
-        if records.is_empty() {
-            return Err(ZkpFlError::Dataset("No records found in dataset".to_string()));
+        let mut csv = CsvColumns::open(path.as_ref(), target_columns, feature_columns)?;
+        let mut feature_values = Vec::new();
+        let mut target_values = Vec::new();
+
+        let mut row = StringRecord::new();
+        while csv.read_row(&mut row)? {
+            feature_values.extend(csv.features(&row)?);
+            target_values.extend(csv.targets(&row)?);
         }
 
-        let num_samples = records.len();
         let num_features = feature_columns.len();
-        
-        let mut features = Array2::zeros((num_samples, num_features));
-        let mut targets = Array1::zeros(num_samples);
-        
-        for (i, record) in records.iter().enumerate() {
-            // Extract target value
-            targets[i] = record.target.unwrap_or(0.0);
-            
-            // Extract feature values based on feature_columns
-            for (j, feature_name) in feature_columns.iter().enumerate() {
-                let value = match feature_name.as_str() {
-                    "age" => record.age.unwrap_or(0.0),
-                    "sex" => record.sex.unwrap_or(0.0),
-                    "chest_pain_type" => record.chest_pain_type.unwrap_or(0.0),
-                    "resting_bp" => record.resting_bp.unwrap_or(0.0),
-                    "cholesterol" => record.cholesterol.unwrap_or(0.0),
-                    "fasting_blood_sugar" => record.fasting_blood_sugar.unwrap_or(0.0),
-                    "resting_ecg" => record.resting_ecg.unwrap_or(0.0),
-                    "max_heart_rate" => record.max_heart_rate.unwrap_or(0.0),
-                    "exercise_angina" => record.exercise_angina.unwrap_or(0.0),
-                    "st_depression" => record.st_depression.unwrap_or(0.0),
-                    "st_slope" => record.st_slope.unwrap_or(0.0),
-                    "vessels_colored" => record.vessels_colored.unwrap_or(0.0),
-                    "thalassemia" => record.thalassemia.unwrap_or(0.0),
-                    _ => 0.0,
-                };
-                features[(i, j)] = value;
-            }
+        let num_samples = target_values.len() / target_columns.len();
+        if num_samples == 0 {
+            return Err(ZkpFlError::Dataset("No records found in dataset".to_string()));
         }
 
+        let features = Array2::from_shape_vec((num_samples, num_features), feature_values)
+            .map_err(|e| ZkpFlError::Dataset(format!("Invalid feature matrix: {}", e)))?;
+        let target_values = Array2::from_shape_vec((num_samples, target_columns.len()), target_values)
+            .map_err(|e| ZkpFlError::Dataset(format!("Invalid target matrix: {}", e)))?;
+        let targets = target_values.column(0).to_owned();
+
         Ok(Self {
             name: "Healthcare Dataset".to_string(),
             description: "Open source healthcare dataset for ZKP linear regression".to_string(),
             features,
             targets,
             feature_names: feature_columns.to_vec(),
+            target_name: target_columns[0].clone(),
+            num_samples,
+            num_features,
+            multi_targets: (target_columns.len() > 1).then(|| MultiTargets {
+                names: target_columns.to_vec(),
+                values: target_values,
+            }),
+        })
+    }
+
+    /// Stream a CSV file, keeping a uniform sample of at most `max_samples`
+    /// rows by reservoir sampling seeded with `seed`. Memory use depends on
+    /// `max_samples`, not on the size of the file, and every row is still
+    /// parsed so malformed data is reported wherever it is.
+    pub fn load_from_csv_sampled<P: AsRef<Path>>(
+        path: P,
+        target_column: &str,
+        feature_columns: &[String],
+        max_samples: usize,
+        seed: u64,
+    ) -> Result<(Self, SampledRows)> {
+        use rand::{Rng, SeedableRng};
+        if max_samples == 0 {
+            return Err(ZkpFlError::Dataset("max_samples must be at least 1".to_string()));
+        }
+
+        let target_columns = [target_column.to_string()];
+        let mut csv = CsvColumns::open(path.as_ref(), &target_columns, feature_columns)?;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+        // Row i replaces a random kept row with probability max_samples / (i + 1)
+        let mut reservoir: Vec<(usize, Vec<f64>, f64)> = Vec::with_capacity(max_samples);
+        let mut rows_seen = 0;
+        let mut row = StringRecord::new();
+        while csv.read_row(&mut row)? {
+            let kept = (rows_seen, csv.features(&row)?, csv.targets(&row)?[0]);
+            if reservoir.len() < max_samples {
+                reservoir.push(kept);
+            } else {
+                let slot = rng.gen_range(0..=rows_seen);
+                if slot < max_samples {
+                    reservoir[slot] = kept;
+                }
+            }
+            rows_seen += 1;
+        }
+        if reservoir.is_empty() {
+            return Err(ZkpFlError::Dataset("No records found in dataset".to_string()));
+        }
+
+        // Keep the file order among the retained rows
+        reservoir.sort_unstable_by_key(|(index, _, _)| *index);
+        let num_samples = reservoir.len();
+        let num_features = feature_columns.len();
+        let mut feature_values = Vec::with_capacity(num_samples * num_features);
+        let mut target_values = Vec::with_capacity(num_samples);
+        for (_, features, target) in reservoir {
+            feature_values.extend(features);
+            target_values.push(target);
+        }
+        let features = Array2::from_shape_vec((num_samples, num_features), feature_values)
+            .map_err(|e| ZkpFlError::Dataset(format!("Invalid feature matrix: {}", e)))?;
+
+        let dataset = Self {
+            name: "Healthcare Dataset".to_string(),
+            description: "Open source healthcare dataset for ZKP linear regression".to_string(),
+            features,
+            targets: Array1::from(target_values),
+            feature_names: feature_columns.to_vec(),
             target_name: target_column.to_string(),
             num_samples,
             num_features,
+            multi_targets: None,
+        };
+        let sampled = SampledRows {
+            rows_seen,
+            rows_retained: num_samples,
+        };
+        Ok((dataset, sampled))
+    }
+
+    /// Load a CSV file with the columns `schema` describes. Categorical
+    /// features are one-hot encoded into one `column=category` feature per
+    /// category, so the dataset can have more features than the schema has
+    /// feature columns.
+    pub fn load_from_csv_schema<P: AsRef<Path>>(path: P, schema: &DatasetSchema) -> Result<Self> {
+        schema.validate()?;
+        let target = schema
+            .target()
+            .ok_or_else(|| ZkpFlError::Dataset("Schema has no target column".to_string()))?;
+        let columns: Vec<&ColumnSchema> = schema.features().collect();
+        let target_columns = [target.name.clone()];
+        let feature_columns: Vec<String> =
+            columns.iter().map(|column| column.name.clone()).collect();
+        let mut csv = CsvColumns::open(path.as_ref(), &target_columns, &feature_columns)?;
+
+        // Categorical cells are kept as text until every category is known
+        let mut cells: Vec<Vec<CsvCell>> = Vec::new();
+        let mut target_values = Vec::new();
+        let mut row = StringRecord::new();
+        while csv.read_row(&mut row)? {
+            let line = row.position().map_or(0, |position| position.line());
+            let values = columns
+                .iter()
+                .zip(csv.feature_cells(&row))
+                .map(|(column, cell)| match column.kind {
+                    ColumnType::Numeric => {
+                        parse_cell(cell, line, &column.name).map(CsvCell::Number)
+                    }
+                    ColumnType::Categorical => {
+                        parse_category(cell, line, column).map(CsvCell::Label)
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            cells.push(values);
+            target_values.push(csv.targets(&row)?[0]);
+        }
+        let num_samples = target_values.len();
+        if num_samples == 0 {
+            return Err(ZkpFlError::Dataset("No records found in dataset".to_string()));
+        }
+
+        // Encoded feature names and, for categorical columns, the categories
+        // in encoding order
+        let mut feature_names = Vec::new();
+        let mut categories: Vec<Option<Vec<String>>> = Vec::with_capacity(columns.len());
+        for (index, column) in columns.iter().enumerate() {
+            if column.kind == ColumnType::Numeric {
+                feature_names.push(column.name.clone());
+                categories.push(None);
+                continue;
+            }
+            let labels = column.categories.clone().unwrap_or_else(|| {
+                let seen: std::collections::BTreeSet<&str> = cells
+                    .iter()
+                    .filter_map(|values| match &values[index] {
+                        CsvCell::Label(label) => Some(label.as_str()),
+                        CsvCell::Number(_) => None,
+                    })
+                    .collect();
+                seen.into_iter().map(str::to_string).collect()
+            });
+            feature_names.extend(labels.iter().map(|label| format!("{}={}", column.name, label)));
+            categories.push(Some(labels));
+        }
+
+        let num_features = feature_names.len();
+        let mut feature_values = Vec::with_capacity(num_samples * num_features);
+        for values in &cells {
+            for (cell, labels) in values.iter().zip(&categories) {
+                match cell {
+                    CsvCell::Number(value) => feature_values.push(*value),
+                    CsvCell::Label(label) => feature_values.extend(
+                        labels
+                            .iter()
+                            .flatten()
+                            .map(|category| if category == label { 1.0 } else { 0.0 }),
+                    ),
+                }
+            }
+        }
+        let features = Array2::from_shape_vec((num_samples, num_features), feature_values)
+            .map_err(|e| ZkpFlError::Dataset(format!("Invalid feature matrix: {}", e)))?;
+
+        Ok(Self {
+            name: "Healthcare Dataset".to_string(),
+            description: "Open source healthcare dataset for ZKP linear regression".to_string(),
+            features,
+            targets: Array1::from(target_values),
+            feature_names,
+            target_name: target.name.clone(),
+            num_samples,
+            num_features,
+            multi_targets: None,
         })
     }
 
+    /// Seeded uniform sample of at most `max_samples` rows, kept in order
+    pub fn sample_rows(&self, max_samples: usize, seed: u64) -> (Self, SampledRows) {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let keep = max_samples.min(self.num_samples);
+        let mut rows = rand::seq::index::sample(&mut rng, self.num_samples, keep).into_vec();
+        rows.sort_unstable();
+        let sampled = SampledRows {
+            rows_seen: self.num_samples,
+            rows_retained: rows.len(),
+        };
+        (self.select_rows(&rows), sampled)
+    }
+
+    /// Number of regression outputs
+    pub fn num_targets(&self) -> usize {
+        self.multi_targets.as_ref().map_or(1, |multi| multi.names.len())
+    }
+
     /// Create a synthetic healthcare dataset for testing
     pub fn create_synthetic(num_samples: usize, num_features: usize) -> Self {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        
+        Self::generate_synthetic(&mut rand::thread_rng(), num_samples, num_features)
+    }
+
+    /// Create a synthetic dataset that is identical for identical seeds
+    pub fn create_synthetic_seeded(num_samples: usize, num_features: usize, seed: u64) -> Self {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        Self::generate_synthetic(&mut rng, num_samples, num_features)
+    }
+
+    /// Create a synthetic binary-classification dataset: features in [0, 1)
+    /// labelled 1 when their mean exceeds one half. Identical for identical seeds.
+    pub fn create_synthetic_binary_seeded(num_samples: usize, num_features: usize, seed: u64) -> Self {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+        let features = Array2::from_shape_fn((num_samples, num_features), |_| rng.gen_range(0.0..1.0));
+        let targets = features
+            .rows()
+            .into_iter()
+            .map(|row| if row.mean().unwrap_or(0.0) > 0.5 { 1.0 } else { 0.0 })
+            .collect();
+
+        Self {
+            name: "Synthetic Binary Dataset".to_string(),
+            description: "Synthetically generated binary classification dataset for testing".to_string(),
+            features,
+            targets,
+            feature_names: (0..num_features).map(|i| format!("feature_{}", i)).collect(),
+            target_name: "label".to_string(),
+            num_samples,
+            num_features,
+            multi_targets: None,
+        }
+    }
+
+    fn generate_synthetic<R: rand::Rng>(rng: &mut R, num_samples: usize, num_features: usize) -> Self {
         let mut features = Array2::zeros((num_samples, num_features));
         let mut targets = Array1::zeros(num_samples);
-        
+
+        // Target weights for age, blood pressure and cholesterol (scaled by their range)
+        let target_terms = [(0, 0.5, 80.0), (2, 0.3, 200.0), (3, 0.2, 400.0)];
+
         // Generate synthetic healthcare-like data
         for i in 0..num_samples {
             for j in 0..num_features {
@@ -125,12 +604,14 @@ impl HealthcareDataset {
                     _ => features[(i, j)] = rng.gen_range(0.0..10.0),
                 }
             }
-            
-            // Generate target based on features with some noise
-            targets[i] = 0.5 * features[(i, 0)] / 80.0 + 
-                        0.3 * features[(i, 2)] / 200.0 + 
-                        0.2 * features[(i, 3)] / 400.0 + 
-                        rng.gen_range(-0.1..0.1); // noise
+
+            // Generate target based on the features present, with some noise
+            targets[i] = target_terms
+                .iter()
+                .filter(|(column, _, _)| *column < num_features)
+                .map(|(column, weight, scale)| weight * features[(i, *column)] / scale)
+                .sum::<f64>()
+                + rng.gen_range(-0.1..0.1); // noise
         }
 
         let feature_names = (0..num_features)
@@ -153,21 +634,58 @@ impl HealthcareDataset {
             target_name: "risk_score".to_string(),
             num_samples,
             num_features,
+            multi_targets: None,
         }
     }
 
-    /// Normalize features to [0, 1] range
-    pub fn normalize(&mut self) {
-        for j in 0..self.num_features {
-            let column = self.features.column(j);
-            let min_val = column.iter().copied().fold(f64::INFINITY, f64::min);
-            let max_val = column.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-            
-            if max_val > min_val {
-                for i in 0..self.num_samples {
-                    self.features[(i, j)] = (self.features[(i, j)] - min_val) / (max_val - min_val);
+    /// Normalize features to [0, 1] range, returning the ranges used so the
+    /// same scaling can be applied to new samples
+    pub fn normalize(&mut self) -> ScalerParams {
+        let (offsets, scales) = self
+            .features
+            .columns()
+            .into_iter()
+            .map(|column| {
+                let min_val = column.iter().copied().fold(f64::INFINITY, f64::min);
+                let max_val = column.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                if max_val > min_val {
+                    (min_val, max_val - min_val)
+                } else {
+                    (0.0, 1.0)
                 }
-            }
+            })
+            .unzip();
+        let params = ScalerParams { method: ScalingMethod::MinMax, offsets, scales };
+        self.apply_scaler(&params);
+        params
+    }
+
+    /// Standardize features to zero mean and unit variance, returning the
+    /// column statistics used
+    pub fn standardize(&mut self) -> ScalerParams {
+        let (offsets, scales) = self
+            .features
+            .columns()
+            .into_iter()
+            .map(|column| {
+                let mean = column.mean().unwrap_or(0.0);
+                let variance = column.mapv(|x| (x - mean) * (x - mean)).mean().unwrap_or(0.0);
+                if variance > 0.0 {
+                    (mean, variance.sqrt())
+                } else {
+                    (0.0, 1.0)
+                }
+            })
+            .unzip();
+        let params = ScalerParams { method: ScalingMethod::Standard, offsets, scales };
+        self.apply_scaler(&params);
+        params
+    }
+
+    fn apply_scaler(&mut self, params: &ScalerParams) {
+        for (j, mut column) in self.features.columns_mut().into_iter().enumerate() {
+            let (offset, scale) = (params.offsets[j], params.scales[j]);
+            column.mapv_inplace(|x| (x - offset) / scale);
         }
     }
 
@@ -176,36 +694,110 @@ impl HealthcareDataset {
         assert!(train_ratio > 0.0 && train_ratio < 1.0);
         
         let train_size = (self.num_samples as f64 * train_ratio) as usize;
-        
-        let train_features = self.features.slice(s![0..train_size, ..]).to_owned();
-        let train_targets = self.targets.slice(s![0..train_size]).to_owned();
-        
-        let test_features = self.features.slice(s![train_size.., ..]).to_owned();
-        let test_targets = self.targets.slice(s![train_size..]).to_owned();
-        
-        let train_dataset = Self {
-            name: format!("{} (Train)", self.name),
-            description: format!("{} - Training set", self.description),
-            features: train_features,
-            targets: train_targets,
-            feature_names: self.feature_names.clone(),
-            target_name: self.target_name.clone(),
-            num_samples: train_size,
-            num_features: self.num_features,
-        };
-        
-        let test_dataset = Self {
-            name: format!("{} (Test)", self.name),
-            description: format!("{} - Test set", self.description),
-            features: test_features,
-            targets: test_targets,
+        let train_rows: Vec<usize> = (0..train_size).collect();
+        let test_rows: Vec<usize> = (train_size..self.num_samples).collect();
+
+        (self.train_subset(&train_rows), self.test_subset(&test_rows))
+    }
+
+    /// Split into training and testing sets that keep the class proportions
+    /// of the target. Rows are grouped by target value, so this is meant for
+    /// binary or categorical targets; each group is shuffled with `seed`
+    /// before `train_ratio` of it goes to the training set.
+    pub fn stratified_split(&self, train_ratio: f64, seed: u64) -> (Self, Self) {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+        assert!(train_ratio > 0.0 && train_ratio < 1.0);
+
+        let mut strata: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+        for (row, target) in self.targets.iter().enumerate() {
+            strata.entry(target.to_bits()).or_default().push(row);
+        }
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut train_rows = Vec::new();
+        let mut test_rows = Vec::new();
+        for rows in strata.values_mut() {
+            rows.shuffle(&mut rng);
+            let train_size = (rows.len() as f64 * train_ratio).round() as usize;
+            train_rows.extend_from_slice(&rows[..train_size]);
+            test_rows.extend_from_slice(&rows[train_size..]);
+        }
+        // Keep the original row order within each partition
+        train_rows.sort_unstable();
+        test_rows.sort_unstable();
+
+        (self.train_subset(&train_rows), self.test_subset(&test_rows))
+    }
+
+    fn train_subset(&self, rows: &[usize]) -> Self {
+        let mut subset = self.select_rows(rows);
+        subset.name = format!("{} (Train)", self.name);
+        subset.description = format!("{} - Training set", self.description);
+        subset
+    }
+
+    fn test_subset(&self, rows: &[usize]) -> Self {
+        let mut subset = self.select_rows(rows);
+        subset.name = format!("{} (Test)", self.name);
+        subset.description = format!("{} - Test set", self.description);
+        subset
+    }
+
+    /// Copy of the dataset holding only `rows`, in the given order
+    fn select_rows(&self, rows: &[usize]) -> Self {
+        Self {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            features: self.features.select(Axis(0), rows),
+            targets: self.targets.select(Axis(0), rows),
             feature_names: self.feature_names.clone(),
             target_name: self.target_name.clone(),
-            num_samples: self.num_samples - train_size,
+            num_samples: rows.len(),
             num_features: self.num_features,
-        };
-        
-        (train_dataset, test_dataset)
+            multi_targets: self.multi_targets.as_ref().map(|multi| MultiTargets {
+                names: multi.names.clone(),
+                values: multi.values.select(Axis(0), rows),
+            }),
+        }
+    }
+
+    /// Blake2b hash over the shape, features (row-major) and targets.
+    /// Independent of the in-memory layout of the underlying arrays.
+    pub fn content_hash(&self) -> String {
+        let mut state = Params::new().to_state();
+        state.update(&(self.num_samples as u64).to_le_bytes());
+        state.update(&(self.num_features as u64).to_le_bytes());
+
+        for row in self.features.rows() {
+            for value in row.iter() {
+                state.update(&value.to_le_bytes());
+            }
+        }
+        for value in self.targets.iter() {
+            state.update(&value.to_le_bytes());
+        }
+
+        hex::encode(state.finalize().as_bytes())
+    }
+
+    /// Population variance of the target column
+    pub fn target_variance(&self) -> f64 {
+        if self.num_samples == 0 {
+            return 0.0;
+        }
+        let mean = self.targets.sum() / self.num_samples as f64;
+        self.targets.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / self.num_samples as f64
+    }
+
+    /// Smallest and largest target value, `None` for an empty dataset
+    pub fn target_range(&self) -> Option<(f64, f64)> {
+        if self.num_samples == 0 {
+            return None;
+        }
+        let min = self.targets.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self.targets.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Some((min, max))
     }
 
     /// Convert to training samples
@@ -228,4 +820,467 @@ impl HealthcareDataset {
     }
 }
 
-use ndarray::s;
+/// Parse one numeric cell; `line` and `column` locate it in the error
+/// CSV reader past the header row, with the positions of the columns a
+/// loader asked for
+struct CsvColumns<'a> {
+    reader: Reader<Box<dyn Read>>,
+    feature_columns: &'a [String],
+    feature_indices: Vec<usize>,
+    target_columns: &'a [String],
+    target_indices: Vec<usize>,
+}
+
+impl<'a> CsvColumns<'a> {
+    fn open(
+        path: &Path,
+        target_columns: &'a [String],
+        feature_columns: &'a [String],
+    ) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| ZkpFlError::Dataset(format!("Failed to open file: {}", e)))?;
+        // Gzipped files are decompressed as they are read
+        let file: Box<dyn Read> = if is_gzipped(path) {
+            Box::new(GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+
+        let mut reader = Reader::from_reader(file);
+        let headers = reader
+            .headers()
+            .map_err(|e| ZkpFlError::Dataset(format!("Failed to read CSV header: {}", e)))?
+            .clone();
+
+        let column_index = |kind: &str, name: &String| {
+            headers.iter().position(|header| header.trim() == name).ok_or_else(|| {
+                ZkpFlError::Dataset(format!(
+                    "{} column '{}' not found in '{}' (columns: {})",
+                    kind,
+                    name,
+                    path.display(),
+                    headers.iter().collect::<Vec<_>>().join(", ")
+                ))
+            })
+        };
+        let feature_indices = feature_columns
+            .iter()
+            .map(|name| column_index("Feature", name))
+            .collect::<Result<Vec<_>>>()?;
+        let target_indices = target_columns
+            .iter()
+            .map(|name| column_index("Target", name))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            reader,
+            feature_columns,
+            feature_indices,
+            target_columns,
+            target_indices,
+        })
+    }
+
+    /// Read the next record into `row`, returning false at the end of the file
+    fn read_row(&mut self, row: &mut StringRecord) -> Result<bool> {
+        self.reader
+            .read_record(row)
+            .map_err(|e| ZkpFlError::Dataset(format!("Failed to parse record: {}", e)))
+    }
+
+    fn features(&self, row: &StringRecord) -> Result<Vec<f64>> {
+        parse_cells(row, &self.feature_indices, self.feature_columns)
+    }
+
+    /// Unparsed feature cells, in the order the feature columns were given
+    fn feature_cells<'s, 'r: 's>(
+        &'s self,
+        row: &'r StringRecord,
+    ) -> impl Iterator<Item = Option<&'r str>> + 's {
+        self.feature_indices.iter().map(move |&index| row.get(index))
+    }
+
+    fn targets(&self, row: &StringRecord) -> Result<Vec<f64>> {
+        parse_cells(row, &self.target_indices, self.target_columns)
+    }
+}
+
+fn parse_cells(row: &StringRecord, indices: &[usize], names: &[String]) -> Result<Vec<f64>> {
+    // Line in the file, so the header is line 1
+    let line = row.position().map_or(0, |position| position.line());
+    indices
+        .iter()
+        .zip(names)
+        .map(|(&index, name)| parse_cell(row.get(index), line, name))
+        .collect()
+}
+
+/// Feature cell of a schema load, before categorical columns are encoded
+enum CsvCell {
+    Number(f64),
+    Label(String),
+}
+
+/// Read one categorical cell, checking it against the schema's categories
+fn parse_category(cell: Option<&str>, line: u64, column: &ColumnSchema) -> Result<String> {
+    let label = match cell.map(str::trim) {
+        None | Some("") => {
+            return Err(ZkpFlError::Dataset(format!(
+                "Missing value in row {}, column '{}'",
+                line, column.name
+            )))
+        }
+        Some(label) => label,
+    };
+    if let Some(categories) = &column.categories {
+        if !categories.iter().any(|category| category == label) {
+            return Err(ZkpFlError::Dataset(format!(
+                "Unknown category '{}' in row {}, column '{}' (expected one of: {})",
+                label,
+                line,
+                column.name,
+                categories.join(", ")
+            )));
+        }
+    }
+    Ok(label.to_string())
+}
+
+fn parse_cell(cell: Option<&str>, line: u64, column: &str) -> Result<f64> {
+    match cell.map(str::trim) {
+        None | Some("") => Err(ZkpFlError::Dataset(format!(
+            "Missing value in row {}, column '{}'",
+            line, column
+        ))),
+        Some(value) => value.parse().map_err(|_| {
+            ZkpFlError::Dataset(format!(
+                "Invalid value '{}' in row {}, column '{}'",
+                value, line, column
+            ))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::ShapeBuilder;
+
+    #[test]
+    fn test_content_hash_is_layout_independent() {
+        let dataset = HealthcareDataset::create_synthetic(20, 5);
+
+        let mut column_major_dataset = dataset.clone();
+        // Same values, column-major storage
+        let mut column_major = Array2::zeros(dataset.features.raw_dim().f());
+        column_major.assign(&dataset.features);
+        column_major_dataset.features = column_major;
+        assert!(!column_major_dataset.features.is_standard_layout());
+        assert_eq!(dataset.content_hash(), column_major_dataset.content_hash());
+
+        let mut modified = dataset.clone();
+        modified.targets[0] += 1.0;
+        assert_ne!(dataset.content_hash(), modified.content_hash());
+    }
+
+    #[test]
+    fn test_dataset_source_parsing() {
+        assert_eq!(
+            "synthetic".parse::<DatasetSource>().unwrap(),
+            DatasetSource::SyntheticSeeded { samples: 1000, features: 5, seed: None }
+        );
+        assert_eq!(
+            "synthetic:200,3,42".parse::<DatasetSource>().unwrap(),
+            DatasetSource::SyntheticSeeded { samples: 200, features: 3, seed: Some(42) }
+        );
+        assert_eq!(
+            "data/heart.CSV".parse::<DatasetSource>().unwrap(),
+            DatasetSource::File { path: PathBuf::from("data/heart.CSV"), format: DatasetFormat::Csv }
+        );
+        assert_eq!(
+            "data/heart.csv.gz".parse::<DatasetSource>().unwrap(),
+            DatasetSource::File { path: PathBuf::from("data/heart.csv.gz"), format: DatasetFormat::Csv }
+        );
+
+        // Malformed specs are rejected instead of falling back to defaults
+        for invalid in ["synthetic:abc,5", "synthetic:100,0", "synthetic:1,2,3,4", "synthetic:10,2,-1", "data/heart.parquet", "data/heart.gz", ""] {
+            let err = invalid.parse::<DatasetSource>().unwrap_err();
+            assert!(matches!(err, ZkpFlError::Dataset(_)), "{}: {}", invalid, err);
+        }
+
+        // Round trip through the config representation
+        let source: DatasetSource = "synthetic:200,3,42".parse().unwrap();
+        assert_eq!(String::from(source.clone()).parse::<DatasetSource>().unwrap(), source);
+    }
+
+    #[test]
+    fn test_csv_with_multiple_targets() {
+        let path = std::env::temp_dir().join(format!("multi_target_{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "age,cholesterol,target,risk\n50,200,1,0.5\n60,240,0,0.1\n70,180,1,0.9\n",
+        )
+        .unwrap();
+        let features = vec!["age".to_string(), "cholesterol".to_string()];
+
+        // A single target keeps the existing shape
+        let single = HealthcareDataset::load_from_csv(&path, "target", &features).unwrap();
+        assert_eq!(single.targets.to_vec(), vec![1.0, 0.0, 1.0]);
+        assert!(single.multi_targets.is_none());
+        assert_eq!(single.num_targets(), 1);
+
+        let targets = vec!["risk".to_string(), "target".to_string()];
+        let multi = HealthcareDataset::load_from_csv_targets(&path, &targets, &features).unwrap();
+        let matrix = &multi.multi_targets.as_ref().unwrap().values;
+        assert_eq!(matrix.dim(), (3, 2));
+        assert_eq!(matrix.column(0).to_vec(), vec![0.5, 0.1, 0.9]);
+        assert_eq!(matrix.column(1).to_vec(), vec![1.0, 0.0, 1.0]);
+        assert_eq!(multi.targets, matrix.column(0));
+        assert_eq!(multi.target_name, "risk");
+
+        let (train, test) = multi.train_test_split(0.67);
+        assert_eq!(train.multi_targets.unwrap().values.nrows(), train.num_samples);
+        assert_eq!(test.multi_targets.unwrap().values.nrows(), test.num_samples);
+
+        let missing = vec!["target".to_string(), "outcome".to_string()];
+        let err = HealthcareDataset::load_from_csv_targets(&path, &missing, &features).unwrap_err();
+        assert!(err.to_string().contains("'outcome' not found"), "{}", err);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_gzipped_csv_matches_plain_csv() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let contents = "age,bmi,target\n50,22.5,1.5\n60,31.0,2.25\n70,27.4,0.75\n";
+        let id = uuid::Uuid::new_v4();
+        let plain_path = std::env::temp_dir().join(format!("gzip_{}.csv", id));
+        let gzipped_path = std::env::temp_dir().join(format!("gzip_{}.csv.gz", id));
+        std::fs::write(&plain_path, contents).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        std::fs::write(&gzipped_path, encoder.finish().unwrap()).unwrap();
+
+        let features = vec!["age".to_string(), "bmi".to_string()];
+        let plain = HealthcareDataset::load_from_csv(&plain_path, "target", &features).unwrap();
+        let gzipped =
+            HealthcareDataset::load_from_csv(&gzipped_path, "target", &features).unwrap();
+        assert_eq!(gzipped.features, plain.features);
+        assert_eq!(gzipped.targets, plain.targets);
+        assert_eq!(gzipped.content_hash(), plain.content_hash());
+
+        // Sources pick the decompressor from the path as well
+        let source: DatasetSource = gzipped_path.display().to_string().parse().unwrap();
+        assert_eq!(source.load("target", &features).unwrap().features, plain.features);
+
+        std::fs::remove_file(&plain_path).unwrap();
+        std::fs::remove_file(&gzipped_path).unwrap();
+    }
+
+    #[test]
+    fn test_csv_with_arbitrary_schema() {
+        let path = std::env::temp_dir().join(format!("housing_{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "id,rooms,area_sqft,year_built,price\n1,3,1400,1995,250000\n2,4,2100,2008,410000\n",
+        )
+        .unwrap();
+
+        // Columns are picked by name, in the requested order
+        let features = vec!["area_sqft".to_string(), "rooms".to_string()];
+        let dataset = HealthcareDataset::load_from_csv(&path, "price", &features).unwrap();
+        assert_eq!(dataset.features.row(0).to_vec(), vec![1400.0, 3.0]);
+        assert_eq!(dataset.features.row(1).to_vec(), vec![2100.0, 4.0]);
+        assert_eq!(dataset.targets.to_vec(), vec![250000.0, 410000.0]);
+
+        let unknown = vec!["bedrooms".to_string()];
+        let err = HealthcareDataset::load_from_csv(&path, "price", &unknown).unwrap_err();
+        assert!(err.to_string().contains("Feature column 'bedrooms' not found"), "{}", err);
+
+        std::fs::write(&path, "rooms,area_sqft,price\n3,1400,250000\n4,,410000\n5,big,1\n").unwrap();
+        let features = vec!["rooms".to_string(), "area_sqft".to_string()];
+        let err = HealthcareDataset::load_from_csv(&path, "price", &features).unwrap_err();
+        assert!(err.to_string().contains("Missing value in row 3, column 'area_sqft'"), "{}", err);
+
+        std::fs::write(&path, "rooms,area_sqft,price\n3,1400,250000\n5,big,1\n").unwrap();
+        let err = HealthcareDataset::load_from_csv(&path, "price", &features).unwrap_err();
+        assert!(err.to_string().contains("'big' in row 3, column 'area_sqft'"), "{}", err);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_schema_one_hot_encodes_categorical_columns() {
+        let dir = std::env::temp_dir().join(format!("schema_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = dir.join("patients.csv");
+        std::fs::write(
+            &data,
+            "id,age,region,smoker,cost\n1,30,north,no,100\n2,45,south,yes,300\n3,50,east,no,200\n",
+        )
+        .unwrap();
+        let schema_path = dir.join("schema.toml");
+        std::fs::write(
+            &schema_path,
+            r#"
+            [[columns]]
+            name = "id"
+            role = "ignore"
+
+            [[columns]]
+            name = "age"
+
+            [[columns]]
+            name = "region"
+            type = "categorical"
+
+            [[columns]]
+            name = "smoker"
+            type = "categorical"
+            categories = ["no", "yes"]
+
+            [[columns]]
+            name = "cost"
+            role = "target"
+            "#,
+        )
+        .unwrap();
+
+        // age, three inferred regions and two listed smoker categories
+        let schema = DatasetSchema::from_file(&schema_path).unwrap();
+        let source: DatasetSource = data.to_str().unwrap().parse().unwrap();
+        let dataset = source.load_with_schema(&schema).unwrap();
+        assert_eq!(dataset.num_features, 6);
+        assert_eq!(
+            dataset.feature_names,
+            vec![
+                "age",
+                "region=east",
+                "region=north",
+                "region=south",
+                "smoker=no",
+                "smoker=yes"
+            ]
+        );
+        assert_eq!(dataset.features.row(1).to_vec(), vec![45.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(dataset.targets.to_vec(), vec![100.0, 300.0, 200.0]);
+
+        // Listed categories reject values outside them
+        std::fs::write(&data, "id,age,region,smoker,cost\n1,30,north,sometimes,100\n").unwrap();
+        let err = source.load_with_schema(&schema).unwrap_err();
+        assert!(err.to_string().contains("Unknown category 'sometimes' in row 2"), "{}", err);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_synthetic_with_few_features() {
+        let dataset = HealthcareDataset::create_synthetic_seeded(10, 2, 7);
+        assert_eq!(dataset.features.dim(), (10, 2));
+        assert!(dataset.targets.iter().all(|t| t.is_finite()));
+    }
+
+    #[test]
+    fn test_seeded_synthetic_is_reproducible() {
+        let first = HealthcareDataset::create_synthetic_seeded(50, 5, 42);
+        let second = HealthcareDataset::create_synthetic_seeded(50, 5, 42);
+        assert_eq!(first.features, second.features);
+        assert_eq!(first.targets, second.targets);
+
+        let other = HealthcareDataset::create_synthetic_seeded(50, 5, 43);
+        assert_ne!(first.features, other.features);
+
+        // A seeded spec generates the same data on every load
+        let source: DatasetSource = "synthetic:50,5,42".parse().unwrap();
+        let loaded = source.load("target", &[]).unwrap();
+        assert_eq!(loaded.features, first.features);
+        assert_eq!(loaded.targets, first.targets);
+    }
+
+    #[test]
+    fn test_stratified_split_keeps_class_ratio() {
+        // Sorted by label: the plain split leaves no positives to train on
+        let targets: Vec<f64> = (0..100).map(|i| if i < 80 { 0.0 } else { 1.0 }).collect();
+        let dataset = HealthcareDataset {
+            name: "Imbalanced".to_string(),
+            description: "Label-sorted".to_string(),
+            features: Array2::from_shape_fn((100, 2), |(i, j)| (i * 2 + j) as f64),
+            targets: Array1::from(targets),
+            feature_names: vec!["a".to_string(), "b".to_string()],
+            target_name: "label".to_string(),
+            num_samples: 100,
+            num_features: 2,
+            multi_targets: None,
+        };
+        let positives = |d: &HealthcareDataset| d.targets.iter().filter(|&&t| t == 1.0).count();
+
+        let (train, _) = dataset.train_test_split(0.75);
+        assert_eq!(positives(&train), 0);
+
+        let (train, test) = dataset.stratified_split(0.75, 7);
+        assert_eq!((train.num_samples, test.num_samples), (75, 25));
+        assert_eq!(positives(&train), 15);
+        assert_eq!(positives(&test), 5);
+
+        // Rows stay intact and every row lands in exactly one partition
+        let mut seen = Vec::new();
+        for part in [&train, &test] {
+            for (features, target) in part.features.rows().into_iter().zip(&part.targets) {
+                let index = features[0] as usize / 2;
+                assert_eq!(*target, dataset.targets[index]);
+                seen.push(index);
+            }
+        }
+        seen.sort_unstable();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+
+        // The same seed picks the same rows
+        let (again, _) = dataset.stratified_split(0.75, 7);
+        assert_eq!(again.features, train.features);
+    }
+
+    #[test]
+    fn test_sampled_csv_load_keeps_cap() {
+        use std::io::Write;
+
+        const ROWS: usize = 200_000;
+        let path = std::env::temp_dir().join(format!("large_{}.csv", uuid::Uuid::new_v4()));
+        let mut file = std::io::BufWriter::new(File::create(&path).unwrap());
+        writeln!(file, "id,x,target").unwrap();
+        for i in 0..ROWS {
+            writeln!(file, "{},{},{}", i, i % 97, i * 2).unwrap();
+        }
+        file.flush().unwrap();
+        drop(file);
+
+        let columns = vec!["id".to_string(), "x".to_string()];
+        let (dataset, sampled) =
+            HealthcareDataset::load_from_csv_sampled(&path, "target", &columns, 500, 3).unwrap();
+        assert_eq!(sampled, SampledRows { rows_seen: ROWS, rows_retained: 500 });
+        assert_eq!(dataset.num_samples, 500);
+        assert_eq!(dataset.features.dim(), (500, 2));
+
+        // Kept rows are genuine, in file order and drawn from across the file
+        let ids: Vec<usize> = dataset.features.column(0).iter().map(|&id| id as usize).collect();
+        for (row, &id) in ids.iter().enumerate() {
+            assert_eq!(dataset.features[[row, 1]], (id % 97) as f64);
+            assert_eq!(dataset.targets[row], (id * 2) as f64);
+        }
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ids.iter().filter(|&&id| id >= ROWS / 2).count() > 150);
+
+        let (again, _) =
+            HealthcareDataset::load_from_csv_sampled(&path, "target", &columns, 500, 3).unwrap();
+        assert_eq!(again.features, dataset.features);
+
+        // A cap above the row count keeps everything
+        let (all, sampled) =
+            HealthcareDataset::load_from_csv_sampled(&path, "target", &columns, ROWS + 1, 3)
+                .unwrap();
+        assert_eq!(sampled.rows_retained, ROWS);
+        assert_eq!(all.num_samples, ROWS);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
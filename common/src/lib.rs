@@ -1,11 +1,21 @@
 pub mod circuit;
 pub mod dataset;
+pub mod logging;
 pub mod metrics;
 pub mod proof;
+pub mod schema;
+pub mod signing;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod types;
+pub mod verify;
 
 pub use circuit::*;
 pub use dataset::*;
+pub use logging::*;
 pub use metrics::*;
 pub use proof::*;
+pub use schema::*;
+pub use signing::*;
 pub use types::*;
+pub use verify::*;
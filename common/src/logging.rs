@@ -0,0 +1,81 @@
+use crate::{Result, ZkpFlError};
+use std::fmt;
+use std::str::FromStr;
+
+/// How the binaries write log lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// env_logger's default human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line for log aggregators
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ZkpFlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(ZkpFlError::Config(format!(
+                "Unknown log format '{}', expected 'text' or 'json'",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Render `record` as a single JSON line with timestamp, level, target and
+/// message fields
+pub fn json_log_line(record: &log::Record) -> String {
+    serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_log_line_fields() {
+        let line = json_log_line(
+            &log::Record::builder()
+                .level(log::Level::Warn)
+                .target("zkp_fl_server::api")
+                .args(format_args!("Proof {} \"rejected\"", 7))
+                .build(),
+        );
+        assert!(!line.contains('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["target"], "zkp_fl_server::api");
+        assert_eq!(value["message"], "Proof 7 \"rejected\"");
+        let timestamp = value["timestamp"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+    }
+
+    #[test]
+    fn test_log_format_parse() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("Text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("yaml".parse::<LogFormat>().is_err());
+        assert_eq!(LogFormat::default().to_string(), "text");
+    }
+}
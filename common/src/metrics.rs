@@ -1,4 +1,9 @@
+use crate::types::{ClientMetrics, Result, ZkpFlError};
 use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use std::collections::HashMap;
@@ -28,6 +33,9 @@ pub struct ZkpMetrics {
     pub circuit_advice_columns: usize,
     pub circuit_fixed_columns: usize,
     pub folding_iterations: usize,
+    /// Largest absolute change quantization made to a proven value
+    #[serde(default)]
+    pub quantization_error: Option<f64>,
 }
 
 /// Training metrics for ML model
@@ -41,6 +49,27 @@ pub struct TrainingMetrics {
     pub initial_loss: f64,
     pub convergence_epoch: Option<usize>,
     pub loss_history: Vec<f64>,
+    /// Target had (near) zero variance, so a low loss is meaningless
+    #[serde(default)]
+    pub degenerate_target: bool,
+    /// Smallest target value seen in training, if recorded
+    #[serde(default)]
+    pub target_min: Option<f64>,
+    /// Largest target value seen in training, if recorded
+    #[serde(default)]
+    pub target_max: Option<f64>,
+    /// Gradient steps taken per epoch
+    #[serde(default)]
+    pub batches_per_epoch: usize,
+    /// Validation loss stopped improving before the last epoch
+    #[serde(default)]
+    pub stopped_early: bool,
+    /// Epoch whose weights were kept, when early stopping tracked one
+    #[serde(default)]
+    pub best_epoch: Option<usize>,
+    /// Learning rate of every epoch, alongside `loss_history`
+    #[serde(default)]
+    pub learning_rate_history: Vec<f64>,
 }
 
 /// System resource metrics
@@ -69,6 +98,21 @@ pub struct BenchmarkResult {
     pub operations: Vec<OperationMetrics>,
     pub success: bool,
     pub error_message: Option<String>,
+    /// Run was aborted before completion; results are partial
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Run was killed after exceeding the client timeout
+    #[serde(default)]
+    pub timed_out: bool,
+    /// Content hash of the dataset the client loaded
+    #[serde(default)]
+    pub dataset_hash: Option<String>,
+    /// Operations performed, including those not kept in `operations`
+    #[serde(default)]
+    pub total_operations: usize,
+    /// CPU cores the proving work was pinned to; empty when unpinned
+    #[serde(default)]
+    pub pinned_cores: Vec<usize>,
     
     // Additional fields for multi-client benchmarking compatibility
     pub id: Option<Uuid>,
@@ -79,6 +123,8 @@ pub struct BenchmarkResult {
     pub total_duration: Option<std::time::Duration>,
     pub successful_clients: Option<usize>,
     pub failed_clients: Option<usize>,
+    /// Failed clients that were killed for exceeding the client timeout
+    pub timed_out_clients: Option<usize>,
     pub avg_training_time: Option<std::time::Duration>,
     pub avg_proof_time: Option<std::time::Duration>,
     pub avg_verification_time: Option<std::time::Duration>,
@@ -88,6 +134,125 @@ pub struct BenchmarkResult {
     pub success_rate: Option<f64>,
 }
 
+/// What benchmark runners read from the file a client saves once its run
+/// ends. Clients write a full `BenchmarkResult`; these fields are the part
+/// runners rely on, so the rest can change without breaking them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientReport {
+    pub client_id: String,
+    pub success: bool,
+    #[serde(default)]
+    pub error_message: Option<String>,
+    /// Wall time of the client run
+    pub total_duration_ms: u64,
+    pub zkp_metrics: ZkpMetrics,
+    pub training_metrics: TrainingMetrics,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub dataset_hash: Option<String>,
+}
+
+impl ClientReport {
+    /// Name of the report `client_id` saves when session `session_id`
+    /// finishes at `finished_at`. The session suffix keeps reports of runs
+    /// finishing in the same second apart.
+    pub fn file_name(client_id: &str, session_id: Uuid, finished_at: DateTime<Utc>) -> String {
+        format!(
+            "benchmark_{}_{}_client_{}.json",
+            finished_at.format("%Y%m%d_%H%M%S"),
+            short_id(session_id),
+            client_id
+        )
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| ZkpFlError::Benchmark(format!("Malformed client report: {}", e)))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            ZkpFlError::Benchmark(format!("Failed to read client report {:?}: {}", path, e))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Newest report `client_id` saved in `dir` no earlier than `since`, so
+    /// reports of earlier runs are never mistaken for the current one
+    pub fn find_latest(dir: &Path, client_id: &str, since: SystemTime) -> Result<Self> {
+        let suffix = format!("_client_{}.json", client_id);
+        let mut latest: Option<(SystemTime, PathBuf)> = None;
+
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let matches = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("benchmark_") && name.ends_with(&suffix));
+                let Some(modified) = entry.metadata().and_then(|m| m.modified()).ok() else {
+                    continue;
+                };
+                if matches
+                    && modified >= since
+                    && latest.as_ref().is_none_or(|(newest, _)| modified > *newest)
+                {
+                    latest = Some((modified, path));
+                }
+            }
+        }
+
+        match latest {
+            Some((_, path)) => Self::load(&path),
+            None => Err(ZkpFlError::Benchmark(format!(
+                "Client {} wrote no benchmark report to {:?}",
+                client_id, dir
+            ))),
+        }
+    }
+
+    /// Per-client benchmark metrics for this single run
+    pub fn client_metrics(&self) -> ClientMetrics {
+        let training_time = Duration::from_millis(self.training_metrics.training_time_ms);
+        let proof_time = Duration::from_millis(self.zkp_metrics.proof_generation_time_ms);
+        let witness_time = Duration::from_millis(self.zkp_metrics.witness_generation_time_ms);
+        let verification_time = Duration::from_millis(self.zkp_metrics.proof_verification_time_ms);
+        let proof_size = self.zkp_metrics.proof_size_bytes;
+        ClientMetrics {
+            client_id: self.client_id.clone(),
+            training_times: vec![training_time],
+            proof_times: vec![proof_time],
+            witness_times: vec![witness_time],
+            verification_times: vec![verification_time],
+            proof_sizes: vec![proof_size],
+            success_count: usize::from(self.success),
+            failure_count: usize::from(!self.success),
+            avg_training_time: training_time,
+            avg_proof_time: proof_time,
+            avg_witness_time: witness_time,
+            avg_verification_time: verification_time,
+            avg_proof_size: proof_size,
+            total_time: Duration::from_millis(self.total_duration_ms),
+        }
+    }
+}
+
+impl From<&BenchmarkResult> for ClientReport {
+    fn from(result: &BenchmarkResult) -> Self {
+        Self {
+            client_id: result.client_id.clone(),
+            success: result.success,
+            error_message: result.error_message.clone(),
+            total_duration_ms: result.total_duration_ms,
+            zkp_metrics: result.zkp_metrics.clone(),
+            training_metrics: result.training_metrics.clone(),
+            cancelled: result.cancelled,
+            dataset_hash: result.dataset_hash.clone(),
+        }
+    }
+}
+
 /// Multi-client benchmark aggregation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiClientBenchmark {
@@ -111,6 +276,147 @@ pub struct AggregateMetrics {
     pub total_proofs_verified: usize,
     pub success_rate: f64,
     pub throughput_proofs_per_second: f64,
+    /// Loss averaged over runs that reported target statistics
+    #[serde(default)]
+    pub loss: Option<LossSummary>,
+    #[serde(default)]
+    pub proof_generation_percentiles: LatencyPercentiles,
+    #[serde(default)]
+    pub verification_percentiles: LatencyPercentiles,
+}
+
+/// Tail latencies of a set of timings, in ms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl LatencyPercentiles {
+    pub fn from_values(values: impl IntoIterator<Item = u64>) -> Self {
+        let mut sorted: Vec<u64> = values.into_iter().collect();
+        sorted.sort_unstable();
+        Self {
+            p50_ms: percentile(&sorted, 0.5),
+            p90_ms: percentile(&sorted, 0.9),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+        }
+    }
+}
+
+/// Nearest-rank percentile `p` (0 to 1) of ascending `sorted_data`: the
+/// smallest value at least a fraction `p` of the data is at or below.
+/// Zero for no data.
+pub fn percentile(sorted_data: &[u64], p: f64) -> f64 {
+    if sorted_data.is_empty() {
+        return 0.0;
+    }
+
+    let rank = (sorted_data.len() as f64 * p).ceil() as usize;
+    let index = rank.saturating_sub(1).min(sorted_data.len() - 1);
+    sorted_data[index] as f64
+}
+
+/// Model quality in raw and target-scale independent terms
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LossSummary {
+    /// Mean squared error, in squared target units
+    pub mse: f64,
+    /// Root mean squared error, in target units
+    pub rmse: f64,
+    /// RMSE divided by the target range
+    pub normalized_rmse: f64,
+}
+
+impl LossSummary {
+    /// Average over all metrics that carry target statistics
+    pub fn from_metrics<'a>(metrics: impl IntoIterator<Item = &'a TrainingMetrics>) -> Option<Self> {
+        let mut count = 0usize;
+        let mut total = Self { mse: 0.0, rmse: 0.0, normalized_rmse: 0.0 };
+        for m in metrics {
+            let Some(normalized_rmse) = m.normalized_rmse() else {
+                continue;
+            };
+            total.mse += m.final_loss;
+            total.rmse += m.final_rmse();
+            total.normalized_rmse += normalized_rmse;
+            count += 1;
+        }
+
+        (count > 0).then(|| Self {
+            mse: total.mse / count as f64,
+            rmse: total.rmse / count as f64,
+            normalized_rmse: total.normalized_rmse / count as f64,
+        })
+    }
+}
+
+/// Chooses which operations keep detailed metrics: one in every `every`.
+/// Aggregate counters should be updated regardless of the decision;
+/// anything derived from detailed records (e.g. percentiles) becomes an
+/// approximation once `every > 1`.
+#[derive(Debug)]
+pub struct MetricsSampler {
+    every: u64,
+    counter: AtomicU64,
+}
+
+impl MetricsSampler {
+    pub fn new(every: u64) -> Self {
+        Self {
+            every: every.max(1),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn should_sample(&self) -> bool {
+        self.counter.fetch_add(1, Ordering::Relaxed) % self.every == 0
+    }
+}
+
+impl Default for MetricsSampler {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// Round a value to the given number of decimal places
+pub fn round_to_precision(value: f64, precision: usize) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    (value * factor).round() / factor
+}
+
+impl AggregateMetrics {
+    /// Copy with floating point fields rounded for reporting. The success
+    /// rate is a ratio reported as a percentage, so it keeps two more places
+    /// to stay exact once scaled by 100.
+    pub fn rounded(&self, precision: usize) -> Self {
+        Self {
+            avg_proof_generation_time_ms: round_to_precision(self.avg_proof_generation_time_ms, precision),
+            avg_proof_verification_time_ms: round_to_precision(self.avg_proof_verification_time_ms, precision),
+            avg_training_time_ms: round_to_precision(self.avg_training_time_ms, precision),
+            success_rate: round_to_precision(self.success_rate, precision + 2),
+            throughput_proofs_per_second: round_to_precision(self.throughput_proofs_per_second, precision),
+            ..self.clone()
+        }
+    }
+}
+
+impl TrainingMetrics {
+    /// Root of the final (mean squared error) loss
+    pub fn final_rmse(&self) -> f64 {
+        self.final_loss.max(0.0).sqrt()
+    }
+
+    /// Final RMSE relative to the target range; `None` without target
+    /// statistics or when the target is constant
+    pub fn normalized_rmse(&self) -> Option<f64> {
+        let range = self.target_max? - self.target_min?;
+        (range > 0.0).then(|| self.final_rmse() / range)
+    }
 }
 
 impl OperationMetrics {
@@ -156,7 +462,38 @@ impl SystemMetrics {
     }
 }
 
+/// First 8 hex digits of `id`, to tell apart files named by the same timestamp
+pub fn short_id(id: Uuid) -> String {
+    id.simple().to_string()[..8].to_string()
+}
+
+/// Write `contents` to a file that must not exist yet, creating its
+/// directory first. Concurrent writers may create the directory at the same
+/// time, but never replace each other's files.
+pub fn write_new_file(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    file.write_all(contents)?;
+    Ok(())
+}
+
 impl BenchmarkResult {
+    /// Save the result as the client report for this session in `dir`
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(ClientReport::file_name(
+            &self.client_id,
+            self.session_id,
+            self.end_time,
+        ));
+        write_new_file(&path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(path)
+    }
+
     pub fn new(session_id: Uuid, client_id: String) -> Self {
         let now = Utc::now();
         Self {
@@ -175,6 +512,7 @@ impl BenchmarkResult {
                 circuit_advice_columns: 0,
                 circuit_fixed_columns: 0,
                 folding_iterations: 0,
+                quantization_error: None,
             },
             training_metrics: TrainingMetrics {
                 dataset_size: 0,
@@ -185,10 +523,22 @@ impl BenchmarkResult {
                 initial_loss: 0.0,
                 convergence_epoch: None,
                 loss_history: Vec::new(),
+                degenerate_target: false,
+                target_min: None,
+                target_max: None,
+                batches_per_epoch: 0,
+                stopped_early: false,
+                best_epoch: None,
+                learning_rate_history: vec![],
             },            system_metrics: Vec::new(),
             operations: Vec::new(),
             success: false,
             error_message: None,
+            cancelled: false,
+            timed_out: false,
+            dataset_hash: None,
+            total_operations: 0,
+            pinned_cores: Vec::new(),
             
             // Initialize optional fields for compatibility
             id: None,
@@ -199,6 +549,7 @@ impl BenchmarkResult {
             total_duration: None,
             successful_clients: None,
             failed_clients: None,
+            timed_out_clients: None,
             avg_training_time: None,
             avg_proof_time: None,
             avg_verification_time: None,
@@ -216,3 +567,211 @@ impl BenchmarkResult {
         self.error_message = error_message;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn training_metrics(final_loss: f64, target_range: Option<(f64, f64)>) -> TrainingMetrics {
+        let mut metrics = BenchmarkResult::new(Uuid::new_v4(), "test".to_string()).training_metrics;
+        metrics.final_loss = final_loss;
+        metrics.target_min = target_range.map(|(min, _)| min);
+        metrics.target_max = target_range.map(|(_, max)| max);
+        metrics
+    }
+
+    #[test]
+    fn test_loss_summary_normalizes_by_target_range() {
+        let small = training_metrics(0.04, Some((0.0, 1.0)));
+        let large = training_metrics(400.0, Some((0.0, 100.0)));
+        assert!((small.final_rmse() - 0.2).abs() < 1e-12);
+        assert!((large.final_rmse() - 20.0).abs() < 1e-12);
+        // Same relative error despite very different target scales
+        assert!((small.normalized_rmse().unwrap() - 0.2).abs() < 1e-12);
+        assert!((large.normalized_rmse().unwrap() - 0.2).abs() < 1e-12);
+
+        // Runs without target statistics are left out of the average
+        let unknown = training_metrics(1.0, None);
+        let summary = LossSummary::from_metrics([&small, &large, &unknown]).unwrap();
+        assert!((summary.mse - 200.02).abs() < 1e-9);
+        assert!((summary.rmse - 10.1).abs() < 1e-9);
+        assert!((summary.normalized_rmse - 0.2).abs() < 1e-12);
+
+        assert_eq!(LossSummary::from_metrics([&unknown]), None);
+    }
+
+    #[test]
+    fn test_rounding_keeps_success_rate_as_a_percentage() {
+        let metrics = AggregateMetrics {
+            avg_proof_generation_time_ms: 1234.5678,
+            min_proof_generation_time_ms: 1000,
+            max_proof_generation_time_ms: 1500,
+            avg_proof_verification_time_ms: 12.346,
+            avg_training_time_ms: 0.4,
+            total_proofs_generated: 5,
+            total_proofs_verified: 3,
+            success_rate: 0.6,
+            throughput_proofs_per_second: 2.5,
+            loss: None,
+            proof_generation_percentiles: LatencyPercentiles::default(),
+            verification_percentiles: LatencyPercentiles::default(),
+        };
+
+        let whole = metrics.rounded(0);
+        assert_eq!(whole.avg_proof_generation_time_ms, 1235.0);
+        assert_eq!(whole.avg_training_time_ms, 0.0);
+        assert_eq!(format!("{:.0}%", whole.success_rate * 100.0), "60%");
+
+        let metrics = AggregateMetrics { success_rate: 29.0 / 30.0, ..metrics };
+        let rounded = metrics.rounded(2);
+        assert_eq!(rounded.avg_proof_verification_time_ms, 12.35);
+        assert_eq!(format!("{:.2}%", rounded.success_rate * 100.0), "96.67%");
+    }
+
+    #[test]
+    fn test_latency_percentiles_are_ordered() {
+        // 1..=100 shuffled: each percentile is its own rank
+        let values = (1..=100u64).map(|v| (v * 37) % 101);
+        let percentiles = LatencyPercentiles::from_values(values);
+        assert_eq!(percentiles.p50_ms, 50.0);
+        assert_eq!(percentiles.p90_ms, 90.0);
+        assert_eq!(percentiles.p95_ms, 95.0);
+        assert_eq!(percentiles.p99_ms, 99.0);
+        assert!(percentiles.p50_ms <= percentiles.p95_ms);
+        assert!(percentiles.p95_ms <= percentiles.p99_ms);
+
+        // A single slow run only shows up in the tail
+        let skewed = LatencyPercentiles::from_values([10, 10, 10, 10, 10, 10, 10, 10, 10, 1000]);
+        assert_eq!(skewed.p50_ms, 10.0);
+        assert_eq!(skewed.p90_ms, 10.0);
+        assert_eq!(skewed.p95_ms, 1000.0);
+        assert_eq!(LatencyPercentiles::from_values([]), LatencyPercentiles::default());
+    }
+
+    #[test]
+    fn test_client_report_fields() {
+        let json = r#"{
+            "session_id": "5f0c7c52-4d8e-4c36-9d1e-0d5b8f1a2c3e",
+            "client_id": "benchmark_client_3",
+            "total_duration_ms": 4200,
+            "success": false,
+            "error_message": "Proof verification failed",
+            "cancelled": true,
+            "dataset_hash": "ab12",
+            "zkp_metrics": {
+                "setup_time_ms": 11,
+                "witness_generation_time_ms": 22,
+                "proof_generation_time_ms": 333,
+                "proof_verification_time_ms": 44,
+                "proof_size_bytes": 1082,
+                "circuit_constraints": 500,
+                "circuit_advice_columns": 10,
+                "circuit_fixed_columns": 5,
+                "folding_iterations": 1,
+                "quantization_error": 0.5
+            },
+            "training_metrics": {
+                "dataset_size": 100,
+                "num_features": 5,
+                "training_time_ms": 1500,
+                "epochs_completed": 10,
+                "final_loss": 0.25,
+                "initial_loss": 1.0,
+                "convergence_epoch": 7,
+                "loss_history": [1.0, 0.25],
+                "target_min": -1.5,
+                "target_max": 3.5
+            }
+        }"#;
+
+        let report = ClientReport::from_json(json).unwrap();
+        assert_eq!(report.client_id, "benchmark_client_3");
+        assert!(!report.success);
+        assert_eq!(report.error_message.as_deref(), Some("Proof verification failed"));
+        assert!(report.cancelled);
+        assert_eq!(report.dataset_hash.as_deref(), Some("ab12"));
+        assert_eq!(report.zkp_metrics.setup_time_ms, 11);
+        assert_eq!(report.zkp_metrics.circuit_constraints, 500);
+        assert_eq!(report.zkp_metrics.quantization_error, Some(0.5));
+        assert_eq!(report.training_metrics.dataset_size, 100);
+        assert_eq!(report.training_metrics.convergence_epoch, Some(7));
+        assert_eq!(report.training_metrics.loss_history, vec![1.0, 0.25]);
+        assert_eq!(report.training_metrics.target_max, Some(3.5));
+
+        let metrics = report.client_metrics();
+        assert_eq!(metrics.client_id, "benchmark_client_3");
+        assert_eq!(metrics.training_times, vec![Duration::from_millis(1500)]);
+        assert_eq!(metrics.avg_proof_time, Duration::from_millis(333));
+        assert_eq!(metrics.avg_witness_time, Duration::from_millis(22));
+        assert_eq!(metrics.avg_verification_time, Duration::from_millis(44));
+        assert_eq!(metrics.proof_sizes, vec![1082]);
+        assert_eq!((metrics.success_count, metrics.failure_count), (0, 1));
+        assert_eq!(metrics.total_time, Duration::from_millis(4200));
+
+        // Missing fields are reported rather than defaulted
+        let err = ClientReport::from_json(r#"{"client_id": "a", "success": true}"#).unwrap_err();
+        assert!(err.to_string().contains("Malformed client report"), "{}", err);
+    }
+
+    #[test]
+    fn test_find_latest_client_report() {
+        let dir = std::env::temp_dir().join(format!("zkp_fl_reports_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let since = SystemTime::now() - Duration::from_secs(1);
+
+        let mut result = BenchmarkResult::new(Uuid::new_v4(), "client_1".to_string());
+        result.finish(true, None);
+        result.save(&dir).unwrap();
+        // Same prefix, different client
+        let name = ClientReport::file_name("client_10", result.session_id, result.end_time);
+        std::fs::write(dir.join(name), "{}").unwrap();
+
+        let report = ClientReport::find_latest(&dir, "client_1", since).unwrap();
+        assert_eq!(report.client_id, "client_1");
+        assert!(report.success);
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        let err = ClientReport::find_latest(&dir, "client_1", later).unwrap_err();
+        assert!(err.to_string().contains("wrote no benchmark report"), "{}", err);
+        assert!(ClientReport::find_latest(&dir, "client_2", since).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_saves_in_the_same_second_do_not_collide() {
+        // Neither save finds the directory in place
+        let dir = std::env::temp_dir()
+            .join(format!("zkp_fl_reports_{}", Uuid::new_v4()))
+            .join("nested");
+        let finished_at = Utc::now();
+        let results: Vec<BenchmarkResult> = (0..2)
+            .map(|_| {
+                let mut result = BenchmarkResult::new(Uuid::new_v4(), "client_1".to_string());
+                result.finish(true, None);
+                result.end_time = finished_at;
+                result
+            })
+            .collect();
+
+        let paths: Vec<PathBuf> = std::thread::scope(|scope| {
+            let saves: Vec<_> = results
+                .iter()
+                .map(|result| scope.spawn(|| result.save(&dir).unwrap()))
+                .collect();
+            saves.into_iter().map(|save| save.join().unwrap()).collect()
+        });
+        assert_ne!(paths[0], paths[1]);
+        for (path, result) in paths.iter().zip(&results) {
+            let report = ClientReport::load(path).unwrap();
+            assert_eq!(report.client_id, "client_1");
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert!(name.contains(&short_id(result.session_id)), "{}", name);
+        }
+
+        // Saving a session twice is refused rather than overwriting it
+        assert!(results[0].save(&dir).is_err());
+
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
@@ -1,10 +1,46 @@
 use serde::{Deserialize, Serialize};
+use blake2b_simd::blake2b;
 use chrono::{DateTime, Utc};
+use ff::PrimeField;
+use group::GroupEncoding;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::poly::ipa::commitment::ParamsIPA;
+use halo2_proofs::protostar::{self, accumulator::Accumulator};
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, Transcript, TranscriptRead,
+    TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+use halo2_poseidon::{ConstantLength, Hash as Poseidon, P128Pow5T3};
+use halo2curves::pasta::{pallas, Fq};
+use rand_core::RngCore;
 use uuid::Uuid;
+use crate::{
+    f64_to_field, CircuitConfig, ConstraintCounts, DifferentialPrivacy, ModelType, Optimizer,
+    QuantizationScheme, Result, Sample, ScalerParams, ZkpFlError,
+};
+
+/// Largest serialized proof the server will accept
+pub const MAX_PROOF_SIZE_BYTES: usize = 10_000_000;
+
+/// Number of public inputs exposed by the circuit (the expected loss)
+pub const NUM_PUBLIC_INPUTS: usize = 1;
+
+/// Proof format produced by this build
+pub const PROOF_FORMAT_VERSION: u32 = 2;
+
+/// Oldest proof format this build can still verify; version 1 payloads
+/// carried placeholder bytes instead of an accumulator
+pub const MIN_PROOF_FORMAT_VERSION: u32 = 2;
+
+/// Accumulator proofs are folded into
+pub type ProofAccumulator = Accumulator<pallas::Affine>;
 
 /// ZKP proof structure that gets sent between client and server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZkpProof {
+    /// Wire format of this proof; proofs from before versioning read as 0
+    #[serde(default)]
+    pub format_version: u32,
     pub proof_id: Uuid,
     pub session_id: Uuid,
     pub client_id: String,
@@ -12,6 +48,13 @@ pub struct ZkpProof {
     pub proof_data: ProofData,
     pub training_commitment: TrainingCommitment,
     pub verification_result: Option<VerificationResult>,
+    /// Plaintext model weights shared for aggregation; must hash to
+    /// `training_commitment.weights_commitment`
+    #[serde(default)]
+    pub weights: Option<Vec<f64>>,
+    /// Plaintext model bias shared alongside `weights`
+    #[serde(default)]
+    pub bias: Option<f64>,
 }
 
 /// The actual proof data from the circuit
@@ -27,6 +70,195 @@ pub struct ProofData {
     pub metadata: ProofMetadata,
 }
 
+/// Encoding of `ProofData::proof_bytes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofPayload {
+    pub timestamp: DateTime<Utc>,
+    pub circuit_k: u32,
+    pub num_features: usize,
+    /// Instance values the accumulator was built over, as field reprs
+    pub instances: Vec<Vec<[u8; 32]>>,
+    /// Transcript `create_accumulator` wrote the accumulator's commitments to
+    pub data: Vec<u8>,
+}
+
+impl ProofPayload {
+    /// Payload of an accumulator for a circuit under `config`, with the
+    /// transcript `create_accumulator` recorded it in
+    pub fn for_accumulator(
+        config: &CircuitConfig,
+        accumulator: &ProofAccumulator,
+        transcript: Vec<u8>,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            timestamp,
+            circuit_k: config.k,
+            num_features: config.num_features,
+            instances: Self::encode_instances(&accumulator.instances),
+            data: transcript,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|e| ZkpFlError::ProofSerialization(format!("Failed to encode proof: {}", e)))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes)
+            .map_err(|e| ZkpFlError::ProofSerialization(format!("Failed to decode proof: {}", e)))
+    }
+
+    /// Instance values of an accumulator in the form the payload carries them
+    pub fn encode_instances(instances: &[Vec<pallas::Scalar>]) -> Vec<Vec<[u8; 32]>> {
+        instances
+            .iter()
+            .map(|column| column.iter().map(|value| value.to_repr()).collect())
+            .collect()
+    }
+}
+
+/// Share by which proof size bounds are widened, so encodings a little off
+/// the estimate, such as longer timestamps, are not refused
+pub const DEFAULT_PROOF_SIZE_TOLERANCE: f64 = 0.25;
+
+/// Encoded proof sizes a circuit can legitimately produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofSizeBounds {
+    pub min_bytes: usize,
+    pub max_bytes: usize,
+}
+
+impl ProofSizeBounds {
+    /// Bounds for `model_type` proofs under `config`, each widened by
+    /// `tolerance` of itself. The floor is the smallest payload carrying the
+    /// public inputs and a commitment per advice column; the ceiling is a
+    /// commitment for every cell of the circuit's 2^k rows, which no
+    /// accumulator approaches, capped at `MAX_PROOF_SIZE_BYTES`.
+    pub fn for_circuit(
+        config: &CircuitConfig,
+        model_type: ModelType,
+        tolerance: f64,
+    ) -> Result<Self> {
+        let counts = ConstraintCounts::for_model(model_type);
+        let point_size = <pallas::Affine as GroupEncoding>::Repr::default().as_ref().len();
+        let commitments = counts.num_advice_columns * point_size;
+        let smallest = ProofPayload {
+            // Whole seconds encode the shortest timestamp
+            timestamp: DateTime::default(),
+            circuit_k: config.k,
+            num_features: config.num_features,
+            instances: vec![vec![[0u8; 32]; NUM_PUBLIC_INPUTS]],
+            data: vec![0u8; commitments],
+        }
+        .to_bytes()?
+        .len();
+
+        let envelope = smallest - commitments;
+        let cells = (1usize << config.k) * (counts.num_advice_columns + counts.num_fixed_columns);
+        let largest = envelope.saturating_add(cells.saturating_mul(point_size));
+        let tolerance = tolerance.max(0.0);
+        Ok(Self {
+            min_bytes: (smallest as f64 * (1.0 - tolerance).max(0.0)).floor() as usize,
+            max_bytes: ((largest as f64 * (1.0 + tolerance)).ceil() as usize)
+                .min(MAX_PROOF_SIZE_BYTES),
+        })
+    }
+
+    /// Error naming the bound a proof of `len` bytes falls outside
+    pub fn check(&self, len: usize) -> Result<()> {
+        if len < self.min_bytes {
+            return Err(ZkpFlError::ProofVerification(format!(
+                "Proof data too small: {} bytes, this circuit's proofs are at least {}",
+                len, self.min_bytes
+            )));
+        }
+        if len > self.max_bytes {
+            return Err(ZkpFlError::ProofVerification(format!(
+                "Proof data too large: {} bytes, this circuit's proofs are at most {}",
+                len, self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Commit to `circuit`'s witness in a new accumulator over the public
+/// `loss`, returning it with the transcript `deserialize_accumulator` replays
+pub fn create_accumulator<C: Circuit<Fq>>(
+    params: &ParamsIPA<pallas::Affine>,
+    proving_key: &protostar::ProvingKey<pallas::Affine>,
+    circuit: &C,
+    loss: Fq,
+    rng: impl RngCore,
+) -> Result<(ProofAccumulator, Vec<u8>)> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    let accumulator = protostar::prover::create_accumulator(
+        params,
+        proving_key,
+        circuit,
+        &[&[loss]],
+        rng,
+        &mut transcript,
+    )
+    .map_err(|e| ZkpFlError::ProofGeneration(format!("Proof generation failed: {:?}", e)))?;
+    Ok((accumulator, transcript.finalize()))
+}
+
+/// Rebuild the accumulator a payload was serialized from by replaying its
+/// transcript: the instances are absorbed first, then every advice
+/// commitment is read back and followed by the challenge squeezed after it
+pub fn deserialize_accumulator(payload: &ProofPayload) -> Result<ProofAccumulator> {
+    let instances = payload
+        .instances
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|repr| {
+                    Option::from(pallas::Scalar::from_repr(*repr)).ok_or_else(|| {
+                        ZkpFlError::ProofSerialization(
+                            "Instance value is not a canonical field element".to_string(),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let point_size = <pallas::Affine as GroupEncoding>::Repr::default().as_ref().len();
+    if !payload.data.len().is_multiple_of(point_size) {
+        return Err(ZkpFlError::ProofSerialization(format!(
+            "Transcript of {} bytes is not a whole number of {}-byte commitments",
+            payload.data.len(),
+            point_size
+        )));
+    }
+
+    let transcript_error =
+        |e: std::io::Error| ZkpFlError::ProofSerialization(format!("Invalid transcript: {}", e));
+    let mut transcript =
+        Blake2bRead::<_, pallas::Affine, Challenge255<_>>::init(payload.data.as_slice());
+    for value in instances.iter().flatten() {
+        transcript.common_scalar(*value).map_err(transcript_error)?;
+    }
+
+    let num_commitments = payload.data.len() / point_size;
+    let mut advice_commitments = Vec::with_capacity(num_commitments);
+    let mut challenges = Vec::with_capacity(num_commitments);
+    for _ in 0..num_commitments {
+        advice_commitments.push(transcript.read_point().map_err(transcript_error)?);
+        challenges.push(transcript.squeeze_challenge().get_scalar());
+    }
+
+    Ok(Accumulator {
+        instances,
+        advice_commitments,
+        challenges,
+    })
+}
+
 /// Commitment to the training process
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingCommitment {
@@ -44,6 +276,133 @@ pub struct TrainingCommitment {
     pub weights_commitment: String,
     /// Final loss value
     pub final_loss: f64,
+    /// Quantization the proven values were snapped to; `None` for float proofs
+    #[serde(default)]
+    pub quantization: Option<QuantizationScheme>,
+    /// Feature scaling applied before training, which predictions on raw
+    /// data must repeat; `None` when the features were used as loaded
+    #[serde(default)]
+    pub scaler: Option<ScalerParams>,
+    /// Update rule and hyperparameters the weights were trained with
+    #[serde(default)]
+    pub optimizer: Optimizer,
+    /// Hash `weights_commitment` was computed with
+    #[serde(default)]
+    pub weights_commitment_scheme: CommitmentScheme,
+    /// Gradient clipping and noise applied during training, so the server
+    /// knows the weights are differentially private; `None` without DP
+    #[serde(default)]
+    pub differential_privacy: Option<DifferentialPrivacy>,
+    /// Commitment to the bias under `weights_commitment_scheme`, which
+    /// `weights_commitment` leaves out; `None` for proofs predating it
+    #[serde(default)]
+    pub bias_commitment: Option<String>,
+}
+
+/// Tag the bias commitment starts from ("bias" in ASCII)
+const BIAS_DOMAIN: u64 = 0x6269_6173;
+
+/// Hash used for the weights commitment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitmentScheme {
+    /// Blake2b over the bincode-serialized weights
+    #[default]
+    Blake2b,
+    /// Poseidon over the field-encoded weights, which a circuit can constrain
+    Poseidon,
+}
+
+impl CommitmentScheme {
+    /// Length of a hex encoded commitment: 64 Blake2b bytes or one field element
+    pub fn hex_len(&self) -> usize {
+        match self {
+            CommitmentScheme::Blake2b => 128,
+            CommitmentScheme::Poseidon => 64,
+        }
+    }
+}
+
+impl TrainingCommitment {
+    /// Blake2b hash of the samples a proof covers, hex encoded, as committed
+    /// in `dataset_hash`
+    pub fn hash_samples(samples: &[Sample]) -> Result<String> {
+        let dataset_bytes = bincode::serialize(samples).map_err(|e| {
+            ZkpFlError::ProofGeneration(format!("Dataset serialization failed: {}", e))
+        })?;
+        Ok(hex::encode(blake2b(&dataset_bytes).as_bytes()))
+    }
+
+    /// Check that `dataset_hash` commits to exactly `samples`
+    pub fn check_dataset_hash(&self, samples: &[Sample]) -> Result<()> {
+        let actual = Self::hash_samples(samples)?;
+        if actual != self.dataset_hash {
+            return Err(ZkpFlError::Dataset(format!(
+                "Dataset hash mismatch: the proof commits to {} but its {} samples hash to {}",
+                self.dataset_hash,
+                samples.len(),
+                actual
+            )));
+        }
+        Ok(())
+    }
+
+    /// Blake2b commitment to a set of model weights, hex encoded
+    pub fn commit_weights(weights: &[f64]) -> Result<String> {
+        let weights_bytes = bincode::serialize(weights).map_err(|e| {
+            ZkpFlError::ProofGeneration(format!("Weights serialization failed: {}", e))
+        })?;
+        Ok(hex::encode(blake2b(&weights_bytes).as_bytes()))
+    }
+
+    /// Poseidon commitment to a set of model weights, hex encoded. The
+    /// weights are encoded at `precision_bits` and chained through two-input
+    /// Poseidon starting from the weight count, so a circuit can recompute it
+    /// one weight per hash.
+    pub fn commit_weights_poseidon(weights: &[f64], precision_bits: u32) -> Result<String> {
+        let mut state = Fq::from(weights.len() as u64);
+        for &weight in weights {
+            let weight = f64_to_field::<Fq>(weight, precision_bits)?;
+            state = Poseidon::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init()
+                .hash([state, weight]);
+        }
+        Ok(hex::encode(state.to_repr()))
+    }
+
+    /// Commitment to the bias under `scheme`. The bias is hashed after a
+    /// domain tag, so it never collides with a one-weight commitment.
+    pub fn commit_bias_with(
+        scheme: CommitmentScheme,
+        bias: f64,
+        precision_bits: u32,
+    ) -> Result<String> {
+        match scheme {
+            CommitmentScheme::Blake2b => {
+                let bias_bytes = bincode::serialize(&(BIAS_DOMAIN, bias)).map_err(|e| {
+                    ZkpFlError::ProofGeneration(format!("Bias serialization failed: {}", e))
+                })?;
+                Ok(hex::encode(blake2b(&bias_bytes).as_bytes()))
+            }
+            CommitmentScheme::Poseidon => {
+                let bias = f64_to_field::<Fq>(bias, precision_bits)?;
+                let state = Poseidon::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init()
+                    .hash([Fq::from(BIAS_DOMAIN), bias]);
+                Ok(hex::encode(state.to_repr()))
+            }
+        }
+    }
+
+    /// Commitment to `weights` under `scheme`
+    pub fn commit_weights_with(
+        scheme: CommitmentScheme,
+        weights: &[f64],
+        precision_bits: u32,
+    ) -> Result<String> {
+        match scheme {
+            CommitmentScheme::Blake2b => Self::commit_weights(weights),
+            CommitmentScheme::Poseidon => Self::commit_weights_poseidon(weights, precision_bits),
+        }
+    }
 }
 
 /// Circuit parameters used for proof generation
@@ -54,16 +413,69 @@ pub struct CircuitParams {
     pub num_advice_columns: usize,
     pub num_fixed_columns: usize,
     pub max_degree: usize,
+    /// Model the circuit was built for; proofs predating it are linear
+    #[serde(default)]
+    pub model_type: ModelType,
+}
+
+impl CircuitParams {
+    /// Parameters of a `model_type` circuit of size `k` whose folding
+    /// constraints reach `max_degree`
+    pub fn for_model(k: u32, model_type: ModelType, max_degree: usize) -> Self {
+        let counts = ConstraintCounts::for_model(model_type);
+        Self {
+            k,
+            num_constraints: counts.num_constraints,
+            num_advice_columns: counts.num_advice_columns,
+            num_fixed_columns: counts.num_fixed_columns,
+            max_degree,
+            model_type,
+        }
+    }
 }
 
 /// Metadata about proof generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofMetadata {
+    /// Total time spent generating the proof, every phase included
     pub generation_time_ms: u64,
     pub proof_size_bytes: usize,
+    /// Time spent computing the circuit assignments and public instance
     pub witness_generation_time_ms: u64,
+    /// Time spent generating the proving key
     pub setup_time_ms: u64,
+    /// Time spent folding the synthesized witness into the accumulator
+    #[serde(default)]
+    pub folding_time_ms: u64,
     pub folding_iterations: usize,
+    /// Largest absolute change quantization made to a proven value
+    #[serde(default)]
+    pub quantization_error: Option<f64>,
+    /// Trained loss next to the loss the circuit actually proved
+    #[serde(default)]
+    pub loss_comparison: Option<LossComparison>,
+}
+
+/// The client trains in f64 but the circuit proves a fixed-point field
+/// element, so the proven loss can differ slightly from the trained one
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LossComparison {
+    /// Loss computed in f64 during training
+    pub training_loss: f64,
+    /// Public loss decoded back from the field element the circuit used
+    pub proven_loss: f64,
+    /// `proven_loss - training_loss`
+    pub gap: f64,
+}
+
+impl LossComparison {
+    pub fn new(training_loss: f64, proven_loss: f64) -> Self {
+        Self {
+            training_loss,
+            proven_loss,
+            gap: proven_loss - training_loss,
+        }
+    }
 }
 
 /// Result of proof verification
@@ -90,6 +502,94 @@ pub struct VerificationResponse {
     pub result: VerificationResult,
 }
 
+/// What the server can verify, queried by clients before training
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerCapabilities {
+    pub server_version: String,
+    pub circuit_k: u32,
+    pub num_features: usize,
+    /// Proof formats the server accepts; absent on servers predating versioning
+    #[serde(default)]
+    pub proof_format_versions: Option<ProofFormatRange>,
+}
+
+/// Global model produced by one federated averaging round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalModel {
+    /// Rounds completed so far; 0 before the first aggregation
+    pub round: u64,
+    pub weights: Vec<f64>,
+    pub bias: f64,
+    pub num_clients: usize,
+    /// Training samples behind the average, summed over the clients
+    pub total_samples: usize,
+    /// Proofs whose weights went into the average
+    pub proof_ids: Vec<Uuid>,
+    /// When the round was aggregated; `None` for the initial model
+    pub aggregated_at: Option<DateTime<Utc>>,
+}
+
+impl GlobalModel {
+    /// Zero-initialized model clients start from before any aggregation
+    pub fn initial(num_features: usize) -> Self {
+        Self {
+            round: 0,
+            weights: vec![0.0; num_features],
+            bias: 0.0,
+            num_clients: 0,
+            total_samples: 0,
+            proof_ids: Vec::new(),
+            aggregated_at: None,
+        }
+    }
+}
+
+/// History entry for one completed aggregation round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub round: u64,
+    /// Clients whose weights went into the round, sorted
+    pub client_ids: Vec<String>,
+    pub num_proofs: usize,
+    /// Final training loss of the contributing clients, weighted like their
+    /// models under mean aggregation
+    pub global_loss: f64,
+    pub aggregated_at: DateTime<Utc>,
+    /// Model the round produced
+    pub model: GlobalModel,
+    /// Clients left out of the round as outliers, sorted
+    #[serde(default)]
+    pub excluded_client_ids: Vec<String>,
+}
+
+/// Inclusive range of proof format versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofFormatRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl ProofFormatRange {
+    /// Formats this build can verify
+    pub fn supported() -> Self {
+        Self {
+            min: MIN_PROOF_FORMAT_VERSION,
+            max: PROOF_FORMAT_VERSION,
+        }
+    }
+
+    /// Fail with a descriptive error if `version` is outside the range
+    pub fn check(&self, version: u32) -> Result<()> {
+        if version < self.min || version > self.max {
+            return Err(ZkpFlError::ProofVerification(format!(
+                "Unsupported proof format version {}, server supports {}..{}",
+                version, self.min, self.max
+            )));
+        }
+        Ok(())
+    }
+}
+
 /// Batch of proofs for multi-client scenarios
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofBatch {
@@ -118,6 +618,7 @@ impl ZkpProof {
         training_commitment: TrainingCommitment,
     ) -> Self {
         Self {
+            format_version: PROOF_FORMAT_VERSION,
             proof_id: Uuid::new_v4(),
             session_id,
             client_id,
@@ -130,6 +631,8 @@ impl ZkpProof {
             },
             training_commitment,
             verification_result: None,
+            weights: None,
+            bias: None,
         }
     }
 
@@ -147,6 +650,42 @@ impl ZkpProof {
     pub fn proof_size(&self) -> usize {
         self.proof_data.proof_bytes.len()
     }
+
+    /// Local structural checks mirroring the server's validation, so clearly
+    /// invalid proofs are rejected before a network round trip
+    pub fn validate_self(&self, circuit_config: &CircuitConfig) -> Result<()> {
+        if self.proof_data.circuit_params.k != circuit_config.k {
+            return Err(ZkpFlError::ProofVerification(format!(
+                "Circuit parameter k mismatch: expected {}, got {}",
+                circuit_config.k, self.proof_data.circuit_params.k
+            )));
+        }
+
+        if self.proof_data.proof_bytes.is_empty() {
+            return Err(ZkpFlError::ProofVerification("Proof data is empty".to_string()));
+        }
+
+        let model_type = self.proof_data.circuit_params.model_type;
+        ProofSizeBounds::for_circuit(circuit_config, model_type, DEFAULT_PROOF_SIZE_TOLERANCE)?
+            .check(self.proof_data.proof_bytes.len())?;
+
+        if self.proof_data.public_inputs.len() != NUM_PUBLIC_INPUTS {
+            return Err(ZkpFlError::ProofVerification(format!(
+                "Public input count mismatch: expected {}, got {}",
+                NUM_PUBLIC_INPUTS,
+                self.proof_data.public_inputs.len()
+            )));
+        }
+
+        if self.training_commitment.num_features != circuit_config.num_features {
+            return Err(ZkpFlError::ProofVerification(format!(
+                "Feature count mismatch: expected {}, got {}",
+                circuit_config.num_features, self.training_commitment.num_features
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 impl ProofBatch {
@@ -186,3 +725,174 @@ impl ProofBatch {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_circuit_config;
+
+    fn test_proof() -> ZkpProof {
+        ZkpProof::new(
+            Uuid::new_v4(),
+            "test_client".to_string(),
+            vec![0u8; 256],
+            vec!["0.1".to_string()],
+            CircuitParams {
+                k: 10,
+                num_constraints: 1000,
+                num_advice_columns: 10,
+                num_fixed_columns: 2,
+                max_degree: 3,
+                model_type: ModelType::Linear,
+            },
+            ProofMetadata {
+                generation_time_ms: 1000,
+                proof_size_bytes: 256,
+                witness_generation_time_ms: 300,
+                setup_time_ms: 200,
+                folding_time_ms: 500,
+                folding_iterations: 1,
+                quantization_error: None,
+                loss_comparison: None,
+            },
+            TrainingCommitment {
+                dataset_hash: "a".repeat(128),
+                num_samples: 100,
+                num_features: 5,
+                learning_rate: 0.01,
+                epochs: 10,
+                weights_commitment: "b".repeat(128),
+                final_loss: 0.1,
+                quantization: None,
+                scaler: None,
+                optimizer: Optimizer::Sgd,
+                weights_commitment_scheme: CommitmentScheme::Blake2b,
+                differential_privacy: None,
+                bias_commitment: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_validate_self() {
+        let config = CircuitConfig { k: 10, num_features: 5, ..test_circuit_config() };
+        assert!(test_proof().validate_self(&config).is_ok());
+
+        let mut proof = test_proof();
+        proof.proof_data.proof_bytes.clear();
+        assert!(proof.validate_self(&config).is_err());
+
+        let mut proof = test_proof();
+        proof.proof_data.public_inputs.push("0.2".to_string());
+        assert!(proof.validate_self(&config).is_err());
+
+        let mut proof = test_proof();
+        proof.proof_data.circuit_params.k = 12;
+        assert!(proof.validate_self(&config).is_err());
+
+        let mut proof = test_proof();
+        proof.proof_data.proof_bytes.truncate(16);
+        assert!(proof.validate_self(&config).is_err());
+    }
+
+    #[test]
+    fn test_proof_size_bounds_scale_with_circuit() {
+        let config = CircuitConfig { k: 10, num_features: 5, ..test_circuit_config() };
+        let bounds = ProofSizeBounds::for_circuit(&config, ModelType::Linear, 0.0).unwrap();
+        let err = bounds.check(bounds.min_bytes - 1).unwrap_err();
+        assert!(err.to_string().contains("too small"), "{}", err);
+        assert!(bounds.check(bounds.min_bytes).is_ok());
+        assert!(bounds.check(bounds.max_bytes).is_ok());
+        let err = bounds.check(bounds.max_bytes + 1).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{}", err);
+
+        // An encoded accumulator of the circuit's shape is never under the floor
+        let counts = ConstraintCounts::for_model(ModelType::Linear);
+        let payload = ProofPayload {
+            timestamp: Utc::now(),
+            circuit_k: config.k,
+            num_features: config.num_features,
+            instances: vec![vec![[1u8; 32]]],
+            data: vec![0u8; 32 * counts.num_advice_columns],
+        };
+        assert!(bounds.check(payload.to_bytes().unwrap().len()).is_ok());
+
+        // More rows raise only the ceiling, which stays under the hard cap
+        let larger = CircuitConfig { k: 14, ..config.clone() };
+        let large = ProofSizeBounds::for_circuit(&larger, ModelType::Linear, 0.0).unwrap();
+        assert_eq!(large.min_bytes, bounds.min_bytes);
+        assert!(large.max_bytes > bounds.max_bytes);
+        let huge = CircuitConfig { k: 24, ..config.clone() };
+        let huge = ProofSizeBounds::for_circuit(&huge, ModelType::Linear, 0.0).unwrap();
+        assert_eq!(huge.max_bytes, MAX_PROOF_SIZE_BYTES);
+
+        let tolerant = ProofSizeBounds::for_circuit(&config, ModelType::Linear, 0.5).unwrap();
+        assert!(tolerant.min_bytes < bounds.min_bytes);
+        assert!(tolerant.max_bytes > bounds.max_bytes);
+    }
+
+    #[test]
+    fn test_format_version_negotiation() {
+        let supported = ProofFormatRange::supported();
+        assert!(supported.check(test_proof().format_version).is_ok());
+
+        let err = supported.check(PROOF_FORMAT_VERSION + 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Proof verification error: Unsupported proof format version {}, server supports {}..{}",
+                PROOF_FORMAT_VERSION + 1,
+                MIN_PROOF_FORMAT_VERSION,
+                PROOF_FORMAT_VERSION
+            )
+        );
+
+        // Proofs serialized before versioning have no field and read as 0
+        let mut json = serde_json::to_value(test_proof()).unwrap();
+        json.as_object_mut().unwrap().remove("format_version");
+        let legacy: ZkpProof = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.format_version, 0);
+        assert!(supported.check(legacy.format_version).is_err());
+    }
+
+    #[test]
+    fn test_proof_payload_round_trip() {
+        let payload = ProofPayload {
+            timestamp: Utc::now(),
+            circuit_k: 10,
+            num_features: 5,
+            instances: vec![],
+            data: vec![7u8; 32],
+        };
+        let bytes = payload.to_bytes().unwrap();
+        assert_eq!(ProofPayload::from_bytes(&bytes).unwrap(), payload);
+
+        let err = ProofPayload::from_bytes(&bytes[..bytes.len() / 2]).unwrap_err();
+        assert!(matches!(err, ZkpFlError::ProofSerialization(_)));
+        assert_eq!(err.status_code(), 400);
+    }
+
+    #[test]
+    fn test_poseidon_weights_commitment() {
+        let weights = vec![0.5, -1.25, 3.0];
+        let commitment = TrainingCommitment::commit_weights_poseidon(&weights, 16).unwrap();
+        assert_eq!(commitment.len(), CommitmentScheme::Poseidon.hex_len());
+        assert_eq!(commitment, TrainingCommitment::commit_weights_poseidon(&weights, 16).unwrap());
+        assert_eq!(
+            commitment,
+            TrainingCommitment::commit_weights_with(CommitmentScheme::Poseidon, &weights, 16)
+                .unwrap()
+        );
+
+        let mut changed = weights.clone();
+        changed[1] = -1.0;
+        assert_ne!(commitment, TrainingCommitment::commit_weights_poseidon(&changed, 16).unwrap());
+        // The weight count is absorbed too, so a trailing zero weight still changes it
+        let mut extended = weights.clone();
+        extended.push(0.0);
+        assert_ne!(commitment, TrainingCommitment::commit_weights_poseidon(&extended, 16).unwrap());
+
+        let blake2b = TrainingCommitment::commit_weights(&weights).unwrap();
+        assert_eq!(blake2b.len(), CommitmentScheme::Blake2b.hex_len());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{Result, ZkpFlError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// How a column's cells are read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    #[default]
+    Numeric,
+    /// Text labels, one-hot encoded into one feature per category
+    Categorical,
+}
+
+/// What a column is used for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnRole {
+    #[default]
+    Feature,
+    Target,
+    /// Present in the file but not loaded
+    Ignore,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    /// Header of the column in the dataset file
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: ColumnType,
+    #[serde(default)]
+    pub role: ColumnRole,
+    /// Categories of a categorical column in encoding order. When unset they
+    /// are the distinct values in the data, sorted; when set, other values
+    /// are rejected so every client encodes the same features.
+    #[serde(default)]
+    pub categories: Option<Vec<String>>,
+}
+
+/// Typed description of a dataset file's columns, read from JSON or TOML:
+///
+/// ```toml
+/// [[columns]]
+/// name = "smoker"
+/// type = "categorical"
+/// categories = ["no", "yes"]
+///
+/// [[columns]]
+/// name = "target"
+/// role = "target"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetSchema {
+    pub columns: Vec<ColumnSchema>,
+}
+
+impl DatasetSchema {
+    /// Read a schema, choosing the format from the `.json` or `.toml` extension
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ZkpFlError::Dataset(format!("Failed to read schema '{}': {}", path.display(), e))
+        })?;
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let schema: Self = if extension.eq_ignore_ascii_case("json") {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        } else if extension.eq_ignore_ascii_case("toml") {
+            toml::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            Err("expected a .json or .toml file".to_string())
+        }
+        .map_err(|e| {
+            ZkpFlError::Dataset(format!("Invalid schema '{}': {}", path.display(), e))
+        })?;
+
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Check for exactly one numeric target, at least one feature and no
+    /// column listed twice
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for column in &self.columns {
+            if !names.insert(column.name.as_str()) {
+                return Err(ZkpFlError::Dataset(format!(
+                    "Schema lists column '{}' more than once",
+                    column.name
+                )));
+            }
+            if column.categories.as_ref().is_some_and(Vec::is_empty) {
+                return Err(ZkpFlError::Dataset(format!(
+                    "Categorical column '{}' has an empty category list",
+                    column.name
+                )));
+            }
+        }
+
+        let targets: Vec<&ColumnSchema> = self
+            .columns
+            .iter()
+            .filter(|column| column.role == ColumnRole::Target)
+            .collect();
+        match targets.as_slice() {
+            [target] if target.kind == ColumnType::Numeric => {}
+            [target] => {
+                return Err(ZkpFlError::Dataset(format!(
+                    "Target column '{}' must be numeric",
+                    target.name
+                )))
+            }
+            _ => {
+                return Err(ZkpFlError::Dataset(format!(
+                    "Schema needs exactly one target column, found {}",
+                    targets.len()
+                )))
+            }
+        }
+        if self.features().next().is_none() {
+            return Err(ZkpFlError::Dataset("Schema has no feature columns".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The target column; the schema must be valid
+    pub fn target(&self) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|column| column.role == ColumnRole::Target)
+    }
+
+    /// Feature columns in file order, before encoding
+    pub fn features(&self) -> impl Iterator<Item = &ColumnSchema> {
+        self.columns.iter().filter(|column| column.role == ColumnRole::Feature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_formats_and_validation() {
+        let toml_schema: DatasetSchema = toml::from_str(
+            r#"
+            [[columns]]
+            name = "age"
+
+            [[columns]]
+            name = "smoker"
+            type = "categorical"
+
+            [[columns]]
+            name = "id"
+            role = "ignore"
+
+            [[columns]]
+            name = "target"
+            role = "target"
+            "#,
+        )
+        .unwrap();
+        assert!(toml_schema.validate().is_ok());
+        assert_eq!(toml_schema.target().unwrap().name, "target");
+        assert_eq!(toml_schema.features().count(), 2);
+
+        let json_schema: DatasetSchema = serde_json::from_str(
+            r#"{"columns": [
+                {"name": "age"},
+                {"name": "smoker", "type": "categorical"},
+                {"name": "id", "role": "ignore"},
+                {"name": "target", "role": "target"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(json_schema, toml_schema);
+
+        let mut no_target = toml_schema.clone();
+        no_target.columns.pop();
+        assert!(no_target.validate().is_err());
+
+        let mut categorical_target = toml_schema;
+        categorical_target.columns[3].kind = ColumnType::Categorical;
+        let err = categorical_target.validate().unwrap_err();
+        assert!(err.to_string().contains("must be numeric"), "{}", err);
+    }
+}
@@ -0,0 +1,45 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Header carrying the hex HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "x-zkpfl-signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn keyed_mac(secret: &str) -> HmacSha256 {
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+/// Hex signature of `body` under `secret`, sent in `SIGNATURE_HEADER`
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac = keyed_mac(secret);
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Check a hex `signature` of `body` in constant time
+pub fn verify_body_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature.trim()) else {
+        return false;
+    };
+    let mut mac = keyed_mac(secret);
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let body = br#"{"requester_id":"client"}"#;
+        let signature = sign_body("secret", body);
+        assert_eq!(signature.len(), 64);
+        assert!(verify_body_signature("secret", body, &signature));
+
+        assert!(!verify_body_signature("other", body, &signature));
+        assert!(!verify_body_signature("secret", br#"{"requester_id":"mallory"}"#, &signature));
+        assert!(!verify_body_signature("secret", body, "not hex"));
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{CircuitConfig, CommitmentScheme, ModelType};
+
+/// Small linear circuit for tests across the workspace; tests override the
+/// fields they depend on with struct update syntax
+pub fn test_circuit_config() -> CircuitConfig {
+    CircuitConfig {
+        k: 8,
+        num_features: 3,
+        max_samples: 100,
+        precision_bits: 16,
+        max_iterations: 100,
+        strict_floats: false,
+        quantize: false,
+        max_loss_gap: None,
+        model_type: ModelType::Linear,
+        weights_commitment: CommitmentScheme::Blake2b,
+    }
+}
@@ -1,5 +1,7 @@
+use crate::dataset::DatasetSource;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -13,6 +15,131 @@ pub struct Config {
     pub benchmarks: BenchmarkConfig,
 }
 
+impl Config {
+    /// Check the settings that only make sense together, reporting every
+    /// violation in one error instead of failing on the first
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        let num_features = self.circuit.num_features;
+
+        if num_features == 0 {
+            errors.push("circuit.num_features must be at least 1".to_string());
+        }
+        // A schema's encoded feature count is only known once the data is read
+        if self.dataset.schema_path.is_some() {
+            if matches!(self.dataset.path, DatasetSource::SyntheticSeeded { .. }) {
+                errors.push("dataset.schema_path needs a file dataset.path".to_string());
+            }
+        } else if self.dataset.feature_columns.is_empty() {
+            errors.push("dataset.feature_columns is empty".to_string());
+        } else if self.dataset.feature_columns.len() != num_features {
+            errors.push(format!(
+                "dataset.feature_columns lists {} columns but circuit.num_features is {}",
+                self.dataset.feature_columns.len(),
+                num_features
+            ));
+        }
+        if let DatasetSource::SyntheticSeeded { features, .. } = self.dataset.path {
+            if features != num_features {
+                errors.push(format!(
+                    "dataset.path generates {} features but circuit.num_features is {}",
+                    features, num_features
+                ));
+            }
+        }
+
+        let split = self.dataset.train_test_split;
+        if !(split > 0.0 && split < 1.0) {
+            errors.push(format!(
+                "dataset.train_test_split must be strictly between 0 and 1, got {}",
+                split
+            ));
+        }
+        if self.server.tls_cert_path.is_some() != self.server.tls_key_path.is_some() {
+            errors.push(
+                "server.tls_cert_path and server.tls_key_path must be set together".to_string(),
+            );
+        }
+        let tolerance = self.server.proof_size_tolerance;
+        if !(tolerance.is_finite() && (0.0..1.0).contains(&tolerance)) {
+            errors.push(format!(
+                "server.proof_size_tolerance must be at least 0 and below 1, got {}",
+                tolerance
+            ));
+        }
+        let trim_fraction = self.server.aggregation_trim_fraction;
+        if !(trim_fraction.is_finite() && (0.0..0.5).contains(&trim_fraction)) {
+            errors.push(format!(
+                "server.aggregation_trim_fraction must be at least 0 and below 0.5, got {}",
+                trim_fraction
+            ));
+        }
+        if let Some(threshold) = self.server.aggregation_outlier_threshold {
+            if !(threshold.is_finite() && threshold > 0.0) {
+                errors.push(format!(
+                    "server.aggregation_outlier_threshold must be positive, got {}",
+                    threshold
+                ));
+            }
+        }
+        let learning_rate = self.client.learning_rate;
+        if !(learning_rate.is_finite() && learning_rate > 0.0) {
+            errors.push(format!("client.learning_rate must be positive, got {}", learning_rate));
+        }
+        if let Some(sigma) = self.client.dp_sigma {
+            if !(sigma.is_finite() && sigma >= 0.0) {
+                errors.push(format!("client.dp_sigma must be non-negative, got {}", sigma));
+            }
+            let clip_norm = self.client.dp_clip_norm;
+            if !(clip_norm.is_finite() && clip_norm > 0.0) {
+                errors.push(format!("client.dp_clip_norm must be positive, got {}", clip_norm));
+            }
+        }
+
+        // Proofs cover every sample; synthetic sources say how many there are
+        let max_samples = self.circuit.max_samples;
+        if max_samples == 0 {
+            errors.push("circuit.max_samples must be at least 1".to_string());
+        }
+        let proven_samples = match self.dataset.path {
+            DatasetSource::SyntheticSeeded { samples, .. } => {
+                let samples = match self.dataset.max_samples {
+                    0 => samples,
+                    kept => samples.min(kept),
+                };
+                if samples > max_samples {
+                    errors.push(format!(
+                        "dataset.path generates {} samples but circuit.max_samples is {}",
+                        samples, max_samples
+                    ));
+                }
+                samples.min(max_samples)
+            }
+            DatasetSource::File { .. } => max_samples,
+        };
+        match self.circuit.quantization() {
+            Err(e) => errors.push(format!("circuit.precision_bits: {}", e)),
+            Ok(quantization) if num_features > 0 => {
+                let budget = crate::CircuitBuilder::new(num_features, proven_samples)
+                    .with_precision_bits(self.circuit.precision_bits as u32)
+                    .with_quantization(quantization)
+                    .with_k(self.circuit.k)
+                    .check_row_budget(self.circuit.model_type, proven_samples);
+                if let Err(e) = budget {
+                    errors.push(format!("circuit.k: {}", e));
+                }
+            }
+            Ok(_) => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ZkpFlError::Config(format!("Invalid configuration: {}", errors.join("; "))))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
@@ -20,6 +147,177 @@ pub struct ServerConfig {
     pub max_clients: usize,
     pub proof_storage_path: String,
     pub log_level: String,
+    /// Sliding window (seconds) used for load/throughput calculations
+    #[serde(default = "default_metrics_window_secs")]
+    pub metrics_window_secs: u64,
+    /// Upper bound on detailed verification records kept for percentiles
+    #[serde(default = "default_metrics_history_max_records")]
+    pub metrics_history_max_records: usize,
+    /// Time (seconds) detailed verification records are kept for the history
+    /// and percentiles, independent of the load window
+    #[serde(default = "default_metrics_history_retention_secs")]
+    pub metrics_history_retention_secs: u64,
+    /// Keep detailed verification records for one in every N verifications
+    /// (1 = all). Counters stay exact; percentiles become approximate.
+    #[serde(default = "default_metrics_sample_every")]
+    pub metrics_sample_every: u64,
+    /// Time (ms) to collect verify requests into one batch; 0 disables batching
+    #[serde(default)]
+    pub verification_batch_window_ms: u64,
+    /// Maximum number of proofs verified together in one batch
+    #[serde(default = "default_verification_batch_max_size")]
+    pub verification_batch_max_size: usize,
+    /// Verify requests allowed per requester per minute, the rate each
+    /// requester's token bucket refills at; 0 disables the limit. Requesters
+    /// are told apart by their signed requester id when
+    /// `request_signing_secret` is set, and by peer address otherwise, so
+    /// unsigned clients behind one proxy share a bucket.
+    #[serde(default)]
+    pub verify_rate_limit_per_minute: u32,
+    /// Verify requests a requester may send in one burst; 0 allows a full
+    /// minute's worth
+    #[serde(default)]
+    pub verify_rate_limit_burst: u32,
+    /// Attach proof and client ids as exemplars to latency buckets in `/api/metrics`
+    #[serde(default)]
+    pub metrics_exemplars: bool,
+    /// Time (seconds) a shutdown waits for queued and in-flight verifications
+    #[serde(default = "default_shutdown_drain_timeout_secs")]
+    pub shutdown_drain_timeout_secs: u64,
+    /// Time (seconds) between background retention cleanups; 0 disables them
+    #[serde(default)]
+    pub retention_interval_secs: u64,
+    /// Hours a proof stays valid: older proofs are refused verification and
+    /// removed by the retention cleanup
+    #[serde(default = "default_proof_ttl_hours", alias = "retention_max_age_hours")]
+    pub proof_ttl_hours: i64,
+    /// Keep at most this many proofs, evicting the oldest beyond it
+    #[serde(default)]
+    pub retention_max_count: Option<usize>,
+    /// Verified clients a round needs before `/api/aggregate` averages it
+    #[serde(default = "default_aggregation_min_clients")]
+    pub aggregation_min_clients: usize,
+    /// How much each client's weights count towards the global model
+    #[serde(default)]
+    pub aggregation_weighting: AggregationWeighting,
+    /// How the contributing models are combined into the global model
+    #[serde(default)]
+    pub aggregation_rule: AggregationRule,
+    /// Share of clients dropped from each end of every coordinate by the
+    /// trimmed mean
+    #[serde(default = "default_aggregation_trim_fraction")]
+    pub aggregation_trim_fraction: f64,
+    /// Leave out clients whose distance from the median model is more than
+    /// this many (robustly estimated) standard deviations above the typical
+    /// client's; unset keeps every client
+    #[serde(default)]
+    pub aggregation_outlier_threshold: Option<f64>,
+    /// Where submitted proofs are persisted
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    /// Shared secret verify requests must be HMAC-signed with; unset accepts
+    /// unsigned requests
+    #[serde(default)]
+    pub request_signing_secret: Option<String>,
+    /// Verifications allowed to run at once, others queue; 0 uses one per CPU
+    #[serde(default)]
+    pub max_concurrent_verifications: usize,
+    /// Share by which the proof size range expected for the circuit is
+    /// widened before undersized or oversized proofs are refused
+    #[serde(default = "default_proof_size_tolerance")]
+    pub proof_size_tolerance: f64,
+    /// Dataset hashes registered out of band, by client id. Proofs from a
+    /// listed client must commit to its registered hash.
+    #[serde(default)]
+    pub registered_dataset_hashes: HashMap<String, String>,
+    /// Check outcomes kept for resubmitted proofs; 0 disables the cache
+    #[serde(default = "default_verification_cache_size")]
+    pub verification_cache_size: usize,
+    /// PEM certificate chain to serve HTTPS with; plain HTTP when unset
+    #[serde(default)]
+    pub tls_cert_path: Option<String>,
+    /// PEM private key for `tls_cert_path`
+    #[serde(default)]
+    pub tls_key_path: Option<String>,
+}
+
+/// Persistence used for submitted proofs and batches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// One JSON file per proof, all loaded into memory on startup
+    #[default]
+    File,
+    /// A single SQLite database with indexed client and timestamp columns
+    Sqlite,
+}
+
+/// Share of the global model each contributing client gets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregationWeighting {
+    /// In proportion to the samples committed in its training commitment
+    #[default]
+    Samples,
+    /// The same for every client, however much data it trained on
+    Uniform,
+}
+
+/// Combination of the contributing models into the global model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationRule {
+    /// Average weighted per `aggregation_weighting`
+    #[default]
+    Mean,
+    /// Coordinate-wise median, unaffected by a minority of extreme clients
+    Median,
+    /// Coordinate-wise mean after dropping the largest and smallest values
+    TrimmedMean,
+}
+
+fn default_metrics_window_secs() -> u64 {
+    60
+}
+
+fn default_metrics_history_max_records() -> usize {
+    10_000
+}
+
+fn default_metrics_history_retention_secs() -> u64 {
+    3600
+}
+
+fn default_metrics_sample_every() -> u64 {
+    1
+}
+
+fn default_verification_batch_max_size() -> usize {
+    32
+}
+
+fn default_shutdown_drain_timeout_secs() -> u64 {
+    30
+}
+
+fn default_proof_ttl_hours() -> i64 {
+    24
+}
+
+fn default_proof_size_tolerance() -> f64 {
+    crate::DEFAULT_PROOF_SIZE_TOLERANCE
+}
+
+fn default_verification_cache_size() -> usize {
+    1024
+}
+
+fn default_aggregation_min_clients() -> usize {
+    2
+}
+
+fn default_aggregation_trim_fraction() -> f64 {
+    0.1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,25 +325,187 @@ pub struct ClientConfig {
     pub server_url: String,
     pub client_id: String,
     pub training_epochs: usize,
+    /// Samples per gradient step; 0 trains on the whole set at once
     pub batch_size: usize,
     pub learning_rate: f64,
+    /// Seed for shuffling the mini-batches every epoch
+    #[serde(default)]
+    pub shuffle_seed: u64,
+    /// Epochs without validation improvement before training stops early;
+    /// 0 always trains for every epoch
+    #[serde(default)]
+    pub patience: usize,
+    /// Smallest drop in validation loss that counts as an improvement
+    #[serde(default)]
+    pub min_delta: f64,
+    /// Gradient update rule, plain SGD unless configured
+    #[serde(default)]
+    pub optimizer: Optimizer,
+    /// How `learning_rate` changes over the epochs; constant unless configured
+    #[serde(default)]
+    pub learning_rate_schedule: LearningRateSchedule,
+    /// Gaussian noise added to gradients, as a multiple of `dp_clip_norm`;
+    /// unset trains without differential privacy
+    #[serde(default)]
+    pub dp_sigma: Option<f64>,
+    /// Largest L2 norm one sample may contribute to a gradient when
+    /// `dp_sigma` is set
+    #[serde(default = "default_dp_clip_norm")]
+    pub dp_clip_norm: f64,
+    /// Seed for the gradient noise
+    #[serde(default)]
+    pub dp_seed: u64,
+    /// Keep detailed metrics for one in every N operations (1 = all)
+    #[serde(default = "default_metrics_sample_every")]
+    pub metrics_sample_every: u64,
+    /// Gzip proof submissions; turn off to read request bodies while debugging
+    #[serde(default = "default_compress_requests")]
+    pub compress_requests: bool,
+    /// Shared secret submissions are HMAC-signed with, matching the server's
+    #[serde(default)]
+    pub request_signing_secret: Option<String>,
+    /// Directory generated proofs are kept in, so training that repeats
+    /// exactly reuses its proof instead of proving again
+    #[serde(default = "default_proof_cache_dir")]
+    pub proof_cache_dir: String,
+    /// Re-hash the proven samples before submitting and refuse to send a
+    /// proof whose dataset hash does not match them
+    #[serde(default = "default_verify_dataset_hash")]
+    pub verify_dataset_hash: bool,
+    /// How often a multi-round client asks for the next global model while
+    /// waiting for the server to aggregate
+    #[serde(default = "default_global_model_poll_ms")]
+    pub global_model_poll_ms: u64,
+    /// Times a submission that failed in transit or with a 5xx is resent
+    #[serde(default = "default_submit_retries")]
+    pub submit_retries: usize,
+    /// Wait (ms) before the first resend, doubled for each one after it
+    #[serde(default = "default_submit_retry_backoff_ms")]
+    pub submit_retry_backoff_ms: u64,
+}
+
+fn default_verify_dataset_hash() -> bool {
+    true
+}
+
+fn default_global_model_poll_ms() -> u64 {
+    2000
+}
+
+fn default_submit_retries() -> usize {
+    3
+}
+
+fn default_submit_retry_backoff_ms() -> u64 {
+    500
+}
+
+fn default_proof_cache_dir() -> String {
+    "./proof_cache".to_string()
+}
+
+fn default_dp_clip_norm() -> f64 {
+    1.0
+}
+
+impl ClientConfig {
+    /// Mechanism applied to gradients, if `dp_sigma` is set
+    pub fn differential_privacy(&self) -> Option<DifferentialPrivacy> {
+        self.dp_sigma.map(|sigma| DifferentialPrivacy {
+            clip_norm: self.dp_clip_norm,
+            sigma,
+        })
+    }
+}
+
+fn default_compress_requests() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitConfig {
     pub k: u32, // Circuit size parameter (2^k rows)
     pub num_features: usize,
+    /// Most samples a proof covers; datasets with more are rejected rather
+    /// than proven on a subset
+    #[serde(default = "default_max_proof_samples")]
+    pub max_samples: usize,
     pub precision_bits: usize,
     pub max_iterations: usize,
+    /// Reject f64 values that cannot be represented exactly at the fixed-point
+    /// scale instead of silently rounding them. Guarantees the circuit sees the
+    /// same values the client computed with, at the cost of failing on most
+    /// unquantized training outputs.
+    #[serde(default)]
+    pub strict_floats: bool,
+    /// Prove over integers quantized to `precision_bits` fractional bits
+    /// rather than scaled floats
+    #[serde(default)]
+    pub quantize: bool,
+    /// Largest difference between the loss training reported and the loss
+    /// the circuit proved that verifiers accept; unchecked when unset
+    #[serde(default)]
+    pub max_loss_gap: Option<f64>,
+    /// Model clients train and prove; logistic proofs need `precision_bits`
+    /// of about 17 or less to keep the sigmoid inside the field
+    #[serde(default)]
+    pub model_type: ModelType,
+    /// Hash clients commit their trained weights with
+    #[serde(default)]
+    pub weights_commitment: crate::CommitmentScheme,
+}
+
+/// Regression model trained by clients and proven by the circuit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelType {
+    /// Gradient descent on the mean squared error
+    #[default]
+    Linear,
+    /// Sigmoid output trained on binary cross-entropy; the circuit proves
+    /// the squared error of a polynomial sigmoid approximation
+    Logistic,
+}
+
+fn default_max_proof_samples() -> usize {
+    crate::DEFAULT_MAX_PROOF_SAMPLES
+}
+
+impl CircuitConfig {
+    /// Quantization scheme proofs are generated and verified with, if enabled
+    pub fn quantization(&self) -> Result<Option<crate::QuantizationScheme>> {
+        if !self.quantize {
+            return Ok(None);
+        }
+        crate::QuantizationScheme::new(self.precision_bits as u32).map(Some)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatasetConfig {
-    pub path: String,
+    /// File path or `synthetic[:samples,features[,seed]]` spec
+    pub path: DatasetSource,
     pub target_column: String,
     pub feature_columns: Vec<String>,
     pub train_test_split: f64,
     pub normalize: bool,
+    /// Split so train and test keep the class proportions of the target
+    #[serde(default)]
+    pub stratified: bool,
+    /// Seed for shuffling rows within each class of a stratified split
+    #[serde(default)]
+    pub split_seed: u64,
+    /// Stream the dataset and keep a uniform sample of at most this many
+    /// rows; 0 loads every row
+    #[serde(default)]
+    pub max_samples: usize,
+    /// Seed for choosing which rows a capped load keeps
+    #[serde(default)]
+    pub sample_seed: u64,
+    /// JSON or TOML schema typing every column of a dataset file; when set
+    /// it replaces `target_column` and `feature_columns`
+    #[serde(default)]
+    pub schema_path: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +513,155 @@ pub struct BenchmarkConfig {
     pub output_path: String,
     pub metrics_interval_ms: u64,
     pub detailed_logging: bool,
+    /// Decimal places for floating point values in reports
+    #[serde(default = "default_report_precision")]
+    pub report_precision: usize,
+    /// Unit for durations in the summary report
+    #[serde(default)]
+    pub report_time_unit: ReportTimeUnit,
+    /// Seconds a benchmark client may run before it is killed; 0 disables
+    #[serde(default)]
+    pub client_timeout_secs: u64,
+}
+
+fn default_report_precision() -> usize {
+    2
+}
+
+impl BenchmarkConfig {
+    /// Per-client run timeout, if one is set
+    pub fn client_timeout(&self) -> Option<std::time::Duration> {
+        (self.client_timeout_secs > 0).then(|| std::time::Duration::from_secs(self.client_timeout_secs))
+    }
+}
+
+/// Unit durations are reported in; seconds read better for long runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportTimeUnit {
+    #[default]
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "s")]
+    Seconds,
+}
+
+impl ReportTimeUnit {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportTimeUnit::Milliseconds => "ms",
+            ReportTimeUnit::Seconds => "s",
+        }
+    }
+
+    /// Convert a duration in milliseconds to this unit
+    pub fn convert_ms(&self, ms: f64) -> f64 {
+        match self {
+            ReportTimeUnit::Milliseconds => ms,
+            ReportTimeUnit::Seconds => ms / 1000.0,
+        }
+    }
+}
+
+/// Update rule for gradient steps, with its hyperparameters
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Optimizer {
+    /// Plain gradient descent
+    #[default]
+    Sgd,
+    /// Gradient descent on an exponentially decaying sum of past gradients
+    Momentum {
+        #[serde(default = "default_momentum_beta")]
+        beta: f64,
+    },
+    /// Steps scaled per weight by running estimates of the gradient's
+    /// first and second moments
+    Adam {
+        #[serde(default = "default_adam_beta1")]
+        beta1: f64,
+        #[serde(default = "default_adam_beta2")]
+        beta2: f64,
+        #[serde(default = "default_adam_epsilon")]
+        epsilon: f64,
+    },
+}
+
+fn default_momentum_beta() -> f64 {
+    0.9
+}
+
+fn default_adam_beta1() -> f64 {
+    0.9
+}
+
+fn default_adam_beta2() -> f64 {
+    0.999
+}
+
+fn default_adam_epsilon() -> f64 {
+    1e-8
+}
+
+impl Optimizer {
+    /// Momentum with the usual decay of 0.9
+    pub fn momentum() -> Self {
+        Optimizer::Momentum { beta: default_momentum_beta() }
+    }
+
+    /// Adam with the hyperparameters from the original paper
+    pub fn adam() -> Self {
+        Optimizer::Adam {
+            beta1: default_adam_beta1(),
+            beta2: default_adam_beta2(),
+            epsilon: default_adam_epsilon(),
+        }
+    }
+}
+
+/// Gaussian mechanism applied to training gradients: every sample's
+/// gradient is clipped to `clip_norm`, then noise with standard deviation
+/// `sigma * clip_norm` is added to the sum
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifferentialPrivacy {
+    pub clip_norm: f64,
+    pub sigma: f64,
+}
+
+/// How the learning rate changes from one epoch to the next
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum LearningRateSchedule {
+    /// The base rate for every epoch
+    #[default]
+    Constant,
+    /// Multiply the rate by `gamma` every `step_size` epochs
+    Step { step_size: usize, gamma: f64 },
+    /// Multiply the rate by `gamma` every epoch
+    Exponential { gamma: f64 },
+    /// Anneal from the base rate towards `min_rate` along half a cosine
+    /// spanning the whole run
+    Cosine {
+        #[serde(default)]
+        min_rate: f64,
+    },
+}
+
+impl LearningRateSchedule {
+    /// Learning rate for `epoch` (counted from 0) of a run of `total_epochs`
+    pub fn rate(&self, base_rate: f64, epoch: usize, total_epochs: usize) -> f64 {
+        match *self {
+            LearningRateSchedule::Constant => base_rate,
+            LearningRateSchedule::Step { step_size, gamma } => {
+                base_rate * gamma.powi((epoch / step_size.max(1)) as i32)
+            }
+            LearningRateSchedule::Exponential { gamma } => base_rate * gamma.powi(epoch as i32),
+            LearningRateSchedule::Cosine { min_rate } => {
+                let progress = epoch as f64 / total_epochs.max(1) as f64;
+                let annealed = (1.0 + (std::f64::consts::PI * progress).cos()) / 2.0;
+                min_rate + (base_rate - min_rate) * annealed
+            }
+        }
+    }
 }
 
 /// Training parameters for linear regression
@@ -63,6 +672,12 @@ pub struct TrainingParams {
     pub loss: f64,
     pub epoch: usize,
     pub learning_rate: f64,
+    /// Update rule the weights were trained with
+    #[serde(default)]
+    pub optimizer: Optimizer,
+    /// Noise mechanism the gradients were trained with, if any
+    #[serde(default)]
+    pub differential_privacy: Option<DifferentialPrivacy>,
 }
 
 /// Dataset sample for training
@@ -88,6 +703,10 @@ pub struct Session {
     pub end_time: Option<DateTime<Utc>>,
     pub status: SessionStatus,
     pub metrics: SessionMetrics,
+    /// Round of a multi-round run the session is in, counting from 1; 0 for
+    /// a single train and submit
+    #[serde(default)]
+    pub round: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,8 +717,11 @@ pub enum SessionStatus {
     ProofGenerated,
     Verifying,
     Verified,
+    /// Submitted this round's proof, waiting for the server to aggregate
+    WaitingForGlobalModel,
     Failed,
     Completed,
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -154,6 +776,9 @@ pub enum ZkpFlError {
     #[error("Proof verification error: {0}")]
     ProofVerification(String),
 
+    #[error("Proof serialization error: {0}")]
+    ProofSerialization(String),
+
     #[error("Verification error: {0}")]
     Verification(String),
 
@@ -169,6 +794,30 @@ pub enum ZkpFlError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Aggregation error: {0}")]
+    Aggregation(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -176,4 +825,118 @@ pub enum ZkpFlError {
     Serialization(#[from] serde_json::Error),
 }
 
+impl ZkpFlError {
+    /// HTTP status the API responds with when a request fails with this error
+    pub fn status_code(&self) -> u16 {
+        match self {
+            ZkpFlError::Circuit(_)
+            | ZkpFlError::ProofVerification(_)
+            | ZkpFlError::ProofSerialization(_)
+            | ZkpFlError::Verification(_)
+            | ZkpFlError::Dataset(_) => 400,
+            ZkpFlError::Unauthorized(_) => 401,
+            ZkpFlError::Aggregation(_) => 409,
+            ZkpFlError::PayloadTooLarge(_) => 413,
+            ZkpFlError::RateLimited(_) => 429,
+            ZkpFlError::Network(_) => 502,
+            ZkpFlError::Cancelled | ZkpFlError::Unavailable(_) => 503,
+            ZkpFlError::Timeout(_) => 504,
+            ZkpFlError::ProofGeneration(_)
+            | ZkpFlError::Benchmark(_)
+            | ZkpFlError::Config(_)
+            | ZkpFlError::Storage(_)
+            | ZkpFlError::Io(_)
+            | ZkpFlError::Serialization(_) => 500,
+        }
+    }
+
+    /// Stable machine-readable identifier for this error kind
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ZkpFlError::Circuit(_) => "circuit_error",
+            ZkpFlError::ProofGeneration(_) => "proof_generation_error",
+            ZkpFlError::ProofVerification(_) => "proof_verification_failed",
+            ZkpFlError::ProofSerialization(_) => "proof_serialization_error",
+            ZkpFlError::Verification(_) => "verification_error",
+            ZkpFlError::Benchmark(_) => "benchmark_error",
+            ZkpFlError::Dataset(_) => "dataset_error",
+            ZkpFlError::Network(_) => "network_error",
+            ZkpFlError::Config(_) => "config_error",
+            ZkpFlError::Cancelled => "cancelled",
+            ZkpFlError::Timeout(_) => "timeout",
+            ZkpFlError::Unavailable(_) => "unavailable",
+            ZkpFlError::Unauthorized(_) => "unauthorized",
+            ZkpFlError::PayloadTooLarge(_) => "payload_too_large",
+            ZkpFlError::RateLimited(_) => "rate_limited",
+            ZkpFlError::Aggregation(_) => "aggregation_error",
+            ZkpFlError::Storage(_) => "storage_error",
+            ZkpFlError::Io(_) => "io_error",
+            ZkpFlError::Serialization(_) => "serialization_error",
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ZkpFlError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shipped_config() -> Config {
+        toml::from_str(include_str!("../../config.toml")).unwrap()
+    }
+
+    fn violations(config: &Config) -> String {
+        match config.validate() {
+            Err(ZkpFlError::Config(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_shipped_config_is_valid() {
+        shipped_config().validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_reports_every_violation() {
+        let mut config = shipped_config();
+        config.dataset.train_test_split = 1.0;
+        config.client.learning_rate = 0.0;
+        config.dataset.feature_columns.pop();
+        config.client.dp_sigma = Some(-1.0);
+        config.client.dp_clip_norm = 0.0;
+        config.server.tls_cert_path = Some("server.crt".to_string());
+
+        let message = violations(&config);
+        assert!(message.contains("train_test_split must be strictly between 0 and 1, got 1"));
+        assert!(message.contains("client.learning_rate must be positive, got 0"));
+        assert!(message.contains("feature_columns lists 4 columns but circuit.num_features is 5"));
+        assert!(message.contains("client.dp_sigma must be non-negative, got -1"));
+        assert!(message.contains("client.dp_clip_norm must be positive, got 0"));
+        assert!(message.contains("tls_cert_path and server.tls_key_path must be set together"));
+        assert_eq!(message.matches("; ").count(), 5, "{}", message);
+    }
+
+    #[test]
+    fn test_validate_checks_features_and_rows() {
+        let mut config = shipped_config();
+        config.dataset.feature_columns.clear();
+        config.dataset.path = "synthetic:1000,3".parse().unwrap();
+        let message = violations(&config);
+        assert!(message.contains("dataset.feature_columns is empty"));
+        assert!(message.contains("generates 3 features but circuit.num_features is 5"));
+
+        // 100 proven samples of 5 features cannot fit in 2^8 rows
+        let mut config = shipped_config();
+        config.circuit.k = 8;
+        let message = violations(&config);
+        assert!(message.contains("circuit.k: "), "{}", message);
+        assert!(message.contains("k=8 only provides 256"), "{}", message);
+
+        // A small synthetic dataset needs fewer rows
+        config.dataset.path = "synthetic:10,5".parse().unwrap();
+        config.circuit.k = 10;
+        config.validate().unwrap();
+    }
+}
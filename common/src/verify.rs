@@ -0,0 +1,880 @@
+use crate::{
+    deserialize_accumulator, f64_to_field, CircuitBuilder, CircuitConfig, ConstraintCounts,
+    LinearModel, LogisticModel, ModelType, ProofAccumulator, ProofData, ProofFormatRange,
+    ProofPayload, ProofSizeBounds, QuantizationScheme, RegressionCircuit, RegressionModel, Result,
+    TrainingCommitment, ZkpFlError, ZkpProof, DEFAULT_PROOF_SIZE_TOLERANCE,
+};
+use halo2_proofs::{
+    poly::commitment::ParamsProver,
+    poly::ipa::commitment::ParamsIPA,
+    protostar,
+};
+use halo2curves::pasta::pallas;
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+type ProvingKey = protostar::ProvingKey<pallas::Affine>;
+
+/// What a proving key depends on beyond the verifier's `CircuitConfig`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyShape {
+    k: u32,
+    model_type: ModelType,
+    num_samples: usize,
+}
+
+/// Outcome of one verification check; a proof failing it is not an error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationResult {
+    pub is_valid: bool,
+    pub error_message: String,
+}
+
+/// Checks of proofs against the circuit a `CircuitConfig` describes, from
+/// structure and commitments to the Protostar decider. Shared by the server
+/// and clients checking proofs offline, so both make the prover's parameter
+/// assumptions in one place. Clones share the parameters and key cache.
+#[derive(Clone)]
+pub struct ZkpVerifier {
+    params: Arc<ParamsIPA<pallas::Affine>>,
+    circuit_config: CircuitConfig,
+    /// Share by which the expected proof size range is widened
+    proof_size_tolerance: f64,
+    /// Keys generated so far, at most one per model type and sample count
+    proving_keys: Arc<Mutex<HashMap<KeyShape, Arc<ProvingKey>>>>,
+}
+
+impl ZkpVerifier {
+    pub fn new(circuit_config: &CircuitConfig) -> Self {
+        // Generate the same SRS parameters as the prover
+        Self {
+            params: Arc::new(ParamsIPA::<pallas::Affine>::new(circuit_config.k)),
+            circuit_config: circuit_config.clone(),
+            proof_size_tolerance: DEFAULT_PROOF_SIZE_TOLERANCE,
+            proving_keys: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Accept proofs up to `tolerance` outside the size range their circuit
+    /// produces
+    pub fn with_proof_size_tolerance(mut self, tolerance: f64) -> Self {
+        self.proof_size_tolerance = tolerance;
+        self
+    }
+
+    pub fn circuit_config(&self) -> &CircuitConfig {
+        &self.circuit_config
+    }
+
+    /// Run every check on `proof`, cheapest first, stopping at the first
+    /// that fails
+    pub fn verify(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        let checks: [fn(&Self, &ZkpProof) -> Result<ValidationResult>; 5] = [
+            Self::validate_proof_structure,
+            |verifier, proof| verifier.verify_training_commitment(&proof.training_commitment),
+            Self::verify_weights_commitment,
+            Self::verify_quantization,
+            Self::verify_loss_comparison,
+        ];
+        for check in checks {
+            let result = check(self, proof)?;
+            if !result.is_valid {
+                return Ok(result);
+            }
+        }
+        self.verify_zkp(proof)
+    }
+
+    /// Check the proof is encoded in a supported format, for this circuit and
+    /// sized like its proofs
+    pub fn validate_proof_structure(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        debug!("Validating proof structure");
+
+        // Older or newer encodings would be misread below, so reject them first
+        if let Err(e) = ProofFormatRange::supported().check(proof.format_version) {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: e.to_string(),
+            });
+        }
+
+        // Check circuit parameters
+        if proof.proof_data.circuit_params.k != self.circuit_config.k {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Circuit parameter k mismatch: expected {}, got {}",
+                    self.circuit_config.k, proof.proof_data.circuit_params.k
+                ),
+            });
+        }
+
+        // Check proof size is reasonable
+        if proof.proof_data.proof_bytes.is_empty() {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Proof data is empty".to_string(),
+            });
+        }
+
+        // Sized for the circuit it claims, so placeholders are caught early
+        let bounds = ProofSizeBounds::for_circuit(
+            &self.circuit_config,
+            proof.proof_data.circuit_params.model_type,
+            self.proof_size_tolerance,
+        )?;
+        if let Err(e) = bounds.check(proof.proof_data.proof_bytes.len()) {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: e.to_string(),
+            });
+        }
+
+        // Check training commitment has required fields
+        if proof.training_commitment.num_features != self.circuit_config.num_features {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Feature count mismatch: expected {}, got {}",
+                    self.circuit_config.num_features, proof.training_commitment.num_features
+                ),
+            });
+        }
+
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+
+    /// Check the training parameters are plausible and the hashes well formed
+    pub fn verify_training_commitment(
+        &self,
+        commitment: &TrainingCommitment,
+    ) -> Result<ValidationResult> {
+        debug!("Verifying training commitment");
+
+        // Check reasonable bounds on training parameters
+        if commitment.learning_rate <= 0.0 || commitment.learning_rate > 1.0 {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!("Invalid learning rate: {}", commitment.learning_rate),
+            });
+        }
+
+        if commitment.epochs == 0 || commitment.epochs > 10000 {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!("Invalid epoch count: {}", commitment.epochs),
+            });
+        }
+
+        if commitment.num_samples == 0 || commitment.num_samples > 1_000_000 {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!("Invalid sample count: {}", commitment.num_samples),
+            });
+        }        // Check hash formats
+        if commitment.dataset_hash.len() != 128 { // Blake2b hash is 64 bytes = 128 hex chars
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Invalid dataset hash format".to_string(),
+            });
+        }
+
+        if commitment.weights_commitment.len() != commitment.weights_commitment_scheme.hex_len() {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Invalid weights commitment format".to_string(),
+            });
+        }
+
+        let scheme = commitment.weights_commitment_scheme;
+        if commitment.bias_commitment.as_ref().is_some_and(|c| c.len() != scheme.hex_len()) {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Invalid bias commitment format".to_string(),
+            });
+        }
+
+        if let Some(scaler) = &commitment.scaler {
+            let usable = scaler.num_features() == commitment.num_features
+                && scaler.scales.len() == commitment.num_features
+                && scaler.offsets.iter().all(|offset| offset.is_finite())
+                && scaler.scales.iter().all(|scale| scale.is_finite() && *scale > 0.0);
+            if !usable {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: "Invalid feature scaling parameters".to_string(),
+                });
+            }
+        }
+
+        debug!("Training commitment verification passed");
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+
+    /// Check submitted weights and bias are the ones committed to
+    pub fn verify_weights_commitment(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        let Some(weights) = &proof.weights else {
+            // Nothing submitted for aggregation, nothing to check
+            return Ok(ValidationResult {
+                is_valid: true,
+                error_message: String::new(),
+            });
+        };
+
+        if weights.len() != proof.training_commitment.num_features {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Submitted weights length mismatch: expected {}, got {}",
+                    proof.training_commitment.num_features,
+                    weights.len()
+                ),
+            });
+        }
+
+        let recomputed = TrainingCommitment::commit_weights_with(
+            proof.training_commitment.weights_commitment_scheme,
+            weights,
+            self.circuit_config.precision_bits as u32,
+        )?;
+        if recomputed != proof.training_commitment.weights_commitment {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Submitted weights do not match weights commitment".to_string(),
+            });
+        }
+
+        // Proofs predating the bias commitment leave their bias unbound
+        if let Some(bias_commitment) = &proof.training_commitment.bias_commitment {
+            let matches = match proof.bias {
+                Some(bias) => {
+                    TrainingCommitment::commit_bias_with(
+                        proof.training_commitment.weights_commitment_scheme,
+                        bias,
+                        self.circuit_config.precision_bits as u32,
+                    )? == *bias_commitment
+                }
+                None => false,
+            };
+            if !matches {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: "Submitted bias does not match bias commitment".to_string(),
+                });
+            }
+        }
+
+        debug!("Weights commitment verification passed");
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+
+    /// Check a quantized proof uses this circuit's scheme and a loss on its grid
+    pub fn verify_quantization(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        let expected = self.circuit_config.quantization()?;
+        let committed = proof.training_commitment.quantization;
+        if committed != expected {
+            let describe = |scheme: Option<QuantizationScheme>| match scheme {
+                Some(scheme) => format!("{}-bit quantized", scheme.precision_bits),
+                None => "unquantized".to_string(),
+            };
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Quantization mismatch: expected {} proof, got {}",
+                    describe(expected),
+                    describe(committed)
+                ),
+            });
+        }
+
+        let Some(scheme) = committed else {
+            return Ok(ValidationResult {
+                is_valid: true,
+                error_message: String::new(),
+            });
+        };
+
+        // The circuit embeds the loss as an integer, so the public value must
+        // sit exactly on the grid and match the committed loss
+        let public_loss = proof
+            .proof_data
+            .public_inputs
+            .first()
+            .and_then(|input| input.parse::<f64>().ok());
+        let valid = match public_loss {
+            Some(loss) => {
+                scheme.snap(loss).ok() == Some(loss) && loss == proof.training_commitment.final_loss
+            }
+            None => false,
+        };
+        if !valid {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Public loss is not the committed {}-bit quantized loss",
+                    scheme.precision_bits
+                ),
+            });
+        }
+
+        debug!("Quantization verification passed");
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+
+    /// Check the reported loss comparison against the public loss and the
+    /// allowed gap
+    pub fn verify_loss_comparison(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        // Older clients do not report a comparison
+        let Some(comparison) = proof.proof_data.metadata.loss_comparison else {
+            return Ok(ValidationResult {
+                is_valid: true,
+                error_message: String::new(),
+            });
+        };
+
+        let public_loss = proof
+            .proof_data
+            .public_inputs
+            .first()
+            .and_then(|input| input.parse::<f64>().ok());
+        let Some(public_loss) = public_loss else {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Public loss is missing or not a number".to_string(),
+            });
+        };
+
+        // The public input is the loss the circuit computed
+        if comparison.proven_loss != public_loss {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Reported proven loss {} does not match the public input ({})",
+                    comparison.proven_loss, public_loss
+                ),
+            });
+        }
+
+        let gap = (comparison.proven_loss - comparison.training_loss).abs();
+        if let Some(max_gap) = self.circuit_config.max_loss_gap {
+            if !gap.is_finite() || gap > max_gap {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: format!(
+                        "Proven loss differs from the training loss by {:e}, more than the allowed {:e}",
+                        gap, max_gap
+                    ),
+                });
+            }
+        }
+
+        debug!("Loss comparison passed (gap {:e})", comparison.gap);
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+
+    /// Check that the proof's accumulator decides for the circuit it claims,
+    /// built over the loss it claims
+    pub fn verify_zkp(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        debug!("Verifying ZKP using Protostar");
+        let proof_data = &proof.proof_data;
+
+        // Cheap structural checks first, so junk never reaches keygen
+        let structure_result = self.check_proof_data(proof_data)?;
+        if !structure_result.is_valid {
+            return Ok(structure_result);
+        }
+
+        let verification_start = Instant::now();
+
+        // Malformed bytes are a client error, not a failed verification
+        let payload = ProofPayload::from_bytes(&proof_data.proof_bytes)?;
+        if payload.circuit_k != proof_data.circuit_params.k {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Encoded proof k={} does not match declared k={}",
+                    payload.circuit_k, proof_data.circuit_params.k
+                ),
+            });
+        }
+
+        // Past the envelope, an accumulator that does not decode was tampered with
+        let accumulator = match deserialize_accumulator(&payload) {
+            Ok(accumulator) => accumulator,
+            Err(e) => {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: format!("Proof does not contain a valid accumulator: {}", e),
+                });
+            }
+        };
+
+        // The accumulator must have been built over the loss the proof claims
+        let expected_instance = match self.public_loss_instance(proof_data) {
+            Ok(instance) => instance,
+            Err(e) => {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: format!("Public loss cannot be embedded: {}", e),
+                });
+            }
+        };
+        if accumulator.instances != vec![vec![expected_instance]] {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Accumulator was not built over the public loss".to_string(),
+            });
+        }
+
+        // Rebuild the circuit the proof claims to satisfy and run the decider on it
+        let quantization = self.circuit_config.quantization()?;
+        let builder = CircuitBuilder::new(
+            self.circuit_config.num_features,
+            self.circuit_config.max_samples,
+        )
+        .with_precision_bits(self.circuit_config.precision_bits as u32)
+        .with_quantization(quantization)
+        .with_k(proof_data.circuit_params.k);
+        let shape = KeyShape {
+            k: proof_data.circuit_params.k,
+            model_type: proof_data.circuit_params.model_type,
+            num_samples: proof.training_commitment.num_samples,
+        };
+        let result = match shape.model_type {
+            ModelType::Linear => self.decide::<LinearModel>(&builder, shape, &accumulator),
+            ModelType::Logistic => self.decide::<LogisticModel>(&builder, shape, &accumulator),
+        };
+        debug!(
+            "ZKP verification completed in {}ms",
+            verification_start.elapsed().as_millis()
+        );
+        result
+    }
+
+    /// Run the decider on `accumulator` against the circuit for model `M`
+    fn decide<M: RegressionModel>(
+        &self,
+        builder: &CircuitBuilder,
+        shape: KeyShape,
+        accumulator: &ProofAccumulator,
+    ) -> Result<ValidationResult> {
+        let circuit = match builder.build_verifier_circuit::<M>(shape.num_samples) {
+            Ok(circuit) => circuit,
+            Err(e) => {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: format!("Cannot rebuild the proven circuit: {}", e),
+                });
+            }
+        };
+        let proving_key = self.proving_key(shape, &circuit)?;
+
+        if !accumulator.decide(&self.params, &proving_key) {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Accumulator failed the Protostar decider".to_string(),
+            });
+        }
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+
+    /// Key for circuits of `shape`, generated on first use. Concurrent misses
+    /// may both generate it; either copy is the same key.
+    fn proving_key<M: RegressionModel>(
+        &self,
+        shape: KeyShape,
+        circuit: &RegressionCircuit<pallas::Scalar, M>,
+    ) -> Result<Arc<ProvingKey>> {
+        if let Some(key) = self.lock_proving_keys().get(&shape) {
+            return Ok(key.clone());
+        }
+
+        debug!("Generating the proving key for {:?}", shape);
+        let key = protostar::ProvingKey::new(self.params.as_ref(), circuit).map_err(|e| {
+            ZkpFlError::ProofVerification(format!("Key generation failed: {:?}", e))
+        })?;
+        let key = Arc::new(key);
+        self.lock_proving_keys().insert(shape, key.clone());
+        Ok(key)
+    }
+
+    fn lock_proving_keys(&self) -> std::sync::MutexGuard<'_, HashMap<KeyShape, Arc<ProvingKey>>> {
+        // The map is only read and inserted into, so a panicked holder left it whole
+        self.proving_keys.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Proving keys generated so far
+    pub fn cached_proving_keys(&self) -> usize {
+        self.lock_proving_keys().len()
+    }
+
+    /// Generate the key for full-size proofs of the configured model, so
+    /// keygen failures surface before the first proof and the most common
+    /// shape is already cached when it arrives
+    pub fn warm_up(&self) -> Result<()> {
+        let config = &self.circuit_config;
+        let builder = CircuitBuilder::new(config.num_features, config.max_samples)
+            .with_precision_bits(config.precision_bits as u32)
+            .with_quantization(config.quantization()?)
+            .with_k(config.k);
+        let shape = KeyShape {
+            k: config.k,
+            model_type: config.model_type,
+            num_samples: config.max_samples,
+        };
+        match shape.model_type {
+            ModelType::Linear => {
+                let circuit = builder.build_verifier_circuit::<LinearModel>(shape.num_samples)?;
+                self.proving_key(shape, &circuit)?;
+            }
+            ModelType::Logistic => {
+                let circuit = builder.build_verifier_circuit::<LogisticModel>(shape.num_samples)?;
+                self.proving_key(shape, &circuit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Field element the circuit's public instance holds for the claimed loss
+    fn public_loss_instance(&self, proof_data: &ProofData) -> Result<pallas::Scalar> {
+        let loss: f64 = proof_data
+            .public_inputs
+            .first()
+            .and_then(|input| input.parse().ok())
+            .ok_or_else(|| {
+                ZkpFlError::ProofVerification("Public loss is not a number".to_string())
+            })?;
+        match self.circuit_config.quantization()? {
+            Some(scheme) => scheme.to_field(loss),
+            None => f64_to_field(loss, self.circuit_config.precision_bits as u32),
+        }
+    }
+
+    /// Structural checks on the proof data that are cheap enough to run
+    /// before any cryptography
+    pub fn check_proof_data(&self, proof_data: &ProofData) -> Result<ValidationResult> {
+        // The reported layout must be the one the circuit for this model builds
+        let params = &proof_data.circuit_params;
+        let expected = ConstraintCounts::for_model(params.model_type);
+        let reported = ConstraintCounts {
+            num_constraints: params.num_constraints,
+            num_advice_columns: params.num_advice_columns,
+            num_fixed_columns: params.num_fixed_columns,
+        };
+        if reported != expected {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: format!(
+                    "Circuit mismatch: proof reports {} constraints, {} advice and {} fixed \
+                     columns, the {:?} circuit has {}, {} and {}",
+                    reported.num_constraints,
+                    reported.num_advice_columns,
+                    reported.num_fixed_columns,
+                    params.model_type,
+                    expected.num_constraints,
+                    expected.num_advice_columns,
+                    expected.num_fixed_columns
+                ),
+            });
+        }
+
+        // Check public inputs format
+        if proof_data.public_inputs.is_empty() {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "No public inputs provided".to_string(),
+            });
+        }
+
+        // Parse and validate public inputs (loss value)
+        if let Ok(loss) = proof_data.public_inputs[0].parse::<f64>() {
+            // Losses are squared errors, so unbounded above for unnormalized targets
+            if !loss.is_finite() || loss < 0.0 {
+                return Ok(ValidationResult {
+                    is_valid: false,
+                    error_message: format!("Invalid loss value: {}", loss),
+                });
+            }
+        } else {
+            return Ok(ValidationResult {
+                is_valid: false,
+                error_message: "Invalid public input format".to_string(),
+            });
+        }
+
+        Ok(ValidationResult {
+            is_valid: true,
+            error_message: String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_circuit_config;
+    use crate::{
+        create_accumulator, field_to_f64, CircuitParams, LossComparison,
+        Optimizer, ProofMetadata, Sample, TrainingParams,
+    };
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    /// Proof of `samples` trained to `params`, assembled the way the client
+    /// prover does
+    fn prove(
+        circuit_config: &CircuitConfig,
+        samples: Vec<Sample>,
+        params: &TrainingParams,
+    ) -> ZkpProof {
+        let precision_bits = circuit_config.precision_bits as u32;
+        let num_samples = samples.len();
+        let dataset_hash = TrainingCommitment::hash_samples(&samples).unwrap();
+        let circuit = CircuitBuilder::new(circuit_config.num_features, circuit_config.max_samples)
+            .with_precision_bits(precision_bits)
+            .with_k(circuit_config.k)
+            .build_circuit(samples, params)
+            .unwrap();
+        let proven_loss = field_to_f64(circuit.expected_loss, precision_bits).unwrap();
+
+        let params_ipa = ParamsIPA::<pallas::Affine>::new(circuit_config.k);
+        let proving_key = protostar::ProvingKey::new(&params_ipa, &circuit).unwrap();
+        let (accumulator, transcript) = create_accumulator(
+            &params_ipa,
+            &proving_key,
+            &circuit,
+            circuit.expected_loss,
+            rand::thread_rng(),
+        )
+        .unwrap();
+        let proof_bytes =
+            ProofPayload::for_accumulator(circuit_config, &accumulator, transcript, Utc::now())
+                .to_bytes()
+                .unwrap();
+
+        let circuit_params = CircuitParams::for_model(
+            circuit_config.k,
+            ModelType::Linear,
+            proving_key.max_folding_constraints_degree(),
+        );
+        let metadata = ProofMetadata {
+            generation_time_ms: 0,
+            proof_size_bytes: proof_bytes.len(),
+            witness_generation_time_ms: 0,
+            setup_time_ms: 0,
+            folding_time_ms: 0,
+            folding_iterations: 1,
+            quantization_error: None,
+            loss_comparison: Some(LossComparison::new(params.loss, proven_loss)),
+        };
+        let scheme = circuit_config.weights_commitment;
+        let training_commitment = TrainingCommitment {
+            dataset_hash,
+            num_samples,
+            num_features: circuit_config.num_features,
+            learning_rate: params.learning_rate,
+            epochs: params.epoch,
+            weights_commitment: TrainingCommitment::commit_weights_with(
+                scheme,
+                &params.weights,
+                precision_bits,
+            )
+            .unwrap(),
+            final_loss: proven_loss,
+            quantization: None,
+            scaler: None,
+            optimizer: params.optimizer,
+            weights_commitment_scheme: scheme,
+            differential_privacy: None,
+            bias_commitment: Some(
+                TrainingCommitment::commit_bias_with(scheme, params.bias, precision_bits).unwrap(),
+            ),
+        };
+
+        let mut proof = ZkpProof::new(
+            Uuid::new_v4(),
+            "client".to_string(),
+            proof_bytes,
+            vec![proven_loss.to_string()],
+            circuit_params,
+            metadata,
+            training_commitment,
+        );
+        proof.weights = Some(params.weights.clone());
+        proof.bias = Some(params.bias);
+        proof
+    }
+
+    #[test]
+    fn test_prover_generated_proof_verifies() {
+        let circuit_config = CircuitConfig { num_features: 2, ..test_circuit_config() };
+        let samples = vec![
+            Sample {
+                features: vec![1.0, 2.0],
+                target: 3.5,
+            },
+            Sample {
+                features: vec![2.0, 3.0],
+                target: 5.0,
+            },
+        ];
+        let training_params = TrainingParams {
+            weights: vec![1.0, 1.0],
+            bias: 0.25,
+            loss: 0.0625,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let verifier = ZkpVerifier::new(&circuit_config);
+        let proof = prove(&circuit_config, samples, &training_params);
+
+        let result = verifier.verify(&proof).unwrap();
+        assert!(result.is_valid, "{}", result.error_message);
+
+        // Each layer of the shared check still catches what it covers
+        let mut tampered = proof.clone();
+        tampered.bias = Some(0.5);
+        let result = verifier.verify(&tampered).unwrap();
+        assert_eq!(result.error_message, "Submitted bias does not match bias commitment");
+
+        let mut tampered = proof.clone();
+        tampered.training_commitment.learning_rate = 0.0;
+        assert!(!verifier.verify(&tampered).unwrap().is_valid);
+
+        let mut tampered = proof;
+        tampered.proof_data.public_inputs = vec!["0.5".to_string()];
+        tampered.training_commitment.final_loss = 0.5;
+        tampered.proof_data.metadata.loss_comparison = None;
+        let result = verifier.verify(&tampered).unwrap();
+        assert_eq!(result.error_message, "Accumulator was not built over the public loss");
+    }
+
+    #[test]
+    fn test_proving_key_is_generated_once_per_shape() {
+        let circuit_config = CircuitConfig { num_features: 1, ..test_circuit_config() };
+        let training_params = TrainingParams {
+            weights: vec![2.0],
+            bias: 0.0,
+            loss: 0.0,
+            epoch: 5,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = |count: usize| -> Vec<Sample> {
+            (1..=count)
+                .map(|i| Sample {
+                    features: vec![i as f64],
+                    target: 2.0 * i as f64,
+                })
+                .collect()
+        };
+        let verifier = ZkpVerifier::new(&circuit_config);
+        assert_eq!(verifier.cached_proving_keys(), 0);
+
+        let proof = prove(&circuit_config, samples(2), &training_params);
+        assert!(verifier.verify_zkp(&proof).unwrap().is_valid);
+        assert_eq!(verifier.cached_proving_keys(), 1);
+
+        // Same shape, from a clone: the key is reused, not regenerated
+        let again = prove(&circuit_config, samples(2), &training_params);
+        assert!(verifier.clone().verify_zkp(&again).unwrap().is_valid);
+        assert_eq!(verifier.cached_proving_keys(), 1);
+
+        // A different sample count is a different circuit
+        let larger = prove(&circuit_config, samples(3), &training_params);
+        assert!(verifier.verify_zkp(&larger).unwrap().is_valid);
+        assert_eq!(verifier.cached_proving_keys(), 2);
+    }
+
+    #[test]
+    fn test_warm_up_caches_the_full_size_key() {
+        let circuit_config = CircuitConfig {
+            num_features: 1,
+            max_samples: 3,
+            ..test_circuit_config()
+        };
+        let verifier = ZkpVerifier::new(&circuit_config);
+        verifier.warm_up().unwrap();
+        assert_eq!(verifier.cached_proving_keys(), 1);
+
+        // A full-size proof uses the key warm-up generated
+        let training_params = TrainingParams {
+            weights: vec![2.0],
+            bias: 0.0,
+            loss: 0.0,
+            epoch: 5,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = (1..=3)
+            .map(|i| Sample {
+                features: vec![i as f64],
+                target: 2.0 * i as f64,
+            })
+            .collect();
+        let proof = prove(&circuit_config, samples, &training_params);
+        assert!(verifier.verify_zkp(&proof).unwrap().is_valid);
+        assert_eq!(verifier.cached_proving_keys(), 1);
+
+        // Circuits too large for k fail warm-up instead of the first proof
+        let too_large = ZkpVerifier::new(&CircuitConfig {
+            max_samples: 10_000,
+            ..circuit_config
+        });
+        assert!(too_large.warm_up().is_err());
+    }
+
+    #[test]
+    fn test_large_losses_pass_the_structure_check() {
+        let circuit_config = CircuitConfig { num_features: 1, ..test_circuit_config() };
+        let training_params = TrainingParams {
+            weights: vec![1.0],
+            bias: 0.0,
+            loss: 0.0,
+            epoch: 1,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+        let samples = vec![Sample {
+            features: vec![1.0],
+            target: 1.0,
+        }];
+        let verifier = ZkpVerifier::new(&circuit_config);
+        let mut proof_data = prove(&circuit_config, samples, &training_params).proof_data;
+
+        proof_data.public_inputs = vec!["25000.5".to_string()];
+        assert!(verifier.check_proof_data(&proof_data).unwrap().is_valid);
+
+        for loss in ["-1", "NaN", "inf"] {
+            proof_data.public_inputs = vec![loss.to_string()];
+            let result = verifier.check_proof_data(&proof_data).unwrap();
+            assert!(!result.is_valid, "{}", loss);
+            assert!(result.error_message.contains("Invalid loss value"), "{}", loss);
+        }
+    }
+}
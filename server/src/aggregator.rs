@@ -0,0 +1,500 @@
+use common::{
+    AggregationRule, AggregationWeighting, GlobalModel, Result, RoundRecord, ZkpFlError, ZkpProof,
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+
+struct RoundState {
+    /// Proofs verified before this instant belong to an earlier round
+    round_started: DateTime<Utc>,
+    global_model: GlobalModel,
+}
+
+/// Federated averaging over the weights clients submit with verified proofs.
+/// Each round averages the latest contribution of every client verified since
+/// the previous round, weighted by its number of training samples unless
+/// uniform weighting is configured. A robust rule and an outlier filter can
+/// keep a few poisoned models from dragging the average.
+pub struct FedAvgAggregator {
+    min_clients: usize,
+    weighting: AggregationWeighting,
+    rule: AggregationRule,
+    /// Share of values trimmed from each end under `TrimmedMean`
+    trim_fraction: f64,
+    /// Standard deviations from the median beyond which a client is left out
+    outlier_threshold: Option<f64>,
+    state: RwLock<RoundState>,
+}
+
+impl FedAvgAggregator {
+    pub fn new(min_clients: usize, num_features: usize) -> Self {
+        Self {
+            min_clients: min_clients.max(1),
+            weighting: AggregationWeighting::Samples,
+            rule: AggregationRule::Mean,
+            trim_fraction: 0.0,
+            outlier_threshold: None,
+            state: RwLock::new(RoundState {
+                round_started: DateTime::<Utc>::MIN_UTC,
+                global_model: GlobalModel::initial(num_features),
+            }),
+        }
+    }
+
+    /// Weight clients per `weighting` instead of by their sample counts
+    pub fn with_weighting(mut self, weighting: AggregationWeighting) -> Self {
+        self.weighting = weighting;
+        self
+    }
+
+    /// Combine models with `rule`, trimming `trim_fraction` of the values
+    /// from each end when it is `TrimmedMean`
+    pub fn with_rule(mut self, rule: AggregationRule, trim_fraction: f64) -> Self {
+        self.rule = rule;
+        self.trim_fraction = trim_fraction;
+        self
+    }
+
+    /// Leave out clients more than `threshold` standard deviations from the
+    /// median model
+    pub fn with_outlier_threshold(mut self, threshold: Option<f64>) -> Self {
+        self.outlier_threshold = threshold;
+        self
+    }
+
+    /// Model produced by the last completed round, or the zero model before
+    /// the first one
+    pub fn global_model(&self) -> GlobalModel {
+        self.state.read().global_model.clone()
+    }
+
+    /// Continue after `last_round`, e.g. one loaded from storage on restart,
+    /// so round numbers keep increasing and its proofs are not averaged again
+    pub fn resume(&self, last_round: &RoundRecord) {
+        let mut state = self.state.write();
+        state.round_started = last_round.aggregated_at;
+        state.global_model = last_round.model.clone();
+    }
+
+    /// Average the current round from `proofs` and start the next one,
+    /// returning the new model and its history entry. Unverified proofs and
+    /// proofs without weights are ignored.
+    pub fn aggregate(&self, proofs: &[ZkpProof]) -> Result<(GlobalModel, RoundRecord)> {
+        let mut state = self.state.write();
+
+        // Latest verified contribution per client in this round
+        let mut contributions: HashMap<&str, (&ZkpProof, DateTime<Utc>)> = HashMap::new();
+        for proof in proofs {
+            let Some(result) = proof.verification_result.as_ref().filter(|r| r.verified) else {
+                continue;
+            };
+            if proof.weights.is_none() || result.verification_timestamp < state.round_started {
+                continue;
+            }
+            let verified_at = result.verification_timestamp;
+            contributions
+                .entry(proof.client_id.as_str())
+                .and_modify(|entry| {
+                    if verified_at > entry.1 {
+                        *entry = (proof, verified_at);
+                    }
+                })
+                .or_insert((proof, verified_at));
+        }
+
+        let mut contributions: Vec<&ZkpProof> =
+            contributions.into_values().map(|(proof, _)| proof).collect();
+        contributions.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+
+        let vectors = model_vectors(&contributions)?;
+        let outliers = match self.outlier_threshold {
+            Some(threshold) if !vectors.is_empty() => outlier_indices(&vectors, threshold),
+            _ => Vec::new(),
+        };
+        let excluded_client_ids: Vec<String> = outliers
+            .iter()
+            .map(|&index| contributions[index].client_id.clone())
+            .collect();
+        let (contributions, vectors): (Vec<&ZkpProof>, Vec<Vec<f64>>) = contributions
+            .into_iter()
+            .zip(vectors)
+            .enumerate()
+            .filter(|(index, _)| !outliers.contains(index))
+            .map(|(_, contribution)| contribution)
+            .unzip();
+        if !excluded_client_ids.is_empty() {
+            warn!(
+                "Excluding outlier clients from round {}: {}",
+                state.global_model.round + 1,
+                excluded_client_ids.join(", ")
+            );
+        }
+
+        if contributions.len() < self.min_clients {
+            return Err(ZkpFlError::Aggregation(format!(
+                "Round {} has {} verified clients{}, at least {} required",
+                state.global_model.round + 1,
+                contributions.len(),
+                if excluded_client_ids.is_empty() {
+                    String::new()
+                } else {
+                    format!(" after excluding {} outliers", excluded_client_ids.len())
+                },
+                self.min_clients
+            )));
+        }
+
+        let shares = contribution_shares(&contributions, self.weighting)?;
+        let mut combined = match self.rule {
+            AggregationRule::Mean => weighted_mean(&vectors, &shares),
+            AggregationRule::Median => coordinate_median(&vectors),
+            AggregationRule::TrimmedMean => trimmed_mean(&vectors, self.trim_fraction),
+        };
+        let bias = combined.pop().unwrap_or(0.0);
+        let weights = combined;
+        let total_samples: usize = contributions
+            .iter()
+            .map(|proof| proof.training_commitment.num_samples)
+            .sum();
+        let global_loss = contributions
+            .iter()
+            .zip(&shares)
+            .map(|(proof, share)| share * proof.training_commitment.final_loss)
+            .sum();
+
+        let now = Utc::now();
+        state.round_started = now;
+        let model = GlobalModel {
+            round: state.global_model.round + 1,
+            weights,
+            bias,
+            num_clients: contributions.len(),
+            total_samples,
+            proof_ids: contributions.iter().map(|proof| proof.proof_id).collect(),
+            aggregated_at: Some(now),
+        };
+        state.global_model = model.clone();
+
+        let record = RoundRecord {
+            round: model.round,
+            client_ids: contributions.iter().map(|proof| proof.client_id.clone()).collect(),
+            num_proofs: contributions.len(),
+            global_loss,
+            aggregated_at: now,
+            model: model.clone(),
+            excluded_client_ids,
+        };
+
+        info!(
+            "Aggregated round {} from {} clients ({} samples, loss {:.6})",
+            model.round, model.num_clients, model.total_samples, global_loss
+        );
+        Ok((model, record))
+    }
+}
+
+/// Fraction of the average each proof gets, summing to 1: its committed
+/// sample count over the total, or an equal split under uniform weighting
+fn contribution_shares(proofs: &[&ZkpProof], weighting: AggregationWeighting) -> Result<Vec<f64>> {
+    let total_samples: usize = proofs
+        .iter()
+        .map(|proof| proof.training_commitment.num_samples)
+        .sum();
+    if total_samples == 0 {
+        return Err(ZkpFlError::Aggregation(
+            "Contributing clients trained on no samples".to_string(),
+        ));
+    }
+
+    Ok(proofs
+        .iter()
+        .map(|proof| match weighting {
+            AggregationWeighting::Samples => {
+                proof.training_commitment.num_samples as f64 / total_samples as f64
+            }
+            AggregationWeighting::Uniform => 1.0 / proofs.len() as f64,
+        })
+        .collect())
+}
+
+/// Each proof's weights with its bias appended, checking they all have the
+/// same length. A missing bias counts as zero.
+fn model_vectors(proofs: &[&ZkpProof]) -> Result<Vec<Vec<f64>>> {
+    let num_weights = proofs
+        .first()
+        .and_then(|proof| proof.weights.as_ref())
+        .map_or(0, Vec::len);
+
+    proofs
+        .iter()
+        .map(|proof| {
+            let weights = proof.weights.as_deref().unwrap_or_default();
+            if weights.len() != num_weights {
+                return Err(ZkpFlError::Aggregation(format!(
+                    "Client {} submitted {} weights, expected {}",
+                    proof.client_id,
+                    weights.len(),
+                    num_weights
+                )));
+            }
+            let mut vector = weights.to_vec();
+            vector.push(proof.bias.unwrap_or(0.0));
+            Ok(vector)
+        })
+        .collect()
+}
+
+/// Sum of the vectors, each scaled by its share
+fn weighted_mean(vectors: &[Vec<f64>], shares: &[f64]) -> Vec<f64> {
+    let mut mean = vec![0.0; vectors.first().map_or(0, Vec::len)];
+    for (vector, share) in vectors.iter().zip(shares) {
+        for (sum, value) in mean.iter_mut().zip(vector) {
+            *sum += share * value;
+        }
+    }
+    mean
+}
+
+/// Values of every coordinate across the vectors, sorted
+fn sorted_coordinates(vectors: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    (0..vectors.first().map_or(0, Vec::len))
+        .map(|coordinate| {
+            let mut values: Vec<f64> = vectors.iter().map(|vector| vector[coordinate]).collect();
+            values.sort_by(f64::total_cmp);
+            values
+        })
+        .collect()
+}
+
+fn coordinate_median(vectors: &[Vec<f64>]) -> Vec<f64> {
+    sorted_coordinates(vectors)
+        .into_iter()
+        .map(|values| sorted_median(&values))
+        .collect()
+}
+
+fn sorted_median(values: &[f64]) -> f64 {
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    sorted_median(&values)
+}
+
+/// Coordinate-wise mean once `trim_fraction` of the values, rounded down,
+/// is dropped from each end; at least one value is always kept
+fn trimmed_mean(vectors: &[Vec<f64>], trim_fraction: f64) -> Vec<f64> {
+    let most = vectors.len().saturating_sub(1) / 2;
+    let trim = ((vectors.len() as f64 * trim_fraction) as usize).min(most);
+    sorted_coordinates(vectors)
+        .into_iter()
+        .map(|values| {
+            let kept = &values[trim..values.len() - trim];
+            kept.iter().sum::<f64>() / kept.len() as f64
+        })
+        .collect()
+}
+
+/// Scales the median absolute deviation to a standard deviation for normally
+/// distributed values
+const MAD_TO_STD: f64 = 1.4826;
+
+/// Indices of the vectors whose distance from the coordinate-wise median is
+/// more than `threshold` standard deviations above the median distance. The
+/// deviation is estimated from the median absolute deviation of the
+/// distances, which a few extreme clients cannot inflate. When more than half
+/// the clients sit at exactly the median distance that deviation is 0, and
+/// any client further away stands out.
+fn outlier_indices(vectors: &[Vec<f64>], threshold: f64) -> Vec<usize> {
+    let center = coordinate_median(vectors);
+    let distances: Vec<f64> = vectors
+        .iter()
+        .map(|vector| {
+            vector
+                .iter()
+                .zip(&center)
+                .map(|(value, middle)| (value - middle).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        })
+        .collect();
+    let typical = median(distances.clone());
+    let spread: Vec<f64> = distances.iter().map(|d| (d - typical).abs()).collect();
+    let deviation = MAD_TO_STD * median(spread);
+
+    distances
+        .iter()
+        .enumerate()
+        .filter(|(_, distance)| **distance - typical > threshold * deviation)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::storage;
+    use common::{TrainingCommitment, VerificationResult};
+
+    pub(crate) fn client_proof(
+        client_id: &str,
+        weights: Vec<f64>,
+        num_samples: usize,
+        verified: bool,
+    ) -> ZkpProof {
+        let mut proof = storage::tests::create_test_proof();
+        proof.client_id = client_id.to_string();
+        proof.proof_data.proof_bytes = vec![];
+        proof.training_commitment.num_samples = num_samples;
+        proof.training_commitment.num_features = weights.len();
+        proof.training_commitment.weights_commitment =
+            TrainingCommitment::commit_weights(&weights).unwrap();
+        proof.weights = Some(weights);
+        proof.bias = Some(1.0);
+        proof.mark_verified(VerificationResult {
+            verified,
+            verification_time_ms: 1,
+            verifier_id: "test".to_string(),
+            verification_timestamp: Utc::now(),
+            error_message: None,
+        });
+        proof
+    }
+
+    #[test]
+    fn test_weighted_by_num_samples() {
+        let aggregator = FedAvgAggregator::new(2, 2);
+        let initial = aggregator.global_model();
+        assert_eq!(initial.round, 0);
+        assert_eq!(initial.weights, vec![0.0, 0.0]);
+        assert!(initial.aggregated_at.is_none());
+
+        let mut proofs = vec![
+            client_proof("a", vec![1.0, 0.0], 100, true),
+            client_proof("b", vec![4.0, 2.0], 300, true),
+            // Unverified proofs never contribute
+            client_proof("c", vec![100.0, 100.0], 1000, false),
+        ];
+        proofs[1].bias = Some(3.0);
+        proofs[1].training_commitment.final_loss = 0.3;
+
+        let (model, record) = aggregator.aggregate(&proofs).unwrap();
+        assert_eq!(model.round, 1);
+        assert_eq!(record.client_ids, vec!["a", "b"]);
+        assert_eq!(record.num_proofs, 2);
+        assert!((record.global_loss - 0.25).abs() < 1e-12);
+        assert_eq!(model.num_clients, 2);
+        assert_eq!(model.total_samples, 400);
+        assert!((model.weights[0] - 3.25).abs() < 1e-12);
+        assert!((model.weights[1] - 1.5).abs() < 1e-12);
+        assert!((model.bias - 2.5).abs() < 1e-12);
+        assert_eq!(aggregator.global_model().round, 1);
+        assert!(aggregator.global_model().aggregated_at.is_some());
+    }
+
+    #[test]
+    fn test_uniform_weighting_ignores_sample_counts() {
+        let proofs = [
+            client_proof("small", vec![0.0], 10, true),
+            client_proof("large", vec![10.0], 990, true),
+        ];
+
+        let (model, _) = FedAvgAggregator::new(2, 1).aggregate(&proofs).unwrap();
+        assert!((model.weights[0] - 9.9).abs() < 1e-9, "{:?}", model.weights);
+
+        let uniform = FedAvgAggregator::new(2, 1).with_weighting(AggregationWeighting::Uniform);
+        let (model, _) = uniform.aggregate(&proofs).unwrap();
+        assert!((model.weights[0] - 5.0).abs() < 1e-9, "{:?}", model.weights);
+        assert_eq!(model.total_samples, 1000);
+    }
+
+    #[test]
+    fn test_extreme_client_is_trimmed_and_flagged() {
+        let mut proofs: Vec<ZkpProof> = [1.0, 1.1, 0.9, 1.05]
+            .iter()
+            .enumerate()
+            .map(|(i, &weight)| client_proof(&format!("honest-{}", i), vec![weight], 100, true))
+            .collect();
+        proofs.push(client_proof("poisoned", vec![1000.0], 100, true));
+
+        // The mean is dragged far off, the trimmed mean drops the extreme value
+        let (model, _) = FedAvgAggregator::new(2, 1).aggregate(&proofs).unwrap();
+        assert!(model.weights[0] > 100.0);
+        let trimmed = FedAvgAggregator::new(2, 1).with_rule(AggregationRule::TrimmedMean, 0.2);
+        let (model, record) = trimmed.aggregate(&proofs).unwrap();
+        assert!((model.weights[0] - 1.05).abs() < 1e-9, "{:?}", model.weights);
+        assert!((model.bias - 1.0).abs() < 1e-9);
+        assert!(record.excluded_client_ids.is_empty());
+
+        let median = FedAvgAggregator::new(2, 1).with_rule(AggregationRule::Median, 0.0);
+        let (model, _) = median.aggregate(&proofs).unwrap();
+        assert_eq!(model.weights, vec![1.05]);
+
+        // The filter leaves the poisoned client out and reports it
+        let filtered = FedAvgAggregator::new(2, 1).with_outlier_threshold(Some(2.0));
+        let (model, record) = filtered.aggregate(&proofs).unwrap();
+        assert_eq!(record.excluded_client_ids, vec!["poisoned"]);
+        assert_eq!(model.num_clients, 4);
+        assert!(!model.proof_ids.contains(&proofs[4].proof_id));
+        assert!((model.weights[0] - 1.0125).abs() < 1e-9, "{:?}", model.weights);
+    }
+
+    #[test]
+    fn test_outlier_is_flagged_among_few_clients() {
+        let mut proofs: Vec<ZkpProof> = [1.0, 1.1, 0.9]
+            .iter()
+            .enumerate()
+            .map(|(i, &weight)| client_proof(&format!("honest-{}", i), vec![weight], 100, true))
+            .collect();
+        proofs.push(client_proof("poisoned", vec![50.0], 100, true));
+
+        // The shipped threshold catches one bad client out of four
+        let filtered = || FedAvgAggregator::new(2, 1).with_outlier_threshold(Some(3.0));
+        let (model, record) = filtered().aggregate(&proofs).unwrap();
+        assert_eq!(record.excluded_client_ids, vec!["poisoned"]);
+        assert!((model.weights[0] - 1.0).abs() < 1e-9, "{:?}", model.weights);
+
+        // Honest clients that agree exactly still only exclude the odd one out
+        proofs[1] = client_proof("honest-1", vec![1.0], 100, true);
+        proofs[2] = client_proof("honest-2", vec![1.0], 100, true);
+        let (_, record) = filtered().aggregate(&proofs).unwrap();
+        assert_eq!(record.excluded_client_ids, vec!["poisoned"]);
+
+        // Clients that all agree are never excluded
+        let agreeing: Vec<ZkpProof> = (0..3)
+            .map(|i| client_proof(&format!("c-{}", i), vec![1.0], 100, true))
+            .collect();
+        let (_, record) = filtered().aggregate(&agreeing).unwrap();
+        assert!(record.excluded_client_ids.is_empty());
+    }
+
+    #[test]
+    fn test_round_needs_min_clients() {
+        let aggregator = FedAvgAggregator::new(2, 1);
+        let first = client_proof("a", vec![1.0], 10, true);
+        let mut second = client_proof("a", vec![3.0], 10, true);
+        second.verification_result.as_mut().unwrap().verification_timestamp +=
+            chrono::Duration::seconds(1);
+
+        // Two proofs from the same client count once, using the latest
+        let err = aggregator.aggregate(&[first.clone(), second.clone()]).unwrap_err();
+        assert_eq!(err.status_code(), 409);
+        assert_eq!(aggregator.global_model().round, 0);
+
+        let other = client_proof("b", vec![5.0], 10, true);
+        let (model, _) = aggregator.aggregate(&[first, second, other.clone()]).unwrap();
+        assert_eq!(model.weights, vec![4.0]);
+
+        // Proofs already averaged do not carry over into the next round
+        assert!(aggregator.aggregate(&[other]).is_err());
+        assert_eq!(aggregator.global_model().round, 1);
+    }
+}
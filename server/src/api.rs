@@ -1,49 +1,112 @@
 use common::{
-    ZkpProof, VerificationRequest, VerificationResponse, VerificationResult
+    ZkpProof, VerificationRequest, VerificationResponse, VerificationResult, ServerCapabilities,
+    ProofFormatRange, ZkpFlError, SIGNATURE_HEADER, verify_body_signature
 };
-use crate::{verifier::ProofVerifier, storage::ProofStorage, metrics::ServerMetrics};
+use crate::{verifier::ProofVerifier, batcher::VerificationBatcher, storage::{ProofStore, ProofQuery}, metrics::{ServerMetrics, OPENMETRICS_CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE}};
+use crate::rate_limit::{RateLimiter, RateLimitStatus};
+use crate::aggregator::FedAvgAggregator;
+use crate::network::PeerAddr;
 use warp::{Filter, Reply, Rejection, reject};
+use warp::http::StatusCode;
+use warp::http::header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE};
+use warp::hyper::body::Bytes;
+use warp::ws::{Message, WebSocket, Ws};
+use futures::{SinkExt, StreamExt};
+use tokio::sync::broadcast;
+use flate2::read::GzDecoder;
+use serde::de::DeserializeOwned;
+use std::io::Read;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::convert::Infallible;
-use log::{info, debug, error};
+use log::{info, debug, error, warn};
 use serde_json;
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 pub fn create_api_routes(
     verifier: Arc<ProofVerifier>,
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
     metrics: Arc<ServerMetrics>,
-) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-    let health = health_route();
+    batcher: Option<Arc<VerificationBatcher>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    aggregator: Arc<FedAvgAggregator>,
+    signing_secret: Option<Arc<str>>,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let health = health_route(verifier.clone(), storage.clone());
+    let capabilities = capabilities_route(verifier.clone());
     let status = status_route(metrics.clone());
-    let verify = verify_route(verifier.clone(), storage.clone(), metrics.clone());
-    let verify_batch = verify_batch_route(verifier.clone(), storage.clone(), metrics.clone());
+    let metrics_export = metrics_route(metrics.clone());
+    let prometheus_export = prometheus_route(metrics.clone());
+    let metrics_stream = metrics_stream_route(metrics.clone());
+    let verify = verify_route(
+        verifier.clone(),
+        storage.clone(),
+        metrics.clone(),
+        batcher,
+        rate_limiter,
+        signing_secret.clone(),
+    );
+    let verify_batch =
+        verify_batch_route(verifier.clone(), storage.clone(), metrics.clone(), signing_secret);
     let proofs = proofs_route(storage.clone());
+    let reverify = reverify_route(verifier.clone(), storage.clone(), metrics.clone());
     let benchmarks = benchmarks_route(storage.clone());
-    let cleanup = cleanup_route(storage.clone());    let api = warp::path("api").and(
+    let benchmarks_zip = benchmarks_zip_route(storage.clone());
+    let cleanup = cleanup_route(storage.clone(), verifier.proof_ttl_hours());
+    let global_model = global_model_route(aggregator.clone());
+    let aggregate = aggregate_route(storage.clone(), aggregator);
+    let rounds = rounds_route(storage.clone());
+
+    let api = warp::path("api").and(
         health
+            .or(capabilities)
             .or(status)
+            .or(metrics_export)
+            .or(prometheus_export)
+            .or(metrics_stream)
             .or(verify)
             .or(verify_batch)
             .or(proofs)
+            .or(reverify)
+            .or(benchmarks_zip)
             .or(benchmarks)
             .or(cleanup)
+            .or(aggregate)
+            .or(rounds)
+            .or(global_model)
     );
 
-    api
+    api.recover(handle_rejection)
 }
 
-fn health_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// `?deep=true` probes storage and the verifier instead of only answering
+fn health_route(
+    verifier: Arc<ProofVerifier>,
+    storage: Arc<dyn ProofStore>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("health")
         .and(warp::get())
-        .map(|| {
-            debug!("Health check requested");
-            warp::reply::json(&serde_json::json!({
-                "status": "healthy",
-                "timestamp": Utc::now(),
-                "service": "zkp-fl-server"
-            }))
+        .and(warp::query::<HealthParams>())
+        .and(warp::any().map(move || verifier.clone()))
+        .and(with_storage(storage))
+        .and_then(handle_health)
+}
+
+fn capabilities_route(
+    verifier: Arc<ProofVerifier>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("capabilities")
+        .and(warp::get())
+        .map(move || {
+            debug!("Capabilities requested");
+            let circuit_config = verifier.circuit_config();
+            warp::reply::json(&ServerCapabilities {
+                server_version: env!("CARGO_PKG_VERSION").to_string(),
+                circuit_k: circuit_config.k,
+                num_features: circuit_config.num_features,
+                proof_format_versions: Some(ProofFormatRange::supported()),
+            })
         })
 }
 
@@ -54,37 +117,131 @@ fn status_route(
         .and(warp::get())
         .map(move || {
             debug!("Status requested");
-            let _snapshot = metrics.get_current_snapshot();            warp::reply::json(&serde_json::json!({
-                "active_clients": 0,
-                "total_proofs_verified": 0,
-                "uptime_seconds": 0,
-                "server_version": "1.0.0"
-            }))
+            let snapshot = metrics.get_current_snapshot();
+            warp::reply::json(&network::ServerStatus {
+                uptime_seconds: snapshot.uptime_seconds,
+                active_clients: snapshot.active_clients,
+                total_proofs_processed: snapshot.total_proofs_processed,
+                total_proofs_verified: snapshot.total_proofs_verified,
+                average_verification_time_ms: snapshot.average_verification_time_ms,
+                verification_success_rate: snapshot.verification_success_rate,
+                server_version: env!("CARGO_PKG_VERSION").to_string(),
+            })
+        })
+}
+
+fn metrics_route(
+    metrics: Arc<ServerMetrics>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("metrics")
+        .and(warp::path::end())
+        .and(warp::get())
+        .map(move || {
+            debug!("Metrics export requested");
+            warp::reply::with_header(
+                metrics.render_openmetrics(),
+                "content-type",
+                OPENMETRICS_CONTENT_TYPE,
+            )
+        })
+}
+
+fn prometheus_route(
+    metrics: Arc<ServerMetrics>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("metrics" / "prometheus")
+        .and(warp::get())
+        .map(move || {
+            debug!("Prometheus metrics scrape requested");
+            warp::reply::with_header(
+                metrics.render_prometheus(),
+                "content-type",
+                PROMETHEUS_CONTENT_TYPE,
+            )
         })
 }
 
+/// Push every snapshot the metrics collection task takes to the socket
+fn metrics_stream_route(
+    metrics: Arc<ServerMetrics>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("ws" / "metrics")
+        .and(warp::ws())
+        .map(move |ws: Ws| {
+            let snapshots = metrics.subscribe();
+            ws.on_upgrade(move |socket| stream_metrics(socket, snapshots))
+        })
+}
+
+async fn stream_metrics(
+    socket: WebSocket,
+    mut snapshots: broadcast::Receiver<crate::metrics::ServerMetricsSnapshot>,
+) {
+    debug!("Metrics stream client connected");
+    let (mut sender, mut receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            snapshot = snapshots.recv() => {
+                let snapshot = match snapshot {
+                    Ok(snapshot) => snapshot,
+                    // A slow client skips the snapshots it missed
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let json = match serde_json::to_string(&snapshot) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        error!("Failed to encode metrics snapshot: {}", e);
+                        continue;
+                    }
+                };
+                if sender.send(Message::text(json)).await.is_err() {
+                    break;
+                }
+            }
+            // Anything the client sends is ignored; the stream ends when it goes away
+            message = receiver.next() => match message {
+                Some(Ok(message)) if !message.is_close() => {}
+                _ => break,
+            },
+        }
+    }
+
+    debug!("Metrics stream client disconnected");
+}
+
 fn verify_route(
     verifier: Arc<ProofVerifier>,
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
     metrics: Arc<ServerMetrics>,
+    batcher: Option<Arc<VerificationBatcher>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    signing_secret: Option<Arc<str>>,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let signed = signing_secret.is_some();
     warp::path("verify")
         .and(warp::post())
-        .and(warp::body::json())
+        .and(json_body(signing_secret))
         .and(with_verifier(verifier))
         .and(with_storage(storage))
         .and(with_metrics(metrics))
+        .and(warp::any().map(move || batcher.clone()))
+        .and(warp::any().map(move || rate_limiter.clone()))
+        .and(warp::any().map(move || signed))
+        .and(peer_addr())
         .and_then(handle_verify_proof)
 }
 
 fn verify_batch_route(
     verifier: Arc<ProofVerifier>,
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
     metrics: Arc<ServerMetrics>,
+    signing_secret: Option<Arc<str>>,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("verify_batch")
         .and(warp::post())
-        .and(warp::body::json())
+        .and(json_body(signing_secret))
         .and(with_verifier(verifier))
         .and(with_storage(storage))
         .and(with_metrics(metrics))
@@ -92,10 +249,12 @@ fn verify_batch_route(
 }
 
 fn proofs_route(
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let get_all = warp::path("proofs")
+        .and(warp::path::end())
         .and(warp::get())
+        .and(warp::query::<ProofQueryParams>())
         .and(with_storage(storage.clone()))
         .and_then(handle_get_all_proofs);
 
@@ -115,8 +274,23 @@ fn proofs_route(
     get_all.or(get_by_id).or(get_by_client)
 }
 
+/// `POST /proofs/{id}/verify` checks a stored proof again, e.g. after a
+/// verifier upgrade, without the client resubmitting it
+fn reverify_route(
+    verifier: Arc<ProofVerifier>,
+    storage: Arc<dyn ProofStore>,
+    metrics: Arc<ServerMetrics>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("proofs" / String / "verify")
+        .and(warp::post())
+        .and(warp::any().map(move || verifier.clone()))
+        .and(with_storage(storage))
+        .and(warp::any().map(move || metrics.clone()))
+        .and_then(handle_reverify_proof)
+}
+
 fn benchmarks_route(
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("benchmarks")
         .and(warp::path("export"))
@@ -125,17 +299,148 @@ fn benchmarks_route(
         .and_then(handle_export_benchmarks)
 }
 
+/// Benchmark report, text summary and per-proof CSV in one ZIP download
+fn benchmarks_zip_route(
+    storage: Arc<dyn ProofStore>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("benchmarks")
+        .and(warp::path("export.zip"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(storage))
+        .map(|storage: Arc<dyn ProofStore>| {
+            info!("Exporting benchmark archive");
+            let body = warp::hyper::Body::wrap_stream(crate::export::stream_export(storage));
+            let mut response = warp::http::Response::new(body);
+            let headers = response.headers_mut();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+            headers.insert(
+                CONTENT_DISPOSITION,
+                HeaderValue::from_static("attachment; filename=\"benchmark_export.zip\""),
+            );
+            response
+        })
+}
+
 fn cleanup_route(
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
+    proof_ttl_hours: i64,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("admin")
         .and(warp::path("cleanup"))
         .and(warp::post())
         .and(warp::query::<CleanupParams>())
+        .and(warp::any().map(move || proof_ttl_hours))
         .and(with_storage(storage))
         .and_then(handle_cleanup)
 }
 
+fn aggregate_route(
+    storage: Arc<dyn ProofStore>,
+    aggregator: Arc<FedAvgAggregator>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("aggregate")
+        .and(warp::post())
+        .and(with_storage(storage))
+        .and(warp::any().map(move || aggregator.clone()))
+        .and_then(handle_aggregate)
+}
+
+fn rounds_route(
+    storage: Arc<dyn ProofStore>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let get_all = warp::path("rounds")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(storage.clone()))
+        .and_then(handle_get_rounds);
+
+    let get_by_number = warp::path!("rounds" / u64)
+        .and(warp::get())
+        .and(with_storage(storage))
+        .and_then(handle_get_round);
+
+    get_all.or(get_by_number)
+}
+
+fn global_model_route(
+    aggregator: Arc<FedAvgAggregator>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("model" / "global")
+        .and(warp::get())
+        .map(move || {
+            debug!("Global model requested");
+            warp::reply::json(&aggregator.global_model())
+        })
+}
+
+/// Largest request body accepted after decompression, so a small gzip
+/// body cannot expand without bound
+const MAX_DECODED_BODY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// JSON request body, gunzipped first when sent with `Content-Encoding: gzip`.
+/// With a signing secret the body must carry a matching signature, which
+/// covers the bytes as sent, before decompression.
+fn json_body<T>(
+    signing_secret: Option<Arc<str>>,
+) -> impl Filter<Extract = (T,), Error = Rejection> + Clone
+where
+    T: DeserializeOwned + Send,
+{
+    warp::header::optional::<String>("content-encoding")
+        .and(warp::header::optional::<String>(SIGNATURE_HEADER))
+        .and(warp::body::bytes())
+        .and_then(move |encoding: Option<String>, signature: Option<String>, body: Bytes| {
+            let signing_secret = signing_secret.clone();
+            async move {
+                if let Some(secret) = signing_secret {
+                    check_signature(&secret, signature.as_deref(), &body)
+                        .map_err(reject::custom)?;
+                }
+                decode_json_body(encoding.as_deref(), &body).map_err(reject::custom)
+            }
+        })
+}
+
+fn check_signature(secret: &str, signature: Option<&str>, body: &[u8]) -> Result<(), ApiError> {
+    let signature = signature.ok_or_else(|| {
+        ApiError::Unauthorized(format!("Missing {} header", SIGNATURE_HEADER))
+    })?;
+    if !verify_body_signature(secret, body, signature) {
+        warn!("Rejected request with an invalid signature");
+        return Err(ApiError::Unauthorized("Request signature does not match".to_string()));
+    }
+    Ok(())
+}
+
+fn decode_json_body<T: DeserializeOwned>(
+    encoding: Option<&str>,
+    body: &[u8],
+) -> Result<T, ApiError> {
+    let decoded;
+    let json = match encoding.map(str::trim) {
+        None | Some("identity") => body,
+        Some(encoding) if encoding.eq_ignore_ascii_case("gzip") => {
+            let mut buffer = Vec::new();
+            GzDecoder::new(body)
+                .take(MAX_DECODED_BODY_BYTES + 1)
+                .read_to_end(&mut buffer)
+                .map_err(|e| ApiError::InvalidBody(format!("Invalid gzip body: {}", e)))?;
+            if buffer.len() as u64 > MAX_DECODED_BODY_BYTES {
+                return Err(ApiError::InvalidBody(format!(
+                    "Decompressed body exceeds {} bytes",
+                    MAX_DECODED_BODY_BYTES
+                )));
+            }
+            decoded = buffer;
+            &decoded
+        }
+        Some(other) => return Err(ApiError::UnsupportedEncoding(other.to_string())),
+    };
+
+    serde_json::from_slice(json).map_err(|e| ApiError::InvalidBody(e.to_string()))
+}
+
 // Helper functions for dependency injection
 fn with_verifier(
     verifier: Arc<ProofVerifier>,
@@ -144,8 +449,8 @@ fn with_verifier(
 }
 
 fn with_storage(
-    storage: Arc<ProofStorage>,
-) -> impl Filter<Extract = (Arc<ProofStorage>,), Error = Infallible> + Clone {
+    storage: Arc<dyn ProofStore>,
+) -> impl Filter<Extract = (Arc<dyn ProofStore>,), Error = Infallible> + Clone {
     warp::any().map(move || storage.clone())
 }
 
@@ -155,22 +460,64 @@ fn with_metrics(
     warp::any().map(move || metrics.clone())
 }
 
+/// Where the request came from: tagged by `network::serve`, or known to warp
+/// when it accepted the connection itself
+fn peer_addr() -> impl Filter<Extract = (Option<SocketAddr>,), Error = Infallible> + Clone {
+    warp::ext::optional::<PeerAddr>()
+        .and(warp::addr::remote())
+        .map(|tagged: Option<PeerAddr>, remote: Option<SocketAddr>| {
+            tagged.map(|PeerAddr(addr)| addr).or(remote)
+        })
+}
+
 // Handler functions
 async fn handle_verify_proof(
     request: VerificationRequest,
     verifier: Arc<ProofVerifier>,
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
     metrics: Arc<ServerMetrics>,
+    batcher: Option<Arc<VerificationBatcher>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    signed: bool,
+    peer: Option<SocketAddr>,
 ) -> Result<impl Reply, Rejection> {
+    // Throttled requests are turned away before they count towards any
+    // metrics. A signed body vouches for its requester id; otherwise the id
+    // is whatever the sender chose, so limit by where the request came from.
+    let rate_key = if signed {
+        request.requester_id.clone()
+    } else {
+        peer.map_or_else(|| "unknown".to_string(), |addr| addr.ip().to_string())
+    };
+    let rate_limit = rate_limiter.map(|limiter| limiter.check(&rate_key));
+    if let Some(status) = rate_limit.filter(|status| status.exceeded) {
+        warn!("Rate limit exceeded for {} ({})", rate_key, request.requester_id);
+        return Err(reject::custom(ApiError::RateLimited(status)));
+    }
+
     info!("Received proof verification request from {}", request.requester_id);
     
     metrics.increment_proof_requests().await;
-      // Verify the proof
-    let verifier_ref = verifier.as_ref();
-    let verification_result = unsafe {
-        // SAFETY: We need mutable access for stats updates
-        let verifier_ptr = verifier_ref as *const ProofVerifier as *mut ProofVerifier;
-        (*verifier_ptr).verify_proof(&request.proof).await
+    metrics.record_client_activity(&request.proof.client_id);
+
+    // Reject formats we cannot read before spending time on verification
+    ProofFormatRange::supported()
+        .check(request.proof.format_version)
+        .map_err(api_reject)?;
+
+    let now = Utc::now();
+    if verifier.is_expired(&request.proof, now) {
+        warn!("Rejected expired proof {}", request.proof.proof_id);
+        return Err(reject::custom(ApiError::ProofExpired {
+            age_hours: (now - request.proof.timestamp).num_hours(),
+            ttl_hours: verifier.proof_ttl_hours(),
+        }));
+    }
+
+    // Verify the proof
+    let verification_result = match batcher {
+        Some(batcher) => batcher.verify(request.proof.clone()).await,
+        None => verifier.verify_proof(&request.proof).await,
     };
 
     match verification_result {
@@ -184,18 +531,28 @@ async fn handle_verify_proof(
             }
 
             metrics.record_verification_result(&result).await;
+            metrics.record_exemplar(request.proof.proof_id, &request.proof.client_id, &result);
 
             let response = VerificationResponse {
                 proof_id: request.proof.proof_id,
                 result,
             };
 
-            Ok(warp::reply::json(&response))
+            let mut response = warp::reply::json(&response).into_response();
+            if let Some(status) = rate_limit {
+                status.apply_headers(response.headers_mut());
+            }
+            Ok(response)
+        }
+        Err(e @ ZkpFlError::ProofSerialization(_)) => {
+            // The client sent bytes we cannot decode; not a verifier failure
+            warn!("Rejected malformed proof {}: {}", request.proof.proof_id, e);
+            Err(api_reject(e))
         }
         Err(e) => {
             error!("Proof verification failed: {}", e);
             metrics.increment_verification_errors().await;
-            Err(reject::custom(ApiError::VerificationError(e.to_string())))
+            Err(api_reject(e))
         }
     }
 }
@@ -203,7 +560,7 @@ async fn handle_verify_proof(
 async fn handle_verify_batch(
     _request: Vec<u8>, // Simplified - just accept raw bytes for now
     _verifier: Arc<ProofVerifier>,
-    _storage: Arc<ProofStorage>,
+    _storage: Arc<dyn ProofStore>,
     _metrics: Arc<ServerMetrics>,
 ) -> Result<impl Reply, Rejection> {
     // Simplified implementation
@@ -214,18 +571,41 @@ async fn handle_verify_batch(
     Ok(warp::reply::json(&response))
 }
 
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(serde::Deserialize)]
+struct ProofQueryParams {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    client_id: Option<String>,
+    verified: Option<bool>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
 async fn handle_get_all_proofs(
-    storage: Arc<ProofStorage>,
+    params: ProofQueryParams,
+    storage: Arc<dyn ProofStore>,
 ) -> Result<impl Reply, Rejection> {
-    debug!("Retrieving all proofs");
-    
-    let proofs = storage.get_all_proofs().await;
-    Ok(warp::reply::json(&proofs))
+    debug!("Retrieving proofs (since: {:?}, until: {:?})", params.since, params.until);
+
+    let query = ProofQuery {
+        client_id: params.client_id,
+        verified: params.verified,
+        since: params.since,
+        until: params.until,
+        limit: params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+        offset: params.offset.unwrap_or(0),
+    };
+    let page = storage.query_proofs(&query).await.map_err(api_reject)?;
+
+    Ok(warp::reply::json(&page))
 }
 
 async fn handle_get_proof_by_id(
     proof_id: String,
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
 ) -> Result<impl Reply, Rejection> {
     debug!("Retrieving proof by ID: {}", proof_id);
     
@@ -238,9 +618,31 @@ async fn handle_get_proof_by_id(
     }
 }
 
+async fn handle_reverify_proof(
+    proof_id: String,
+    verifier: Arc<ProofVerifier>,
+    storage: Arc<dyn ProofStore>,
+    metrics: Arc<ServerMetrics>,
+) -> Result<impl Reply, Rejection> {
+    let uuid = Uuid::parse_str(&proof_id)
+        .map_err(|_| reject::custom(ApiError::InvalidProofId))?;
+    let mut proof = storage
+        .get_proof(&uuid)
+        .await
+        .ok_or_else(|| reject::custom(ApiError::ProofNotFound))?;
+    info!("Re-verifying stored proof {} from client {}", uuid, proof.client_id);
+
+    let result = verifier.verify_proof(&proof).await.map_err(api_reject)?;
+    proof.mark_verified(result.clone());
+    storage.store_proof(proof).await.map_err(api_reject)?;
+    metrics.record_verification_result(&result).await;
+
+    Ok(warp::reply::json(&result))
+}
+
 async fn handle_get_proofs_by_client(
     client_id: String,
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
 ) -> Result<impl Reply, Rejection> {
     debug!("Retrieving proofs for client: {}", client_id);
     
@@ -249,16 +651,58 @@ async fn handle_get_proofs_by_client(
 }
 
 async fn handle_export_benchmarks(
-    storage: Arc<ProofStorage>,
+    storage: Arc<dyn ProofStore>,
 ) -> Result<impl Reply, Rejection> {
     info!("Exporting benchmark data");
     
     let benchmark_data = storage.export_benchmark_data().await
-        .map_err(|e| reject::custom(ApiError::BenchmarkError(e.to_string())))?;
+        .map_err(api_reject)?;
     
     Ok(warp::reply::json(&benchmark_data))
 }
 
+#[derive(serde::Deserialize)]
+struct HealthParams {
+    #[serde(default)]
+    deep: bool,
+}
+
+async fn handle_health(
+    params: HealthParams,
+    verifier: Arc<ProofVerifier>,
+    storage: Arc<dyn ProofStore>,
+) -> Result<impl Reply, Rejection> {
+    debug!("Health check requested (deep: {})", params.deep);
+    let mut body = serde_json::json!({
+        "status": "healthy",
+        "timestamp": Utc::now(),
+        "service": "zkp-fl-server"
+    });
+    if !params.deep {
+        return Ok(warp::reply::with_status(warp::reply::json(&body), StatusCode::OK));
+    }
+
+    let check = |result: common::Result<()>| match result {
+        Ok(()) => serde_json::json!({ "healthy": true }),
+        Err(e) => serde_json::json!({ "healthy": false, "error": e.to_string() }),
+    };
+    let checks = serde_json::json!({
+        "storage": check(storage.check_writable().await),
+        "verifier": check(verifier.check_ready()),
+    });
+    let healthy = checks
+        .as_object()
+        .is_some_and(|checks| checks.values().all(|check| check["healthy"] == true));
+    if !healthy {
+        warn!("Deep health check failed: {}", checks);
+        body["status"] = "unhealthy".into();
+    }
+    body["checks"] = checks;
+
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
+
 #[derive(serde::Deserialize)]
 struct CleanupParams {
     max_age_hours: Option<i64>,
@@ -266,13 +710,14 @@ struct CleanupParams {
 
 async fn handle_cleanup(
     params: CleanupParams,
-    storage: Arc<ProofStorage>,
+    proof_ttl_hours: i64,
+    storage: Arc<dyn ProofStore>,
 ) -> Result<impl Reply, Rejection> {
-    let max_age = params.max_age_hours.unwrap_or(24);
+    let max_age = params.max_age_hours.unwrap_or(proof_ttl_hours);
     info!("Cleaning up proofs older than {} hours", max_age);
     
     let removed_count = storage.cleanup_old_proofs(max_age).await
-        .map_err(|e| reject::custom(ApiError::CleanupError(e.to_string())))?;
+        .map_err(api_reject)?;
     
     Ok(warp::reply::json(&serde_json::json!({
         "removed_count": removed_count,
@@ -280,76 +725,160 @@ async fn handle_cleanup(
     })))
 }
 
+async fn handle_aggregate(
+    storage: Arc<dyn ProofStore>,
+    aggregator: Arc<FedAvgAggregator>,
+) -> Result<impl Reply, Rejection> {
+    info!("Aggregating verified client weights");
+
+    let proofs = storage.get_verified_proofs().await;
+    let (model, round) = aggregator.aggregate(&proofs).map_err(api_reject)?;
+    storage.store_round(round).await.map_err(api_reject)?;
+
+    Ok(warp::reply::json(&model))
+}
+
+async fn handle_get_rounds(storage: Arc<dyn ProofStore>) -> Result<impl Reply, Rejection> {
+    debug!("Retrieving aggregation rounds");
+    Ok(warp::reply::json(&storage.get_rounds().await))
+}
+
+async fn handle_get_round(
+    round: u64,
+    storage: Arc<dyn ProofStore>,
+) -> Result<impl Reply, Rejection> {
+    debug!("Retrieving aggregation round {}", round);
+    match storage.get_round(round).await {
+        Some(record) => Ok(warp::reply::json(&record)),
+        None => Err(reject::custom(ApiError::RoundNotFound(round))),
+    }
+}
+
 // Error handling
 #[derive(Debug)]
 enum ApiError {
-    VerificationError(String),
-    StorageError(String),
-    BenchmarkError(String),
-    CleanupError(String),
+    /// Failure from the verifier, storage or any other server component
+    Internal(ZkpFlError),
     InvalidProofId,
     ProofNotFound,
+    RoundNotFound(u64),
     BatchTooLarge,
+    RateLimited(RateLimitStatus),
+    InvalidBody(String),
+    UnsupportedEncoding(String),
+    Unauthorized(String),
+    /// The proof was created longer ago than the server's proof TTL
+    ProofExpired { age_hours: i64, ttl_hours: i64 },
 }
 
 impl reject::Reject for ApiError {}
 
-async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
-    let code;
-    let message;
+impl From<ZkpFlError> for ApiError {
+    fn from(error: ZkpFlError) -> Self {
+        ApiError::Internal(error)
+    }
+}
 
-    if err.is_not_found() {
-        code = warp::http::StatusCode::NOT_FOUND;
-        message = "Not Found";
-    } else if let Some(api_error) = err.find::<ApiError>() {
-        match api_error {
-            ApiError::VerificationError(msg) => {
-                code = warp::http::StatusCode::BAD_REQUEST;
-                message = msg;
-            }
-            ApiError::StorageError(msg) => {
-                code = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
-                message = msg;
-            }
-            ApiError::BenchmarkError(msg) => {
-                code = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
-                message = msg;
-            }
-            ApiError::CleanupError(msg) => {
-                code = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
-                message = msg;
-            }
-            ApiError::InvalidProofId => {
-                code = warp::http::StatusCode::BAD_REQUEST;
-                message = "Invalid proof ID format";
-            }
-            ApiError::ProofNotFound => {
-                code = warp::http::StatusCode::NOT_FOUND;
-                message = "Proof not found";
-            }
-            ApiError::BatchTooLarge => {
-                code = warp::http::StatusCode::BAD_REQUEST;
-                message = "Batch size too large (max 100 proofs)";
-            }
+impl ApiError {
+    /// Status, stable error code and message for the response body
+    fn response_parts(&self) -> (StatusCode, &'static str, String) {
+        match self {
+            ApiError::Internal(e) => (
+                StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                e.error_code(),
+                e.to_string(),
+            ),
+            ApiError::InvalidProofId => (
+                StatusCode::BAD_REQUEST,
+                "invalid_proof_id",
+                "Invalid proof ID format".to_string(),
+            ),
+            ApiError::ProofNotFound => (
+                StatusCode::NOT_FOUND,
+                "proof_not_found",
+                "Proof not found".to_string(),
+            ),
+            ApiError::RoundNotFound(round) => (
+                StatusCode::NOT_FOUND,
+                "round_not_found",
+                format!("Round {} not found", round),
+            ),
+            ApiError::BatchTooLarge => (
+                StatusCode::BAD_REQUEST,
+                "batch_too_large",
+                "Batch size too large (max 100 proofs)".to_string(),
+            ),
+            ApiError::InvalidBody(reason) => (
+                StatusCode::BAD_REQUEST,
+                "invalid_body",
+                format!("Invalid request body: {}", reason),
+            ),
+            ApiError::UnsupportedEncoding(encoding) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_encoding",
+                format!("Unsupported content encoding '{}'", encoding),
+            ),
+            ApiError::Unauthorized(reason) => (
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                reason.clone(),
+            ),
+            ApiError::ProofExpired { age_hours, ttl_hours } => (
+                StatusCode::BAD_REQUEST,
+                "proof_expired",
+                format!(
+                    "Proof expired: created {}h ago, proofs are valid for {}h",
+                    age_hours, ttl_hours
+                ),
+            ),
+            ApiError::RateLimited(status) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                format!(
+                    "Rate limit of {} verify requests exceeded, retry in {}s",
+                    status.limit,
+                    status.retry_after_secs()
+                ),
+            ),
         }
+    }
+}
+
+fn api_reject(error: ZkpFlError) -> Rejection {
+    reject::custom(ApiError::from(error))
+}
+
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let rate_limit = match err.find::<ApiError>() {
+        Some(ApiError::RateLimited(status)) => Some(*status),
+        _ => None,
+    };
+    let (code, error_code, message) = if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "not_found", "Not Found".to_string())
+    } else if let Some(api_error) = err.find::<ApiError>() {
+        api_error.response_parts()
     } else if err.find::<warp::filters::body::BodyDeserializeError>().is_some() {
-        code = warp::http::StatusCode::BAD_REQUEST;
-        message = "Invalid request body";
+        (StatusCode::BAD_REQUEST, "invalid_body", "Invalid request body".to_string())
+    } else if err.find::<warp::reject::InvalidQuery>().is_some() {
+        (StatusCode::BAD_REQUEST, "invalid_query", "Invalid query parameters".to_string())
     } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
-        code = warp::http::StatusCode::METHOD_NOT_ALLOWED;
-        message = "Method Not Allowed";
+        (StatusCode::METHOD_NOT_ALLOWED, "method_not_allowed", "Method Not Allowed".to_string())
     } else {
         error!("Unhandled rejection: {:?}", err);
-        code = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
-        message = "Internal Server Error";
-    }
+        (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", "Internal Server Error".to_string())
+    };
 
     let json = warp::reply::json(&serde_json::json!({
         "error": message,
-        "code": code.as_u16()
+        "code": code.as_u16(),
+        "error_code": error_code
     }));
 
-    Ok(warp::reply::with_status(json, code))
+    let mut response = warp::reply::with_status(json, code).into_response();
+    if let Some(status) = rate_limit {
+        status.apply_headers(response.headers_mut());
+    }
+    Ok(response)
 }
 
 // Import network types that are used in this file
@@ -363,6 +892,7 @@ mod network {
         pub total_proofs_processed: usize,
         pub total_proofs_verified: usize,
         pub average_verification_time_ms: f64,
+        pub verification_success_rate: f64,
         pub server_version: String,
     }
 
@@ -379,3 +909,661 @@ mod network {
         pub total_verification_time_ms: u64,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::{create_test_proof, test_config};
+    use crate::storage::FileProofStore;
+    use common::test_utils::test_circuit_config;
+    use common::StorageBackend;
+
+    /// Storage directory unique to one test run, so runs never see each
+    /// other's proofs
+    fn temp_storage_path(name: &str) -> String {
+        let dir = format!("test_proofs_api_{}_{}", name, Uuid::new_v4());
+        std::env::temp_dir().join(dir).to_string_lossy().into_owned()
+    }
+
+    async fn rejection_body(error: ZkpFlError) -> (StatusCode, serde_json::Value) {
+        let response = handle_rejection(api_reject(error)).await.unwrap().into_response();
+        let status = response.status();
+        let body = warp::hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_errors_map_to_stable_statuses() {
+        let (status, body) = rejection_body(ZkpFlError::ProofVerification("bad k".to_string())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error_code"], "proof_verification_failed");
+        assert_eq!(body["error"], "Proof verification error: bad k");
+
+        let io_error = std::io::Error::other("disk full");
+        let (status, body) = rejection_body(ZkpFlError::Io(io_error)).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error_code"], "io_error");
+        assert_eq!(body["code"], 500);
+    }
+
+    #[tokio::test]
+    async fn test_proofs_route_pages_and_filters() {
+        let config = test_config(&temp_storage_path("query"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        for client_id in ["a", "b", "a"] {
+            let mut proof = create_test_proof();
+            proof.client_id = client_id.to_string();
+            storage.store_proof(proof).await.unwrap();
+        }
+        let routes = proofs_route(storage);
+
+        let response = warp::test::request()
+            .path("/proofs?client_id=a&verified=false&limit=1&offset=1")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["limit"], 1);
+        assert_eq!(body["offset"], 1);
+        assert_eq!(body["proofs"].as_array().unwrap().len(), 1);
+        assert_eq!(body["proofs"][0]["client_id"], "a");
+
+        // Verified proofs are filtered out, and oversized pages are capped
+        let response = warp::test::request()
+            .path("/proofs?verified=true&limit=100000")
+            .reply(&routes)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["total"], 0);
+        assert_eq!(body["limit"], MAX_PAGE_SIZE);
+
+        let response = warp::test::request()
+            .path("/proofs?verified=maybe")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_stored_proof_is_reverified() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("reverify"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let proof = crate::verifier::tests::create_genuine_proof(&circuit_config);
+        let proof_id = proof.proof_id;
+        assert!(proof.verification_result.is_none());
+        storage.store_proof(proof).await.unwrap();
+        let routes = reverify_route(verifier, storage.clone(), Arc::new(ServerMetrics::new()))
+            .recover(handle_rejection);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path(&format!("/proofs/{}/verify", proof_id))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let result: VerificationResult = serde_json::from_slice(response.body()).unwrap();
+        assert!(result.verified, "{:?}", result.error_message);
+        assert!(storage.get_proof(&proof_id).await.unwrap().is_verified());
+
+        let response = warp::test::request()
+            .method("POST")
+            .path(&format!("/proofs/{}/verify", Uuid::new_v4()))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["error_code"], "proof_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_benchmark_archive_unzips_to_report_summary_and_csv() {
+        use crate::export::{PROOFS_ENTRY, REPORT_ENTRY, SUMMARY_ENTRY};
+
+        let config = test_config(&temp_storage_path("export_zip"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        for client_id in ["a", "b", "c"] {
+            let mut proof = create_test_proof();
+            proof.client_id = client_id.to_string();
+            storage.store_proof(proof).await.unwrap();
+        }
+        let routes = benchmarks_zip_route(storage);
+
+        let response = warp::test::request()
+            .path("/benchmarks/export.zip")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/zip");
+
+        let mut archive =
+            zip::ZipArchive::new(std::io::Cursor::new(response.body().to_vec())).unwrap();
+        let mut names: Vec<String> =
+            archive.file_names().map(|name| name.unwrap().into_owned()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec![REPORT_ENTRY, PROOFS_ENTRY, SUMMARY_ENTRY]);
+
+        let mut report = String::new();
+        archive.by_name(REPORT_ENTRY).unwrap().read_to_string(&mut report).unwrap();
+        let report: common::MultiClientBenchmark = serde_json::from_str(&report).unwrap();
+        assert_eq!(report.num_clients, 3);
+
+        let mut summary = String::new();
+        archive.by_name(SUMMARY_ENTRY).unwrap().read_to_string(&mut summary).unwrap();
+        assert!(summary.contains(&report.benchmark_id.to_string()), "{}", summary);
+
+        let mut proofs = csv::Reader::from_reader(archive.by_name(PROOFS_ENTRY).unwrap());
+        assert_eq!(&proofs.headers().unwrap()[0], "proof_id");
+        let clients: Vec<String> = proofs
+            .records()
+            .map(|record| record.unwrap()[1].to_string())
+            .collect();
+        assert_eq!(clients.len(), 3);
+        assert!(clients.iter().any(|client| client == "b"));
+    }
+
+    #[tokio::test]
+    async fn test_gzipped_submission_verifies() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("gzip"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        let routes = verify_route(verifier, storage.clone(), metrics, None, None, None)
+            .recover(handle_rejection);
+
+        let proof = crate::verifier::tests::create_genuine_proof(&circuit_config);
+        let json = serde_json::to_vec(&VerificationRequest {
+            proof: proof.clone(),
+            requester_id: "client".to_string(),
+        })
+        .unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&json).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        assert!(gzipped.len() < json.len());
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .header("content-encoding", "gzip")
+            .body(gzipped.clone())
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: VerificationResponse = serde_json::from_slice(response.body()).unwrap();
+        assert!(body.result.verified, "{:?}", body.result.error_message);
+        assert!(storage.get_proof(&proof.proof_id).await.unwrap().is_verified());
+
+        // Plain JSON is still accepted
+        let response = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .body(json)
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .header("content-encoding", "br")
+            .body(gzipped.clone())
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .header("content-encoding", "gzip")
+            .body(&gzipped[..gzipped.len() / 2])
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["error_code"], "invalid_body");
+    }
+
+    #[tokio::test]
+    async fn test_signed_submissions() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("signed"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        let routes = verify_route(verifier, storage, metrics, None, None, Some(Arc::from("secret")))
+            .recover(handle_rejection);
+
+        let proof = crate::verifier::tests::create_genuine_proof(&circuit_config);
+        let json = serde_json::to_vec(&VerificationRequest {
+            proof,
+            requester_id: "client".to_string(),
+        })
+        .unwrap();
+        let submit = |body: Vec<u8>, signature: Option<String>| {
+            let mut request = warp::test::request().method("POST").path("/verify").body(body);
+            if let Some(signature) = signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+            request.reply(&routes)
+        };
+
+        let response = submit(json.clone(), Some(common::sign_body("secret", &json))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A body altered after signing, a different key or no signature are refused
+        let tampered = String::from_utf8(json.clone())
+            .unwrap()
+            .replace("\"client\"", "\"mallory\"")
+            .into_bytes();
+        let response = submit(tampered, Some(common::sign_body("secret", &json))).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["error_code"], "unauthorized");
+
+        let response = submit(json.clone(), Some(common::sign_body("wrong", &json))).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = submit(json, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_rounds_are_recorded() {
+        use crate::aggregator::tests::client_proof;
+
+        let config = test_config(&temp_storage_path("rounds"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let aggregator = Arc::new(FedAvgAggregator::new(2, 1));
+        let routes = aggregate_route(storage.clone(), aggregator)
+            .or(rounds_route(storage.clone()))
+            .recover(handle_rejection);
+        let aggregate = || warp::test::request().method("POST").path("/aggregate").reply(&routes);
+
+        for client_id in ["a", "b"] {
+            storage.store_proof(client_proof(client_id, vec![1.0], 10, true)).await.unwrap();
+        }
+        assert_eq!(aggregate().await.status(), StatusCode::OK);
+        for client_id in ["a", "b", "c"] {
+            storage.store_proof(client_proof(client_id, vec![2.0], 10, true)).await.unwrap();
+        }
+        assert_eq!(aggregate().await.status(), StatusCode::OK);
+
+        let response = warp::test::request().path("/rounds").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let rounds: Vec<common::RoundRecord> = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(rounds.iter().map(|r| r.round).collect::<Vec<_>>(), vec![1, 2]);
+
+        let response = warp::test::request().path("/rounds/2").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let round: common::RoundRecord = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(round.client_ids, vec!["a", "b", "c"]);
+        assert_eq!(round.num_proofs, 3);
+        assert_eq!(round.model.weights, vec![2.0]);
+
+        let response = warp::test::request().path("/rounds/3").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["error_code"], "round_not_found");
+
+        // Rounds are persisted with the proofs
+        let reopened = FileProofStore::new(&config, false).unwrap();
+        assert_eq!(reopened.get_rounds().await.len(), 2);
+        assert_eq!(reopened.get_round(1).await.unwrap().client_ids, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_response_carries_retry_after() {
+        let limiter = RateLimiter::new(1, 1);
+        limiter.check("client");
+        let status = limiter.check("client");
+
+        let rejection = reject::custom(ApiError::RateLimited(status));
+        let response = handle_rejection(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["x-ratelimit-remaining"], "0");
+        assert_eq!(response.headers()["retry-after"], "60");
+    }
+
+    #[tokio::test]
+    async fn test_verify_bursts_are_limited_until_refill() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("burst"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        // Bursts of two, then one request every 250ms
+        let limiter = Arc::new(RateLimiter::with_refill(2, 4.0));
+        let routes = verify_route(verifier, storage, metrics, None, Some(limiter), None)
+            .recover(handle_rejection);
+
+        // Proofs in a format the server cannot read are turned away before
+        // verification, so the burst takes far less than one refill
+        let mut proof = create_test_proof();
+        proof.format_version = common::PROOF_FORMAT_VERSION + 1;
+        let request = VerificationRequest {
+            proof,
+            requester_id: "client".to_string(),
+        };
+        let submit = |peer: [u8; 4], port: u16, requester_id: &str| {
+            let request = VerificationRequest {
+                requester_id: requester_id.to_string(),
+                ..request.clone()
+            };
+            let routes = routes.clone();
+            async move {
+                warp::test::request()
+                    .method("POST")
+                    .path("/verify")
+                    .remote_addr(SocketAddr::from((peer, port)))
+                    .json(&request)
+                    .reply(&routes)
+                    .await
+            }
+        };
+
+        let peer = [10, 0, 0, 1];
+        assert_eq!(submit(peer, 5000, "client").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(submit(peer, 5001, "client").await.status(), StatusCode::BAD_REQUEST);
+        // Neither a new connection nor a new requester id escapes the limit
+        let rejected = submit(peer, 5002, "someone-else").await;
+        assert_eq!(rejected.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(rejected.headers()["retry-after"], "1");
+        // Other peers are unaffected
+        let other = submit([10, 0, 0, 2], 5000, "client").await;
+        assert_eq!(other.status(), StatusCode::BAD_REQUEST);
+
+        // Well past a full refill the peer is let through again
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        assert_eq!(submit(peer, 5003, "client").await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_signed_requesters_are_limited_separately() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("signed_burst"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        // One request each, with no refill while the test runs
+        let limiter = Arc::new(RateLimiter::with_refill(1, 0.001));
+        let secret = Some(Arc::from("secret"));
+        let routes = verify_route(verifier, storage, metrics.clone(), None, Some(limiter), secret)
+            .recover(handle_rejection);
+
+        // Proofs in a format the server cannot read are turned away before
+        // verification, so every request is cheap
+        let mut proof = create_test_proof();
+        proof.format_version = common::PROOF_FORMAT_VERSION + 1;
+        let submit = |requester_id: &str, client_id: &str| {
+            let mut proof = proof.clone();
+            proof.client_id = client_id.to_string();
+            let body = serde_json::to_vec(&VerificationRequest {
+                proof,
+                requester_id: requester_id.to_string(),
+            })
+            .unwrap();
+            let routes = routes.clone();
+            async move {
+                warp::test::request()
+                    .method("POST")
+                    .path("/verify")
+                    .remote_addr(SocketAddr::from(([10, 0, 0, 1], 5000)))
+                    .header(SIGNATURE_HEADER, common::sign_body("secret", &body))
+                    .body(body)
+                    .reply(&routes)
+                    .await
+            }
+        };
+
+        // Signed requesters behind one address each have their own bucket
+        assert_eq!(submit("a", "a").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(submit("b", "b").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(metrics.get_current_snapshot().active_clients, 2);
+
+        // Throttled requests are not counted, so they cannot mark clients active
+        assert_eq!(submit("a", "spoofed").await.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(metrics.get_current_snapshot().active_clients, 2);
+    }
+
+    #[tokio::test]
+    async fn test_proofs_past_ttl_are_rejected_as_expired() {
+        let circuit_config = test_circuit_config();
+        let verifier =
+            Arc::new(ProofVerifier::new(&circuit_config).unwrap().with_proof_ttl_hours(1));
+        let config = test_config(&temp_storage_path("ttl"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        let routes = verify_route(verifier, storage.clone(), metrics, None, None, None)
+            .recover(handle_rejection);
+
+        let mut request = VerificationRequest {
+            proof: crate::verifier::tests::create_genuine_proof(&circuit_config),
+            requester_id: "client".to_string(),
+        };
+        request.proof.timestamp = Utc::now() - chrono::Duration::minutes(59);
+        let fresh = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .json(&request)
+            .reply(&routes)
+            .await;
+        assert_eq!(fresh.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(fresh.body()).unwrap();
+        assert_eq!(body["result"]["verified"], true);
+
+        request.proof.proof_id = Uuid::new_v4();
+        request.proof.timestamp = Utc::now() - chrono::Duration::minutes(61);
+        let expired = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .json(&request)
+            .reply(&routes)
+            .await;
+        assert_eq!(expired.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_slice(expired.body()).unwrap();
+        assert_eq!(body["error_code"], "proof_expired");
+        assert!(body["error"].as_str().unwrap().contains("valid for 1h"), "{}", body);
+        // Expired proofs are not stored
+        assert!(storage.get_proof(&request.proof.proof_id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_status_counts_submitted_proofs() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("status"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        let routes = verify_route(verifier, storage, metrics.clone(), None, None, None)
+            .or(status_route(metrics))
+            .recover(handle_rejection);
+
+        let status = || async {
+            let response = warp::test::request().path("/status").reply(&routes).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            serde_json::from_slice::<network::ServerStatus>(response.body()).unwrap()
+        };
+        let before = status().await;
+        assert_eq!(before.total_proofs_processed, 0);
+        assert_eq!(before.active_clients, 0);
+        assert_eq!(before.server_version, env!("CARGO_PKG_VERSION"));
+
+        let proof = crate::verifier::tests::create_genuine_proof(&circuit_config);
+        let response = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .json(&VerificationRequest {
+                proof,
+                requester_id: "client".to_string(),
+            })
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let after = status().await;
+        assert_eq!(after.total_proofs_processed, 1);
+        assert_eq!(after.total_proofs_verified, 1);
+        assert_eq!(after.active_clients, 1);
+        assert_eq!(after.verification_success_rate, 1.0);
+        assert!(after.average_verification_time_ms >= 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_full_api_serves_submitted_proofs() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("end_to_end"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        let aggregator = Arc::new(FedAvgAggregator::new(2, 3));
+        // The same filter main serves, so route wiring is covered too
+        let routes = create_api_routes(verifier, storage, metrics, None, None, aggregator, None);
+        let get = |path: &'static str| {
+            let routes = routes.clone();
+            async move {
+                let response = warp::test::request().path(path).reply(&routes).await;
+                assert_eq!(response.status(), StatusCode::OK, "GET {}", path);
+                serde_json::from_slice::<serde_json::Value>(response.body()).unwrap()
+            }
+        };
+
+        assert_eq!(get("/api/status").await["total_proofs_processed"], 0);
+        assert_eq!(get("/api/proofs").await["total"], 0);
+
+        let proof = crate::verifier::tests::create_genuine_proof(&circuit_config);
+        let proof_id = proof.proof_id;
+        let response = warp::test::request()
+            .method("POST")
+            .path("/api/verify")
+            .json(&VerificationRequest {
+                proof,
+                requester_id: "client".to_string(),
+            })
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let verification: VerificationResponse = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(verification.proof_id, proof_id);
+        assert!(verification.result.verified);
+
+        let response = warp::test::request()
+            .path(&format!("/api/proofs/{}", proof_id))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stored: ZkpProof = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(stored.proof_id, proof_id);
+        assert!(stored.verification_result.unwrap().verified);
+
+        let listed = get("/api/proofs").await;
+        assert_eq!(listed["total"], 1);
+        assert_eq!(listed["proofs"][0]["proof_id"], proof_id.to_string());
+
+        let status = get("/api/status").await;
+        assert_eq!(status["total_proofs_processed"], 1);
+        assert_eq!(status["total_proofs_verified"], 1);
+        assert_eq!(status["active_clients"], 1);
+
+        let response = warp::test::request()
+            .path(&format!("/api/proofs/{}", Uuid::new_v4()))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_stream_pushes_snapshots() {
+        let circuit_config = test_circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let config = test_config(&temp_storage_path("ws"), StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let metrics = Arc::new(ServerMetrics::new());
+        let verify = verify_route(verifier, storage, metrics.clone(), None, None, None);
+        let stream = metrics_stream_route(metrics.clone());
+
+        let mut client = warp::test::ws()
+            .path("/ws/metrics")
+            .handshake(stream.clone())
+            .await
+            .unwrap();
+        // Clients that come and go leave the stream working for the rest
+        let dropped = warp::test::ws().path("/ws/metrics").handshake(stream).await.unwrap();
+        drop(dropped);
+
+        let proof = crate::verifier::tests::create_genuine_proof(&circuit_config);
+        let response = warp::test::request()
+            .method("POST")
+            .path("/verify")
+            .json(&VerificationRequest {
+                proof,
+                requester_id: "client".to_string(),
+            })
+            .reply(&verify)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Stands in for a tick of the metrics collection task
+        metrics.publish_snapshot();
+        let message = client.recv().await.unwrap();
+        let snapshot: serde_json::Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+        assert_eq!(snapshot["total_proofs_processed"], 1);
+        assert_eq!(snapshot["total_proofs_verified"], 1);
+        assert_eq!(snapshot["active_clients"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_deep_health_reports_unwritable_storage() {
+        let circuit_config = common::CircuitConfig { max_samples: 10, ..test_circuit_config() };
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let path = temp_storage_path("health");
+        let config = test_config(&path, StorageBackend::File);
+        let storage: Arc<dyn ProofStore> = Arc::new(FileProofStore::new(&config, true).unwrap());
+        let routes = health_route(verifier.clone(), storage);
+        let check = |query: &'static str| {
+            let routes = routes.clone();
+            async move {
+                let response = warp::test::request().path(query).reply(&routes).await;
+                let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+                (response.status(), body)
+            }
+        };
+
+        // Not ready until the proving key has been generated
+        let (status, body) = check("/health?deep=true").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["verifier"]["healthy"], false);
+        assert!(body["checks"]["verifier"]["error"].is_string());
+
+        verifier.warm_up().await.unwrap();
+        let (status, body) = check("/health?deep=true").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["checks"]["storage"]["healthy"], true);
+        assert_eq!(body["checks"]["verifier"]["healthy"], true);
+
+        // A file where the storage directory should be cannot be written into,
+        // even by root
+        std::fs::remove_dir_all(&path).unwrap();
+        std::fs::write(&path, b"not a directory").unwrap();
+
+        let (status, body) = check("/health?deep=true").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "unhealthy");
+        assert_eq!(body["checks"]["storage"]["healthy"], false);
+        assert!(body["checks"]["storage"]["error"].is_string());
+        assert_eq!(body["checks"]["verifier"]["healthy"], true);
+
+        // The shallow check does not touch storage
+        let (status, body) = check("/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.get("checks").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,284 @@
+use crate::verifier::ProofVerifier;
+use common::{Result, VerificationResult, ZkpFlError, ZkpProof};
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Pending requests buffered before the batching task falls behind
+const QUEUE_CAPACITY: usize = 1024;
+
+struct PendingVerification {
+    proof: ZkpProof,
+    respond_to: oneshot::Sender<Result<VerificationResult>>,
+}
+
+/// What happened to the queue during a shutdown drain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Queued verifications that completed after the drain started
+    pub drained: usize,
+    /// Queued verifications rejected because the drain timed out
+    pub rejected: usize,
+}
+
+/// Coalesces verify requests that arrive within a short window into one batch.
+/// Each caller still gets its own result back.
+pub struct VerificationBatcher {
+    sender: mpsc::Sender<PendingVerification>,
+    batches_processed: Arc<AtomicUsize>,
+    // Carries the drain deadline once shutdown starts
+    drain_deadline: watch::Sender<Option<Instant>>,
+    worker: Mutex<Option<JoinHandle<DrainReport>>>,
+}
+
+impl VerificationBatcher {
+    pub fn start(verifier: Arc<ProofVerifier>, window: Duration, max_batch_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let batches_processed = Arc::new(AtomicUsize::new(0));
+        let (drain_deadline, drain_signal) = watch::channel(None);
+
+        info!(
+            "Verification batching enabled: window={}ms, max batch size={}",
+            window.as_millis(),
+            max_batch_size
+        );
+        let worker = tokio::spawn(Self::run(
+            verifier,
+            receiver,
+            drain_signal,
+            window,
+            max_batch_size.max(1),
+            batches_processed.clone(),
+        ));
+
+        Self {
+            sender,
+            batches_processed,
+            drain_deadline,
+            worker: Mutex::new(Some(worker)),
+        }
+    }
+
+    /// Queue a proof for the next batch and wait for its result
+    pub async fn verify(&self, proof: ZkpProof) -> Result<VerificationResult> {
+        let (respond_to, response) = oneshot::channel();
+        self.sender
+            .send(PendingVerification { proof, respond_to })
+            .await
+            .map_err(|_| ZkpFlError::Unavailable("Server is shutting down".to_string()))?;
+
+        response
+            .await
+            .map_err(|_| ZkpFlError::Verification("Verification batch dropped".to_string()))?
+    }
+
+    pub fn batches_processed(&self) -> usize {
+        self.batches_processed.load(Ordering::Relaxed)
+    }
+
+    /// Stop taking new requests and finish the queued ones. Batches not
+    /// started within `timeout` are answered with a 503 instead.
+    pub async fn drain(&self, timeout: Duration) -> DrainReport {
+        let _ = self.drain_deadline.send(Some(Instant::now() + timeout));
+
+        let Some(worker) = self.worker.lock().take() else {
+            return DrainReport::default();
+        };
+        match worker.await {
+            Ok(report) => report,
+            Err(e) => {
+                warn!("Verification batcher failed while draining: {}", e);
+                DrainReport::default()
+            }
+        }
+    }
+
+    async fn run(
+        verifier: Arc<ProofVerifier>,
+        mut receiver: mpsc::Receiver<PendingVerification>,
+        mut drain_signal: watch::Receiver<Option<Instant>>,
+        window: Duration,
+        max_batch_size: usize,
+        batches_processed: Arc<AtomicUsize>,
+    ) -> DrainReport {
+        let mut report = DrainReport::default();
+        let mut drain_deadline: Option<Instant> = None;
+
+        loop {
+            let next = if drain_deadline.is_some() {
+                receiver.recv().await
+            } else {
+                tokio::select! {
+                    pending = receiver.recv() => pending,
+                    _ = drain_signal.changed() => {
+                        // A dropped batcher drains immediately
+                        drain_deadline = Some(drain_signal.borrow().unwrap_or_else(Instant::now));
+                        // New requests now fail; buffered ones are still received
+                        receiver.close();
+                        info!("Draining verification queue");
+                        continue;
+                    }
+                }
+            };
+            let Some(first) = next else {
+                break;
+            };
+
+            let mut batch = vec![first];
+            let deadline = Instant::now() + window;
+
+            while batch.len() < max_batch_size {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(pending)) => batch.push(pending),
+                    // Window elapsed or every sender is gone
+                    Ok(None) | Err(_) => break,
+                }
+            }
+
+            if drain_deadline.map_or(false, |limit| Instant::now() >= limit) {
+                warn!("Drain timed out, rejecting {} queued verifications", batch.len());
+                report.rejected += batch.len();
+                for pending in batch {
+                    let _ = pending.respond_to.send(Err(ZkpFlError::Unavailable(
+                        "Server shut down before this proof was verified".to_string(),
+                    )));
+                }
+                continue;
+            }
+
+            debug!("Processing verification batch of {}", batch.len());
+            let batch_len = batch.len();
+            let (proofs, responders): (Vec<_>, Vec<_>) = batch
+                .into_iter()
+                .map(|pending| (pending.proof, pending.respond_to))
+                .unzip();
+
+            let results = verifier.verify_batch(&proofs).await;
+            let batch_number = batches_processed.fetch_add(1, Ordering::Relaxed) + 1;
+            debug!("Verification batch #{} completed", batch_number);
+            // A batch already in flight when the drain started counts too,
+            // though this loop only notices the drain once it is done
+            if drain_deadline.is_some() || drain_signal.borrow().is_some() {
+                report.drained += batch_len;
+            }
+
+            for (respond_to, result) in responders.into_iter().zip(results) {
+                // The caller may have gone away; nothing to do then
+                let _ = respond_to.send(result);
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::tests::create_genuine_proof;
+    use common::test_utils::test_circuit_config;
+    use common::CircuitConfig;
+
+    fn circuit_config() -> CircuitConfig {
+        CircuitConfig { num_features: 5, ..test_circuit_config() }
+    }
+
+    fn create_proof() -> ZkpProof {
+        create_genuine_proof(&circuit_config())
+    }
+
+    #[tokio::test]
+    async fn test_requests_within_window_share_a_batch() {
+        let circuit_config = circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let batcher = Arc::new(VerificationBatcher::start(
+            verifier,
+            Duration::from_millis(200),
+            32,
+        ));
+
+        let proofs: Vec<ZkpProof> = (0..5).map(|_| create_proof()).collect();
+        let tasks: Vec<_> = proofs
+            .iter()
+            .cloned()
+            .map(|proof| {
+                let batcher = batcher.clone();
+                tokio::spawn(async move { batcher.verify(proof).await })
+            })
+            .collect();
+
+        for task in tasks {
+            assert!(task.await.unwrap().unwrap().verified);
+        }
+        assert_eq!(batcher.batches_processed(), 1);
+    }
+
+    /// Queue six proofs on a fresh batcher, drain it with `timeout`, and
+    /// return the report with how many callers saw a result and a 503
+    async fn drain_six(
+        timeout: Duration,
+    ) -> (Arc<VerificationBatcher>, DrainReport, usize, usize) {
+        let circuit_config = circuit_config();
+        let verifier = Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let batcher = Arc::new(VerificationBatcher::start(
+            verifier,
+            Duration::from_millis(100),
+            2,
+        ));
+        let proofs: Vec<ZkpProof> = (0..6).map(|_| create_proof()).collect();
+
+        // On the test's single-threaded runtime yielding once lets every
+        // spawned request reach the queue before the drain starts, while the
+        // batching task has at most started on the first batch
+        let tasks: Vec<_> = proofs
+            .into_iter()
+            .map(|proof| {
+                let batcher = batcher.clone();
+                tokio::spawn(async move { batcher.verify(proof).await })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        let report = batcher.drain(timeout).await;
+
+        let (mut verified, mut unavailable) = (0, 0);
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(result) => {
+                    assert!(result.verified);
+                    verified += 1;
+                }
+                Err(e) => {
+                    assert_eq!(e.status_code(), 503);
+                    unavailable += 1;
+                }
+            }
+        }
+        (batcher, report, verified, unavailable)
+    }
+
+    #[tokio::test]
+    async fn test_drain_answers_every_queued_request() {
+        // With time to spare every queued proof is verified
+        let (batcher, report, verified, unavailable) = drain_six(Duration::from_secs(600)).await;
+        assert_eq!(report, DrainReport { drained: 6, rejected: 0 });
+        assert_eq!((verified, unavailable), (6, 0));
+
+        // Once drained, new requests are turned away
+        let late = batcher.verify(create_proof()).await.unwrap_err();
+        assert_eq!(late.status_code(), 503);
+        assert_eq!(batcher.drain(Duration::ZERO).await, DrainReport::default());
+
+        // Past the deadline the rest are answered with a 503; nothing is
+        // dropped silently
+        let (_, report, verified, unavailable) = drain_six(Duration::ZERO).await;
+        assert_eq!(report.drained + report.rejected, 6);
+        assert_eq!(report.drained, verified);
+        assert_eq!(report.rejected, unavailable);
+        assert!(report.rejected > 0);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::storage::{ProofQuery, ProofStore};
+use common::{LatencyPercentiles, MultiClientBenchmark, Result, ZkpFlError, ZkpProof};
+use futures::Stream;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use warp::hyper::body::Bytes;
+use zip::write::{SimpleFileOptions, ZipWriter};
+use zip::CompressionMethod;
+
+/// Aggregated `MultiClientBenchmark`, as served by `/api/benchmarks/export`
+pub const REPORT_ENTRY: &str = "benchmark_report.json";
+pub const SUMMARY_ENTRY: &str = "summary.txt";
+/// One row per stored proof
+pub const PROOFS_ENTRY: &str = "proofs.csv";
+
+/// Proofs read from storage at a time while writing the CSV
+const EXPORT_PAGE_SIZE: usize = 500;
+/// Archive bytes buffered before they are handed to the response
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks queued ahead of a slow download before the writer waits
+const CHANNEL_CHUNKS: usize = 16;
+
+/// Stream a ZIP of the benchmark report, a text summary and a per-proof
+/// CSV. The archive is written on a blocking thread as the response is
+/// read, so it is never held in memory whole; a failure part way through
+/// ends the stream with an error.
+pub fn stream_export(storage: Arc<dyn ProofStore>) -> impl Stream<Item = io::Result<Bytes>> {
+    let (sender, receiver) = mpsc::channel(CHANNEL_CHUNKS);
+    let runtime = Handle::current();
+    let error_sender = sender.clone();
+    tokio::task::spawn_blocking(move || {
+        let writer = ChannelWriter {
+            buffer: Vec::with_capacity(CHUNK_BYTES),
+            sender,
+        };
+        if let Err(e) = write_export(writer, storage.as_ref(), &runtime) {
+            log::error!("Benchmark export failed: {}", e);
+            let _ = error_sender.blocking_send(Err(io::Error::other(e.to_string())));
+        }
+    });
+    ReceiverStream::new(receiver)
+}
+
+fn write_export(writer: ChannelWriter, storage: &dyn ProofStore, runtime: &Handle) -> Result<()> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new_stream(writer);
+
+    let benchmark = runtime.block_on(storage.export_benchmark_data())?;
+    zip.start_file(REPORT_ENTRY, options).map_err(zip_error)?;
+    serde_json::to_writer_pretty(&mut zip, &benchmark)?;
+
+    zip.start_file(SUMMARY_ENTRY, options).map_err(zip_error)?;
+    zip.write_all(benchmark_summary(&benchmark).as_bytes())?;
+
+    zip.start_file(PROOFS_ENTRY, options).map_err(zip_error)?;
+    let mut csv = csv::Writer::from_writer(&mut zip);
+    csv.write_record(PROOF_COLUMNS).map_err(csv_error)?;
+    let mut query = ProofQuery {
+        client_id: None,
+        verified: None,
+        since: None,
+        until: None,
+        limit: EXPORT_PAGE_SIZE,
+        offset: 0,
+    };
+    loop {
+        let page = runtime.block_on(storage.query_proofs(&query))?;
+        for proof in &page.proofs {
+            csv.write_record(proof_row(proof)).map_err(csv_error)?;
+        }
+        query.offset += page.proofs.len();
+        if page.proofs.len() < EXPORT_PAGE_SIZE || query.offset >= page.total {
+            break;
+        }
+    }
+    csv.flush()?;
+    drop(csv);
+
+    let mut writer = zip.finish().map_err(zip_error)?.into_inner();
+    writer.flush()?;
+    Ok(())
+}
+
+const PROOF_COLUMNS: [&str; 11] = [
+    "proof_id",
+    "client_id",
+    "session_id",
+    "timestamp",
+    "verified",
+    "verification_time_ms",
+    "generation_time_ms",
+    "proof_size_bytes",
+    "num_samples",
+    "final_loss",
+    "k",
+];
+
+fn proof_row(proof: &ZkpProof) -> [String; 11] {
+    let result = proof.verification_result.as_ref();
+    let metadata = &proof.proof_data.metadata;
+    [
+        proof.proof_id.to_string(),
+        proof.client_id.clone(),
+        proof.session_id.to_string(),
+        proof.timestamp.to_rfc3339(),
+        result.map_or(String::new(), |r| r.verified.to_string()),
+        result.map_or(String::new(), |r| r.verification_time_ms.to_string()),
+        metadata.generation_time_ms.to_string(),
+        metadata.proof_size_bytes.to_string(),
+        proof.training_commitment.num_samples.to_string(),
+        proof.training_commitment.final_loss.to_string(),
+        proof.proof_data.circuit_params.k.to_string(),
+    ]
+}
+
+/// Plain-text overview of the report for reading without tooling
+pub fn benchmark_summary(benchmark: &MultiClientBenchmark) -> String {
+    let metrics = &benchmark.aggregate_metrics;
+    let loss = match &metrics.loss {
+        Some(loss) => format!(
+            "- Final loss (MSE): {:.6}\n- RMSE: {:.6}\n- Normalized RMSE: {:.6}",
+            loss.mse, loss.rmse, loss.normalized_rmse
+        ),
+        None => "- Not available (no target statistics reported)".to_string(),
+    };
+    format!(
+        "ZKP-FL Benchmark Export\n\
+         =======================\n\
+         \n\
+         Benchmark ID: {}\n\
+         From: {}\n\
+         To: {}\n\
+         Clients: {}\n\
+         \n\
+         Proofs:\n\
+         - Generated: {}\n\
+         - Verified: {}\n\
+         - Success rate: {:.2}%\n\
+         - Throughput: {:.2} proofs/second\n\
+         \n\
+         Timings (ms):\n\
+         - Average proof generation: {:.2} (min {}, max {})\n\
+         - Average verification: {:.2}\n\
+         - Average training: {:.2}\n\
+         {}\n\
+         {}\n\
+         \n\
+         Model Quality:\n\
+         {}\n",
+        benchmark.benchmark_id,
+        benchmark.start_time.format("%Y-%m-%d %H:%M:%S UTC"),
+        benchmark.end_time.format("%Y-%m-%d %H:%M:%S UTC"),
+        benchmark.num_clients,
+        metrics.total_proofs_generated,
+        metrics.total_proofs_verified,
+        metrics.success_rate * 100.0,
+        metrics.throughput_proofs_per_second,
+        metrics.avg_proof_generation_time_ms,
+        metrics.min_proof_generation_time_ms,
+        metrics.max_proof_generation_time_ms,
+        metrics.avg_proof_verification_time_ms,
+        metrics.avg_training_time_ms,
+        percentile_line("Proof generation", &metrics.proof_generation_percentiles),
+        percentile_line("Verification", &metrics.verification_percentiles),
+        loss,
+    )
+}
+
+fn percentile_line(label: &str, percentiles: &LatencyPercentiles) -> String {
+    format!(
+        "- {} p50/p90/p95/p99: {:.2} / {:.2} / {:.2} / {:.2}",
+        label, percentiles.p50_ms, percentiles.p90_ms, percentiles.p95_ms, percentiles.p99_ms
+    )
+}
+
+fn zip_error(error: zip::result::ZipError) -> ZkpFlError {
+    ZkpFlError::Storage(format!("Failed to write export archive: {}", error))
+}
+
+fn csv_error(error: csv::Error) -> ZkpFlError {
+    ZkpFlError::Storage(format!("Failed to write proof CSV: {}", error))
+}
+
+/// Hands archive bytes to the response in chunks, waiting while the
+/// download falls behind
+struct ChannelWriter {
+    buffer: Vec<u8>,
+    sender: mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl ChannelWriter {
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(CHUNK_BYTES));
+        self.sender
+            .blocking_send(Ok(Bytes::from(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "export download was closed"))
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_BYTES {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()
+    }
+}
@@ -3,14 +3,28 @@ mod storage;
 mod api;
 mod metrics;
 mod network;
+mod batcher;
+mod rate_limit;
+mod aggregator;
+mod sqlite_storage;
+mod verification_pool;
+mod verification_cache;
+mod export;
 
 use clap::Parser;
-use common::{Config, Result, ZkpFlError};
-use log::{info, error};
+use common::{Config, LogFormat, Result, ZkpFlError};
+use log::{info, error, warn};
+use std::io::Write;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
 use warp::Filter;
 
+/// Time past the drain deadline for turned-away requests to get their 503
+const UNAVAILABLE_REPLY_GRACE: Duration = Duration::from_secs(1);
+
 #[derive(Parser, Debug)]
 #[command(name = "zkp-fl-server")]
 #[command(about = "ZKP Federated Learning Server")]
@@ -30,17 +44,33 @@ pub struct Args {
     /// Enable detailed logging
     #[arg(short, long)]
     verbose: bool,
+
+    /// Log line format: text or json
+    #[arg(long, default_value = "text")]
+    log_format: LogFormat,
     
     /// Clear proof storage on startup
     #[arg(long)]
     clear_storage: bool,
+
+    /// Try the following ports when the configured one is in use
+    #[arg(long)]
+    auto_port: bool,
 }
 
 pub struct Server {
     config: Config,
     verifier: Arc<verifier::ProofVerifier>,
-    storage: Arc<storage::ProofStorage>,
+    storage: Arc<dyn storage::ProofStore>,
     metrics: Arc<metrics::ServerMetrics>,
+    batcher: Option<Arc<batcher::VerificationBatcher>>,
+    rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
+    aggregator: Arc<aggregator::FedAvgAggregator>,
+    pool: Arc<verification_pool::VerificationPool>,
+    in_flight: Arc<network::InFlightRequests>,
+    auto_port: bool,
+    /// Serves HTTPS instead of HTTP when set
+    tls: Option<tokio_rustls::TlsAcceptor>,
 }
 
 impl Server {
@@ -56,18 +86,90 @@ impl Server {
             config.server.port = port;
         }
 
+        Self::with_config(config, args.clear_storage, args.auto_port).await
+    }
+
+    /// Build the server from an already loaded configuration
+    pub async fn with_config(config: Config, clear_storage: bool, auto_port: bool) -> Result<Self> {
         info!("Initializing ZKP-FL server on {}:{}", config.server.host, config.server.port);
 
+        // Unreadable or invalid certificates fail startup, not the first request
+        let tls = match (&config.server.tls_cert_path, &config.server.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => Some(network::load_tls(cert_path, key_path)?),
+            _ => None,
+        };
+
         // Initialize components
-        let verifier = Arc::new(verifier::ProofVerifier::new(&config.circuit)?);
-        let storage = Arc::new(storage::ProofStorage::new(&config.server, args.clear_storage)?);
-        let metrics = Arc::new(metrics::ServerMetrics::new());
+        let pool = Arc::new(verification_pool::VerificationPool::with_config(&config.server));
+        info!("Running up to {} verifications at once", pool.capacity());
+        let cache =
+            verification_cache::VerificationCache::with_config(&config.server).map(Arc::new);
+        let verifier = Arc::new(
+            verifier::ProofVerifier::new(&config.circuit)?
+                .with_pool(pool.clone())
+                .with_cache(cache.clone())
+                .with_proof_ttl_hours(config.server.proof_ttl_hours)
+                .with_proof_size_tolerance(config.server.proof_size_tolerance)
+                .with_registered_dataset_hashes(config.server.registered_dataset_hashes.clone()),
+        );
+        // A circuit the parameters cannot hold is reported by deep health
+        // checks rather than by every proof that arrives
+        if let Err(e) = verifier.warm_up().await {
+            warn!("Verifier is not ready: {}", e);
+        }
+        let storage = storage::open_store(&config.server, clear_storage)?;
+        let metrics = Arc::new(
+            metrics::ServerMetrics::with_config(&config.server)
+                .with_verification_pool(pool.clone())
+                .with_verification_cache(cache),
+        );
+        let batcher = (config.server.verification_batch_window_ms > 0).then(|| {
+            Arc::new(batcher::VerificationBatcher::start(
+                verifier.clone(),
+                std::time::Duration::from_millis(config.server.verification_batch_window_ms),
+                config.server.verification_batch_max_size,
+            ))
+        });
+
+        let rate_limiter = (config.server.verify_rate_limit_per_minute > 0).then(|| {
+            let limiter = rate_limit::RateLimiter::new(
+                config.server.verify_rate_limit_per_minute,
+                config.server.verify_rate_limit_burst,
+            );
+            info!(
+                "Rate limiting verify requests to {} per client per minute, bursts of {}",
+                config.server.verify_rate_limit_per_minute,
+                limiter.capacity()
+            );
+            Arc::new(limiter)
+        });
+
+        let aggregator = Arc::new(
+            aggregator::FedAvgAggregator::new(
+                config.server.aggregation_min_clients,
+                config.circuit.num_features,
+            )
+            .with_weighting(config.server.aggregation_weighting)
+            .with_rule(config.server.aggregation_rule, config.server.aggregation_trim_fraction)
+            .with_outlier_threshold(config.server.aggregation_outlier_threshold),
+        );
+        if let Some(last_round) = storage.get_rounds().await.pop() {
+            info!("Resuming federated averaging after round {}", last_round.round);
+            aggregator.resume(&last_round);
+        }
 
         Ok(Self {
             config,
             verifier,
             storage,
             metrics,
+            batcher,
+            rate_limiter,
+            aggregator,
+            pool,
+            in_flight: Arc::new(network::InFlightRequests::default()),
+            auto_port,
+            tls,
         })
     }
 
@@ -77,9 +179,19 @@ impl Server {
         
         let config: Config = toml::from_str(&config_str)
             .map_err(|e| ZkpFlError::Config(format!("Failed to parse config: {}", e)))?;
+        config.validate()?;
         
         Ok(config)
-    }    pub async fn run(&self) -> Result<()> {
+    }
+
+    /// Serve until Ctrl+C or SIGTERM, then drain and shut down
+    pub async fn run(&self) -> Result<()> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Serve until `shutdown` resolves, then stop accepting connections, let
+    /// in-flight requests finish and flush storage
+    pub async fn run_until(&self, shutdown: impl std::future::Future<Output = ()>) -> Result<()> {
         info!("Starting ZKP-FL server...");
         let start_time = Instant::now();
 
@@ -87,26 +199,68 @@ impl Server {
         let routes = self.create_routes();
 
         // Start metrics collection task
-        let metrics_task = self.start_metrics_collection();
+        let mut metrics_task = self.start_metrics_collection();
+        let retention_task = self.start_retention_cleanup();
 
         // Start server
         let addr = format!("{}:{}", self.config.server.host, self.config.server.port)
             .parse::<std::net::SocketAddr>()
             .map_err(|e| ZkpFlError::Config(format!("Invalid server address: {}", e)))?;
 
-        info!("Server ready on http://{}", addr);
+        let listener = network::bind_listener(addr, self.auto_port)?;
+        let bound_addr = listener.local_addr()?;
+
+        let (stop_accepting, stop_signal) = oneshot::channel::<()>();
+        let stop_signal = async {
+            stop_signal.await.ok();
+        };
+        // Both schemes serve on the socket bound above, so it is never released
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        let mut server = match &self.tls {
+            Some(acceptor) => {
+                info!("Server ready on https://{}", bound_addr);
+                tokio::spawn(network::serve(
+                    network::tls_incoming(listener, acceptor.clone()),
+                    warp::service(routes),
+                    stop_signal,
+                ))
+            }
+            None => {
+                info!("Server ready on http://{}", bound_addr);
+                tokio::spawn(network::serve(
+                    TcpListenerStream::new(listener),
+                    warp::service(routes),
+                    stop_signal,
+                ))
+            }
+        };
         info!("Startup time: {}ms", start_time.elapsed().as_millis());
 
         // Run server and metrics collection concurrently
         tokio::select! {
-            result = warp::serve(routes).run(addr) => {
+            result = &mut server => {
                 info!("Server stopped: {:?}", result);
             }
-            result = metrics_task => {
+            result = &mut metrics_task => {
                 info!("Metrics collection stopped: {:?}", result);
             }
+            _ = shutdown => {
+                info!("Shutdown requested");
+                self.drain(stop_accepting, server).await;
+            }
         }
 
+        if let Some(batcher) = &self.batcher {
+            info!("Processed {} verification batches", batcher.batches_processed());
+        }
+        // Neither may write to storage once the final flush has started
+        metrics_task.abort();
+        if let Some(retention_task) = retention_task {
+            retention_task.abort();
+        }
+        self.persist_final_metrics().await?;
+        self.storage.flush().await?;
+
         info!("Server shut down cleanly");
         Ok(())
     }
@@ -116,16 +270,131 @@ impl Server {
             self.verifier.clone(),
             self.storage.clone(),
             self.metrics.clone(),
+            self.batcher.clone(),
+            self.rate_limiter.clone(),
+            self.aggregator.clone(),
+            self.config.server.request_signing_secret.as_deref().map(Arc::from),
         );
 
+        // Count each request until its reply is ready, so shutdown can wait for them
+        let in_flight = self.in_flight.clone();
+        let api_routes = warp::any()
+            .map(move || in_flight.enter())
+            .and(api_routes)
+            .map(|_guard: network::InFlightGuard, reply| reply);
+
         // CORS headers
         let cors = warp::cors()
             .allow_any_origin()
-            .allow_headers(vec!["content-type"])
+            .allow_headers(vec!["content-type", common::SIGNATURE_HEADER])
             .allow_methods(vec!["GET", "POST", "PUT", "DELETE"]);
 
         api_routes.with(cors)
-    }    fn start_metrics_collection(&self) -> tokio::task::JoinHandle<()> {
+    }
+
+    /// Stop accepting connections, then give queued and in-flight
+    /// verifications until the drain timeout to finish
+    async fn drain(&self, stop_accepting: oneshot::Sender<()>, mut server: JoinHandle<()>) {
+        let timeout = Duration::from_secs(self.config.server.shutdown_drain_timeout_secs);
+        let deadline = tokio::time::Instant::now() + timeout;
+        let _ = stop_accepting.send(());
+
+        let pending = self.in_flight.count();
+        info!(
+            "Waiting up to {}s for {} in-flight requests ({} verifications running, {} queued)",
+            timeout.as_secs(),
+            pending,
+            self.pool.in_flight(),
+            self.pool.queue_depth()
+        );
+
+        if let Some(batcher) = &self.batcher {
+            let report = batcher.drain(timeout).await;
+            info!(
+                "Verification queue drained: {} completed, {} rejected",
+                report.drained, report.rejected
+            );
+        }
+
+        if tokio::time::timeout_at(deadline, &mut server).await.is_err() {
+            warn!(
+                "{} in-flight requests did not finish within {}s, shutting down anyway",
+                self.in_flight.count(),
+                timeout.as_secs()
+            );
+            // Requests still waiting for a verification slot get a 503 rather
+            // than a dropped connection, then whatever is left is cut off
+            self.pool.close();
+            if tokio::time::timeout(UNAVAILABLE_REPLY_GRACE, &mut server).await.is_err() {
+                server.abort();
+            }
+        }
+        info!(
+            "Drained {} of {} in-flight requests",
+            pending.saturating_sub(self.in_flight.count()),
+            pending
+        );
+    }
+
+    async fn persist_final_metrics(&self) -> Result<()> {
+        let snapshot = self.metrics.get_current_snapshot();
+        self.storage.update_metrics(&snapshot).await;
+
+        // A server that never stored a proof may not have its directory yet
+        let dir = std::path::Path::new(&self.config.server.proof_storage_path);
+        tokio::fs::create_dir_all(dir).await?;
+        let path = dir.join("final_metrics.json");
+        let final_metrics = serde_json::json!({
+            "snapshot": snapshot,
+            "performance": self.metrics.get_performance_metrics(),
+            "shutdown_at": chrono::Utc::now(),
+        });
+        tokio::fs::write(&path, serde_json::to_string_pretty(&final_metrics)?).await?;
+        info!("Final metrics written to {}", path.display());
+        Ok(())
+    }
+
+    fn start_retention_cleanup(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let interval_secs = self.config.server.retention_interval_secs;
+        if interval_secs == 0 {
+            return None;
+        }
+
+        let metrics = self.metrics.clone();
+        let storage = self.storage.clone();
+        let config = self.config.server.clone();
+        info!(
+            "Retention cleanup every {}s: max age {}h, max count {}",
+            interval_secs,
+            config.proof_ttl_hours,
+            config
+                .retention_max_count
+                .map_or_else(|| "unlimited".to_string(), |count| count.to_string())
+        );
+
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+            loop {
+                interval.tick().await;
+
+                match storage::apply_retention(storage.as_ref(), &config).await {
+                    Ok(removed) => {
+                        // Refresh the size estimate so the log reflects the cleanup
+                        storage.update_metrics(&metrics.get_current_snapshot()).await;
+                        info!(
+                            "Retention cleanup removed {} proofs, storage size now {} bytes",
+                            removed,
+                            storage.get_stats().storage_size_bytes
+                        );
+                    }
+                    Err(e) => error!("Retention cleanup failed: {}", e),
+                }
+            }
+        }))
+    }
+
+    fn start_metrics_collection(&self) -> tokio::task::JoinHandle<()> {
         let metrics = self.metrics.clone();
         let storage = self.storage.clone();
         let interval_ms = self.config.benchmarks.metrics_interval_ms;
@@ -138,8 +407,8 @@ impl Server {
             loop {
                 interval.tick().await;
                 
-                // Collect current metrics
-                let current_metrics = metrics.get_current_snapshot();
+                // Collect current metrics and push them to metrics stream clients
+                let current_metrics = metrics.publish_snapshot();
                 
                 // Update storage metrics
                 storage.update_metrics(&current_metrics).await;
@@ -156,26 +425,272 @@ impl Server {
     }
 }
 
+/// Resolve on Ctrl+C, or on SIGTERM where the platform has it
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("Cannot listen for SIGTERM, only Ctrl+C stops the server: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("Cannot listen for Ctrl+C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
     
     // Initialize logging
     let log_level = if args.verbose { "debug" } else { "info" };
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level));
+    if args.log_format == LogFormat::Json {
+        logger.format(|buf, record| writeln!(buf, "{}", common::json_log_line(record)));
+    }
+    logger.init();
     
     info!("ZKP-FL Server starting...");
     
     let server = Server::new(args).await?;
     
     match server.run().await {
-        Ok(()) => {
-            info!("Server shut down cleanly");
-            Ok(())
-        }
+        Ok(()) => Ok(()),
         Err(e) => {
             error!("Server error: {}", e);
             Err(e)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{StorageBackend, VerificationRequest, VerificationResponse};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Shipped config with a circuit small enough for quick genuine proofs,
+    /// listening on a free local port
+    fn test_server_config(storage_path: &str) -> Config {
+        let mut config: Config = toml::from_str(include_str!("../../config.toml")).unwrap();
+        config.circuit.k = 8;
+        config.circuit.num_features = 3;
+        config.circuit.precision_bits = 16;
+        config.server = storage::tests::test_config(storage_path, StorageBackend::File);
+        config.server.host = "127.0.0.1".to_string();
+        config.server.port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_pending_verification() {
+        let mut config = test_server_config("/tmp/test_server_shutdown");
+        let port = config.server.port;
+        config.server.max_concurrent_verifications = 1;
+        let server = Arc::new(Server::with_config(config.clone(), true, false).await.unwrap());
+
+        // Hold the only verification slot so the request stays pending
+        let slot = server.pool.acquire().await.unwrap();
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .run_until(async {
+                        shutdown_signal.await.ok();
+                    })
+                    .await
+            }
+        });
+
+        let proof = verifier::tests::create_genuine_proof(&config.circuit);
+        let body = serde_json::to_string(&VerificationRequest {
+            proof: proof.clone(),
+            requester_id: "client".to_string(),
+        })
+        .unwrap();
+        let mut stream = loop {
+            match tokio::net::TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        };
+        let request = format!(
+            "POST /api/verify HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        while server.pool.queue_depth() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        shutdown.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!running.is_finished(), "shutdown did not wait for the verification");
+        drop(slot);
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        let (_, json) = response.split_once("\r\n\r\n").unwrap();
+        let response: VerificationResponse = serde_json::from_str(json).unwrap();
+        assert!(response.result.verified, "{:?}", response.result.error_message);
+
+        running.await.unwrap().unwrap();
+        assert_eq!(server.in_flight.count(), 0);
+        assert!(server.storage.get_proof(&proof.proof_id).await.unwrap().is_verified());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_deadline_answers_queued_verification() {
+        let mut config = test_server_config("/tmp/test_server_shutdown_deadline");
+        let port = config.server.port;
+        config.server.max_concurrent_verifications = 1;
+        config.server.verification_batch_window_ms = 0;
+        config.server.shutdown_drain_timeout_secs = 1;
+        let server = Arc::new(Server::with_config(config.clone(), true, false).await.unwrap());
+
+        // The only slot is never given back, so the request cannot finish
+        let _slot = server.pool.acquire().await.unwrap();
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .run_until(async {
+                        shutdown_signal.await.ok();
+                    })
+                    .await
+            }
+        });
+
+        let body = serde_json::to_string(&VerificationRequest {
+            proof: verifier::tests::create_genuine_proof(&config.circuit),
+            requester_id: "client".to_string(),
+        })
+        .unwrap();
+        let mut stream = loop {
+            match tokio::net::TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        };
+        let request = format!(
+            "POST /api/verify HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        while server.pool.queue_depth() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        shutdown.send(()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 503"), "{}", response);
+
+        tokio::time::timeout(Duration::from_secs(5), running)
+            .await
+            .expect("shutdown did not finish after the drain deadline")
+            .unwrap()
+            .unwrap();
+        assert_eq!(server.in_flight.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_https_health_check() {
+        let dir = std::env::temp_dir().join(format!("zkp_fl_tls_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.join("server.crt");
+        let key_path = dir.join("server.key");
+        std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+        std::fs::write(&key_path, certified.key_pair.serialize_pem()).unwrap();
+
+        let mut config = test_server_config(&dir.join("proofs").to_string_lossy());
+        let port = config.server.port;
+        config.server.tls_cert_path = Some(cert_path.to_string_lossy().into_owned());
+        config.server.tls_key_path = Some(key_path.to_string_lossy().into_owned());
+        let server = Arc::new(Server::with_config(config, true, false).await.unwrap());
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .run_until(async {
+                        shutdown_signal.await.ok();
+                    })
+                    .await
+            }
+        });
+
+        let root = reqwest::Certificate::from_pem(certified.cert.pem().as_bytes()).unwrap();
+        let client = reqwest::Client::builder()
+            .add_root_certificate(root)
+            .build()
+            .unwrap();
+        let url = format!("https://localhost:{}/api/health", port);
+        let response = loop {
+            match client.get(&url).send().await {
+                Ok(response) => break response,
+                Err(e) if e.is_connect() => tokio::time::sleep(Duration::from_millis(20)).await,
+                Err(e) => panic!("HTTPS request failed: {}", e),
+            }
+        };
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "healthy");
+
+        // Plain HTTP is not served alongside
+        let plain = format!("http://localhost:{}/api/health", port);
+        assert!(reqwest::get(&plain).await.map_or(true, |r| !r.status().is_success()));
+
+        shutdown.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_tls_files_fail_startup() {
+        let dir = std::env::temp_dir().join(format!("zkp_fl_bad_tls_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.join("server.crt");
+        let key_path = dir.join("server.key");
+        std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+        std::fs::write(&key_path, "not a key").unwrap();
+
+        let mut config = test_server_config(&dir.join("proofs").to_string_lossy());
+        config.server.tls_cert_path = Some(cert_path.to_string_lossy().into_owned());
+        config.server.tls_key_path = Some(key_path.to_string_lossy().into_owned());
+        match Server::with_config(config.clone(), true, false).await {
+            Err(ZkpFlError::Config(message)) => assert!(message.contains("No TLS private key")),
+            other => panic!("expected a config error, got {:?}", other.err()),
+        }
+
+        config.server.tls_key_path = Some(dir.join("missing.key").to_string_lossy().into_owned());
+        match Server::with_config(config, true, false).await {
+            Err(ZkpFlError::Config(message)) => assert!(message.contains("Failed to read TLS")),
+            other => panic!("expected a config error, got {:?}", other.err()),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,14 +1,70 @@
-use common::{VerificationResult, SystemMetrics};
+use common::{percentile, VerificationResult, SystemMetrics, ServerConfig, MetricsSampler};
 use parking_lot::RwLock;
 use log::{debug, info};
+use std::sync::Arc;
 use std::time::Instant;
 use chrono::{DateTime, Utc};
-use std::collections::VecDeque;
+use crate::verification_cache::VerificationCache;
+use crate::verification_pool::VerificationPool;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Upper bounds (seconds) of the verification latency histogram buckets
+const LATENCY_BUCKETS_SECS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Recent latency-to-proof associations kept for exemplars
+const EXEMPLAR_RING_SIZE: usize = 256;
+
+/// Content type of the `/api/metrics` exposition
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Content type of the `/api/metrics/prometheus` exposition
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Age (seconds) past which detailed records leave the history, unless the
+/// config sets another
+const DEFAULT_HISTORY_RETENTION_SECS: i64 = 3600;
+
+/// Snapshots buffered per stream subscriber before a slow one starts skipping
+const SNAPSHOT_CHANNEL_CAPACITY: usize = 16;
 
 pub struct ServerMetrics {
     start_time: Instant,
     stats: RwLock<ServerStats>,
     verification_history: RwLock<VecDeque<VerificationRecord>>,
+    // Per-second verification counts covering the load window. Unlike the
+    // detailed history this never drops data inside the window, so the load
+    // figure stays exact at any throughput.
+    load_buckets: RwLock<VecDeque<(i64, usize)>>,
+    load_window_secs: i64,
+    max_history_records: usize,
+    // Detailed records older than this are evicted; the load window only
+    // governs the load buckets
+    history_retention_secs: i64,
+    // Only sampled verifications are pushed into the detailed history
+    history_sampler: MetricsSampler,
+    // Cumulative counts are derived at export; these are per bucket, with
+    // the last slot for +Inf
+    latency_buckets: RwLock<[u64; LATENCY_BUCKETS_SECS.len() + 1]>,
+    // Present only when exemplars are enabled
+    exemplars: Option<RwLock<VecDeque<LatencyExemplar>>>,
+    verification_pool: Option<Arc<VerificationPool>>,
+    verification_cache: Option<Arc<VerificationCache>>,
+    // When each client last submitted; clients seen inside the load window
+    // count as active
+    client_activity: RwLock<HashMap<String, DateTime<Utc>>>,
+    snapshots: broadcast::Sender<ServerMetricsSnapshot>,
+}
+
+/// Links one observed latency to the proof that produced it
+#[derive(Debug, Clone)]
+struct LatencyExemplar {
+    proof_id: Uuid,
+    client_id: String,
+    latency_secs: f64,
+    timestamp: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,12 +73,11 @@ pub struct ServerStats {
     pub total_proofs_processed: usize,
     pub total_proofs_verified: usize,
     pub total_verification_errors: usize,
-    pub active_clients: usize,
     pub total_verification_time_ms: u64,
     pub last_update: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ServerMetricsSnapshot {
     pub uptime_seconds: u64,
     pub active_clients: usize,
@@ -32,6 +87,14 @@ pub struct ServerMetricsSnapshot {
     pub average_verification_time_ms: f64,
     pub verification_success_rate: f64,
     pub current_load: f64,
+    /// Verifications waiting for a slot in the verification pool
+    pub verification_queue_depth: usize,
+    /// Verifications currently holding a pool slot
+    pub verifications_in_flight: usize,
+    /// Resubmitted proofs answered from the verification cache
+    pub verification_cache_hits: u64,
+    /// Share of cache lookups that were hits
+    pub verification_cache_hit_rate: f64,
     pub system_metrics: SystemMetrics,
 }
 
@@ -45,6 +108,33 @@ struct VerificationRecord {
 
 impl ServerMetrics {
     pub fn new() -> Self {
+        Self::with_limits(60, 10_000)
+    }
+
+    /// Report the queue depth and slot usage of `pool` in snapshots
+    pub fn with_verification_pool(mut self, pool: Arc<VerificationPool>) -> Self {
+        self.verification_pool = Some(pool);
+        self
+    }
+
+    /// Report the hits of `cache`, if the server has one, in snapshots
+    pub fn with_verification_cache(mut self, cache: Option<Arc<VerificationCache>>) -> Self {
+        self.verification_cache = cache;
+        self
+    }
+
+    pub fn with_config(config: &ServerConfig) -> Self {
+        let mut metrics =
+            Self::with_limits(config.metrics_window_secs, config.metrics_history_max_records);
+        metrics.history_sampler = MetricsSampler::new(config.metrics_sample_every);
+        metrics.history_retention_secs = config.metrics_history_retention_secs.max(1) as i64;
+        if config.metrics_exemplars {
+            metrics.exemplars = Some(RwLock::new(VecDeque::with_capacity(EXEMPLAR_RING_SIZE)));
+        }
+        metrics
+    }
+
+    fn with_limits(load_window_secs: u64, max_history_records: usize) -> Self {
         Self {
             start_time: Instant::now(),
             stats: RwLock::new(ServerStats {
@@ -52,11 +142,21 @@ impl ServerMetrics {
                 total_proofs_processed: 0,
                 total_proofs_verified: 0,
                 total_verification_errors: 0,
-                active_clients: 0,
                 total_verification_time_ms: 0,
                 last_update: Utc::now(),
             }),
             verification_history: RwLock::new(VecDeque::new()),
+            load_buckets: RwLock::new(VecDeque::new()),
+            load_window_secs: load_window_secs.max(1) as i64,
+            max_history_records: max_history_records.max(1),
+            history_retention_secs: DEFAULT_HISTORY_RETENTION_SECS,
+            history_sampler: MetricsSampler::default(),
+            latency_buckets: RwLock::new([0; LATENCY_BUCKETS_SECS.len() + 1]),
+            exemplars: None,
+            verification_pool: None,
+            verification_cache: None,
+            client_activity: RwLock::new(HashMap::new()),
+            snapshots: broadcast::channel(SNAPSHOT_CHANNEL_CAPACITY).0,
         }
     }
 
@@ -79,6 +179,13 @@ impl ServerMetrics {
         
         stats.last_update = Utc::now();
 
+        self.record_load_sample(result.verification_timestamp);
+        self.latency_buckets.write()[latency_bucket(result.verification_time_ms)] += 1;
+
+        if !self.history_sampler.should_sample() {
+            return;
+        }
+
         // Add to verification history
         let mut history = self.verification_history.write();
         history.push_back(VerificationRecord {
@@ -88,8 +195,12 @@ impl ServerMetrics {
             client_id: result.verifier_id.clone(),
         });
 
-        // Keep only last 1000 records
-        if history.len() > 1000 {
+        // Evict by age first, then enforce the memory cap
+        let cutoff = Utc::now() - chrono::Duration::seconds(self.history_retention_secs);
+        while history.front().map_or(false, |record| record.timestamp < cutoff) {
+            history.pop_front();
+        }
+        while history.len() > self.max_history_records {
             history.pop_front();
         }
 
@@ -97,6 +208,200 @@ impl ServerMetrics {
                result.verified, result.verification_time_ms);
     }
 
+    /// Remember which proof produced a latency so a slow bucket can point at
+    /// it. No-op unless exemplars are enabled.
+    pub fn record_exemplar(&self, proof_id: Uuid, client_id: &str, result: &VerificationResult) {
+        let Some(exemplars) = &self.exemplars else {
+            return;
+        };
+
+        let mut ring = exemplars.write();
+        if ring.len() == EXEMPLAR_RING_SIZE {
+            ring.pop_front();
+        }
+        ring.push_back(LatencyExemplar {
+            proof_id,
+            client_id: client_id.to_string(),
+            latency_secs: result.verification_time_ms as f64 / 1000.0,
+            timestamp: result.verification_timestamp,
+        });
+    }
+
+    /// Every counter and gauge either exposition format exports, named without
+    /// the `_total` suffix counters get on their samples
+    fn metric_table(&self) -> Vec<Metric> {
+        use MetricKind::{Counter, Gauge};
+
+        let snapshot = self.get_current_snapshot();
+        let performance = self.get_performance_metrics();
+        let total_proof_requests = self.stats.read().total_proof_requests;
+        let metric = |name, kind, help, value| Metric { name, kind, help, value };
+        vec![
+            metric(
+                "zkp_fl_proof_requests",
+                Counter,
+                "Verify requests received",
+                total_proof_requests as f64,
+            ),
+            metric(
+                "zkp_fl_proofs_processed",
+                Counter,
+                "Proofs that completed verification",
+                snapshot.total_proofs_processed as f64,
+            ),
+            metric(
+                "zkp_fl_proofs_verified",
+                Counter,
+                "Proofs that verified successfully",
+                snapshot.total_proofs_verified as f64,
+            ),
+            metric(
+                "zkp_fl_verification_errors",
+                Counter,
+                "Verifications that failed with an error",
+                snapshot.total_verification_errors as f64,
+            ),
+            metric(
+                "zkp_fl_uptime_seconds",
+                Gauge,
+                "Time since the server started",
+                snapshot.uptime_seconds as f64,
+            ),
+            metric(
+                "zkp_fl_active_clients",
+                Gauge,
+                "Clients that submitted within the load window",
+                snapshot.active_clients as f64,
+            ),
+            metric(
+                "zkp_fl_verification_queue_depth",
+                Gauge,
+                "Verifications waiting for a pool slot",
+                snapshot.verification_queue_depth as f64,
+            ),
+            metric(
+                "zkp_fl_verification_cache_hits",
+                Counter,
+                "Resubmitted proofs answered from the verification cache",
+                snapshot.verification_cache_hits as f64,
+            ),
+            metric(
+                "zkp_fl_verification_cache_hit_rate",
+                Gauge,
+                "Share of verification cache lookups that were hits",
+                snapshot.verification_cache_hit_rate,
+            ),
+            metric(
+                "zkp_fl_throughput_proofs_per_second",
+                Gauge,
+                "Verifications per second over the load window",
+                performance.throughput_proofs_per_second,
+            ),
+            metric(
+                "zkp_fl_error_rate",
+                Gauge,
+                "Share of processed proofs that failed with an error",
+                performance.error_rate,
+            ),
+            metric(
+                "zkp_fl_verification_success_rate",
+                Gauge,
+                "Share of processed proofs that verified",
+                snapshot.verification_success_rate,
+            ),
+        ]
+    }
+
+    /// Counters and the latency histogram in OpenMetrics text format. Bucket
+    /// lines carry the most recent exemplar that fell into them.
+    pub fn render_openmetrics(&self) -> String {
+        let stats = self.stats.read().clone();
+        let buckets = *self.latency_buckets.read();
+        let mut bucket_exemplars: [Option<LatencyExemplar>; LATENCY_BUCKETS_SECS.len() + 1] =
+            Default::default();
+        if let Some(exemplars) = &self.exemplars {
+            for exemplar in exemplars.read().iter() {
+                let bucket = latency_bucket((exemplar.latency_secs * 1000.0).round() as u64);
+                bucket_exemplars[bucket] = Some(exemplar.clone());
+            }
+        }
+
+        let mut out = String::new();
+        for metric in self.metric_table() {
+            let _ = writeln!(out, "# TYPE {} {}", metric.name, metric.kind.as_str());
+            let _ = writeln!(out, "# HELP {} {}", metric.name, metric.help);
+            let sample = metric.kind.sample_suffix();
+            let _ = writeln!(out, "{}{} {}", metric.name, sample, metric.value);
+        }
+
+        let name = "zkp_fl_verification_latency_seconds";
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let _ = writeln!(out, "# UNIT {} seconds", name);
+        let _ = writeln!(out, "# HELP {} Time spent verifying a proof", name);
+        let mut cumulative = 0;
+        for (i, count) in buckets.iter().enumerate() {
+            cumulative += count;
+            let le = LATENCY_BUCKETS_SECS
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), |bound| bound.to_string());
+            let _ = write!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, cumulative);
+            if let Some(exemplar) = &bucket_exemplars[i] {
+                let _ = write!(
+                    out,
+                    " # {{proof_id=\"{}\",client_id=\"{}\"}} {} {:.3}",
+                    exemplar.proof_id,
+                    escape_label_value(&exemplar.client_id),
+                    exemplar.latency_secs,
+                    exemplar.timestamp.timestamp_millis() as f64 / 1000.0
+                );
+            }
+            out.push('\n');
+        }
+        let _ = writeln!(
+            out,
+            "{}_sum {}",
+            name,
+            stats.total_verification_time_ms as f64 / 1000.0
+        );
+        let _ = writeln!(out, "{}_count {}", name, cumulative);
+        out.push_str("# EOF\n");
+        out
+    }
+
+    /// Snapshot and performance figures in the Prometheus text format, with
+    /// recent verification time percentiles as a summary
+    pub fn render_prometheus(&self) -> String {
+        let snapshot = self.get_current_snapshot();
+        let performance = self.get_performance_metrics();
+
+        let mut out = String::new();
+        for metric in self.metric_table() {
+            let name = format!("{}{}", metric.name, metric.kind.sample_suffix());
+            let _ = writeln!(out, "# HELP {} {}", name, metric.help);
+            let _ = writeln!(out, "# TYPE {} {}", name, metric.kind.as_str());
+            let _ = writeln!(out, "{} {}", name, metric.value);
+        }
+
+        let name = "zkp_fl_verification_time_seconds";
+        let _ = writeln!(out, "# HELP {} Verification time of recent proofs", name);
+        let _ = writeln!(out, "# TYPE {} summary", name);
+        for (quantile, millis) in [
+            ("0.5", performance.p50_verification_time_ms),
+            ("0.95", performance.p95_verification_time_ms),
+            ("0.99", performance.p99_verification_time_ms),
+        ] {
+            let _ = writeln!(out, "{}{{quantile=\"{}\"}} {}", name, quantile, millis / 1000.0);
+        }
+        let _ = writeln!(
+            out,
+            "{}_sum {}",
+            name,
+            performance.average_verification_time_ms * snapshot.total_proofs_processed as f64 / 1000.0
+        );
+        let _ = writeln!(out, "{}_count {}", name, snapshot.total_proofs_processed);
+        out
+    }
+
     pub async fn increment_verification_errors(&self) {
         let mut stats = self.stats.write();
         stats.total_verification_errors += 1;
@@ -105,12 +410,22 @@ impl ServerMetrics {
         debug!("Total verification errors: {}", stats.total_verification_errors);
     }
 
-    pub async fn set_active_clients(&self, count: usize) {
-        let mut stats = self.stats.write();
-        stats.active_clients = count;
-        stats.last_update = Utc::now();
-        
-        debug!("Active clients: {}", count);
+    /// Note a submission from `client_id`, keeping it active for the load window
+    pub fn record_client_activity(&self, client_id: &str) {
+        let now = Utc::now();
+        let cutoff = now - chrono::Duration::seconds(self.load_window_secs);
+        let mut activity = self.client_activity.write();
+        activity.retain(|_, last_seen| *last_seen >= cutoff);
+        activity.insert(client_id.to_string(), now);
+    }
+
+    fn count_active_clients(&self) -> usize {
+        let cutoff = Utc::now() - chrono::Duration::seconds(self.load_window_secs);
+        self.client_activity
+            .read()
+            .values()
+            .filter(|last_seen| **last_seen >= cutoff)
+            .count()
     }
 
     pub fn get_current_snapshot(&self) -> ServerMetricsSnapshot {
@@ -131,33 +446,67 @@ impl ServerMetrics {
 
         // Calculate current load based on recent activity
         let current_load = self.calculate_current_load();
+        let pool = self.verification_pool.as_deref();
+        let cache = self.verification_cache.as_deref();
 
         ServerMetricsSnapshot {
             uptime_seconds: uptime,
-            active_clients: stats.active_clients,
+            active_clients: self.count_active_clients(),
             total_proofs_processed: stats.total_proofs_processed,
             total_proofs_verified: stats.total_proofs_verified,
             total_verification_errors: stats.total_verification_errors,
             average_verification_time_ms,
             verification_success_rate,
             current_load,
+            verification_queue_depth: pool.map_or(0, |pool| pool.queue_depth()),
+            verifications_in_flight: pool.map_or(0, |pool| pool.in_flight()),
+            verification_cache_hits: cache.map_or(0, |cache| cache.hits()),
+            verification_cache_hit_rate: cache.map_or(0.0, |cache| cache.hit_rate()),
             system_metrics: SystemMetrics::current(), // This would collect actual system metrics
         }
     }
 
+    /// Take a snapshot and send it to every live stream subscriber
+    pub fn publish_snapshot(&self) -> ServerMetricsSnapshot {
+        let snapshot = self.get_current_snapshot();
+        // No subscribers is not an error
+        let _ = self.snapshots.send(snapshot.clone());
+        snapshot
+    }
+
+    /// Receive every snapshot published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerMetricsSnapshot> {
+        self.snapshots.subscribe()
+    }
+
+    fn record_load_sample(&self, timestamp: DateTime<Utc>) {
+        let second = timestamp.timestamp();
+        let mut buckets = self.load_buckets.write();
+
+        match buckets.back_mut() {
+            Some((bucket_second, count)) if *bucket_second == second => *count += 1,
+            _ => buckets.push_back((second, 1)),
+        }
+
+        let oldest = Utc::now().timestamp() - self.load_window_secs;
+        while buckets.front().map_or(false, |(bucket_second, _)| *bucket_second <= oldest) {
+            buckets.pop_front();
+        }
+    }
+
     fn calculate_current_load(&self) -> f64 {
-        let history = self.verification_history.read();
-        let now = Utc::now();
-        let one_minute_ago = now - chrono::Duration::minutes(1);
+        let buckets = self.load_buckets.read();
+        let oldest = Utc::now().timestamp() - self.load_window_secs;
 
-        // Count verifications in the last minute
-        let recent_verifications = history
+        // Count verifications inside the configured window
+        let recent_verifications: usize = buckets
             .iter()
-            .filter(|record| record.timestamp >= one_minute_ago)
-            .count();
+            .filter(|(second, _)| *second > oldest)
+            .map(|(_, count)| count)
+            .sum();
 
         // Calculate load as verifications per second
-        recent_verifications as f64 / 60.0
+        recent_verifications as f64 / self.load_window_secs as f64
     }
 
     pub fn get_verification_history(&self, minutes: i64) -> Vec<VerificationRecord> {
@@ -178,6 +527,7 @@ impl ServerMetrics {
         // Calculate percentiles from recent verification times
         let mut recent_times: Vec<u64> = history
             .iter()
+            .rev()
             .take(100) // Last 100 verifications
             .map(|r| r.verification_time_ms)
             .collect();
@@ -227,13 +577,18 @@ impl ServerMetrics {
             total_proofs_processed: 0,
             total_proofs_verified: 0,
             total_verification_errors: 0,
-            active_clients: 0,
             total_verification_time_ms: 0,
             last_update: Utc::now(),
         };
+        self.client_activity.write().clear();
 
         let mut history = self.verification_history.write();
         history.clear();
+        self.load_buckets.write().clear();
+        *self.latency_buckets.write() = [0; LATENCY_BUCKETS_SECS.len() + 1];
+        if let Some(exemplars) = &self.exemplars {
+            exemplars.write().clear();
+        }
 
         info!("Server metrics reset");
     }
@@ -249,14 +604,52 @@ pub struct PerformanceMetrics {
     pub error_rate: f64,
 }
 
-fn percentile(sorted_data: &[u64], p: f64) -> f64 {
-    if sorted_data.is_empty() {
-        return 0.0;
+/// One row of the table both exposition formats are rendered from
+struct Metric {
+    name: &'static str,
+    kind: MetricKind,
+    help: &'static str,
+    value: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+        }
     }
-    
-    let index = (sorted_data.len() as f64 * p) as usize;
-    let index = index.min(sorted_data.len() - 1);
-    sorted_data[index] as f64
+
+    /// Suffix of the sample name, which for counters differs from the family
+    fn sample_suffix(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "_total",
+            MetricKind::Gauge => "",
+        }
+    }
+}
+
+/// Index of the histogram bucket a latency falls into
+fn latency_bucket(latency_ms: u64) -> usize {
+    let secs = latency_ms as f64 / 1000.0;
+    LATENCY_BUCKETS_SECS
+        .iter()
+        .position(|bound| secs <= *bound)
+        .unwrap_or(LATENCY_BUCKETS_SECS.len())
+}
+
+/// Escape a value for use inside a quoted OpenMetrics label
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
 impl Default for ServerMetrics {
@@ -299,6 +692,218 @@ mod tests {
         assert_eq!(snapshot.average_verification_time_ms, 100.0);
     }
 
+    #[tokio::test]
+    async fn test_history_outlives_the_load_window() {
+        let metrics = ServerMetrics::with_limits(1, 1_000);
+        let result = |verification_time_ms, age_secs| VerificationResult {
+            verified: true,
+            verification_time_ms,
+            verifier_id: "test".to_string(),
+            verification_timestamp: Utc::now() - chrono::Duration::seconds(age_secs),
+            error_message: None,
+        };
+
+        // Older than the load window, well inside the history retention
+        for _ in 0..100 {
+            metrics.record_verification_result(&result(1, 120)).await;
+        }
+        assert_eq!(metrics.get_verification_history(5).len(), 100);
+        assert_eq!(metrics.get_current_snapshot().current_load, 0.0);
+
+        // Percentiles follow the newest verifications, not the oldest
+        for _ in 0..100 {
+            metrics.record_verification_result(&result(500, 0)).await;
+        }
+        assert_eq!(metrics.get_verification_history(5).len(), 200);
+        assert_eq!(metrics.get_performance_metrics().p50_verification_time_ms, 500.0);
+
+        // Past the retention the records do go
+        let mut metrics = ServerMetrics::with_limits(1, 1_000);
+        metrics.history_retention_secs = 60;
+        metrics.record_verification_result(&result(1, 120)).await;
+        assert!(metrics.get_verification_history(5).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_is_exact_when_history_is_capped() {
+        let metrics = ServerMetrics::with_limits(60, 100);
+
+        for _ in 0..600 {
+            let verification_result = VerificationResult {
+                verified: true,
+                verification_time_ms: 1,
+                verifier_id: "test".to_string(),
+                verification_timestamp: Utc::now(),
+                error_message: None,
+            };
+            metrics.record_verification_result(&verification_result).await;
+        }
+
+        // History is capped, but the load window still sees every verification
+        assert_eq!(metrics.verification_history.read().len(), 100);
+        assert_eq!(metrics.get_current_snapshot().current_load, 600.0 / 60.0);
+    }
+
+    #[tokio::test]
+    async fn test_sampling_keeps_counters_exact() {
+        let mut metrics = ServerMetrics::new();
+        metrics.history_sampler = MetricsSampler::new(10);
+
+        for _ in 0..100 {
+            let verification_result = VerificationResult {
+                verified: true,
+                verification_time_ms: 5,
+                verifier_id: "test".to_string(),
+                verification_timestamp: Utc::now(),
+                error_message: None,
+            };
+            metrics.record_verification_result(&verification_result).await;
+        }
+
+        assert_eq!(metrics.verification_history.read().len(), 10);
+        let snapshot = metrics.get_current_snapshot();
+        assert_eq!(snapshot.total_proofs_processed, 100);
+        assert_eq!(snapshot.current_load, 100.0 / 60.0);
+    }
+
+    #[tokio::test]
+    async fn test_openmetrics_exemplars() {
+        let mut metrics = ServerMetrics::new();
+        let proof_id = Uuid::new_v4();
+        let verification_result = VerificationResult {
+            verified: true,
+            verification_time_ms: 80,
+            verifier_id: "test".to_string(),
+            verification_timestamp: Utc::now(),
+            error_message: None,
+        };
+
+        // Without exemplars enabled nothing is linked
+        metrics.record_verification_result(&verification_result).await;
+        metrics.record_exemplar(proof_id, "client_1", &verification_result);
+        let plain = metrics.render_openmetrics();
+        assert!(plain.contains("zkp_fl_verification_latency_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(!plain.contains("proof_id"));
+        assert!(plain.ends_with("# EOF\n"));
+
+        metrics.exemplars = Some(RwLock::new(VecDeque::new()));
+        metrics.record_exemplar(proof_id, "client_1", &verification_result);
+        let rendered = metrics.render_openmetrics();
+        let bucket = rendered
+            .lines()
+            .find(|line| {
+                line.starts_with("zkp_fl_verification_latency_seconds_bucket{le=\"0.1\"}")
+            })
+            .unwrap();
+        assert!(bucket.contains(&format!(
+            "# {{proof_id=\"{}\",client_id=\"client_1\"}} 0.08",
+            proof_id
+        )));
+        // Exemplars stay on the bucket the latency fell into
+        assert!(!rendered
+            .lines()
+            .any(|line| line.contains("le=\"0.05\"") && line.contains("proof_id")));
+        assert!(rendered.contains("zkp_fl_verification_latency_seconds_count 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_both_formats_export_the_same_metrics() {
+        let metrics = ServerMetrics::new();
+        metrics.increment_proof_requests().await;
+        let verification_result = VerificationResult {
+            verified: true,
+            verification_time_ms: 20,
+            verifier_id: "test".to_string(),
+            verification_timestamp: Utc::now(),
+            error_message: None,
+        };
+        metrics.record_verification_result(&verification_result).await;
+
+        let openmetrics = metrics.render_openmetrics();
+        let prometheus = metrics.render_prometheus();
+        for metric in metrics.metric_table() {
+            let kind = metric.kind.as_str();
+            assert!(openmetrics.contains(&format!("# TYPE {} {}\n", metric.name, kind)));
+            let sample = format!("{}{}", metric.name, metric.kind.sample_suffix());
+            assert!(prometheus.contains(&format!("# TYPE {} {}\n", sample, kind)));
+            for rendered in [&openmetrics, &prometheus] {
+                assert!(rendered.lines().any(|line| line.starts_with(&format!("{} ", sample))));
+            }
+        }
+        for rendered in [&openmetrics, &prometheus] {
+            assert!(rendered.contains("zkp_fl_proofs_verified_total 1\n"));
+            assert!(!rendered.contains("zkpfl_"));
+        }
+    }
+
+    /// Check every line against the Prometheus text format and return the
+    /// samples by name (labels included)
+    fn parse_prometheus(text: &str) -> std::collections::HashMap<String, f64> {
+        let valid_name = |name: &str| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        };
+
+        let mut typed = std::collections::HashSet::new();
+        let mut samples = std::collections::HashMap::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let keyword = parts.next().unwrap();
+                let name = parts.next().unwrap();
+                assert!(valid_name(name), "bad metric name in {:?}", line);
+                if keyword == "TYPE" {
+                    let kind = parts.next().unwrap();
+                    assert!(["counter", "gauge", "summary", "histogram", "untyped"].contains(&kind));
+                    assert!(typed.insert(name.to_string()), "duplicate TYPE for {}", name);
+                } else {
+                    assert_eq!(keyword, "HELP", "unexpected comment {:?}", line);
+                }
+                continue;
+            }
+
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let name = series.split('{').next().unwrap();
+            assert!(valid_name(name), "bad metric name in {:?}", line);
+            if let Some(labels) = series.strip_prefix(name).filter(|labels| !labels.is_empty()) {
+                assert!(labels.starts_with('{') && labels.ends_with('}'), "bad labels in {:?}", line);
+            }
+            let family = ["_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix).filter(|base| typed.contains(*base)))
+                .unwrap_or(name);
+            assert!(typed.contains(family), "{} has no preceding TYPE line", name);
+            samples.insert(series.to_string(), value.parse::<f64>().unwrap());
+        }
+        samples
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_export() {
+        let metrics = ServerMetrics::new();
+        for (verified, verification_time_ms) in [(true, 100), (false, 300)] {
+            metrics.increment_proof_requests().await;
+            metrics.record_verification_result(&VerificationResult {
+                verified,
+                verification_time_ms,
+                verifier_id: "test".to_string(),
+                verification_timestamp: Utc::now(),
+                error_message: None,
+            }).await;
+        }
+
+        let samples = parse_prometheus(&metrics.render_prometheus());
+        assert_eq!(samples["zkp_fl_proofs_processed_total"], 2.0);
+        assert_eq!(samples["zkp_fl_proofs_verified_total"], 1.0);
+        assert_eq!(samples["zkp_fl_proof_requests_total"], 2.0);
+        assert_eq!(samples["zkp_fl_verification_success_rate"], 0.5);
+        assert_eq!(samples["zkp_fl_verification_time_seconds{quantile=\"0.99\"}"], 0.3);
+        assert!((samples["zkp_fl_verification_time_seconds_sum"] - 0.4).abs() < 1e-9);
+        assert_eq!(samples["zkp_fl_verification_time_seconds_count"], 2.0);
+        assert!(samples.contains_key("zkp_fl_uptime_seconds"));
+    }
+
     #[test]
     fn test_percentile_calculation() {
         let data = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -1,4 +1,35 @@
+use common::{Result, ZkpFlError};
+use futures::Stream;
+use hyper::server::accept;
+use hyper::service::{make_service_fn, service_fn, Service};
+use hyper::{Body, Request, Response};
+use log::{debug, error, warn};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Ports tried after the configured one when `--auto-port` is set
+const AUTO_PORT_ATTEMPTS: u16 = 10;
+
+/// Handshaken TLS connections waiting to be served
+const TLS_ACCEPT_BACKLOG: usize = 128;
+
+/// Connections that have not finished the TLS handshake by then are dropped
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept (e.g. out of file descriptors) before retrying
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
 
 /// Server status information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,3 +62,246 @@ pub struct BatchVerificationResult {
     pub verification_time_ms: u64,
     pub error: Option<String>,
 }
+
+/// Counts requests between arrival and reply, so a shutdown can report how
+/// many it waited for
+#[derive(Debug, Default)]
+pub struct InFlightRequests(AtomicUsize);
+
+/// Keeps a request counted as in flight until dropped
+pub struct InFlightGuard(Arc<InFlightRequests>);
+
+impl InFlightRequests {
+    pub fn enter(self: &Arc<Self>) -> InFlightGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(self.clone())
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Address a request's connection came from. warp only knows it for sockets
+/// it accepted itself, so `serve` attaches it to every request instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerAddr(pub SocketAddr);
+
+/// An accepted connection that knows its peer
+pub trait Connection: AsyncRead + AsyncWrite + Send + Unpin + 'static {
+    fn peer_addr(&self) -> Option<SocketAddr>;
+}
+
+impl Connection for TcpStream {
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        TcpStream::peer_addr(self).ok()
+    }
+}
+
+impl Connection for TlsStream<TcpStream> {
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.get_ref().0.peer_addr().ok()
+    }
+}
+
+/// Read and check the certificate chain and private key, so bad PEM is a
+/// startup error rather than a panic once serving begins
+pub fn load_tls(cert_path: &str, key_path: &str) -> Result<TlsAcceptor> {
+    let read_pem = |path: &str| {
+        std::fs::read(path)
+            .map_err(|e| ZkpFlError::Config(format!("Failed to read TLS file {}: {}", path, e)))
+    };
+
+    let cert_pem = read_pem(cert_path)?;
+    let certs = rustls_pemfile::certs(&mut cert_pem.as_slice())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| {
+            ZkpFlError::Config(format!("Invalid TLS certificate in {}: {}", cert_path, e))
+        })?;
+    if certs.is_empty() {
+        return Err(ZkpFlError::Config(format!("No TLS certificate found in {}", cert_path)));
+    }
+
+    let key_pem = read_pem(key_path)?;
+    let key = rustls_pemfile::private_key(&mut key_pem.as_slice())
+        .map_err(|e| ZkpFlError::Config(format!("Invalid TLS key in {}: {}", key_path, e)))?
+        .ok_or_else(|| ZkpFlError::Config(format!("No TLS private key found in {}", key_path)))?;
+
+    let mut config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| ZkpFlError::Config(format!("Unusable TLS certificate or key: {}", e)))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// TLS connections accepted on `listener`. Handshakes run concurrently, so a
+/// slow client holds up no one else; failed ones are logged and dropped.
+pub fn tls_incoming(
+    listener: tokio::net::TcpListener,
+    acceptor: TlsAcceptor,
+) -> impl Stream<Item = std::io::Result<TlsStream<TcpStream>>> {
+    let (sender, receiver) = tokio::sync::mpsc::channel(TLS_ACCEPT_BACKLOG);
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        warn!("Failed to accept a connection: {}", e);
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                        continue;
+                    }
+                },
+                // The server is done with the stream, so stop listening
+                _ = sender.closed() => break,
+            };
+
+            let acceptor = acceptor.clone();
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = sender.send(Ok(stream)).await;
+                    }
+                    Ok(Err(e)) => debug!("TLS handshake with {} failed: {}", peer, e),
+                    Err(_) => debug!("TLS handshake with {} timed out", peer),
+                }
+            });
+        }
+    });
+    ReceiverStream::new(receiver)
+}
+
+/// Serve `service` on the connections `incoming` yields until `shutdown`
+/// resolves, tagging each request with the `PeerAddr` it came from
+pub async fn serve<I, C, S>(incoming: I, service: S, shutdown: impl Future<Output = ()>)
+where
+    I: Stream<Item = std::io::Result<C>>,
+    C: Connection,
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    let make_service = make_service_fn(move |connection: &C| {
+        let peer = connection.peer_addr();
+        let service = service.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut request: Request<Body>| {
+                if let Some(peer) = peer {
+                    request.extensions_mut().insert(PeerAddr(peer));
+                }
+                service.clone().call(request)
+            }))
+        }
+    });
+
+    let served = hyper::Server::builder(accept::from_stream(incoming))
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown)
+        .await;
+    if let Err(e) = served {
+        error!("Server error: {}", e);
+    }
+}
+
+/// Bind the server socket up front so an occupied port is reported clearly.
+/// With `auto_port`, the next few ports are tried before giving up.
+pub fn bind_listener(addr: SocketAddr, auto_port: bool) -> Result<TcpListener> {
+    let attempts = if auto_port { AUTO_PORT_ATTEMPTS } else { 0 };
+
+    for offset in 0..=attempts {
+        let Some(port) = addr.port().checked_add(offset) else {
+            break;
+        };
+        let candidate = SocketAddr::new(addr.ip(), port);
+
+        match TcpListener::bind(candidate) {
+            Ok(listener) => {
+                listener.set_nonblocking(true)?;
+                return Ok(listener);
+            }
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {
+                if auto_port {
+                    warn!("Port {} is already in use, trying the next one", port);
+                }
+            }
+            Err(e) => {
+                return Err(ZkpFlError::Config(format!(
+                    "Failed to bind {}: {}",
+                    candidate, e
+                )))
+            }
+        }
+    }
+
+    Err(ZkpFlError::Config(if auto_port {
+        format!(
+            "Ports {}-{} are all in use",
+            addr.port(),
+            addr.port().saturating_add(attempts)
+        )
+    } else {
+        format!(
+            "Port {} is already in use; pick another with --port or pass --auto-port",
+            addr.port()
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_listener_handles_occupied_port() {
+        let occupied = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = occupied.local_addr().unwrap();
+
+        match bind_listener(addr, false) {
+            Err(ZkpFlError::Config(message)) => {
+                assert!(message.contains(&addr.port().to_string()));
+            }
+            other => panic!("expected a config error, got {:?}", other.map(|l| l.local_addr())),
+        }
+
+        // The neighbouring port may itself be taken, so only require progress
+        if let Ok(listener) = bind_listener(addr, true) {
+            assert_ne!(listener.local_addr().unwrap().port(), addr.port());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_their_peer_address() {
+        use warp::Filter;
+
+        let listener = bind_listener("127.0.0.1:0".parse().unwrap(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+        let routes = warp::ext::optional::<PeerAddr>()
+            .map(|peer: Option<PeerAddr>| peer.map(|PeerAddr(peer)| peer.ip().to_string()))
+            .map(|peer: Option<String>| peer.unwrap_or_default());
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(
+            tokio_stream::wrappers::TcpListenerStream::new(listener),
+            warp::service(routes),
+            async {
+                stopped.await.ok();
+            },
+        ));
+
+        let body = reqwest::get(format!("http://{}/", addr)).await.unwrap().text().await.unwrap();
+        assert_eq!(body, "127.0.0.1");
+
+        stop.send(()).unwrap();
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use warp::http::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+struct TokenBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Where a client stands against its budget, reported back in headers so
+/// clients can throttle themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Largest burst a client may send at once
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the next request would be allowed; zero while requests remain
+    pub reset_after: Duration,
+    /// The request that produced this status was over budget
+    pub exceeded: bool,
+}
+
+impl RateLimitStatus {
+    /// Whole seconds a rejected client should wait, never zero
+    pub fn retry_after_secs(&self) -> u64 {
+        self.reset_after.as_secs_f64().ceil().max(1.0) as u64
+    }
+
+    /// Add `X-RateLimit-*` headers, plus `Retry-After` when over budget
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+        if self.exceeded {
+            headers.insert(RETRY_AFTER, HeaderValue::from(self.retry_after_secs()));
+        }
+    }
+}
+
+/// Checks between sweeps for buckets that have refilled
+const PRUNE_EVERY_CHECKS: u64 = 256;
+
+/// Token bucket per requester limiting verify requests. Each requester may
+/// burst up to the bucket capacity, after which requests are allowed only as
+/// fast as the bucket refills. Buckets that have refilled are dropped, since a new
+/// bucket would be full too, so idle requesters take no memory.
+pub struct RateLimiter {
+    capacity: u32,
+    refill_per_sec: f64,
+    clients: DashMap<String, TokenBucket>,
+    checks: AtomicU64,
+}
+
+impl RateLimiter {
+    /// Refill `requests_per_minute` tokens a minute into buckets holding
+    /// `burst`, or a full minute's worth when `burst` is 0
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = if burst == 0 { requests_per_minute } else { burst };
+        Self::with_refill(capacity, f64::from(requests_per_minute) / 60.0)
+    }
+
+    pub fn with_refill(capacity: u32, refill_per_sec: f64) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            refill_per_sec,
+            clients: DashMap::new(),
+            checks: AtomicU64::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Buckets currently tracked
+    #[cfg(test)]
+    fn tracked_clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Drop buckets that have refilled to capacity
+    pub fn prune(&self) {
+        let now = Instant::now();
+        let capacity = f64::from(self.capacity);
+        self.clients.retain(|_, bucket| {
+            let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+            bucket.tokens + elapsed * self.refill_per_sec < capacity
+        });
+    }
+
+    /// Take a token for a request from `client_id`
+    pub fn check(&self, client_id: &str) -> RateLimitStatus {
+        let checks = self.checks.fetch_add(1, Ordering::Relaxed) + 1;
+        if checks.is_multiple_of(PRUNE_EVERY_CHECKS) {
+            self.prune();
+        }
+
+        let now = Instant::now();
+        let capacity = f64::from(self.capacity);
+        let mut bucket = self
+            .clients
+            .entry(client_id.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: capacity,
+                refilled: now,
+            });
+
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(capacity);
+        bucket.refilled = now;
+
+        let exceeded = bucket.tokens < 1.0;
+        if !exceeded {
+            bucket.tokens -= 1.0;
+        }
+        let reset_after = if bucket.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::try_from_secs_f64((1.0 - bucket.tokens) / self.refill_per_sec)
+                .unwrap_or(Duration::MAX)
+        };
+
+        RateLimitStatus {
+            limit: self.capacity,
+            remaining: bucket.tokens as u32,
+            reset_after,
+            exceeded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_is_per_client_and_refills() {
+        // Two requests at once, then one every 50ms
+        let limiter = RateLimiter::with_refill(2, 20.0);
+
+        assert_eq!(limiter.check("a").remaining, 1);
+        assert_eq!(limiter.check("a").remaining, 0);
+        let rejected = limiter.check("a");
+        assert!(rejected.exceeded);
+        assert!(rejected.reset_after <= Duration::from_millis(50));
+        assert!(rejected.retry_after_secs() >= 1);
+
+        let mut headers = HeaderMap::new();
+        rejected.apply_headers(&mut headers);
+        assert_eq!(headers["x-ratelimit-limit"], "2");
+        assert_eq!(headers["x-ratelimit-remaining"], "0");
+        assert!(headers.contains_key(RETRY_AFTER));
+
+        // Other clients have their own bucket
+        assert!(!limiter.check("b").exceeded);
+
+        std::thread::sleep(Duration::from_millis(60));
+        let status = limiter.check("a");
+        assert!(!status.exceeded);
+        assert_eq!(status.remaining, 0);
+        assert!(limiter.check("a").exceeded);
+
+        // The bucket never refills past its capacity
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(limiter.check("a").remaining, 1);
+    }
+
+    #[test]
+    fn test_refilled_buckets_are_pruned() {
+        let limiter = RateLimiter::with_refill(2, 20.0);
+        limiter.check("a");
+        limiter.check("b");
+        limiter.check("b");
+        assert_eq!(limiter.tracked_clients(), 2);
+
+        // "a" is back to capacity after 50ms, "b" needs 100ms
+        std::thread::sleep(Duration::from_millis(70));
+        limiter.prune();
+        assert_eq!(limiter.tracked_clients(), 1);
+        std::thread::sleep(Duration::from_millis(60));
+        limiter.prune();
+        assert_eq!(limiter.tracked_clients(), 0);
+
+        // A pruned client starts again from a full bucket
+        assert_eq!(limiter.check("b").remaining, 1);
+
+        // Checks sweep on their own, so one-off peers do not accumulate
+        let limiter = RateLimiter::with_refill(1, 1000.0);
+        for i in 0..PRUNE_EVERY_CHECKS {
+            limiter.check(&i.to_string());
+            std::thread::sleep(Duration::from_micros(10));
+        }
+        assert!(limiter.tracked_clients() < PRUNE_EVERY_CHECKS as usize);
+    }
+
+    #[test]
+    fn test_burst_defaults_to_a_minute_of_requests() {
+        let limiter = RateLimiter::new(3, 0);
+        for _ in 0..3 {
+            assert!(!limiter.check("a").exceeded);
+        }
+        let rejected = limiter.check("a");
+        assert!(rejected.exceeded);
+        assert_eq!(rejected.retry_after_secs(), 20);
+
+        assert_eq!(RateLimiter::new(60, 5).check("a").limit, 5);
+    }
+}
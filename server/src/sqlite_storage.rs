@@ -0,0 +1,442 @@
+use crate::storage::{benchmark_from_proofs, ProofPage, ProofQuery, ProofStore, StorageStats};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::{
+    MultiClientBenchmark, ProofBatch, Result, RoundRecord, ServerConfig, ZkpFlError, ZkpProof,
+};
+use log::{debug, info, warn};
+use parking_lot::{Mutex, RwLock};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Params};
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Database file created inside `proof_storage_path`
+const DATABASE_FILE: &str = "proofs.db";
+
+/// Proofs and batches are stored as JSON alongside the columns queries filter on
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS proofs (
+        proof_id TEXT PRIMARY KEY,
+        client_id TEXT NOT NULL,
+        timestamp_us INTEGER NOT NULL,
+        verified INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_proofs_client_id ON proofs (client_id);
+    CREATE INDEX IF NOT EXISTS idx_proofs_timestamp ON proofs (timestamp_us);
+    CREATE INDEX IF NOT EXISTS idx_proofs_verified ON proofs (verified);
+    CREATE TABLE IF NOT EXISTS batches (
+        batch_id TEXT PRIMARY KEY,
+        timestamp_us INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_batches_timestamp ON batches (timestamp_us);
+    CREATE TABLE IF NOT EXISTS rounds (
+        round INTEGER PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+";
+
+/// Backend keeping proofs in a single SQLite database. Nothing is loaded into
+/// memory on startup, and lookups by client or time go through indexes.
+pub struct SqliteProofStore {
+    // Queries are short, so one connection behind a lock is enough
+    conn: Mutex<Connection>,
+    path: PathBuf,
+    stats: RwLock<StorageStats>,
+}
+
+fn storage_error(e: rusqlite::Error) -> ZkpFlError {
+    ZkpFlError::Storage(e.to_string())
+}
+
+impl SqliteProofStore {
+    pub fn new(config: &ServerConfig, clear_on_startup: bool) -> Result<Self> {
+        let storage_path = PathBuf::from(&config.proof_storage_path);
+        std::fs::create_dir_all(&storage_path)?;
+        let path = storage_path.join(DATABASE_FILE);
+
+        let conn = Connection::open(&path).map_err(storage_error)?;
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+
+        if clear_on_startup {
+            info!("Clearing proof storage on startup");
+            conn.execute_batch("DELETE FROM proofs; DELETE FROM batches; DELETE FROM rounds;")
+                .map_err(storage_error)?;
+        }
+
+        info!("SQLite proof storage initialized at: {:?}", path);
+        Ok(Self {
+            conn: Mutex::new(conn),
+            path,
+            stats: RwLock::new(StorageStats::default()),
+        })
+    }
+
+    fn select_proofs<P: Params>(&self, sql: &str, params: P) -> Result<Vec<ZkpProof>> {
+        self.select_json(sql, params)
+    }
+
+    /// Deserialize the `data` column of every row `sql` selects
+    fn select_json<T: DeserializeOwned, P: Params>(&self, sql: &str, params: P) -> Result<Vec<T>> {
+        let conn = self.conn.lock();
+        let mut statement = conn.prepare_cached(sql).map_err(storage_error)?;
+        let rows = statement
+            .query_map(params, |row| row.get::<_, String>(0))
+            .map_err(storage_error)?;
+
+        let mut values = Vec::new();
+        for data in rows {
+            values.push(serde_json::from_str(&data.map_err(storage_error)?)?);
+        }
+        Ok(values)
+    }
+
+    /// The trait's list queries cannot fail, so errors are logged and yield no proofs
+    fn list_proofs<P: Params>(&self, sql: &str, params: P) -> Vec<ZkpProof> {
+        self.select_proofs(sql, params).unwrap_or_else(|e| {
+            warn!("Failed to query proofs: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Deserialize the `data` column of the single row `sql` selects for `id`
+    fn load_row<T: DeserializeOwned>(&self, sql: &str, id: &Uuid) -> Result<Option<T>> {
+        let data: Option<String> = self
+            .conn
+            .lock()
+            .query_row(sql, params![id.to_string()], |row| row.get(0))
+            .optional()
+            .map_err(storage_error)?;
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+}
+
+#[async_trait]
+impl ProofStore for SqliteProofStore {
+    async fn store_proof(&self, proof: ZkpProof) -> Result<()> {
+        debug!("Storing proof {} from client {}", proof.proof_id, proof.client_id);
+
+        let data = serde_json::to_string(&proof)?;
+        let conn = self.conn.lock();
+        let previous: Option<bool> = conn
+            .query_row(
+                "SELECT verified FROM proofs WHERE proof_id = ?1",
+                params![proof.proof_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(storage_error)?;
+        conn
+            .execute(
+                "INSERT OR REPLACE INTO proofs (proof_id, client_id, timestamp_us, verified, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    proof.proof_id.to_string(),
+                    proof.client_id,
+                    proof.timestamp.timestamp_micros(),
+                    proof.is_verified(),
+                    data
+                ],
+            )
+            .map_err(storage_error)?;
+        drop(conn);
+
+        let was_verified = previous.unwrap_or(false);
+        self.stats.write().record_store(previous.is_none(), was_verified, proof.is_verified());
+        Ok(())
+    }
+
+    async fn get_proof(&self, proof_id: &Uuid) -> Option<ZkpProof> {
+        self
+            .load_row("SELECT data FROM proofs WHERE proof_id = ?1", proof_id)
+            .unwrap_or_else(|e| {
+                warn!("Failed to load proof {}: {}", proof_id, e);
+                None
+            })
+    }
+
+    async fn update_proof_verification(&self, proof_id: &Uuid, verified: bool) -> Result<()> {
+        let exists = self
+            .conn
+            .lock()
+            .query_row(
+                "SELECT 1 FROM proofs WHERE proof_id = ?1",
+                params![proof_id.to_string()],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(storage_error)?
+            .is_some();
+        if exists && verified {
+            self.stats.write().total_proofs_verified += 1;
+        }
+        Ok(())
+    }
+
+    async fn create_batch(&self, client_proofs: Vec<ZkpProof>) -> Result<Uuid> {
+        let batch = ProofBatch::new(client_proofs);
+        info!("Creating proof batch {} with {} proofs", batch.batch_id, batch.proofs.len());
+
+        let data = serde_json::to_string(&batch)?;
+        self.conn
+            .lock()
+            .execute(
+                "INSERT INTO batches (batch_id, timestamp_us, data) VALUES (?1, ?2, ?3)",
+                params![batch.batch_id.to_string(), batch.timestamp.timestamp_micros(), data],
+            )
+            .map_err(storage_error)?;
+
+        self.stats.write().total_batches += 1;
+        Ok(batch.batch_id)
+    }
+
+    async fn get_batch(&self, batch_id: &Uuid) -> Option<ProofBatch> {
+        self
+            .load_row("SELECT data FROM batches WHERE batch_id = ?1", batch_id)
+            .unwrap_or_else(|e| {
+                warn!("Failed to load batch {}: {}", batch_id, e);
+                None
+            })
+    }
+
+    async fn get_all_proofs(&self) -> Vec<ZkpProof> {
+        self.list_proofs("SELECT data FROM proofs", [])
+    }
+
+    async fn get_verified_proofs(&self) -> Vec<ZkpProof> {
+        self.list_proofs("SELECT data FROM proofs WHERE verified = 1", [])
+    }
+
+    async fn get_client_proofs(&self, client_id: &str) -> Vec<ZkpProof> {
+        self.list_proofs("SELECT data FROM proofs WHERE client_id = ?1", params![client_id])
+    }
+
+    async fn get_proofs_in_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<ZkpProof> {
+        self.list_proofs(
+            "SELECT data FROM proofs WHERE timestamp_us >= ?1 AND timestamp_us < ?2
+             ORDER BY timestamp_us",
+            params![start.timestamp_micros(), end.timestamp_micros()],
+        )
+    }
+
+    async fn query_proofs(&self, query: &ProofQuery) -> Result<ProofPage> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(client_id) = &query.client_id {
+            values.push(client_id.clone().into());
+            conditions.push(format!("client_id = ?{}", values.len()));
+        }
+        if let Some(verified) = query.verified {
+            values.push(verified.into());
+            conditions.push(format!("verified = ?{}", values.len()));
+        }
+        if let Some(since) = query.since {
+            values.push(since.timestamp_micros().into());
+            conditions.push(format!("timestamp_us >= ?{}", values.len()));
+        }
+        if let Some(until) = query.until {
+            values.push(until.timestamp_micros().into());
+            conditions.push(format!("timestamp_us < ?{}", values.len()));
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let total: usize = self
+            .conn
+            .lock()
+            .query_row(
+                &format!("SELECT COUNT(*) FROM proofs {}", filter),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .map_err(storage_error)?;
+
+        // SQLite limits are signed; saturate rather than wrap huge values
+        values.push(i64::try_from(query.limit).unwrap_or(i64::MAX).into());
+        values.push(i64::try_from(query.offset).unwrap_or(i64::MAX).into());
+        let proofs = self.select_proofs(
+            &format!(
+                "SELECT data FROM proofs {} ORDER BY timestamp_us, proof_id LIMIT ?{} OFFSET ?{}",
+                filter,
+                values.len() - 1,
+                values.len()
+            ),
+            params_from_iter(values.iter()),
+        )?;
+
+        Ok(ProofPage {
+            proofs,
+            total,
+            limit: query.limit,
+            offset: query.offset,
+        })
+    }
+
+    async fn cleanup_old_proofs(&self, max_age_hours: i64) -> Result<usize> {
+        let cutoff = (Utc::now() - chrono::Duration::hours(max_age_hours)).timestamp_micros();
+        let removed_count = {
+            let conn = self.conn.lock();
+            let removed = conn
+                .execute("DELETE FROM proofs WHERE timestamp_us < ?1", params![cutoff])
+                .map_err(storage_error)?;
+            conn.execute("DELETE FROM batches WHERE timestamp_us < ?1", params![cutoff])
+                .map_err(storage_error)?;
+            removed
+        };
+
+        self.stats.write().last_cleanup = Some(Utc::now());
+
+        if removed_count > 0 {
+            info!("Cleaned up {} old proofs (older than {} hours)", removed_count, max_age_hours);
+        }
+        Ok(removed_count)
+    }
+
+    async fn evict_oldest_proofs(&self, max_count: usize) -> Result<usize> {
+        let conn = self.conn.lock();
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM proofs", [], |row| row.get(0))
+            .map_err(storage_error)?;
+        if count <= max_count {
+            return Ok(0);
+        }
+
+        let evicted = conn
+            .execute(
+                "DELETE FROM proofs WHERE proof_id IN (
+                     SELECT proof_id FROM proofs ORDER BY timestamp_us, proof_id LIMIT ?1
+                 )",
+                params![count - max_count],
+            )
+            .map_err(storage_error)?;
+
+        info!("Evicted {} oldest proofs (keeping at most {})", evicted, max_count);
+        Ok(evicted)
+    }
+
+    async fn update_metrics(&self, _current_metrics: &crate::metrics::ServerMetricsSnapshot) {
+        match std::fs::metadata(&self.path) {
+            Ok(metadata) => self.stats.write().storage_size_bytes = metadata.len(),
+            Err(e) => warn!("Failed to read size of {:?}: {}", self.path, e),
+        }
+    }
+
+    fn get_stats(&self) -> StorageStats {
+        self.stats.read().clone()
+    }
+
+    async fn export_benchmark_data(&self) -> Result<MultiClientBenchmark> {
+        let proofs = self.select_proofs("SELECT data FROM proofs", [])?;
+        info!("Exporting benchmark data for {} proofs", proofs.len());
+        Ok(benchmark_from_proofs(&proofs))
+    }
+
+    async fn store_round(&self, round: RoundRecord) -> Result<()> {
+        let data = serde_json::to_string(&round)?;
+        // SQLite integers are signed; round numbers never get near the limit
+        self.conn
+            .lock()
+            .execute(
+                "INSERT OR REPLACE INTO rounds (round, data) VALUES (?1, ?2)",
+                params![round.round as i64, data],
+            )
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn get_rounds(&self) -> Vec<RoundRecord> {
+        self.select_json("SELECT data FROM rounds ORDER BY round", [])
+            .unwrap_or_else(|e| {
+                warn!("Failed to query rounds: {}", e);
+                Vec::new()
+            })
+    }
+
+    async fn get_round(&self, round: u64) -> Option<RoundRecord> {
+        self.select_json("SELECT data FROM rounds WHERE round = ?1", params![round as i64])
+            .unwrap_or_else(|e| {
+                warn!("Failed to load round {}: {}", round, e);
+                Vec::new()
+            })
+            .pop()
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.conn.lock().cache_flush().map_err(storage_error)
+    }
+
+    async fn check_writable(&self) -> Result<()> {
+        // Taking the write lock fails on a read-only or unreachable database
+        self.conn
+            .lock()
+            .execute_batch("BEGIN IMMEDIATE; ROLLBACK;")
+            .map_err(storage_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::{create_test_proof, test_config};
+    use common::StorageBackend;
+
+    #[tokio::test]
+    async fn test_proofs_survive_reopen() {
+        let config = test_config("/tmp/test_proofs_sqlite_reopen", StorageBackend::Sqlite);
+        let storage = SqliteProofStore::new(&config, true).unwrap();
+        let proof = create_test_proof();
+        storage.store_proof(proof.clone()).await.unwrap();
+        let round = RoundRecord {
+            round: 1,
+            client_ids: vec![proof.client_id.clone()],
+            num_proofs: 1,
+            global_loss: 0.1,
+            aggregated_at: Utc::now(),
+            model: common::GlobalModel::initial(5),
+            excluded_client_ids: Vec::new(),
+        };
+        storage.store_round(round).await.unwrap();
+        drop(storage);
+
+        let reopened = SqliteProofStore::new(&config, false).unwrap();
+        let loaded = reopened.get_proof(&proof.proof_id).await.unwrap();
+        assert_eq!(loaded.proof_id, proof.proof_id);
+        assert_eq!(loaded.proof_size(), proof.proof_size());
+        assert_eq!(reopened.get_round(1).await.unwrap().client_ids, vec![proof.client_id]);
+        assert!(reopened.get_round(2).await.is_none());
+        drop(reopened);
+
+        let cleared = SqliteProofStore::new(&config, true).unwrap();
+        assert!(cleared.get_all_proofs().await.is_empty());
+        assert!(cleared.get_rounds().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queries_use_indexes() {
+        let config = test_config("/tmp/test_proofs_sqlite_indexes", StorageBackend::Sqlite);
+        let storage = SqliteProofStore::new(&config, true).unwrap();
+        let conn = storage.conn.lock();
+
+        let plan = |sql: &str| -> String {
+            let mut statement = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql)).unwrap();
+            let details: Vec<String> = statement
+                .query_map([], |row| row.get(3))
+                .unwrap()
+                .map(|detail| detail.unwrap())
+                .collect();
+            details.join("; ")
+        };
+
+        assert!(plan("SELECT data FROM proofs WHERE client_id = 'a'")
+            .contains("idx_proofs_client_id"));
+        assert!(plan("SELECT data FROM proofs WHERE timestamp_us >= 0 AND timestamp_us < 1")
+            .contains("idx_proofs_timestamp"));
+        assert!(plan("DELETE FROM proofs WHERE timestamp_us < 1").contains("idx_proofs_timestamp"));
+    }
+}
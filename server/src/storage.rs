@@ -1,7 +1,9 @@
 use common::{
     ZkpProof, ProofBatch, ServerConfig, MultiClientBenchmark, 
-    BenchmarkResult, Result, ZkpFlError
+    BenchmarkResult, Result, RoundRecord, StorageBackend, ZkpFlError
 };
+use crate::sqlite_storage::SqliteProofStore;
+use async_trait::async_trait;
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use log::{info, debug, warn};
@@ -9,14 +11,74 @@ use std::sync::Arc;
 use std::path::PathBuf;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use serde::Serialize;
 use serde_json;
 
-pub struct ProofStorage {
+/// Storage backend for submitted proofs and batches
+#[async_trait]
+pub trait ProofStore: Send + Sync {
+    async fn store_proof(&self, proof: ZkpProof) -> Result<()>;
+
+    async fn get_proof(&self, proof_id: &Uuid) -> Option<ZkpProof>;
+
+    async fn update_proof_verification(&self, proof_id: &Uuid, verified: bool) -> Result<()>;
+
+    async fn create_batch(&self, client_proofs: Vec<ZkpProof>) -> Result<Uuid>;
+
+    async fn get_batch(&self, batch_id: &Uuid) -> Option<ProofBatch>;
+
+    async fn get_all_proofs(&self) -> Vec<ZkpProof>;
+
+    async fn get_verified_proofs(&self) -> Vec<ZkpProof>;
+
+    async fn get_client_proofs(&self, client_id: &str) -> Vec<ZkpProof>;
+
+    /// Proofs with `start <= timestamp < end`, oldest first
+    async fn get_proofs_in_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<ZkpProof>;
+
+    /// One page of the proofs matching `query`, oldest first
+    async fn query_proofs(&self, query: &ProofQuery) -> Result<ProofPage>;
+
+    /// Remove proofs and batches older than `max_age_hours`, returning the number of proofs removed
+    async fn cleanup_old_proofs(&self, max_age_hours: i64) -> Result<usize>;
+
+    /// Remove the oldest proofs until at most `max_count` remain, returning the number removed
+    async fn evict_oldest_proofs(&self, max_count: usize) -> Result<usize>;
+
+    async fn update_metrics(&self, current_metrics: &crate::metrics::ServerMetricsSnapshot);
+
+    fn get_stats(&self) -> StorageStats;
+
+    async fn export_benchmark_data(&self) -> Result<MultiClientBenchmark>;
+
+    /// Record a completed aggregation round, replacing any with its number
+    async fn store_round(&self, round: RoundRecord) -> Result<()>;
+
+    /// Every recorded round, oldest first
+    async fn get_rounds(&self) -> Vec<RoundRecord>;
+
+    async fn get_round(&self, round: u64) -> Option<RoundRecord>;
+
+    /// Write out anything still buffered before the process exits. Backends
+    /// that write every change through as it happens have nothing to do.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Confirm new proofs could be stored right now, for deep health checks
+    async fn check_writable(&self) -> Result<()>;
+}
+
+/// Default backend: proofs are kept in memory and persisted as JSON files
+pub struct FileProofStore {
     // In-memory storage for active proofs
     active_proofs: DashMap<Uuid, ZkpProof>,
     
     // Batch storage for multi-client scenarios
     proof_batches: DashMap<Uuid, ProofBatch>,
+
+    // Completed aggregation rounds by number
+    rounds: DashMap<u64, RoundRecord>,
     
     // Storage configuration
     storage_path: PathBuf,
@@ -25,6 +87,38 @@ pub struct ProofStorage {
     stats: Arc<RwLock<StorageStats>>,
 }
 
+/// Filters and page window for `ProofStore::query_proofs`
+#[derive(Debug, Clone, Default)]
+pub struct ProofQuery {
+    pub client_id: Option<String>,
+    pub verified: Option<bool>,
+    /// Only proofs with `since <= timestamp`
+    pub since: Option<DateTime<Utc>>,
+    /// Only proofs with `timestamp < until`
+    pub until: Option<DateTime<Utc>>,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+impl ProofQuery {
+    pub fn matches(&self, proof: &ZkpProof) -> bool {
+        self.client_id.as_ref().is_none_or(|client_id| &proof.client_id == client_id)
+            && self.verified.is_none_or(|verified| proof.is_verified() == verified)
+            && self.since.is_none_or(|since| proof.timestamp >= since)
+            && self.until.is_none_or(|until| proof.timestamp < until)
+    }
+}
+
+/// A single page of proofs returned from a query
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofPage {
+    pub proofs: Vec<ZkpProof>,
+    /// Number of proofs matching the filters, across all pages
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct StorageStats {
     pub total_proofs_stored: usize,
@@ -34,15 +128,27 @@ pub struct StorageStats {
     pub last_cleanup: Option<DateTime<Utc>>,
 }
 
-impl ProofStorage {
+impl StorageStats {
+    /// Account for one stored proof; a proof stored again under an existing
+    /// id is only counted once, and only a change in verification moves the
+    /// verified count
+    pub(crate) fn record_store(&mut self, is_new: bool, was_verified: bool, verified: bool) {
+        if is_new {
+            self.total_proofs_stored += 1;
+        }
+        match (was_verified, verified) {
+            (false, true) => self.total_proofs_verified += 1,
+            (true, false) => {
+                self.total_proofs_verified = self.total_proofs_verified.saturating_sub(1)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl FileProofStore {
     pub fn new(config: &ServerConfig, clear_on_startup: bool) -> Result<Self> {
         let storage_path = PathBuf::from(&config.proof_storage_path);
-        
-        // Create storage directory if it doesn't exist
-        if let Some(parent) = storage_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| ZkpFlError::Io(e))?;
-        }
 
         if clear_on_startup {
             info!("Clearing proof storage on startup");
@@ -50,13 +156,16 @@ impl ProofStorage {
                 std::fs::remove_dir_all(&storage_path)
                     .map_err(|e| ZkpFlError::Io(e))?;
             }
-            std::fs::create_dir_all(&storage_path)
-                .map_err(|e| ZkpFlError::Io(e))?;
         }
 
+        // Create storage directory if it doesn't exist
+        std::fs::create_dir_all(&storage_path)
+            .map_err(|e| ZkpFlError::Io(e))?;
+
         let storage = Self {
             active_proofs: DashMap::new(),
             proof_batches: DashMap::new(),
+            rounds: DashMap::new(),
             storage_path,
             stats: Arc::new(RwLock::new(StorageStats::default())),
         };
@@ -64,37 +173,206 @@ impl ProofStorage {
         // Load existing proofs if not clearing
         if !clear_on_startup {
             storage.load_existing_proofs()?;
+            storage.load_existing_rounds()?;
         }
 
         info!("Proof storage initialized at: {:?}", storage.storage_path);
         Ok(storage)
     }
 
-    pub async fn store_proof(&self, proof: ZkpProof) -> Result<()> {
+    async fn persist_proof(&self, proof: &ZkpProof) -> Result<()> {
+        let filename = format!("proof_{}.json", proof.proof_id);
+        let filepath = self.storage_path.join("proofs").join(filename);
+        
+        // Ensure proofs directory exists
+        if let Some(parent) = filepath.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ZkpFlError::Io(e))?;
+        }
+
+        let json_data = serde_json::to_string_pretty(proof)
+            .map_err(|e| ZkpFlError::Serialization(e))?;
+
+        tokio::fs::write(&filepath, json_data).await
+            .map_err(|e| ZkpFlError::Io(e))?;
+
+        Ok(())
+    }
+
+    async fn persist_batch(&self, batch: &ProofBatch) -> Result<()> {
+        let filename = format!("batch_{}.json", batch.batch_id);
+        let filepath = self.storage_path.join("batches").join(filename);
+        
+        // Ensure batches directory exists
+        if let Some(parent) = filepath.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ZkpFlError::Io(e))?;
+        }
+
+        let json_data = serde_json::to_string_pretty(batch)
+            .map_err(|e| ZkpFlError::Serialization(e))?;
+
+        tokio::fs::write(&filepath, json_data).await
+            .map_err(|e| ZkpFlError::Io(e))?;
+
+        Ok(())
+    }
+
+    fn load_existing_proofs(&self) -> Result<()> {
+        let proofs_dir = self.storage_path.join("proofs");
+        if !proofs_dir.exists() {
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(&proofs_dir)
+            .map_err(|e| ZkpFlError::Io(e))?;
+
+        let mut loaded_count = 0;
+        for entry in entries {
+            let entry = entry.map_err(|e| ZkpFlError::Io(e))?;
+            let path = entry.path();
+            
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                match self.load_proof_from_file(&path) {
+                    Ok(proof) => {
+                        self.active_proofs.insert(proof.proof_id, proof);
+                        loaded_count += 1;
+                    }
+                    Err(e) => {
+                        warn!("Failed to load proof from {:?}: {}", path, e);
+                    }
+                }
+            }
+        }
+
+        if loaded_count > 0 {
+            info!("Loaded {} existing proofs from storage", loaded_count);
+        }
+
+        Ok(())
+    }
+
+    fn load_existing_rounds(&self) -> Result<()> {
+        let rounds_dir = self.storage_path.join("rounds");
+        if !rounds_dir.exists() {
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(&rounds_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let record = std::fs::read_to_string(&path)
+                .map_err(ZkpFlError::from)
+                .and_then(|content| Ok(serde_json::from_str::<RoundRecord>(&content)?));
+            match record {
+                Ok(record) => {
+                    self.rounds.insert(record.round, record);
+                }
+                Err(e) => warn!("Failed to load round from {:?}: {}", path, e),
+            }
+        }
+
+        if !self.rounds.is_empty() {
+            info!("Loaded {} aggregation rounds from storage", self.rounds.len());
+        }
+        Ok(())
+    }
+
+    /// Delete the persisted copies of evicted proofs so they are not reloaded on restart
+    async fn remove_persisted_proofs(&self, proof_ids: &[Uuid]) {
+        self.remove_persisted_files("proofs", "proof", proof_ids).await;
+    }
+
+    /// Delete the persisted copies of expired batches
+    async fn remove_persisted_batches(&self, batch_ids: &[Uuid]) {
+        self.remove_persisted_files("batches", "batch", batch_ids).await;
+    }
+
+    /// Delete `<dir>/<prefix>_<id>.json` for each id, ignoring ones already gone
+    async fn remove_persisted_files(&self, dir: &str, prefix: &str, ids: &[Uuid]) {
+        for id in ids {
+            let filepath = self
+                .storage_path
+                .join(dir)
+                .join(format!("{}_{}.json", prefix, id));
+            match tokio::fs::remove_file(&filepath).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to remove {:?}: {}", filepath, e),
+            }
+        }
+    }
+
+    fn load_proof_from_file(&self, path: &std::path::Path) -> Result<ZkpProof> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ZkpFlError::Io(e))?;
+        
+        let proof: ZkpProof = serde_json::from_str(&content)
+            .map_err(|e| ZkpFlError::Serialization(e))?;
+        
+        Ok(proof)
+    }
+
+    async fn calculate_storage_size(&self) -> u64 {
+        // Calculate the total size of stored data
+        let mut total_size = 0u64;
+        
+        // Size of in-memory proofs (approximate)
+        for proof in self.active_proofs.iter() {
+            total_size += proof.proof_size() as u64;
+        }
+        
+        // Add storage overhead estimate
+        total_size += (self.active_proofs.len() * 1024) as u64; // ~1KB overhead per proof
+
+        total_size
+    }
+}
+
+/// Open the backend selected by `config.storage_backend`
+pub fn open_store(config: &ServerConfig, clear_on_startup: bool) -> Result<Arc<dyn ProofStore>> {
+    Ok(match config.storage_backend {
+        StorageBackend::File => Arc::new(FileProofStore::new(config, clear_on_startup)?),
+        StorageBackend::Sqlite => Arc::new(SqliteProofStore::new(config, clear_on_startup)?),
+    })
+}
+
+/// Apply the retention policy from `config`: age-based cleanup first, then
+/// the optional cap on the number of proofs. Returns the number removed.
+pub async fn apply_retention(storage: &dyn ProofStore, config: &ServerConfig) -> Result<usize> {
+    let mut removed = storage.cleanup_old_proofs(config.proof_ttl_hours).await?;
+    if let Some(max_count) = config.retention_max_count {
+        removed += storage.evict_oldest_proofs(max_count).await?;
+    }
+    Ok(removed)
+}
+
+#[async_trait]
+impl ProofStore for FileProofStore {
+    async fn store_proof(&self, proof: ZkpProof) -> Result<()> {
         debug!("Storing proof {} from client {}", proof.proof_id, proof.client_id);
 
         // Store in memory
-        self.active_proofs.insert(proof.proof_id, proof.clone());
+        let previous = self.active_proofs.insert(proof.proof_id, proof.clone());
 
         // Persist to disk
         self.persist_proof(&proof).await?;
 
-        // Update statistics
-        {
-            let mut stats = self.stats.write();
-            stats.total_proofs_stored += 1;
-            if proof.is_verified() {
-                stats.total_proofs_verified += 1;
-            }
-        }
+        // Update statistics; storing a proof again only changes its verification
+        let was_verified = previous.as_ref().is_some_and(ZkpProof::is_verified);
+        self.stats.write().record_store(previous.is_none(), was_verified, proof.is_verified());
 
         debug!("Proof {} stored successfully", proof.proof_id);
         Ok(())
     }
 
-    pub async fn get_proof(&self, proof_id: &Uuid) -> Option<ZkpProof> {
+    async fn get_proof(&self, proof_id: &Uuid) -> Option<ZkpProof> {
         self.active_proofs.get(proof_id).map(|entry| entry.clone())
-    }    pub async fn update_proof_verification(&self, proof_id: &Uuid, verified: bool) -> Result<()> {
+    }
+
+    async fn update_proof_verification(&self, proof_id: &Uuid, verified: bool) -> Result<()> {
         if let Some(_proof_entry) = self.active_proofs.get_mut(proof_id) {
             // Update verification status would go here
             // For now, we just update stats
@@ -106,7 +384,7 @@ impl ProofStorage {
         Ok(())
     }
 
-    pub async fn create_batch(&self, client_proofs: Vec<ZkpProof>) -> Result<Uuid> {
+    async fn create_batch(&self, client_proofs: Vec<ZkpProof>) -> Result<Uuid> {
         let batch = ProofBatch::new(client_proofs);
         let batch_id = batch.batch_id;
         
@@ -127,15 +405,15 @@ impl ProofStorage {
         Ok(batch_id)
     }
 
-    pub async fn get_batch(&self, batch_id: &Uuid) -> Option<ProofBatch> {
+    async fn get_batch(&self, batch_id: &Uuid) -> Option<ProofBatch> {
         self.proof_batches.get(batch_id).map(|entry| entry.clone())
     }
 
-    pub async fn get_all_proofs(&self) -> Vec<ZkpProof> {
+    async fn get_all_proofs(&self) -> Vec<ZkpProof> {
         self.active_proofs.iter().map(|entry| entry.clone()).collect()
     }
 
-    pub async fn get_verified_proofs(&self) -> Vec<ZkpProof> {
+    async fn get_verified_proofs(&self) -> Vec<ZkpProof> {
         self.active_proofs
             .iter()
             .filter(|entry| entry.is_verified())
@@ -143,7 +421,7 @@ impl ProofStorage {
             .collect()
     }
 
-    pub async fn get_client_proofs(&self, client_id: &str) -> Vec<ZkpProof> {
+    async fn get_client_proofs(&self, client_id: &str) -> Vec<ZkpProof> {
         self.active_proofs
             .iter()
             .filter(|entry| entry.client_id == client_id)
@@ -151,24 +429,68 @@ impl ProofStorage {
             .collect()
     }
 
-    pub async fn cleanup_old_proofs(&self, max_age_hours: i64) -> Result<usize> {
+    async fn get_proofs_in_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<ZkpProof> {
+        let mut proofs: Vec<ZkpProof> = self.active_proofs
+            .iter()
+            .filter(|entry| entry.timestamp >= start && entry.timestamp < end)
+            .map(|entry| entry.clone())
+            .collect();
+
+        proofs.sort_by_key(|proof| proof.timestamp);
+        proofs
+    }
+
+    async fn query_proofs(&self, query: &ProofQuery) -> Result<ProofPage> {
+        // Sort just the keys so only the requested page is cloned
+        let mut matching: Vec<(DateTime<Utc>, Uuid)> = self.active_proofs
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .map(|entry| (entry.timestamp, entry.proof_id))
+            .collect();
+        matching.sort_unstable();
+
+        let proofs = matching
+            .iter()
+            .skip(query.offset)
+            .take(query.limit)
+            .filter_map(|(_, proof_id)| self.active_proofs.get(proof_id).map(|entry| entry.clone()))
+            .collect();
+
+        Ok(ProofPage {
+            proofs,
+            total: matching.len(),
+            limit: query.limit,
+            offset: query.offset,
+        })
+    }
+
+    async fn cleanup_old_proofs(&self, max_age_hours: i64) -> Result<usize> {
         let cutoff_time = Utc::now() - chrono::Duration::hours(max_age_hours);
-        let mut removed_count = 0;
+        let mut removed_ids = Vec::new();
 
         // Remove old proofs from memory
-        self.active_proofs.retain(|_, proof| {
+        self.active_proofs.retain(|proof_id, proof| {
             if proof.timestamp < cutoff_time {
-                removed_count += 1;
+                removed_ids.push(*proof_id);
                 false
             } else {
                 true
             }
         });
+        self.remove_persisted_proofs(&removed_ids).await;
+        let removed_count = removed_ids.len();
 
         // Clean up old batch data
-        self.proof_batches.retain(|_, batch| {
-            batch.timestamp >= cutoff_time
+        let mut removed_batches = Vec::new();
+        self.proof_batches.retain(|batch_id, batch| {
+            if batch.timestamp < cutoff_time {
+                removed_batches.push(*batch_id);
+                false
+            } else {
+                true
+            }
         });
+        self.remove_persisted_batches(&removed_batches).await;
 
         // Update cleanup timestamp
         {
@@ -183,7 +505,29 @@ impl ProofStorage {
         Ok(removed_count)
     }
 
-    pub async fn update_metrics(&self, _current_metrics: &crate::metrics::ServerMetricsSnapshot) {
+    async fn evict_oldest_proofs(&self, max_count: usize) -> Result<usize> {
+        let mut by_age: Vec<(DateTime<Utc>, Uuid)> = self
+            .active_proofs
+            .iter()
+            .map(|entry| (entry.timestamp, entry.proof_id))
+            .collect();
+        if by_age.len() <= max_count {
+            return Ok(0);
+        }
+
+        by_age.sort_unstable();
+        let excess = by_age.len() - max_count;
+        let evicted: Vec<Uuid> = by_age[..excess].iter().map(|(_, proof_id)| *proof_id).collect();
+        for proof_id in &evicted {
+            self.active_proofs.remove(proof_id);
+        }
+        self.remove_persisted_proofs(&evicted).await;
+
+        info!("Evicted {} oldest proofs (keeping at most {})", evicted.len(), max_count);
+        Ok(evicted.len())
+    }
+
+    async fn update_metrics(&self, _current_metrics: &crate::metrics::ServerMetricsSnapshot) {
         // Update storage size calculation
         let storage_size = self.calculate_storage_size().await;
         
@@ -193,172 +537,163 @@ impl ProofStorage {
         }
     }
 
-    pub fn get_stats(&self) -> StorageStats {
+    fn get_stats(&self) -> StorageStats {
         self.stats.read().clone()
     }
 
-    async fn persist_proof(&self, proof: &ZkpProof) -> Result<()> {
-        let filename = format!("proof_{}.json", proof.proof_id);
-        let filepath = self.storage_path.join("proofs").join(filename);
-        
-        // Ensure proofs directory exists
-        if let Some(parent) = filepath.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| ZkpFlError::Io(e))?;
-        }
-
-        let json_data = serde_json::to_string_pretty(proof)
-            .map_err(|e| ZkpFlError::Serialization(e))?;
-
-        tokio::fs::write(&filepath, json_data).await
-            .map_err(|e| ZkpFlError::Io(e))?;
-
-        Ok(())
+    async fn export_benchmark_data(&self) -> Result<MultiClientBenchmark> {
+        info!("Exporting benchmark data for {} proofs", self.active_proofs.len());
+        let proofs: Vec<ZkpProof> = self.active_proofs.iter().map(|entry| entry.clone()).collect();
+        Ok(benchmark_from_proofs(&proofs))
     }
 
-    async fn persist_batch(&self, batch: &ProofBatch) -> Result<()> {
-        let filename = format!("batch_{}.json", batch.batch_id);
-        let filepath = self.storage_path.join("batches").join(filename);
-        
-        // Ensure batches directory exists
-        if let Some(parent) = filepath.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| ZkpFlError::Io(e))?;
-        }
-
-        let json_data = serde_json::to_string_pretty(batch)
-            .map_err(|e| ZkpFlError::Serialization(e))?;
-
-        tokio::fs::write(&filepath, json_data).await
-            .map_err(|e| ZkpFlError::Io(e))?;
+    async fn store_round(&self, round: RoundRecord) -> Result<()> {
+        let rounds_dir = self.storage_path.join("rounds");
+        tokio::fs::create_dir_all(&rounds_dir).await?;
+        let filepath = rounds_dir.join(format!("round_{}.json", round.round));
+        tokio::fs::write(&filepath, serde_json::to_string_pretty(&round)?).await?;
 
+        self.rounds.insert(round.round, round);
         Ok(())
     }
 
-    fn load_existing_proofs(&self) -> Result<()> {
-        let proofs_dir = self.storage_path.join("proofs");
-        if !proofs_dir.exists() {
-            return Ok(());
-        }
-
-        let entries = std::fs::read_dir(&proofs_dir)
-            .map_err(|e| ZkpFlError::Io(e))?;
-
-        let mut loaded_count = 0;
-        for entry in entries {
-            let entry = entry.map_err(|e| ZkpFlError::Io(e))?;
-            let path = entry.path();
-            
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                match self.load_proof_from_file(&path) {
-                    Ok(proof) => {
-                        self.active_proofs.insert(proof.proof_id, proof);
-                        loaded_count += 1;
-                    }
-                    Err(e) => {
-                        warn!("Failed to load proof from {:?}: {}", path, e);
-                    }
-                }
-            }
-        }
+    async fn get_rounds(&self) -> Vec<RoundRecord> {
+        let mut rounds: Vec<RoundRecord> =
+            self.rounds.iter().map(|entry| entry.value().clone()).collect();
+        rounds.sort_by_key(|record| record.round);
+        rounds
+    }
 
-        if loaded_count > 0 {
-            info!("Loaded {} existing proofs from storage", loaded_count);
-        }
+    async fn get_round(&self, round: u64) -> Option<RoundRecord> {
+        self.rounds.get(&round).map(|entry| entry.value().clone())
+    }
 
+    async fn check_writable(&self) -> Result<()> {
+        // Probes are named per call so concurrent checks never remove each
+        // other's file
+        tokio::fs::create_dir_all(&self.storage_path).await?;
+        let probe = self.storage_path.join(format!(".health_check_{}", Uuid::new_v4()));
+        tokio::fs::write(&probe, b"ok").await?;
+        tokio::fs::remove_file(&probe).await?;
         Ok(())
     }
+}
 
-    fn load_proof_from_file(&self, path: &std::path::Path) -> Result<ZkpProof> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| ZkpFlError::Io(e))?;
-        
-        let proof: ZkpProof = serde_json::from_str(&content)
-            .map_err(|e| ZkpFlError::Serialization(e))?;
-        
-        Ok(proof)
-    }
+/// Summarise stored proofs as a multi-client benchmark from the timings
+/// each proof carries. Proofs record no training time, so the training
+/// average is 0, and throughput is over the span between the first and last
+/// proof.
+pub(crate) fn benchmark_from_proofs(proofs: &[ZkpProof]) -> MultiClientBenchmark {
+    let client_results: Vec<BenchmarkResult> = proofs.iter().map(proof_benchmark_result).collect();
 
-    async fn calculate_storage_size(&self) -> u64 {
-        // Calculate the total size of stored data
-        let mut total_size = 0u64;
-        
-        // Size of in-memory proofs (approximate)
-        for proof in self.active_proofs.iter() {
-            total_size += proof.proof_size() as u64;
+    let generation_times: Vec<u64> = proofs
+        .iter()
+        .map(|proof| proof.proof_data.metadata.generation_time_ms)
+        .collect();
+    let verification_times: Vec<u64> = proofs
+        .iter()
+        .filter_map(|proof| proof.verification_result.as_ref())
+        .map(|result| result.verification_time_ms)
+        .collect();
+    let mean = |values: &[u64]| {
+        if values.is_empty() {
+            0.0
+        } else {
+            values.iter().sum::<u64>() as f64 / values.len() as f64
         }
-        
-        // Add storage overhead estimate
-        total_size += (self.active_proofs.len() * 1024) as u64; // ~1KB overhead per proof
+    };
 
-        total_size
-    }
+    let total_proofs_generated = proofs.len();
+    let total_proofs_verified = proofs.iter().filter(|proof| proof.is_verified()).count();
+    let now = Utc::now();
+    let start_time = proofs.iter().map(|proof| proof.timestamp).min().unwrap_or(now);
+    let end_time = proofs.iter().map(|proof| proof.timestamp).max().unwrap_or(now);
+    let span_secs = (end_time - start_time).num_milliseconds() as f64 / 1000.0;
 
-    pub async fn export_benchmark_data(&self) -> Result<MultiClientBenchmark> {
-        info!("Exporting benchmark data for {} proofs", self.active_proofs.len());
+    let aggregate_metrics = common::AggregateMetrics {
+        avg_proof_generation_time_ms: mean(&generation_times),
+        min_proof_generation_time_ms: generation_times.iter().copied().min().unwrap_or(0),
+        max_proof_generation_time_ms: generation_times.iter().copied().max().unwrap_or(0),
+        avg_proof_verification_time_ms: mean(&verification_times),
+        avg_training_time_ms: 0.0,
+        total_proofs_generated,
+        total_proofs_verified,
+        success_rate: if total_proofs_generated > 0 {
+            total_proofs_verified as f64 / total_proofs_generated as f64
+        } else {
+            0.0
+        },
+        throughput_proofs_per_second: if span_secs > 0.0 {
+            total_proofs_generated as f64 / span_secs
+        } else {
+            0.0
+        },
+        loss: None,
+        proof_generation_percentiles: common::LatencyPercentiles::from_values(generation_times),
+        verification_percentiles: common::LatencyPercentiles::from_values(verification_times),
+    };
 
-        // Collect all client results
-        let mut client_results = Vec::new();
-        
-        for proof in self.active_proofs.iter() {
-            // Convert proof to benchmark result (simplified)
-            let benchmark_result = BenchmarkResult::new(
-                proof.session_id,
-                proof.client_id.clone(),
-            );
-            
-            client_results.push(benchmark_result);
-        }
+    let num_clients = proofs
+        .iter()
+        .map(|proof| proof.client_id.as_str())
+        .collect::<std::collections::HashSet<_>>()
+        .len();
 
-        // Calculate aggregate metrics
-        let num_clients = client_results.len();
-        let stats = self.get_stats();
-        
-        let aggregate_metrics = common::AggregateMetrics {
-            avg_proof_generation_time_ms: 1000.0, // Would calculate from actual data
-            min_proof_generation_time_ms: 500,
-            max_proof_generation_time_ms: 2000,
-            avg_proof_verification_time_ms: 200.0,
-            avg_training_time_ms: 5000.0,
-            total_proofs_generated: stats.total_proofs_stored,
-            total_proofs_verified: stats.total_proofs_verified,
-            success_rate: if stats.total_proofs_stored > 0 {
-                stats.total_proofs_verified as f64 / stats.total_proofs_stored as f64
-            } else {
-                0.0
-            },
-            throughput_proofs_per_second: 0.5, // Would calculate from timing data
-        };
+    MultiClientBenchmark {
+        benchmark_id: Uuid::new_v4(),
+        start_time,
+        end_time,
+        num_clients,
+        client_results,
+        aggregate_metrics,
+    }
+}
 
-        let benchmark = MultiClientBenchmark {
-            benchmark_id: Uuid::new_v4(),
-            start_time: Utc::now() - chrono::Duration::hours(1), // Estimate
-            end_time: Utc::now(),
-            num_clients,
-            client_results,
-            aggregate_metrics,
-        };
+/// One client's run as far as its proof records it
+fn proof_benchmark_result(proof: &ZkpProof) -> BenchmarkResult {
+    let metadata = &proof.proof_data.metadata;
+    let params = &proof.proof_data.circuit_params;
+    let commitment = &proof.training_commitment;
+    let verification = proof.verification_result.as_ref();
 
-        Ok(benchmark)
-    }
+    let mut result = BenchmarkResult::new(proof.session_id, proof.client_id.clone());
+    result.start_time = proof.timestamp;
+    result.end_time = proof.timestamp;
+    result.total_duration_ms = metadata.generation_time_ms;
+    result.zkp_metrics.setup_time_ms = metadata.setup_time_ms;
+    result.zkp_metrics.witness_generation_time_ms = metadata.witness_generation_time_ms;
+    result.zkp_metrics.proof_generation_time_ms = metadata.generation_time_ms;
+    result.zkp_metrics.proof_verification_time_ms =
+        verification.map_or(0, |result| result.verification_time_ms);
+    result.zkp_metrics.proof_size_bytes = metadata.proof_size_bytes;
+    result.zkp_metrics.circuit_constraints = params.num_constraints;
+    result.zkp_metrics.circuit_advice_columns = params.num_advice_columns;
+    result.zkp_metrics.circuit_fixed_columns = params.num_fixed_columns;
+    result.zkp_metrics.folding_iterations = metadata.folding_iterations;
+    result.zkp_metrics.quantization_error = metadata.quantization_error;
+    result.training_metrics.dataset_size = commitment.num_samples;
+    result.training_metrics.num_features = commitment.num_features;
+    result.training_metrics.epochs_completed = commitment.epochs;
+    result.training_metrics.final_loss = commitment.final_loss;
+    result.success = proof.is_verified();
+    result.error_message = verification.and_then(|result| result.error_message.clone());
+    result
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use common::{ServerConfig, TrainingCommitment, ProofData, CircuitParams, ProofMetadata};
+    use common::{
+        ServerConfig, TrainingCommitment, ProofData, CircuitParams, ProofMetadata, ModelType,
+        CommitmentScheme, Optimizer, VerificationResult, AggregationWeighting,
+        AggregationRule,
+    };
 
     #[tokio::test]
     async fn test_proof_storage() {
-        let config = ServerConfig {
-            host: "localhost".to_string(),
-            port: 8080,
-            max_clients: 10,
-            proof_storage_path: "/tmp/test_proofs".to_string(),
-            log_level: "info".to_string(),
-        };
+        let config = test_config("/tmp/test_proofs", StorageBackend::File);
 
-        let storage = ProofStorage::new(&config, true).unwrap();
+        let storage = FileProofStore::new(&config, true).unwrap();
         
         // Create test proof
         let proof = create_test_proof();
@@ -372,7 +707,343 @@ mod tests {
         assert_eq!(retrieved.unwrap().proof_id, proof.proof_id);
     }
 
-    fn create_test_proof() -> ZkpProof {
+    #[tokio::test]
+    async fn test_get_proofs_in_range() {
+        let config = test_config("/tmp/test_proofs_range", StorageBackend::File);
+
+        let storage = FileProofStore::new(&config, true).unwrap();
+        let now = Utc::now();
+
+        for hours_ago in [1, 5, 30] {
+            let mut proof = create_test_proof();
+            proof.timestamp = now - chrono::Duration::hours(hours_ago);
+            storage.store_proof(proof).await.unwrap();
+        }
+
+        let recent = storage
+            .get_proofs_in_range(now - chrono::Duration::hours(6), now)
+            .await;
+        assert_eq!(recent.len(), 2);
+        assert!(recent[0].timestamp < recent[1].timestamp);
+
+        let older = storage
+            .get_proofs_in_range(now - chrono::Duration::days(2), now - chrono::Duration::hours(6))
+            .await;
+        assert_eq!(older.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retention_cleanup() {
+        let config = ServerConfig {
+            retention_interval_secs: 60,
+            retention_max_count: Some(2),
+            ..test_config("/tmp/test_proofs_retention", StorageBackend::File)
+        };
+
+        let storage = FileProofStore::new(&config, true).unwrap();
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for hours_ago in [1, 2, 5, 30] {
+            let mut proof = create_test_proof();
+            proof.timestamp = now - chrono::Duration::hours(hours_ago);
+            ids.push(proof.proof_id);
+            storage.store_proof(proof).await.unwrap();
+        }
+        let old_batch = storage.create_batch(vec![create_test_proof()]).await.unwrap();
+        storage.proof_batches.get_mut(&old_batch).unwrap().timestamp =
+            now - chrono::Duration::hours(30);
+        let recent_batch = storage.create_batch(vec![create_test_proof()]).await.unwrap();
+        let batch_file = |batch_id: Uuid| {
+            PathBuf::from(&config.proof_storage_path)
+                .join("batches")
+                .join(format!("batch_{}.json", batch_id))
+        };
+
+        // One proof is too old, then the oldest remaining one is over the cap
+        assert_eq!(apply_retention(&storage, &config).await.unwrap(), 2);
+        assert!(storage.get_batch(&old_batch).await.is_none());
+        assert!(!batch_file(old_batch).exists());
+        assert!(batch_file(recent_batch).exists());
+        let remaining: Vec<Uuid> = storage
+            .get_proofs_in_range(now - chrono::Duration::days(2), now)
+            .await
+            .into_iter()
+            .map(|proof| proof.proof_id)
+            .collect();
+        assert_eq!(remaining, vec![ids[1], ids[0]]);
+
+        // Evicted proofs are gone from disk too, so a restart does not bring them back
+        let reloaded = FileProofStore::new(&config, false).unwrap();
+        assert_eq!(reloaded.get_all_proofs().await.len(), 2);
+        assert!(reloaded.get_proof(&ids[3]).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_backends_behave_alike() {
+        for (path, backend) in [
+            ("/tmp/test_proofs_backend_file", StorageBackend::File),
+            ("/tmp/test_proofs_backend_sqlite", StorageBackend::Sqlite),
+        ] {
+            let storage = open_store(&test_config(path, backend), true).unwrap();
+            let now = Utc::now();
+
+            let mut ids = Vec::new();
+            for (client_id, hours_ago) in [("a", 1), ("b", 2), ("a", 30)] {
+                let mut proof = create_test_proof();
+                proof.client_id = client_id.to_string();
+                proof.timestamp = now - chrono::Duration::hours(hours_ago);
+                ids.push(proof.proof_id);
+                storage.store_proof(proof).await.unwrap();
+            }
+
+            // Storing again replaces the proof, e.g. once it has been verified
+            let mut verified = storage.get_proof(&ids[1]).await.unwrap();
+            verified.mark_verified(VerificationResult {
+                verified: true,
+                verification_time_ms: 1,
+                verifier_id: "test".to_string(),
+                verification_timestamp: now,
+                error_message: None,
+            });
+            storage.store_proof(verified).await.unwrap();
+            let stats = storage.get_stats();
+            assert_eq!(stats.total_proofs_stored, 3, "{:?}", backend);
+            assert_eq!(stats.total_proofs_verified, 1, "{:?}", backend);
+
+            assert_eq!(storage.get_all_proofs().await.len(), 3, "{:?}", backend);
+            assert_eq!(storage.get_client_proofs("a").await.len(), 2, "{:?}", backend);
+            assert!(storage.get_client_proofs("c").await.is_empty(), "{:?}", backend);
+            let verified_ids: Vec<Uuid> = storage
+                .get_verified_proofs()
+                .await
+                .iter()
+                .map(|proof| proof.proof_id)
+                .collect();
+            assert_eq!(verified_ids, vec![ids[1]], "{:?}", backend);
+
+            let recent: Vec<Uuid> = storage
+                .get_proofs_in_range(now - chrono::Duration::hours(6), now)
+                .await
+                .iter()
+                .map(|proof| proof.proof_id)
+                .collect();
+            assert_eq!(recent, vec![ids[1], ids[0]], "{:?}", backend);
+
+            let batch_id = storage.create_batch(vec![create_test_proof()]).await.unwrap();
+            assert_eq!(storage.get_batch(&batch_id).await.unwrap().proofs.len(), 1);
+            assert!(storage.get_batch(&Uuid::new_v4()).await.is_none());
+
+            assert_eq!(storage.cleanup_old_proofs(24).await.unwrap(), 1, "{:?}", backend);
+            assert!(storage.get_proof(&ids[2]).await.is_none());
+            assert_eq!(storage.evict_oldest_proofs(1).await.unwrap(), 1, "{:?}", backend);
+            assert!(storage.get_proof(&ids[1]).await.is_none());
+            assert!(storage.get_proof(&ids[0]).await.is_some());
+            assert!(storage.get_stats().last_cleanup.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_proofs_pages_and_filters() {
+        for (path, backend) in [
+            ("/tmp/test_proofs_query_file", StorageBackend::File),
+            ("/tmp/test_proofs_query_sqlite", StorageBackend::Sqlite),
+        ] {
+            let storage = open_store(&test_config(path, backend), true).unwrap();
+            let now = Utc::now();
+
+            // Oldest first: a, b, a (verified), b, a (verified)
+            let mut ids = Vec::new();
+            for (i, client_id) in ["a", "b", "a", "b", "a"].into_iter().enumerate() {
+                let mut proof = create_test_proof();
+                proof.client_id = client_id.to_string();
+                proof.timestamp = now - chrono::Duration::minutes(10 - i as i64);
+                if i % 2 == 0 && i > 0 {
+                    proof.mark_verified(VerificationResult {
+                        verified: true,
+                        verification_time_ms: 1,
+                        verifier_id: "test".to_string(),
+                        verification_timestamp: now,
+                        error_message: None,
+                    });
+                }
+                ids.push(proof.proof_id);
+                storage.store_proof(proof).await.unwrap();
+            }
+
+            let page_ids = |page: &ProofPage| -> Vec<Uuid> {
+                page.proofs.iter().map(|proof| proof.proof_id).collect()
+            };
+            let query = |limit, offset| ProofQuery {
+                limit,
+                offset,
+                ..ProofQuery::default()
+            };
+
+            let first = storage.query_proofs(&query(2, 0)).await.unwrap();
+            assert_eq!(first.total, 5, "{:?}", backend);
+            assert_eq!(page_ids(&first), vec![ids[0], ids[1]], "{:?}", backend);
+
+            // The last page is short, and pages past the end are empty
+            let last = storage.query_proofs(&query(2, 4)).await.unwrap();
+            assert_eq!(page_ids(&last), vec![ids[4]], "{:?}", backend);
+            let past_end = storage.query_proofs(&query(2, 5)).await.unwrap();
+            assert!(past_end.proofs.is_empty());
+            assert_eq!(past_end.total, 5);
+            assert!(storage.query_proofs(&query(0, 0)).await.unwrap().proofs.is_empty());
+
+            let client_a = ProofQuery {
+                client_id: Some("a".to_string()),
+                ..query(10, 0)
+            };
+            let page = storage.query_proofs(&client_a).await.unwrap();
+            assert_eq!(page_ids(&page), vec![ids[0], ids[2], ids[4]], "{:?}", backend);
+
+            let verified_a = ProofQuery {
+                verified: Some(true),
+                ..client_a.clone()
+            };
+            let page = storage.query_proofs(&verified_a).await.unwrap();
+            assert_eq!(page_ids(&page), vec![ids[2], ids[4]], "{:?}", backend);
+
+            let unverified_b = ProofQuery {
+                client_id: Some("b".to_string()),
+                verified: Some(false),
+                offset: 1,
+                ..query(10, 0)
+            };
+            let page = storage.query_proofs(&unverified_b).await.unwrap();
+            assert_eq!(page.total, 2, "{:?}", backend);
+            assert_eq!(page_ids(&page), vec![ids[3]], "{:?}", backend);
+
+            let window = ProofQuery {
+                verified: Some(false),
+                since: Some(now - chrono::Duration::minutes(9)),
+                until: Some(now - chrono::Duration::minutes(7)),
+                ..query(10, 0)
+            };
+            let page = storage.query_proofs(&window).await.unwrap();
+            assert_eq!(page_ids(&page), vec![ids[1]], "{:?}", backend);
+
+            let nobody = ProofQuery {
+                client_id: Some("c".to_string()),
+                ..query(10, 0)
+            };
+            assert_eq!(storage.query_proofs(&nobody).await.unwrap().total, 0, "{:?}", backend);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_benchmark_export_aggregates_stored_timings() {
+        for (path, backend) in [
+            ("/tmp/test_proofs_export_file", StorageBackend::File),
+            ("/tmp/test_proofs_export_sqlite", StorageBackend::Sqlite),
+        ] {
+            let storage = open_store(&test_config(path, backend), true).unwrap();
+            let now = Utc::now();
+            for (client_id, generation_ms, verification, secs_ago) in [
+                ("a", 1000, Some((true, 100)), 10),
+                ("b", 3000, Some((false, 300)), 5),
+                ("a", 2000, None, 0),
+            ] {
+                let mut proof = create_test_proof();
+                proof.client_id = client_id.to_string();
+                proof.timestamp = now - chrono::Duration::seconds(secs_ago);
+                proof.proof_data.metadata.generation_time_ms = generation_ms;
+                if let Some((verified, verification_time_ms)) = verification {
+                    proof.mark_verified(VerificationResult {
+                        verified,
+                        verification_time_ms,
+                        verifier_id: "test".to_string(),
+                        verification_timestamp: now,
+                        error_message: None,
+                    });
+                }
+                storage.store_proof(proof).await.unwrap();
+            }
+
+            let benchmark = storage.export_benchmark_data().await.unwrap();
+            let metrics = &benchmark.aggregate_metrics;
+            assert_eq!(benchmark.num_clients, 2, "{:?}", backend);
+            assert_eq!(benchmark.client_results.len(), 3, "{:?}", backend);
+            assert_eq!(metrics.avg_proof_generation_time_ms, 2000.0, "{:?}", backend);
+            assert_eq!(metrics.min_proof_generation_time_ms, 1000);
+            assert_eq!(metrics.max_proof_generation_time_ms, 3000);
+            // Only proofs that went through verification have a time
+            assert_eq!(metrics.avg_proof_verification_time_ms, 200.0, "{:?}", backend);
+            assert_eq!(metrics.total_proofs_generated, 3);
+            assert_eq!(metrics.total_proofs_verified, 1);
+            assert!((metrics.success_rate - 1.0 / 3.0).abs() < 1e-12);
+            assert!((metrics.throughput_proofs_per_second - 0.3).abs() < 1e-9);
+            assert_eq!(metrics.proof_generation_percentiles.p50_ms, 2000.0);
+            assert_eq!(benchmark.end_time - benchmark.start_time, chrono::Duration::seconds(10));
+
+            let fastest = benchmark
+                .client_results
+                .iter()
+                .find(|result| result.zkp_metrics.proof_generation_time_ms == 1000)
+                .unwrap();
+            assert!(fastest.success);
+            assert_eq!(fastest.zkp_metrics.proof_verification_time_ms, 100);
+            assert_eq!(fastest.zkp_metrics.witness_generation_time_ms, 500);
+            assert_eq!(fastest.zkp_metrics.setup_time_ms, 200);
+            assert_eq!(fastest.zkp_metrics.proof_size_bytes, 1024);
+            assert_eq!(fastest.training_metrics.dataset_size, 100);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fresh_storage_path_is_writable() {
+        let path = std::env::temp_dir().join(format!("test_proofs_fresh_{}", Uuid::new_v4()));
+        let config = test_config(&path.to_string_lossy(), StorageBackend::File);
+
+        // Without clearing, nothing else would create the directory before
+        // the first proof is stored
+        let storage = FileProofStore::new(&config, false).unwrap();
+        let (first, second) = tokio::join!(storage.check_writable(), storage.check_writable());
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    pub(crate) fn test_config(path: &str, storage_backend: StorageBackend) -> ServerConfig {
+        ServerConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            max_clients: 10,
+            proof_storage_path: path.to_string(),
+            log_level: "info".to_string(),
+            metrics_window_secs: 60,
+            metrics_history_max_records: 10_000,
+            metrics_history_retention_secs: 3600,
+            metrics_sample_every: 1,
+            verification_batch_window_ms: 0,
+            verification_batch_max_size: 32,
+            verify_rate_limit_per_minute: 0,
+            verify_rate_limit_burst: 0,
+            metrics_exemplars: false,
+            shutdown_drain_timeout_secs: 30,
+            retention_interval_secs: 0,
+            proof_ttl_hours: 24,
+            retention_max_count: None,
+            aggregation_min_clients: 2,
+            aggregation_weighting: AggregationWeighting::Samples,
+            aggregation_rule: AggregationRule::Mean,
+            aggregation_trim_fraction: 0.1,
+            aggregation_outlier_threshold: None,
+            storage_backend,
+            request_signing_secret: None,
+            max_concurrent_verifications: 0,
+            proof_size_tolerance: 0.25,
+            registered_dataset_hashes: Default::default(),
+            verification_cache_size: 0,
+            tls_cert_path: None,
+            tls_key_path: None,
+        }
+    }
+
+    pub(crate) fn create_test_proof() -> ZkpProof {
         let training_commitment = TrainingCommitment {
             dataset_hash: "a".repeat(128),
             num_samples: 100,
@@ -381,6 +1052,12 @@ mod tests {
             epochs: 10,
             weights_commitment: "b".repeat(64),
             final_loss: 0.1,
+            quantization: None,
+            scaler: None,
+            optimizer: Optimizer::Sgd,
+            weights_commitment_scheme: CommitmentScheme::Blake2b,
+            differential_privacy: None,
+            bias_commitment: None,
         };
 
         let circuit_params = CircuitParams {
@@ -389,6 +1066,7 @@ mod tests {
             num_advice_columns: 10,
             num_fixed_columns: 5,
             max_degree: 3,
+            model_type: ModelType::Linear,
         };
 
         let metadata = ProofMetadata {
@@ -396,7 +1074,10 @@ mod tests {
             proof_size_bytes: 1024,
             witness_generation_time_ms: 500,
             setup_time_ms: 200,
+            folding_time_ms: 300,
             folding_iterations: 1,
+            quantization_error: None,
+            loss_comparison: None,
         };
 
         ZkpProof::new(
@@ -0,0 +1,119 @@
+use common::{ServerConfig, ZkpProof};
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Content hash identifying what the proof check depends on
+pub type ProofContentHash = [u8; 32];
+
+/// Outcome of checking a proof, kept for resubmissions of the same proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedOutcome {
+    pub is_valid: bool,
+    pub error_message: String,
+}
+
+/// Bounded LRU of proof check outcomes, so a retried submission of the same
+/// proof skips the expensive cryptographic check
+pub struct VerificationCache {
+    entries: Mutex<LruCache<ProofContentHash, CachedOutcome>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VerificationCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache holding `verification_cache_size` outcomes, or none when it is 0
+    pub fn with_config(config: &ServerConfig) -> Option<Self> {
+        NonZeroUsize::new(config.verification_cache_size).map(Self::new)
+    }
+
+    /// Hash of the proof bytes and public inputs, plus the claimed circuit
+    /// parameters and sample count the proof is checked against, so a
+    /// resubmission claiming a different circuit is checked afresh
+    pub fn content_hash(proof: &ZkpProof) -> ProofContentHash {
+        let proof_data = &proof.proof_data;
+        let mut state = blake2b_simd::Params::new().hash_length(32).to_state();
+        state.update(&(proof_data.proof_bytes.len() as u64).to_le_bytes());
+        state.update(&proof_data.proof_bytes);
+        for input in &proof_data.public_inputs {
+            state.update(&(input.len() as u64).to_le_bytes());
+            state.update(input.as_bytes());
+        }
+        let params = &proof_data.circuit_params;
+        state.update(&params.k.to_le_bytes());
+        state.update(format!("{:?}", params.model_type).as_bytes());
+        state.update(&(proof.training_commitment.num_samples as u64).to_le_bytes());
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(state.finalize().as_bytes());
+        hash
+    }
+
+    /// Cached outcome for `hash`, counting the lookup as a hit or a miss
+    pub fn get(&self, hash: &ProofContentHash) -> Option<CachedOutcome> {
+        let outcome = self.entries.lock().get(hash).cloned();
+        let counter = if outcome.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        outcome
+    }
+
+    pub fn insert(&self, hash: ProofContentHash, outcome: CachedOutcome) {
+        self.entries.lock().put(hash, outcome);
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Share of lookups answered from the cache, 0 before the first lookup
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.hits();
+        let lookups = hits + self.misses();
+        if lookups == 0 {
+            0.0
+        } else {
+            hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(is_valid: bool) -> CachedOutcome {
+        CachedOutcome {
+            is_valid,
+            error_message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_least_recently_used_outcome_is_evicted() {
+        let cache = VerificationCache::new(NonZeroUsize::new(2).unwrap());
+        cache.insert([1; 32], outcome(true));
+        cache.insert([2; 32], outcome(false));
+        assert_eq!(cache.get(&[1; 32]), Some(outcome(true)));
+
+        // [2] is now the least recently used
+        cache.insert([3; 32], outcome(true));
+        assert_eq!(cache.get(&[2; 32]), None);
+        assert!(cache.get(&[1; 32]).is_some());
+        assert!(cache.get(&[3; 32]).is_some());
+        assert_eq!((cache.hits(), cache.misses()), (3, 1));
+        assert_eq!(cache.hit_rate(), 0.75);
+    }
+}
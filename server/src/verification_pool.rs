@@ -0,0 +1,72 @@
+use common::{Result, ServerConfig, ZkpFlError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Caps how many verifications run at once. Verifications past the cap wait
+/// for a slot instead of competing for CPU.
+pub struct VerificationPool {
+    slots: Semaphore,
+    capacity: usize,
+    queued: AtomicUsize,
+}
+
+/// What callers turned away by a closed pool are answered with
+pub fn closed_error() -> ZkpFlError {
+    ZkpFlError::Unavailable("Server shut down before this proof was verified".to_string())
+}
+
+/// Counts a caller as queued until it gets a slot or gives up waiting
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl VerificationPool {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            slots: Semaphore::new(capacity),
+            capacity,
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Pool sized by `max_concurrent_verifications`, or one slot per CPU when 0
+    pub fn with_config(config: &ServerConfig) -> Self {
+        let capacity = match config.max_concurrent_verifications {
+            0 => std::thread::available_parallelism().map_or(1, |cpus| cpus.get()),
+            capacity => capacity,
+        };
+        Self::new(capacity)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Wait for a free slot, held until the returned permit is dropped.
+    /// Fails once the pool is closed.
+    pub async fn acquire(&self) -> Result<SemaphorePermit<'_>> {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        let _queued = QueuedGuard(&self.queued);
+        self.slots.acquire().await.map_err(|_| closed_error())
+    }
+
+    /// Turn away every waiting and future caller; slots already held are kept
+    pub fn close(&self) {
+        self.slots.close();
+    }
+
+    /// Verifications waiting for a slot
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Verifications currently holding a slot
+    pub fn in_flight(&self) -> usize {
+        self.capacity - self.slots.available_permits()
+    }
+}
@@ -1,29 +1,40 @@
 use common::{
-    ZkpProof, VerificationResult, TrainingCommitment, ProofData,
-    CircuitConfig, Result
+    ZkpProof, VerificationResult, ValidationResult, ZkpVerifier, CircuitConfig, Result, ZkpFlError
 };
-use halo2_proofs::{
-    poly::ipa::{
-        commitment::{ParamsIPA},
-    },
-    poly::commitment::ParamsProver,
-};
-use halo2curves::pasta::pallas;
 use log::{info, debug};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use crate::verification_cache::{CachedOutcome, VerificationCache};
+use crate::verification_pool::{closed_error, VerificationPool};
+
+/// Proof lifetime of verifiers not given one by the server config
+const DEFAULT_PROOF_TTL_HOURS: i64 = 24;
 
 pub struct ProofVerifier {
-    params: ParamsIPA<pallas::Affine>,
-    circuit_config: CircuitConfig,
-    stats: VerificationStats,
+    zkp: ZkpVerifier,
+    /// Updated in one write per verification, so readers never see a
+    /// half-counted result
+    stats: RwLock<VerificationStats>,
+    /// Bounds concurrent verifications when set
+    pool: Option<Arc<VerificationPool>>,
+    /// Proofs created longer ago than this are refused
+    proof_ttl_hours: i64,
+    /// Outcomes of the cryptographic check for proofs seen before
+    cache: Option<Arc<VerificationCache>>,
+    /// Dataset hash each listed client must commit to
+    registered_dataset_hashes: HashMap<String, String>,
+    /// Outcome of the startup keygen for the configured circuit, once known
+    warm_up: OnceLock<std::result::Result<(), String>>,
 }
 
-#[derive(Debug, Default)]
-struct VerificationStats {
-    total_verifications: usize,
-    successful_verifications: usize,
-    total_verification_time_ms: u64,
+#[derive(Debug, Default, Clone)]
+pub struct VerificationStats {
+    pub total_verifications: usize,
+    pub successful_verifications: usize,
+    pub total_verification_time_ms: u64,
 }
 
 impl ProofVerifier {
@@ -33,55 +44,248 @@ impl ProofVerifier {
         let setup_start = Instant::now();
         
         // Generate the same SRS parameters as the client
-        let params = ParamsIPA::<pallas::Affine>::new(circuit_config.k);
+        let zkp = ZkpVerifier::new(circuit_config);
         
         info!("Proof verifier initialized in {}ms", setup_start.elapsed().as_millis());
         
         Ok(Self {
-            params,
-            circuit_config: circuit_config.clone(),
-            stats: VerificationStats::default(),
+            zkp,
+            stats: RwLock::new(VerificationStats::default()),
+            pool: None,
+            proof_ttl_hours: DEFAULT_PROOF_TTL_HOURS,
+            cache: None,
+            registered_dataset_hashes: HashMap::new(),
+            warm_up: OnceLock::new(),
         })
     }
 
-    pub async fn verify_proof(&mut self, proof: &ZkpProof) -> Result<VerificationResult> {
+    /// Refuse proofs older than `hours`
+    pub fn with_proof_ttl_hours(mut self, hours: i64) -> Self {
+        self.proof_ttl_hours = hours;
+        self
+    }
+
+    pub fn proof_ttl_hours(&self) -> i64 {
+        self.proof_ttl_hours
+    }
+
+    /// Whether `proof` was created longer ago than the TTL as of `now`
+    pub fn is_expired(&self, proof: &ZkpProof, now: DateTime<Utc>) -> bool {
+        now - proof.timestamp > chrono::Duration::hours(self.proof_ttl_hours)
+    }
+
+    /// Accept proofs up to `tolerance` outside the size range their circuit
+    /// produces
+    pub fn with_proof_size_tolerance(mut self, tolerance: f64) -> Self {
+        self.zkp = self.zkp.with_proof_size_tolerance(tolerance);
+        self
+    }
+
+    /// Require proofs from the clients in `hashes` to commit to the dataset
+    /// hash registered for them
+    pub fn with_registered_dataset_hashes(mut self, hashes: HashMap<String, String>) -> Self {
+        self.registered_dataset_hashes = hashes;
+        self
+    }
+
+    /// Run every verification in a slot of `pool`, queueing when it is full
+    pub fn with_pool(mut self, pool: Arc<VerificationPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Reuse the cryptographic check outcome of proofs already in `cache`.
+    /// The cheap structural checks still run on every submission, so a
+    /// cached proof resent with different weights or metadata is still caught.
+    pub fn with_cache(mut self, cache: Option<Arc<VerificationCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub async fn verify_proof(&self, proof: &ZkpProof) -> Result<VerificationResult> {
         info!("Verifying proof {} from client {}", proof.proof_id, proof.client_id);
+        let _slot = match &self.pool {
+            Some(pool) => Some(pool.acquire().await?),
+            None => None,
+        };
         let start_time = Instant::now();
-        
+
+        // Phases 1 and 2: everything short of the cryptography
+        if let Some(rejected) = self.check_before_zkp(proof, start_time)? {
+            return Ok(rejected);
+        }
+
+        // Phase 3: Verify the actual ZKP, unless this proof was checked before
+        let zkp_result = match self.cached_outcome(proof) {
+            Some(result) => result,
+            None => {
+                let result = self.verify_zkp(proof).await?;
+                self.cache_outcome(proof, &result);
+                result
+            }
+        };
+
+        Ok(self.record_result(zkp_result, start_time))
+    }
+
+    /// Verify several proofs in one pass: the cheap checks run per proof, then
+    /// one blocking task runs the decider for every proof left, generating the
+    /// proving key once per circuit shape in the batch. Results are returned in
+    /// the order of `proofs`.
+    pub async fn verify_batch(&self, proofs: &[ZkpProof]) -> Vec<Result<VerificationResult>> {
+        info!("Verifying batch of {} proofs", proofs.len());
+        let _slot = match &self.pool {
+            Some(pool) => match pool.acquire().await {
+                Ok(slot) => Some(slot),
+                Err(_) => return proofs.iter().map(|_| Err(closed_error())).collect(),
+            },
+            None => None,
+        };
+        let start_time = Instant::now();
+
+        let mut results: Vec<Option<Result<VerificationResult>>> =
+            Vec::with_capacity(proofs.len());
+        let mut pending = Vec::new();
+        for (index, proof) in proofs.iter().enumerate() {
+            let result = match self.check_before_zkp(proof, start_time) {
+                Ok(Some(rejected)) => Some(Ok(rejected)),
+                Err(e) => Some(Err(e)),
+                Ok(None) => match self.cached_outcome(proof) {
+                    Some(outcome) => Some(Ok(self.record_result(outcome, start_time))),
+                    None => {
+                        pending.push(index);
+                        None
+                    }
+                },
+            };
+            results.push(result);
+        }
+
+        if !pending.is_empty() {
+            let zkp = self.zkp.clone();
+            let to_decide: Vec<ZkpProof> = pending.iter().map(|&i| proofs[i].clone()).collect();
+            let decided = tokio::task::spawn_blocking(move || {
+                to_decide.iter().map(|proof| zkp.verify_zkp(proof)).collect::<Vec<_>>()
+            })
+            .await;
+
+            match decided {
+                Ok(decided) => {
+                    for (index, outcome) in pending.into_iter().zip(decided) {
+                        results[index] = Some(outcome.map(|result| {
+                            self.cache_outcome(&proofs[index], &result);
+                            self.record_result(result, start_time)
+                        }));
+                    }
+                }
+                Err(e) => {
+                    let message = format!("Verification task failed: {}", e);
+                    for index in pending {
+                        results[index] = Some(Err(ZkpFlError::Verification(message.clone())));
+                    }
+                }
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every proof in the batch has a result"))
+            .collect()
+    }
+
+    /// The checks that need no cryptography, in order. Returns the rejection
+    /// for the first one the proof fails.
+    fn check_before_zkp(
+        &self,
+        proof: &ZkpProof,
+        start_time: Instant,
+    ) -> Result<Option<VerificationResult>> {
         // Phase 1: Validate proof structure
         let validation_result = self.validate_proof_structure(proof)?;
         if !validation_result.is_valid {
-            return Ok(VerificationResult {
-                verified: false,
-                verification_time_ms: start_time.elapsed().as_millis() as u64,
-                verifier_id: "server".to_string(),
-                verification_timestamp: Utc::now(),
-                error_message: Some(validation_result.error_message),
-            });
+            return Ok(Some(Self::rejected(validation_result, start_time)));
         }
 
         // Phase 2: Verify training commitment
-        let commitment_result = self.verify_training_commitment(&proof.training_commitment)?;
+        let commitment_result = self.zkp.verify_training_commitment(&proof.training_commitment)?;
         if !commitment_result.is_valid {
-            return Ok(VerificationResult {
-                verified: false,
-                verification_time_ms: start_time.elapsed().as_millis() as u64,
-                verifier_id: "server".to_string(),
-                verification_timestamp: Utc::now(),
-                error_message: Some(commitment_result.error_message),
-            });
+            return Ok(Some(Self::rejected(commitment_result, start_time)));
         }
 
-        // Phase 3: Verify the actual ZKP
-        let zkp_result = self.verify_zkp(&proof.proof_data).await?;
-        
+        // Phase 2a: A registered client must have trained on its registered data
+        let dataset_result = self.verify_registered_dataset(proof);
+        if !dataset_result.is_valid {
+            return Ok(Some(Self::rejected(dataset_result, start_time)));
+        }
+
+        // Phase 2b: Submitted weights must be the ones committed to
+        let weights_result = self.zkp.verify_weights_commitment(proof)?;
+        if !weights_result.is_valid {
+            return Ok(Some(Self::rejected(weights_result, start_time)));
+        }
+
+        // Phase 2c: Quantized proofs must use our scheme and a representable loss
+        let quantization_result = self.zkp.verify_quantization(proof)?;
+        if !quantization_result.is_valid {
+            return Ok(Some(Self::rejected(quantization_result, start_time)));
+        }
+
+        // Phase 2d: Reported proven loss must be what the public input encodes
+        let loss_result = self.zkp.verify_loss_comparison(proof)?;
+        if !loss_result.is_valid {
+            return Ok(Some(Self::rejected(loss_result, start_time)));
+        }
+
+        Ok(None)
+    }
+
+    fn rejected(check: ValidationResult, start_time: Instant) -> VerificationResult {
+        VerificationResult {
+            verified: false,
+            verification_time_ms: start_time.elapsed().as_millis() as u64,
+            verifier_id: "server".to_string(),
+            verification_timestamp: Utc::now(),
+            error_message: Some(check.error_message),
+        }
+    }
+
+    fn cached_outcome(&self, proof: &ZkpProof) -> Option<ValidationResult> {
+        let outcome = self.cache.as_ref()?.get(&VerificationCache::content_hash(proof))?;
+        debug!("Proof {} found in the verification cache", proof.proof_id);
+        Some(ValidationResult {
+            is_valid: outcome.is_valid,
+            error_message: outcome.error_message,
+        })
+    }
+
+    fn cache_outcome(&self, proof: &ZkpProof, result: &ValidationResult) {
+        if let Some(cache) = &self.cache {
+            cache.insert(
+                VerificationCache::content_hash(proof),
+                CachedOutcome {
+                    is_valid: result.is_valid,
+                    error_message: result.error_message.clone(),
+                },
+            );
+        }
+    }
+
+    /// Count a proof that reached the cryptographic check and build its result
+    fn record_result(
+        &self,
+        zkp_result: ValidationResult,
+        start_time: Instant,
+    ) -> VerificationResult {
         let verification_time = start_time.elapsed().as_millis() as u64;
-        
+
         // Update stats
-        self.stats.total_verifications += 1;
-        self.stats.total_verification_time_ms += verification_time;
-        if zkp_result.is_valid {
-            self.stats.successful_verifications += 1;
+        {
+            let mut stats = self.stats.write();
+            stats.total_verifications += 1;
+            stats.total_verification_time_ms += verification_time;
+            if zkp_result.is_valid {
+                stats.successful_verifications += 1;
+            }
         }
 
         let verification_result = VerificationResult {
@@ -92,61 +296,39 @@ impl ProofVerifier {
             error_message: if zkp_result.is_valid { None } else { Some(zkp_result.error_message) },
         };
 
-        info!("Proof verification completed: verified={}, time={}ms", 
+        info!("Proof verification completed: verified={}, time={}ms",
               verification_result.verified, verification_time);
-        
-        Ok(verification_result)
+
+        verification_result
     }
 
+    /// Shared structure checks, then the server's own checks on when the
+    /// proof was created
     fn validate_proof_structure(&self, proof: &ZkpProof) -> Result<ValidationResult> {
-        debug!("Validating proof structure");
-
-        // Check circuit parameters
-        if proof.proof_data.circuit_params.k != self.circuit_config.k {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: format!(
-                    "Circuit parameter k mismatch: expected {}, got {}",
-                    self.circuit_config.k, proof.proof_data.circuit_params.k
-                ),
-            });
-        }
-
-        // Check proof size is reasonable
-        if proof.proof_data.proof_bytes.is_empty() {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Proof data is empty".to_string(),
-            });
+        let structure_result = self.zkp.validate_proof_structure(proof)?;
+        if !structure_result.is_valid {
+            return Ok(structure_result);
         }
 
-        if proof.proof_data.proof_bytes.len() > 10_000_000 { // 10MB max
+        // Check timestamps are reasonable
+        let now = Utc::now();
+        if proof.timestamp > now {
             return Ok(ValidationResult {
                 is_valid: false,
-                error_message: "Proof data too large".to_string(),
+                error_message: "Proof timestamp is in the future".to_string(),
             });
         }
-
-        // Check training commitment has required fields
-        if proof.training_commitment.num_features != self.circuit_config.num_features {
+        if self.is_expired(proof, now) {
             return Ok(ValidationResult {
                 is_valid: false,
                 error_message: format!(
-                    "Feature count mismatch: expected {}, got {}",
-                    self.circuit_config.num_features, proof.training_commitment.num_features
+                    "Proof expired: created {}h ago, proofs are valid for {}h",
+                    (now - proof.timestamp).num_hours(),
+                    self.proof_ttl_hours
                 ),
             });
         }
 
-        // Check timestamps are reasonable
-        let now = Utc::now();
-        if proof.timestamp > now || (now - proof.timestamp).num_hours() > 24 {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Proof timestamp is invalid".to_string(),
-            });
-        }
-
         debug!("Proof structure validation passed");
         Ok(ValidationResult {
             is_valid: true,
@@ -154,170 +336,116 @@ impl ProofVerifier {
         })
     }
 
-    fn verify_training_commitment(&self, commitment: &TrainingCommitment) -> Result<ValidationResult> {
-        debug!("Verifying training commitment");
-
-        // Check reasonable bounds on training parameters
-        if commitment.learning_rate <= 0.0 || commitment.learning_rate > 1.0 {
-            return Ok(ValidationResult {
+    fn verify_registered_dataset(&self, proof: &ZkpProof) -> ValidationResult {
+        let committed = &proof.training_commitment.dataset_hash;
+        match self.registered_dataset_hashes.get(&proof.client_id) {
+            Some(registered) if !registered.eq_ignore_ascii_case(committed) => ValidationResult {
                 is_valid: false,
-                error_message: format!("Invalid learning rate: {}", commitment.learning_rate),
-            });
-        }
-
-        if commitment.epochs == 0 || commitment.epochs > 10000 {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: format!("Invalid epoch count: {}", commitment.epochs),
-            });
-        }
-
-        if commitment.num_samples == 0 || commitment.num_samples > 1_000_000 {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: format!("Invalid sample count: {}", commitment.num_samples),
-            });
-        }        // Check hash formats
-        if commitment.dataset_hash.len() != 128 { // Blake2b hash is 64 bytes = 128 hex chars
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Invalid dataset hash format".to_string(),
-            });
-        }
-
-        if commitment.weights_commitment.len() != 128 {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Invalid weights commitment format".to_string(),
-            });
+                error_message: format!(
+                    "Dataset hash mismatch for client {}: the proof commits to {} but {} is \
+                     registered",
+                    proof.client_id, committed, registered
+                ),
+            },
+            _ => ValidationResult {
+                is_valid: true,
+                error_message: String::new(),
+            },
         }
-
-        debug!("Training commitment verification passed");
-        Ok(ValidationResult {
-            is_valid: true,
-            error_message: String::new(),
-        })
     }
 
-    async fn verify_zkp(&self, proof_data: &ProofData) -> Result<ValidationResult> {
-        debug!("Verifying ZKP using Protostar");
-
-        // In a real implementation, this would:
-        // 1. Deserialize the proof (accumulator)
-        // 2. Create the verifier circuit
-        // 3. Run the Protostar verification algorithm
-        
-        // For now, we simulate the verification process
-        let verification_start = Instant::now();
-        
-        // Simulate some verification work
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-        
-        // Parse proof data (in real implementation, deserialize the accumulator)
-        let proof_result = self.simulate_proof_verification(proof_data);
-        
-        let verification_time = verification_start.elapsed();
-        debug!("ZKP verification completed in {}ms", verification_time.as_millis());
-
-        proof_result
+    /// Keygen and the decider are CPU-bound, so they run on a blocking thread
+    /// rather than stalling the runtime. Callers keep their pool slot meanwhile.
+    async fn verify_zkp(&self, proof: &ZkpProof) -> Result<ValidationResult> {
+        let zkp = self.zkp.clone();
+        let proof = proof.clone();
+        tokio::task::spawn_blocking(move || zkp.verify_zkp(&proof))
+            .await
+            .map_err(|e| ZkpFlError::Verification(format!("Verification task failed: {}", e)))?
     }
 
-    fn simulate_proof_verification(&self, proof_data: &ProofData) -> Result<ValidationResult> {
-        // This is a simulation of the actual proof verification
-        // In a real implementation, you would:
-        // 1. Deserialize the Protostar accumulator from proof_data.proof_bytes
-        // 2. Create a verification circuit with the same parameters
-        // 3. Run the Protostar verifier algorithm
-        
-        // Basic checks on proof structure
-        if proof_data.proof_bytes.len() < 100 {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Proof data too small".to_string(),
-            });
-        }
-
-        // Check circuit parameters are consistent
-        if proof_data.circuit_params.num_advice_columns == 0 {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Invalid circuit parameters".to_string(),
-            });
+    /// Generate the proving key for the configured circuit off the runtime
+    /// and record whether it worked, for deep health checks. Only the first
+    /// call does any work.
+    pub async fn warm_up(&self) -> Result<()> {
+        if self.warm_up.get().is_none() {
+            let zkp = self.zkp.clone();
+            let started = Instant::now();
+            let outcome = tokio::task::spawn_blocking(move || zkp.warm_up())
+                .await
+                .map_err(|e| ZkpFlError::Verification(format!("Warm-up task failed: {}", e)))
+                .and_then(|outcome| outcome)
+                .map_err(|e| e.to_string());
+            if outcome.is_ok() {
+                info!("Proving key ready in {}ms", started.elapsed().as_millis());
+            }
+            let _ = self.warm_up.set(outcome);
         }
+        self.check_ready()
+    }
 
-        // Check public inputs format
-        if proof_data.public_inputs.is_empty() {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "No public inputs provided".to_string(),
-            });
-        }
-
-        // Parse and validate public inputs (loss value)
-        if let Ok(loss) = proof_data.public_inputs[0].parse::<f64>() {
-            if loss < 0.0 || loss > 1000.0 {
-                return Ok(ValidationResult {
-                    is_valid: false,
-                    error_message: format!("Invalid loss value: {}", loss),
-                });
-            }
-        } else {
-            return Ok(ValidationResult {
-                is_valid: false,
-                error_message: "Invalid public input format".to_string(),
-            });
+    /// Confirm the verifier can check proofs for the configured circuit
+    pub fn check_ready(&self) -> Result<()> {
+        // Every verification rebuilds the circuit with this scheme
+        self.zkp.circuit_config().quantization()?;
+        match self.warm_up.get() {
+            Some(Ok(())) => Ok(()),
+            Some(Err(e)) => Err(ZkpFlError::Verification(format!(
+                "Proving key generation failed: {}", e
+            ))),
+            None => Err(ZkpFlError::Verification(
+                "Proving key has not been generated yet".to_string(),
+            )),
         }
+    }
 
-        // Simulate successful verification (in practice, this would be the actual Protostar verification)
-        Ok(ValidationResult {
-            is_valid: true,
-            error_message: String::new(),
-        })
+    pub fn circuit_config(&self) -> &CircuitConfig {
+        self.zkp.circuit_config()
     }
 
     pub fn get_stats(&self) -> VerificationStats {
-        VerificationStats {
-            total_verifications: self.stats.total_verifications,
-            successful_verifications: self.stats.successful_verifications,
-            total_verification_time_ms: self.stats.total_verification_time_ms,
-        }
+        self.stats.read().clone()
     }
 
     pub fn get_average_verification_time(&self) -> f64 {
-        if self.stats.total_verifications == 0 {
+        let stats = self.stats.read();
+        if stats.total_verifications == 0 {
             0.0
         } else {
-            self.stats.total_verification_time_ms as f64 / self.stats.total_verifications as f64
+            stats.total_verification_time_ms as f64 / stats.total_verifications as f64
         }
     }
 
     pub fn get_success_rate(&self) -> f64 {
-        if self.stats.total_verifications == 0 {
+        let stats = self.stats.read();
+        if stats.total_verifications == 0 {
             0.0
         } else {
-            self.stats.successful_verifications as f64 / self.stats.total_verifications as f64
+            stats.successful_verifications as f64 / stats.total_verifications as f64
         }
     }
 }
 
-#[derive(Debug)]
-struct ValidationResult {
-    is_valid: bool,
-    error_message: String,
-}
-
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use common::{TrainingParams, Sample, CircuitParams, ProofMetadata, ProofData};
+    use crate::storage;
+    use common::{TrainingParams, Sample, LossComparison};
+    use common::{field_to_f64, CommitmentScheme, Optimizer, ScalerParams, ScalingMethod};
+    use common::{CircuitBuilder, ConstraintCounts, ModelType, ProofPayload, PROOF_FORMAT_VERSION};
+    use common::{ProofSizeBounds, QuantizationScheme, TrainingCommitment};
+    use common::test_utils::test_circuit_config;
+    use halo2_proofs::poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA};
+    use halo2_proofs::protostar;
+    use halo2_proofs::transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer};
+    use halo2curves::pasta::pallas;
 
     #[tokio::test]
     async fn test_proof_verifier_creation() {
         let circuit_config = CircuitConfig {
-            k: 8,
             num_features: 5,
             precision_bits: 32,
-            max_iterations: 100,
+            ..test_circuit_config()
         };
 
         let verifier = ProofVerifier::new(&circuit_config);
@@ -327,62 +455,532 @@ mod tests {
     #[tokio::test]
     async fn test_proof_structure_validation() {
         let circuit_config = CircuitConfig {
-            k: 8,
             num_features: 5,
             precision_bits: 32,
-            max_iterations: 100,
+            ..test_circuit_config()
         };
 
         let verifier = ProofVerifier::new(&circuit_config).unwrap();
         
         // Create a valid proof structure
-        let proof = create_test_proof();
+        let mut proof = create_test_proof();
+        assert_eq!(proof.format_version, PROOF_FORMAT_VERSION);
         let result = verifier.validate_proof_structure(&proof).unwrap();
         assert!(result.is_valid);
+
+        // Formats this build does not know are rejected with the version named
+        let mut future = proof.clone();
+        future.format_version = PROOF_FORMAT_VERSION + 1;
+        let result = verifier.validate_proof_structure(&future).unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .error_message
+            .contains(&format!("Unsupported proof format version {}", PROOF_FORMAT_VERSION + 1)));
+
+        // Proofs are accepted until they are older than the TTL
+        let verifier = verifier.with_proof_ttl_hours(2);
+        let mut aged = proof.clone();
+        aged.timestamp = Utc::now() - chrono::Duration::minutes(119);
+        assert!(verifier.validate_proof_structure(&aged).unwrap().is_valid);
+        aged.timestamp = Utc::now() - chrono::Duration::minutes(121);
+        let result = verifier.validate_proof_structure(&aged).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.error_message.contains("Proof expired"), "{}", result.error_message);
+
+        // A proof claiming a different circuit layout is rejected
+        proof.proof_data.circuit_params.num_advice_columns += 1;
+        let result = verifier.zkp.check_proof_data(&proof.proof_data).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.error_message.contains("Circuit mismatch"));
+
+        // Scaling parameters must cover every feature with a usable scale
+        let mut commitment = proof.training_commitment.clone();
+        commitment.weights_commitment = "b".repeat(128);
+        commitment.scaler = Some(ScalerParams {
+            method: ScalingMethod::MinMax,
+            offsets: vec![0.0; 5],
+            scales: vec![1.0; 5],
+        });
+        assert!(verifier.zkp.verify_training_commitment(&commitment).unwrap().is_valid);
+        commitment.scaler.as_mut().unwrap().scales[3] = 0.0;
+        assert!(!verifier.zkp.verify_training_commitment(&commitment).unwrap().is_valid);
     }
 
-    fn create_test_proof() -> ZkpProof {
-        let training_commitment = TrainingCommitment {
-            dataset_hash: "a".repeat(128),
-            num_samples: 100,
+    #[tokio::test]
+    async fn test_proof_size_is_bounded_by_the_circuit() {
+        let circuit_config = CircuitConfig {
+            k: 12,
             num_features: 5,
-            learning_rate: 0.01,
-            epochs: 10,
-            weights_commitment: "b".repeat(64),
-            final_loss: 0.1,
+            precision_bits: 32,
+            ..test_circuit_config()
         };
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+
+        // A correctly sized proof passes
+        let proof = create_genuine_proof(&circuit_config);
+        assert!(verifier.validate_proof_structure(&proof).unwrap().is_valid);
+
+        // A placeholder too short to hold the circuit's commitments does not
+        let mut placeholder = proof.clone();
+        placeholder.proof_data.proof_bytes = vec![0u8; 128];
+        let result = verifier.validate_proof_structure(&placeholder).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.error_message.contains("too small"), "{}", result.error_message);
+
+        // Proofs past the ceiling for 2^k rows are refused, but with more rows
+        // the same size is legitimate
+        let bounds =
+            ProofSizeBounds::for_circuit(&circuit_config, ModelType::Linear, 0.25).unwrap();
+        let mut oversized = proof.clone();
+        oversized.proof_data.proof_bytes = vec![0u8; bounds.max_bytes + 1];
+        let result = verifier.validate_proof_structure(&oversized).unwrap();
+        assert!(result.error_message.contains("too large"), "{}", result.error_message);
+        let larger = CircuitConfig { k: 13, ..circuit_config };
+        let larger_bounds =
+            ProofSizeBounds::for_circuit(&larger, ModelType::Linear, 0.25).unwrap();
+        assert!(larger_bounds.check(bounds.max_bytes + 1).is_ok());
+
+        // The tolerance widens the accepted range
+        let strict = ProofVerifier::new(&circuit_config).unwrap().with_proof_size_tolerance(0.0);
+        let floor = ProofSizeBounds::for_circuit(&circuit_config, ModelType::Linear, 0.0)
+            .unwrap()
+            .min_bytes;
+        let mut short = proof;
+        short.proof_data.proof_bytes.truncate(floor - 1);
+        assert!(!strict.validate_proof_structure(&short).unwrap().is_valid);
+        assert!(verifier.validate_proof_structure(&short).unwrap().is_valid);
+    }
 
-        let circuit_params = CircuitParams {
-            k: 8,
-            num_constraints: 100,
-            num_advice_columns: 10,
-            num_fixed_columns: 5,
-            max_degree: 3,
+    #[tokio::test]
+    async fn test_weights_commitment_verification() {
+        let circuit_config = CircuitConfig {
+            num_features: 5,
+            precision_bits: 32,
+            ..test_circuit_config()
         };
 
-        let metadata = ProofMetadata {
-            generation_time_ms: 1000,
-            proof_size_bytes: 1024,
-            witness_generation_time_ms: 500,
-            setup_time_ms: 200,
-            folding_iterations: 1,
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5];
+
+        let mut proof = create_test_proof();
+        proof.training_commitment.weights_commitment =
+            TrainingCommitment::commit_weights(&weights).unwrap();
+        proof.weights = Some(weights);
+        assert!(verifier.zkp.verify_weights_commitment(&proof).unwrap().is_valid);
+
+        // Tampering with a single weight breaks the link to the commitment
+        proof.weights.as_mut().unwrap()[2] = 0.31;
+        assert!(!verifier.zkp.verify_weights_commitment(&proof).unwrap().is_valid);
+
+        // Poseidon commitments are recomputed with the scheme the proof names
+        let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5];
+        proof.training_commitment.weights_commitment_scheme = CommitmentScheme::Poseidon;
+        proof.training_commitment.weights_commitment =
+            TrainingCommitment::commit_weights_poseidon(&weights, 32).unwrap();
+        proof.weights = Some(weights);
+        assert!(verifier.zkp.verify_weights_commitment(&proof).unwrap().is_valid);
+        let zkp = &verifier.zkp;
+        assert!(zkp.verify_training_commitment(&proof.training_commitment).unwrap().is_valid);
+        proof.training_commitment.weights_commitment_scheme = CommitmentScheme::Blake2b;
+        assert!(!verifier.zkp.verify_weights_commitment(&proof).unwrap().is_valid);
+    }
+
+    #[tokio::test]
+    async fn test_bias_commitment_verification() {
+        let circuit_config = CircuitConfig {
+            num_features: 5,
+            precision_bits: 32,
+            ..test_circuit_config()
         };
 
-        let proof_data = ProofData {
-            proof_bytes: vec![0u8; 1024],
-            public_inputs: vec!["0.1".to_string()],
-            circuit_params,
-            metadata,
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5];
+
+        let mut proof = create_test_proof();
+        proof.training_commitment.weights_commitment =
+            TrainingCommitment::commit_weights(&weights).unwrap();
+        proof.training_commitment.bias_commitment = Some(
+            TrainingCommitment::commit_bias_with(CommitmentScheme::Blake2b, 0.5, 32).unwrap(),
+        );
+        proof.weights = Some(weights);
+        proof.bias = Some(0.5);
+        assert!(verifier.zkp.verify_weights_commitment(&proof).unwrap().is_valid);
+        let zkp = &verifier.zkp;
+        assert!(zkp.verify_training_commitment(&proof.training_commitment).unwrap().is_valid);
+
+        // Only the bias changed, and the weights commitment alone would not notice
+        proof.bias = Some(0.6);
+        let result = verifier.zkp.verify_weights_commitment(&proof).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.error_message.contains("bias"), "{}", result.error_message);
+
+        proof.bias = None;
+        assert!(!verifier.zkp.verify_weights_commitment(&proof).unwrap().is_valid);
+
+        proof.training_commitment.bias_commitment = Some("c".repeat(64));
+        let result = verifier.zkp.verify_training_commitment(&proof.training_commitment).unwrap();
+        assert_eq!(result.error_message, "Invalid bias commitment format");
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_proof_is_serialization_error() {
+        let circuit_config = CircuitConfig {
+            num_features: 5,
+            precision_bits: 32,
+            ..test_circuit_config()
         };
 
-        ZkpProof::new(
-            Uuid::new_v4(),
-            "test_client".to_string(),
-            proof_data.proof_bytes.clone(),
-            proof_data.public_inputs.clone(),
-            proof_data.circuit_params.clone(),
-            proof_data.metadata.clone(),
-            training_commitment,
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let proof = create_test_proof();
+        let err = verifier.verify_zkp(&proof).await.unwrap_err();
+        assert!(matches!(err, common::ZkpFlError::ProofSerialization(_)));
+        assert_eq!(err.status_code(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_quantized_loss_verification() {
+        let circuit_config = CircuitConfig {
+            num_features: 5,
+            quantize: true,
+            ..test_circuit_config()
+        };
+
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let scheme = circuit_config.quantization().unwrap().unwrap();
+        let loss = scheme.snap(0.1).unwrap();
+
+        let mut proof = create_test_proof();
+        assert!(!verifier.zkp.verify_quantization(&proof).unwrap().is_valid);
+
+        proof.training_commitment.quantization = Some(scheme);
+        proof.training_commitment.final_loss = loss;
+        proof.proof_data.public_inputs = vec![loss.to_string()];
+        assert!(verifier.zkp.verify_quantization(&proof).unwrap().is_valid);
+
+        // A loss off the quantization grid was not what the circuit proved
+        proof.proof_data.public_inputs = vec!["0.1".to_string()];
+        assert!(!verifier.zkp.verify_quantization(&proof).unwrap().is_valid);
+
+        // Nor is a scheme other than the server's
+        proof.proof_data.public_inputs = vec![loss.to_string()];
+        proof.training_commitment.quantization = Some(QuantizationScheme::new(8).unwrap());
+        assert!(!verifier.zkp.verify_quantization(&proof).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_loss_comparison_verification() {
+        let circuit_config = CircuitConfig { num_features: 5, ..test_circuit_config() };
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let mut proof = create_test_proof();
+        assert!(verifier.zkp.verify_loss_comparison(&proof).unwrap().is_valid);
+
+        proof.proof_data.public_inputs = vec!["0.123456".to_string()];
+        proof.proof_data.metadata.loss_comparison = Some(LossComparison::new(0.2, 0.123456));
+        assert!(verifier.zkp.verify_loss_comparison(&proof).unwrap().is_valid);
+
+        // Claiming a proven loss other than the public input
+        proof.proof_data.metadata.loss_comparison = Some(LossComparison::new(0.2, 0.1234567));
+        assert!(!verifier.zkp.verify_loss_comparison(&proof).unwrap().is_valid);
+
+        // A training loss far from what was proven, once a tolerance is set
+        let verifier = ProofVerifier::new(&CircuitConfig {
+            max_loss_gap: Some(0.01),
+            ..circuit_config
+        })
+        .unwrap();
+        proof.proof_data.metadata.loss_comparison = Some(LossComparison::new(0.2, 0.123456));
+        assert!(!verifier.zkp.verify_loss_comparison(&proof).unwrap().is_valid);
+        proof.proof_data.metadata.loss_comparison = Some(LossComparison::new(0.125, 0.123456));
+        assert!(verifier.zkp.verify_loss_comparison(&proof).unwrap().is_valid);
+    }
+
+    /// Storage fixture whose circuit shape matches what the verifier expects
+    /// for a linear model
+    fn create_test_proof() -> ZkpProof {
+        let mut proof = storage::tests::create_test_proof();
+        let counts = ConstraintCounts::for_model(ModelType::Linear);
+        let circuit_params = &mut proof.proof_data.circuit_params;
+        circuit_params.num_constraints = counts.num_constraints;
+        circuit_params.num_advice_columns = counts.num_advice_columns;
+        circuit_params.num_fixed_columns = counts.num_fixed_columns;
+        proof
+    }
+
+    /// Proof over a real accumulator for a single sample, accepted by a
+    /// verifier running `circuit_config`
+    pub(crate) fn create_genuine_proof(circuit_config: &CircuitConfig) -> ZkpProof {
+        let num_features = circuit_config.num_features;
+        let samples = vec![Sample {
+            features: vec![1.0; num_features],
+            target: 1.0,
+        }];
+        let training_params = TrainingParams {
+            weights: vec![0.25; num_features],
+            bias: 0.5,
+            loss: 0.0,
+            epoch: 10,
+            learning_rate: 0.01,
+            optimizer: Optimizer::Sgd,
+            differential_privacy: None,
+        };
+
+        let quantization = circuit_config.quantization().unwrap();
+        let circuit = CircuitBuilder::new(num_features, 1)
+            .with_precision_bits(circuit_config.precision_bits as u32)
+            .with_quantization(quantization)
+            .with_k(circuit_config.k)
+            .build_circuit(samples, &training_params)
+            .unwrap();
+        let public_instance = vec![circuit.expected_loss];
+        let public_instances: &[&[pallas::Scalar]] = &[&public_instance];
+
+        let params = ParamsIPA::<pallas::Affine>::new(circuit_config.k);
+        let proving_key = protostar::ProvingKey::new(&params, &circuit).unwrap();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let accumulator = protostar::prover::create_accumulator(
+            &params,
+            &proving_key,
+            &circuit,
+            public_instances,
+            &mut rand::thread_rng(),
+            &mut transcript,
         )
+        .unwrap();
+        let payload = ProofPayload {
+            timestamp: Utc::now(),
+            circuit_k: circuit_config.k,
+            num_features,
+            instances: ProofPayload::encode_instances(&accumulator.instances),
+            data: transcript.finalize(),
+        };
+
+        let loss = match quantization {
+            Some(scheme) => scheme.decode_field(circuit.expected_loss),
+            None => field_to_f64(circuit.expected_loss, circuit_config.precision_bits as u32),
+        }
+        .unwrap();
+        let mut proof = create_test_proof();
+        proof.proof_data.proof_bytes = payload.to_bytes().unwrap();
+        proof.proof_data.circuit_params.k = circuit_config.k;
+        proof.proof_data.public_inputs = vec![loss.to_string()];
+        proof.training_commitment.num_samples = 1;
+        proof.training_commitment.num_features = num_features;
+        proof.training_commitment.final_loss = loss;
+        proof.training_commitment.quantization = quantization;
+        proof.training_commitment.weights_commitment =
+            TrainingCommitment::commit_weights(&training_params.weights).unwrap();
+        proof
+    }
+
+    #[tokio::test]
+    async fn test_genuine_proof_verification() {
+        let circuit_config = test_circuit_config();
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let proof = create_genuine_proof(&circuit_config);
+        let result = verifier.verify_proof(&proof).await.unwrap();
+        assert!(result.verified, "{:?}", result.error_message);
+
+        // Claiming a different loss than the accumulator was built over
+        let mut tampered = proof.clone();
+        tampered.proof_data.public_inputs = vec!["0.5".to_string()];
+        tampered.training_commitment.final_loss = 0.5;
+        assert!(!verifier.verify_proof(&tampered).await.unwrap().verified);
+
+        // Rewriting the instance inside the proof bytes
+        let mut payload = ProofPayload::from_bytes(&proof.proof_data.proof_bytes).unwrap();
+        payload.instances[0][0][0] ^= 1;
+        let mut tampered = proof.clone();
+        tampered.proof_data.proof_bytes = payload.to_bytes().unwrap();
+        assert!(!verifier.verify_proof(&tampered).await.unwrap().verified);
+
+        // Overwriting a commitment with bytes that are not a curve point
+        let mut payload = ProofPayload::from_bytes(&proof.proof_data.proof_bytes).unwrap();
+        payload.data[..32].fill(0xff);
+        let mut tampered = proof.clone();
+        tampered.proof_data.proof_bytes = payload.to_bytes().unwrap();
+        let result = verifier.verify_proof(&tampered).await.unwrap();
+        assert!(!result.verified);
+        assert!(result.error_message.unwrap().contains("accumulator"));
+
+        // A sample count the circuit cannot have been built for
+        let mut tampered = proof.clone();
+        tampered.training_commitment.num_samples = 1000;
+        assert!(!verifier.verify_proof(&tampered).await.unwrap().verified);
+
+        // Quantized proofs go through the same checks
+        let circuit_config = CircuitConfig {
+            quantize: true,
+            ..circuit_config
+        };
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let proof = create_genuine_proof(&circuit_config);
+        let result = verifier.verify_proof(&proof).await.unwrap();
+        assert!(result.verified, "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_registered_dataset_hash_is_enforced() {
+        let circuit_config = test_circuit_config();
+        let proof = create_genuine_proof(&circuit_config);
+        let registered = |hash: String| {
+            let hashes = HashMap::from([(proof.client_id.clone(), hash)]);
+            ProofVerifier::new(&circuit_config)
+                .unwrap()
+                .with_registered_dataset_hashes(hashes)
+        };
+
+        let verifier = registered(proof.training_commitment.dataset_hash.to_uppercase());
+        let result = verifier.verify_proof(&proof).await.unwrap();
+        assert!(result.verified, "{:?}", result.error_message);
+
+        let verifier = registered("b".repeat(128));
+        let result = verifier.verify_proof(&proof).await.unwrap();
+        assert!(!result.verified);
+        assert!(result.error_message.unwrap().contains("Dataset hash mismatch"));
+
+        // Clients without a registered hash are not checked
+        let mut other = proof.clone();
+        other.client_id = "unregistered".to_string();
+        assert!(verifier.verify_proof(&other).await.unwrap().verified);
+    }
+
+    #[tokio::test]
+    async fn test_resubmitted_proof_is_answered_from_cache() {
+        let circuit_config = test_circuit_config();
+        let cache = Arc::new(VerificationCache::new(std::num::NonZeroUsize::new(8).unwrap()));
+        let verifier = ProofVerifier::new(&circuit_config)
+            .unwrap()
+            .with_cache(Some(cache.clone()));
+        let metrics =
+            crate::metrics::ServerMetrics::new().with_verification_cache(Some(cache.clone()));
+        let proof = create_genuine_proof(&circuit_config);
+
+        let first = verifier.verify_proof(&proof).await.unwrap();
+        assert!(first.verified, "{:?}", first.error_message);
+        assert_eq!(cache.hits(), 0);
+
+        let start = Instant::now();
+        let second = verifier.verify_proof(&proof).await.unwrap();
+        assert!(second.verified);
+        assert!(start.elapsed() < std::time::Duration::from_millis(50));
+        assert!(second.verification_time_ms <= first.verification_time_ms);
+        assert_eq!(cache.hits(), 1);
+        let snapshot = metrics.get_current_snapshot();
+        assert_eq!(snapshot.verification_cache_hits, 1);
+        assert_eq!(snapshot.verification_cache_hit_rate, 0.5);
+
+        // The cheap checks still run, so tampered weights are not let through
+        let mut tampered = proof.clone();
+        tampered.weights = Some(vec![9.0; 3]);
+        assert!(!verifier.verify_proof(&tampered).await.unwrap().verified);
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_verifications_keep_stats_consistent() {
+        let circuit_config = test_circuit_config();
+        let verifier = std::sync::Arc::new(ProofVerifier::new(&circuit_config).unwrap());
+        let genuine = create_genuine_proof(&circuit_config);
+        let mut tampered = genuine.clone();
+        tampered.proof_data.public_inputs = vec!["0.5".to_string()];
+        tampered.training_commitment.final_loss = 0.5;
+
+        // Every third proof fails the cryptographic check
+        let tasks: Vec<_> = (0..48)
+            .map(|i| {
+                let verifier = verifier.clone();
+                let proof = if i % 3 == 0 { tampered.clone() } else { genuine.clone() };
+                tokio::spawn(async move { verifier.verify_proof(&proof).await.unwrap().verified })
+            })
+            .collect();
+        let mut verified = 0;
+        for task in tasks {
+            if task.await.unwrap() {
+                verified += 1;
+            }
+        }
+
+        let stats = verifier.get_stats();
+        assert_eq!(verified, 32);
+        assert_eq!(stats.total_verifications, 48);
+        assert_eq!(stats.successful_verifications, verified);
+        assert!((verifier.get_success_rate() - 32.0 / 48.0).abs() < 1e-12);
+        let average = stats.total_verification_time_ms as f64 / 48.0;
+        assert_eq!(verifier.get_average_verification_time(), average);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_verification_pool_caps_concurrency() {
+        let circuit_config = test_circuit_config();
+        let pool = Arc::new(VerificationPool::new(2));
+        let verifier =
+            Arc::new(ProofVerifier::new(&circuit_config).unwrap().with_pool(pool.clone()));
+        let metrics = crate::metrics::ServerMetrics::new().with_verification_pool(pool.clone());
+        let proof = create_genuine_proof(&circuit_config);
+
+        // With every slot taken, submissions queue instead of running
+        let first = pool.acquire().await.unwrap();
+        let second = pool.acquire().await.unwrap();
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let verifier = verifier.clone();
+                let proof = proof.clone();
+                tokio::spawn(async move { verifier.verify_proof(&proof).await.unwrap().verified })
+            })
+            .collect();
+        let deadline = Instant::now() + std::time::Duration::from_secs(10);
+        while pool.queue_depth() < 6 {
+            assert!(Instant::now() < deadline, "verifications never queued");
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert_eq!(verifier.get_stats().total_verifications, 0);
+        let snapshot = metrics.get_current_snapshot();
+        assert_eq!(snapshot.verification_queue_depth, 6);
+        assert_eq!(snapshot.verifications_in_flight, 2);
+
+        // Freed slots drain the queue, never running more than the cap at once
+        drop(first);
+        drop(second);
+        let mut peak = 0;
+        while verifier.get_stats().total_verifications < 6 {
+            assert!(Instant::now() < deadline, "queued verifications never ran");
+            peak = peak.max(pool.in_flight());
+            tokio::task::yield_now().await;
+        }
+        for task in tasks {
+            assert!(task.await.unwrap());
+        }
+        assert!(peak <= pool.capacity());
+        assert_eq!(pool.queue_depth(), 0);
+        assert_eq!(pool.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_batch_generates_one_key_per_circuit_shape() {
+        let circuit_config = test_circuit_config();
+        let verifier = ProofVerifier::new(&circuit_config).unwrap();
+        let genuine = create_genuine_proof(&circuit_config);
+        let mut tampered = genuine.clone();
+        tampered.proof_data.public_inputs = vec!["0.5".to_string()];
+        tampered.training_commitment.final_loss = 0.5;
+        tampered.proof_data.metadata.loss_comparison = None;
+        let mut empty = genuine.clone();
+        empty.proof_data.proof_bytes.clear();
+
+        let batch = [genuine.clone(), empty, tampered, genuine];
+        let results: Vec<_> = verifier
+            .verify_batch(&batch)
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect();
+
+        // In order, each proof judged on its own
+        let verified: Vec<_> = results.iter().map(|result| result.verified).collect();
+        assert_eq!(verified, [true, false, false, true]);
+        assert_eq!(results[1].error_message.as_deref(), Some("Proof data is empty"));
+        // Three proofs reached the decider, all of one shape
+        assert_eq!(verifier.get_stats().total_verifications, 3);
+        assert_eq!(verifier.zkp.cached_proving_keys(), 1);
     }
 }